@@ -3,29 +3,38 @@ use crate::{
         BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, MetricsArgs, RpcArgs, StorageType,
         UoPoolArgs,
     },
-    utils::unwrap_path_or_home,
+    utils::{unwrap_path_or_home, wait_for_shutdown_signal},
 };
 use alloy_chains::{Chain, NamedChain};
-use ethers::{providers::Middleware, types::Address};
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, U256},
+};
 use parking_lot::RwLock;
-use silius_bundler::{EthereumClient, FlashbotsClient};
+use silius_bundler::{
+    BroadcastClient, BundleLimits, ConditionalClient, DepositTopUp, EthereumClient,
+    FlashbotsClient, FlashbotsOptions, RetryPolicy, SendBundleOp,
+};
 use silius_contracts::EntryPoint;
 use silius_grpc::{
     bundler_client::BundlerClient, bundler_service_run, uo_pool_client::UoPoolClient,
     uopool_service_run,
 };
 use silius_mempool::{
-    init_env,
+    init_env, init_rocksdb,
+    rocksdb::tables as rocksdb_tables,
     validate::validator::{new_canonical, new_canonical_unsafe},
-    CodeHashes, DatabaseTable, EntitiesReputation, Mempool, Reputation, UserOperations,
-    UserOperationsByEntity, UserOperationsBySender, WriteMap,
+    CodeHashes, DatabaseTable, EntitiesReputation, FeeOracle, Mempool, Reputation, RocksDbTable,
+    UserOperations, UserOperationsByEntity, UserOperationsBySender, WriteMap,
 };
 use silius_metrics::{launch_metrics_exporter, mempool::MetricsHandler};
 use silius_primitives::{
     bundler::SendStrategy,
+    chain::ChainProfiles,
     constants::{
         entry_point, flashbots_relay_endpoints,
-        storage::DATABASE_FOLDER_NAME,
+        storage::{DATABASE_FOLDER_NAME, ROCKSDB_FOLDER_NAME},
         supported_chains::CHAINS,
         validation::reputation::{
             BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, MIN_UNSTAKE_DELAY, THROTTLING_SLACK,
@@ -34,7 +43,7 @@ use silius_primitives::{
     provider::BlockStream,
     reputation::ReputationEntry,
     simulation::CodeHash,
-    UserOperationHash, UserOperationSigned, Wallet,
+    EntryPointVersion, UserOperationHash, UserOperationSigned, Wallet,
 };
 use silius_rpc::{
     debug_api::{DebugApiServer, DebugApiServerImpl},
@@ -48,8 +57,10 @@ use std::{
     net::SocketAddr,
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
-use tracing::{info, warn};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
 
 pub async fn launch_bundler<M>(
     bundler_args: BundlerArgs,
@@ -59,7 +70,7 @@ pub async fn launch_bundler<M>(
     metrics_args: MetricsArgs,
     eth_client: Arc<M>,
     block_streams: Vec<BlockStream>,
-) -> eyre::Result<()>
+) -> eyre::Result<JoinHandle<()>>
 where
     M: Middleware + Clone + 'static,
 {
@@ -69,15 +80,21 @@ where
         block_streams,
         common_args.chain,
         common_args.entry_points.clone(),
+        common_args.declared_entry_point_versions(),
+        common_args.min_priority_fee_per_gas,
+        common_args.chain_profiles()?,
         metrics_args.clone(),
     )
     .await?;
 
-    launch_bundling(
+    let bundling = launch_bundling(
         bundler_args.clone(),
         eth_client.clone(),
         common_args.chain,
-        common_args.entry_points,
+        common_args.entry_points.clone(),
+        common_args.declared_entry_point_versions(),
+        common_args.min_priority_fee_per_gas,
+        common_args.chain_profiles()?,
         format!("http://{:?}:{:?}", uopool_args.uopool_addr, uopool_args.uopool_port),
         metrics_args.clone(),
     )
@@ -95,7 +112,7 @@ where
         launch_metrics_exporter(metrics_args.listen_addr(), metrics_args.custom_label_value);
     }
 
-    Ok(())
+    Ok(bundling)
 }
 
 pub async fn launch_bundling<M>(
@@ -103,9 +120,12 @@ pub async fn launch_bundling<M>(
     eth_client: Arc<M>,
     chain: Option<NamedChain>,
     entry_points: Vec<Address>,
+    entry_point_versions: HashMap<Address, EntryPointVersion>,
+    min_priority_fee_per_gas: Option<U256>,
+    chain_profiles: ChainProfiles,
     uopool_grpc_listen_address: String,
     metrics_args: MetricsArgs,
-) -> eyre::Result<()>
+) -> eyre::Result<JoinHandle<()>>
 where
     M: Middleware + Clone + 'static,
 {
@@ -116,14 +136,34 @@ where
         "Bundling component connected to Ethereum execution client with version {}",
         eth_client_version,
     );
+    check_entry_points(eth_client.clone(), &entry_points, &entry_point_versions).await?;
 
     let chain_id = eth_client.get_chainid().await?.as_u64();
     let chain_conn = Chain::from(chain_id);
-
-    let wallet: Wallet;
-    if args.send_bundle_mode == SendStrategy::Flashbots {
-        wallet = Wallet::from_file(args.mnemonic_file.into(), chain_id, true)
-            .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
+    let chain_profile = chain_profiles.get(&chain_conn);
+    info!("Chain profile: {chain_profile:?}");
+
+    let mut wallet: Wallet;
+    if args.send_bundle_mode == SendStrategy::Flashbots ||
+        !args.broadcast_relay_endpoints.is_empty()
+    {
+        wallet = Wallet::from_file(
+            args.mnemonic_file.clone().into(),
+            chain_id,
+            args.flashbots_key_file.is_none(),
+        )
+        .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
+        if let Some(ref key_file) = args.flashbots_key_file {
+            let key = std::fs::read_to_string(key_file).map_err(|error| {
+                eyre::format_err!("Could not load Flashbots key file: {}", error)
+            })?;
+            wallet.flashbots_signer = Some(
+                key.trim()
+                    .parse::<LocalWallet>()
+                    .map_err(|error| eyre::format_err!("Invalid Flashbots key: {}", error))?
+                    .with_chain_id(chain_id),
+            );
+        }
         info!("Wallet Signer {:?}", wallet.signer);
         info!("Flashbots Signer {:?}", wallet.flashbots_signer);
     } else {
@@ -136,58 +176,110 @@ where
     let uopool_grpc_client = UoPoolClient::connect(uopool_grpc_listen_address).await?;
     info!("Connected to uopool gRPC service");
 
-    match args.send_bundle_mode {
+    let deposit_top_up = args
+        .deposit_top_up_threshold
+        .zip(args.deposit_top_up_target)
+        .map(|(threshold, target)| DepositTopUp::new(threshold, target));
+    let flashbots_options = FlashbotsOptions {
+        target_blocks: args.flashbots_target_blocks,
+        refund_percent: args.flashbots_refund_percent,
+        refund_recipient: args.flashbots_refund_recipient,
+    };
+
+    let client: Arc<dyn SendBundleOp> = match args.send_bundle_mode {
         SendStrategy::EthereumClient => {
-            let client = Arc::new(EthereumClient::new(eth_client.clone(), wallet.clone()));
-            bundler_service_run(
-                SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
-                entry_points,
-                chain_conn,
-                args.beneficiary,
-                args.min_balance,
-                args.bundle_interval,
-                eth_client,
-                client,
-                uopool_grpc_client,
-                metrics_args.enable_metrics,
-                args.enable_access_list,
-            );
+            Arc::new(EthereumClient::new(eth_client.clone(), wallet.clone()))
+        }
+        SendStrategy::Conditional => {
+            Arc::new(ConditionalClient::new(eth_client.clone(), wallet.clone()))
         }
         SendStrategy::Flashbots => {
-            match chain_conn
-                .named()
-                .expect("Flashbots is only supported on Mainnet, Goerli and Sepolia")
-            {
-                NamedChain::Mainnet | NamedChain::Goerli | NamedChain::Sepolia => {}
-                _ => panic!("Flashbots is only supported on Mainnet, Goerli and Sepolia"),
-            }
+            let relay_endpoint = match args.flashbots_relay_endpoint.clone() {
+                Some(relay_endpoint) => relay_endpoint,
+                None => match chain_conn.named() {
+                    Some(NamedChain::Mainnet) => flashbots_relay_endpoints::FLASHBOTS.into(),
+                    Some(NamedChain::Goerli) => flashbots_relay_endpoints::FLASHBOTS_GOERLI.into(),
+                    Some(NamedChain::Sepolia) => {
+                        flashbots_relay_endpoints::FLASHBOTS_SEPOLIA.into()
+                    }
+                    _ => panic!("Flashbots is only supported on Mainnet, Goerli and Sepolia (use --flashbots-relay-endpoint for other relays)"),
+                },
+            };
+
+            Arc::new(
+                FlashbotsClient::new(
+                    eth_client.clone(),
+                    Some(vec![relay_endpoint]),
+                    wallet.clone(),
+                )?
+                .with_options(flashbots_options),
+            )
+        }
+    };
 
-            let client = Arc::new(FlashbotsClient::new(
+    let mut client = BroadcastClient::new(client);
+    for endpoint in args.broadcast_endpoints.iter() {
+        info!("Broadcasting bundles to the Ethereum execution client {endpoint}");
+        let provider = Arc::new(Provider::<Http>::try_from(endpoint.as_str())?);
+        client = client.with_client(Arc::new(EthereumClient::new(provider, wallet.clone())));
+    }
+    for relay_endpoint in args.broadcast_relay_endpoints.iter() {
+        info!("Broadcasting bundles to the relay {relay_endpoint}");
+        client = client.with_client(Arc::new(
+            FlashbotsClient::new(
                 eth_client.clone(),
-                Some(vec![flashbots_relay_endpoints::FLASHBOTS.into()]),
+                Some(vec![relay_endpoint.clone()]),
                 wallet.clone(),
-            )?);
-            bundler_service_run(
-                SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
-                entry_points,
-                chain_conn,
-                args.beneficiary,
-                args.min_balance,
-                args.bundle_interval,
-                eth_client,
-                client,
-                uopool_grpc_client,
-                metrics_args.enable_metrics,
-                args.enable_access_list,
-            );
-        }
+            )?
+            .with_options(flashbots_options),
+        ));
     }
 
+    let bundling = bundler_service_run(
+        SocketAddr::new(args.bundler_addr, args.bundler_port),
+        wallet,
+        entry_points,
+        entry_point_versions,
+        chain_conn,
+        args.beneficiaries.clone(),
+        args.beneficiary_strategy,
+        args.min_balance.unwrap_or(chain_profile.min_balance),
+        args.bundle_interval.unwrap_or(chain_profile.bundle_interval),
+        args.bundle_intervals.iter().cloned().collect(),
+        eth_client,
+        Arc::new(client),
+        uopool_grpc_client,
+        metrics_args.enable_metrics,
+        args.enable_access_list,
+        RetryPolicy::new(
+            args.send_bundle_max_attempts,
+            Duration::from_millis(args.send_bundle_retry_delay),
+        ),
+        Duration::from_secs(args.receipt_timeout),
+        args.stuck_bundle_blocks,
+        BundleLimits::new(
+            args.max_bundle_size,
+            args.max_bundle_gas_perc,
+            args.max_bundle_ops_per_sender,
+        )
+        .with_max_validation_gas_perc(args.max_bundle_validation_gas_perc),
+        args.dry_run,
+        args.bundle_fee_strategy,
+        args.multicall,
+        args.max_bundle_loss,
+        args.confirmations,
+        min_priority_fee_per_gas.unwrap_or(chain_profile.min_priority_fee_per_gas),
+        deposit_top_up,
+        async {
+            if let Err(err) = wait_for_shutdown_signal().await {
+                error!("Waiting for the shutdown signal failed with error: {err:?}");
+            }
+        },
+    )?;
+
     info!("Started bundler gRPC service at {:?}:{:?}", args.bundler_addr, args.bundler_port);
 
-    Ok(())
+    Ok(bundling)
 }
 
 pub async fn launch_uopool<M>(
@@ -196,6 +288,9 @@ pub async fn launch_uopool<M>(
     block_streams: Vec<BlockStream>,
     chain: Option<NamedChain>,
     entry_points: Vec<Address>,
+    entry_point_versions: HashMap<Address, EntryPointVersion>,
+    min_priority_fee_per_gas: Option<U256>,
+    chain_profiles: ChainProfiles,
     metrics_args: MetricsArgs,
 ) -> eyre::Result<()>
 where
@@ -208,8 +303,13 @@ where
         "UoPool component connected to Ethereum execution client with version {}",
         eth_client_version
     );
+    check_entry_points(eth_client.clone(), &entry_points, &entry_point_versions).await?;
 
     let chain = Chain::from(eth_client.get_chainid().await?.as_u64());
+    let chain_profile = chain_profiles.get(&chain);
+    info!("Chain profile: {chain_profile:?}");
+    let min_priority_fee_per_gas =
+        min_priority_fee_per_gas.unwrap_or(chain_profile.min_priority_fee_per_gas);
     let datadir = unwrap_path_or_home(args.datadir)?;
     let p2p_config = if args.p2p_opts.enable_p2p {
         Some(args.p2p_opts.to_config(&chain, &datadir))
@@ -220,16 +320,36 @@ where
     let entrypoint_api = EntryPoint::new(
         eth_client.clone(),
         Address::from_str(entry_point::ADDRESS).expect("address should be valid"),
-    );
+    )
+    .with_validation_tracer(args.validation_tracer.clone());
 
-    match (args.uopool_mode, args.storage_type) {
+    if args.mode() == silius_primitives::UoPoolMode::Unsafe {
+        warn!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
+        warn!("Uopool is running in UNSAFE mode! The validation is not traced, so the opcode and storage access rules are NOT enforced. Only use it in trusted environments!!");
+        warn!("!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!!");
+    }
+
+    match (args.mode(), args.storage_type) {
         (silius_primitives::UoPoolMode::Standard, StorageType::Memory) => {
             let validator = new_canonical(
                 entrypoint_api,
                 chain,
                 args.max_verification_gas,
-                args.min_priority_fee_per_gas,
-            );
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+                args.forbidden_opcodes(),
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
             let mempool = Mempool::new(
                 Arc::new(RwLock::new(MetricsHandler::new(HashMap::<
                     UserOperationHash,
@@ -254,9 +374,13 @@ where
             for whiteaddr in args.whitelist.iter() {
                 reputation.add_whitelist(whiteaddr);
             }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
             uopool_service_run(
                 SocketAddr::new(args.uopool_addr, args.uopool_port),
                 entry_points,
+                entry_point_versions,
                 eth_client,
                 block_streams,
                 chain,
@@ -266,6 +390,16 @@ where
                 validator,
                 p2p_config,
                 metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
             )
             .await?;
             info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
@@ -275,8 +409,21 @@ where
                 entrypoint_api,
                 chain,
                 args.max_verification_gas,
-                args.min_priority_fee_per_gas,
-            );
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+                args.forbidden_opcodes(),
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
             let env = Arc::new(
                 init_env::<WriteMap>(datadir.join(DATABASE_FOLDER_NAME)).expect("Init mdbx failed"),
             );
@@ -302,9 +449,13 @@ where
             for whiteaddr in args.whitelist.iter() {
                 reputation.add_whitelist(whiteaddr);
             }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
             uopool_service_run(
                 SocketAddr::new(args.uopool_addr, args.uopool_port),
                 entry_points,
+                entry_point_versions,
                 eth_client,
                 block_streams,
                 chain,
@@ -314,6 +465,92 @@ where
                 validator,
                 p2p_config,
                 metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
+            )
+            .await?;
+            info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
+        }
+        (silius_primitives::UoPoolMode::Standard, StorageType::RocksDb) => {
+            let validator = new_canonical(
+                entrypoint_api,
+                chain,
+                args.max_verification_gas,
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+                args.forbidden_opcodes(),
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
+            let db = Arc::new(
+                init_rocksdb(datadir.join(ROCKSDB_FOLDER_NAME)).expect("Init rocksdb failed"),
+            );
+            let mempool = Mempool::new(
+                MetricsHandler::new(RocksDbTable::<rocksdb_tables::UserOperations>::new(
+                    db.clone(),
+                )),
+                RocksDbTable::<rocksdb_tables::UserOperationsBySender>::new(db.clone()),
+                RocksDbTable::<rocksdb_tables::UserOperationsByEntity>::new(db.clone()),
+                RocksDbTable::<rocksdb_tables::CodeHashes>::new(db.clone()),
+            );
+            let mut reputation = Reputation::new(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                args.min_stake,
+                MIN_UNSTAKE_DELAY.into(),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                MetricsHandler::new(RocksDbTable::<rocksdb_tables::EntitiesReputation>::new(
+                    db.clone(),
+                )),
+            );
+            for whiteaddr in args.whitelist.iter() {
+                reputation.add_whitelist(whiteaddr);
+            }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
+            uopool_service_run(
+                SocketAddr::new(args.uopool_addr, args.uopool_port),
+                entry_points,
+                entry_point_versions,
+                eth_client,
+                block_streams,
+                chain,
+                args.max_verification_gas,
+                mempool,
+                reputation,
+                validator,
+                p2p_config,
+                metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
             )
             .await?;
             info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
@@ -323,8 +560,20 @@ where
                 entrypoint_api,
                 chain,
                 args.max_verification_gas,
-                args.min_priority_fee_per_gas,
-            );
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
             let mempool = Mempool::new(
                 Arc::new(RwLock::new(MetricsHandler::new(HashMap::<
                     UserOperationHash,
@@ -349,9 +598,13 @@ where
             for whiteaddr in args.whitelist.iter() {
                 reputation.add_whitelist(whiteaddr);
             }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
             uopool_service_run(
                 SocketAddr::new(args.uopool_addr, args.uopool_port),
                 entry_points,
+                entry_point_versions,
                 eth_client,
                 block_streams,
                 chain,
@@ -361,6 +614,16 @@ where
                 validator,
                 p2p_config,
                 metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
             )
             .await?;
             info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
@@ -370,8 +633,20 @@ where
                 entrypoint_api,
                 chain,
                 args.max_verification_gas,
-                args.min_priority_fee_per_gas,
-            );
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
             let env = Arc::new(
                 init_env::<WriteMap>(datadir.join(DATABASE_FOLDER_NAME)).expect("Init mdbx failed"),
             );
@@ -397,9 +672,88 @@ where
             for whiteaddr in args.whitelist.iter() {
                 reputation.add_whitelist(whiteaddr);
             }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
+            uopool_service_run(
+                SocketAddr::new(args.uopool_addr, args.uopool_port),
+                entry_points,
+                entry_point_versions,
+                eth_client,
+                block_streams,
+                chain,
+                args.max_verification_gas,
+                mempool,
+                reputation,
+                validator,
+                p2p_config,
+                metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
+            )
+            .await?;
+            info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
+        }
+        (silius_primitives::UoPoolMode::Unsafe, StorageType::RocksDb) => {
+            let validator = new_canonical_unsafe(
+                entrypoint_api,
+                chain,
+                args.max_verification_gas,
+                min_priority_fee_per_gas,
+                args.max_uo_size,
+                args.gas_increase_perc.into(),
+                args.equal_fee_replacement,
+                args.max_uos_per_sender,
+                args.max_pre_verification_gas_perc,
+                chain_profile.pre_verification_gas_overhead,
+                args.min_base_fee_perc,
+                args.staked_grace_perc,
+            )
+            .with_code_cache(args.code_cache_size)
+            .with_block(args.validation_block)
+            .with_rpc_timeout(Duration::from_millis(args.validation_rpc_timeout))
+            .with_paymaster_allowlist(args.paymaster_allowlist());
+            let db = Arc::new(
+                init_rocksdb(datadir.join(ROCKSDB_FOLDER_NAME)).expect("Init rocksdb failed"),
+            );
+            let mempool = Mempool::new(
+                MetricsHandler::new(RocksDbTable::<rocksdb_tables::UserOperations>::new(
+                    db.clone(),
+                )),
+                RocksDbTable::<rocksdb_tables::UserOperationsBySender>::new(db.clone()),
+                RocksDbTable::<rocksdb_tables::UserOperationsByEntity>::new(db.clone()),
+                RocksDbTable::<rocksdb_tables::CodeHashes>::new(db.clone()),
+            );
+            let mut reputation = Reputation::new(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                args.min_stake,
+                MIN_UNSTAKE_DELAY.into(),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                MetricsHandler::new(RocksDbTable::<rocksdb_tables::EntitiesReputation>::new(
+                    db.clone(),
+                )),
+            );
+            for whiteaddr in args.whitelist.iter() {
+                reputation.add_whitelist(whiteaddr);
+            }
+            for blockaddr in args.blocklist.iter() {
+                reputation.add_blacklist(blockaddr);
+            }
             uopool_service_run(
                 SocketAddr::new(args.uopool_addr, args.uopool_port),
                 entry_points,
+                entry_point_versions,
                 eth_client,
                 block_streams,
                 chain,
@@ -409,6 +763,16 @@ where
                 validator,
                 p2p_config,
                 metrics_args.enable_metrics,
+                args.uo_ttl,
+                args.max_uos,
+                args.max_transient_failures,
+                args.max_bundle_attempts,
+                args.validation_concurrency,
+                args.sort_strategy,
+                args.enable_admin,
+                FeeOracle::new(min_priority_fee_per_gas, args.min_base_fee_perc),
+                args.trusted_senders(),
+                chain_profile.pre_verification_gas_overhead,
             )
             .await?;
             info!("Started uopool gRPC service at {:?}:{:?}", args.uopool_addr, args.uopool_port);
@@ -445,6 +809,10 @@ pub async fn launch_rpc(
         server = server.with_proxy(eth_client_proxy_address);
     }
 
+    if let Some(rate_limiter) = args.rate_limiter() {
+        server = server.with_rate_limit(rate_limiter);
+    }
+
     if metrics_args.enable_metrics {
         info!("Enabling json rpc server metrics.");
         server = server.with_metrics()
@@ -465,15 +833,30 @@ pub async fn launch_rpc(
     info!("Connected to uopool gRPC service...");
 
     if args.is_api_method_enabled("eth") {
+        // the bundling service is optional for the `eth` namespace (only used to speed up
+        // `eth_getUserOperationReceipt` for user operations submitted by this bundler)
+        let bundler_grpc_client = BundlerClient::connect(bundler_grpc_listen_address.clone())
+            .await
+            .map_err(|err| warn!("Failed to connect to bundling gRPC service: {err:?}"))
+            .ok();
+
         if http_api.contains("eth") {
             server.add_methods(
-                EthApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+                EthApiServerImpl {
+                    uopool_grpc_client: uopool_grpc_client.clone(),
+                    bundler_grpc_client: bundler_grpc_client.clone(),
+                }
+                .into_rpc(),
                 JsonRpcServerType::Http,
             )?;
         }
         if ws_api.contains("eth") {
             server.add_methods(
-                EthApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+                EthApiServerImpl {
+                    uopool_grpc_client: uopool_grpc_client.clone(),
+                    bundler_grpc_client,
+                }
+                .into_rpc(),
                 JsonRpcServerType::Ws,
             )?;
         }
@@ -561,3 +944,36 @@ where
 
     Ok(eth_client.client_version().await?)
 }
+
+async fn check_entry_points<M>(
+    eth_client: Arc<M>,
+    entry_points: &[Address],
+    entry_point_versions: &HashMap<Address, EntryPointVersion>,
+) -> eyre::Result<()>
+where
+    M: Middleware + Clone + 'static,
+{
+    let chain_id = eth_client.get_chainid().await?.as_u64();
+    let canonical = [entry_point::ADDRESS, entry_point::ADDRESS_V07]
+        .iter()
+        .filter_map(|addr| addr.parse::<Address>().ok())
+        .collect::<Vec<_>>();
+
+    for ep in entry_points {
+        if !entry_point_versions.contains_key(ep) && !canonical.contains(ep) {
+            warn!(
+                "No version declared for the entry point {:?} at a non-canonical address, assuming version {}",
+                ep,
+                EntryPointVersion::default().as_str()
+            );
+        }
+
+        let version = EntryPointVersion::from_declared(entry_point_versions, ep);
+        EntryPoint::new(eth_client.clone(), *ep).verify_version(version, chain_id).await.map_err(
+            |err| eyre::format_err!("Entry point {:?} verification failed: {}", ep, err),
+        )?;
+        info!("Entry point {:?} verified with version {}", ep, version.as_str());
+    }
+
+    Ok(())
+}
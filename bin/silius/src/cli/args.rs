@@ -1,28 +1,48 @@
 use crate::utils::{
-    parse_address, parse_duration, parse_enr, parse_label_value, parse_send_bundle_mode,
-    parse_u256, parse_uopool_mode,
+    parse_address, parse_beneficiary_strategy, parse_bundle_interval, parse_duration, parse_enr,
+    parse_entry_point_version, parse_equal_fee_replacement, parse_fee_strategy, parse_label_value,
+    parse_send_bundle_mode, parse_sort_strategy, parse_u256, parse_uopool_mode,
+    parse_validation_tracer,
 };
 use alloy_chains::{Chain, NamedChain};
 use clap::{Parser, ValueEnum};
 use discv5::Enr;
 use ethers::types::{Address, U256};
 use expanded_pathbuf::ExpandedPathBuf;
+use silius_mempool::{validate::PaymasterAllowlist, TrustedSenders};
 use silius_metrics::label::LabelValue;
 use silius_p2p::{
     config::{gossipsub_config, Config, ConfigBuilder},
     listen_addr::{ListenAddr, ListenAddress},
 };
 use silius_primitives::{
-    bundler::SendStrategy,
+    bundler::{BeneficiaryStrategy, FeeStrategy, SendStrategy},
+    chain::{ChainProfile, ChainProfiles},
     constants::{
-        bundler::BUNDLE_INTERVAL,
+        bundler::{
+            BUNDLE_CONFIRMATIONS, FLASHBOTS_TARGET_BLOCKS, MAX_BUNDLE_GAS_PERC,
+            MAX_BUNDLE_VALIDATION_GAS_PERC, MAX_USER_OPERATIONS_PER_SENDER, RECEIPT_TIMEOUT,
+            SEND_BUNDLE_MAX_ATTEMPTS, SEND_BUNDLE_RETRY_DELAY, STUCK_BUNDLE_BLOCKS,
+        },
         grpc::{BUNDLER_PORT, MEMPOOL_PORT},
+        mempool::{GAS_INCREASE_PERC, REORG_DEPTH, VALIDATION_CONCURRENCY},
         p2p::{NODE_ENR_FILE_NAME, NODE_KEY_FILE_NAME},
-        rpc::{HTTP_PORT, WS_PORT},
+        rpc::{HTTP_PORT, RATE_LIMIT_WINDOW, WS_PORT},
+        validation::{
+            reputation::SAME_SENDER_MEMPOOL_COUNT,
+            sanity::{
+                MAX_UO_SIZE, MAX_VERIFICATION_GAS, MIN_BASE_FEE_PERC, RPC_TIMEOUT,
+                STAKED_GRACE_PERC,
+            },
+        },
     },
-    UoPoolMode,
+    mempool::{EqualFeeReplacement, SortStrategy},
+    simulation::ValidationTracer,
+    EntryPointVersion, UoPoolMode,
 };
+use silius_rpc::rate_limit::RateLimiter;
 use std::{
+    collections::{HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     time::Duration,
@@ -32,6 +52,7 @@ use std::{
 pub enum StorageType {
     Database,
     Memory,
+    RocksDb,
 }
 
 /// Bundler CLI args
@@ -49,31 +70,187 @@ pub struct BundlerArgs {
     #[clap(long)]
     pub mnemonic_file: PathBuf,
 
-    /// The bundler beneficiary address.
-    #[clap(long, value_parser=parse_address)]
-    pub beneficiary: Address,
+    /// The bundler beneficiary addresses, separated by commas.
+    #[clap(long = "beneficiary", required = true, value_delimiter = ',', value_parser=parse_address)]
+    pub beneficiaries: Vec<Address>,
+
+    /// Sets how the beneficiary of each bundle is selected when several are set (`round-robin`
+    /// or `lowest-balance`).
+    ///
+    /// By default, this option is set to `round-robin`.
+    #[clap(long, default_value = "round-robin", value_parser=parse_beneficiary_strategy)]
+    pub beneficiary_strategy: BeneficiaryStrategy,
 
     /// The minimum balance required for the beneficiary address.
     ///
-    /// By default, this option is set to `100000000000000000`.
-    #[clap(long, default_value = "100000000000000000", value_parser=parse_u256)]
-    pub min_balance: U256,
+    /// By default, this option is set to `100000000000000000` (or the minimum balance of the
+    /// selected chain profile).
+    #[clap(long, value_parser=parse_u256)]
+    pub min_balance: Option<U256>,
 
     /// The bundle interval in seconds.
     ///
-    /// By default the interval time is set to 10
-    #[clap(long, default_value_t = BUNDLE_INTERVAL)]
-    pub bundle_interval: u64,
+    /// By default the interval time is set to 10 (or the bundle interval of the selected chain
+    /// profile).
+    #[clap(long)]
+    pub bundle_interval: Option<u64>,
+
+    /// The bundle intervals in seconds for specific entry points, in the form of
+    /// `entry_point=interval` separated by commas.
+    ///
+    /// Entry points without an interval use `bundle_interval`.
+    #[clap(long, value_delimiter = ',', value_parser=parse_bundle_interval)]
+    pub bundle_intervals: Vec<(Address, u64)>,
 
-    /// Sets the send bundle mode.
+    /// Sets the send bundle mode (`ethereum-client`, `flashbots` or `conditional`).
     ///
     /// By default, this option is set to `ethereum-client`.
     #[clap(long, default_value = "ethereum-client", value_parser=parse_send_bundle_mode)]
     pub send_bundle_mode: SendStrategy,
 
+    /// The Flashbots-style relay the bundles are sent to in the `flashbots` send bundle mode.
+    ///
+    /// By default, the Flashbots relay of the connected chain is used.
+    #[clap(long)]
+    pub flashbots_relay_endpoint: Option<String>,
+
+    /// Path to the file with the hex-encoded private key used to sign the Flashbots bundles.
+    ///
+    /// By default, the Flashbots key is derived from the mnemonic file.
+    #[clap(long)]
+    pub flashbots_key_file: Option<PathBuf>,
+
+    /// The number of consecutive blocks a Flashbots bundle is submitted for.
+    ///
+    /// By default, this option is set to 3.
+    #[clap(long, default_value_t = FLASHBOTS_TARGET_BLOCKS)]
+    pub flashbots_target_blocks: u64,
+
+    /// The percentage of the MEV refunded to the refund recipient of the Flashbots bundles.
+    #[clap(long, value_parser = clap::value_parser!(u8).range(0..=99))]
+    pub flashbots_refund_percent: Option<u8>,
+
+    /// The recipient of the MEV refund of the Flashbots bundles.
+    #[clap(long, value_parser=parse_address)]
+    pub flashbots_refund_recipient: Option<Address>,
+
+    /// Additional Ethereum execution client endpoints the bundles are sent to, concurrently with
+    /// the send bundle mode (the first confirmed inclusion settles the bundle).
+    #[clap(long, value_delimiter = ',')]
+    pub broadcast_endpoints: Vec<String>,
+
+    /// Additional Flashbots-style relays the bundles are sent to, concurrently with the send
+    /// bundle mode (the first confirmed inclusion settles the bundle).
+    #[clap(long, value_delimiter = ',')]
+    pub broadcast_relay_endpoints: Vec<String>,
+
     /// Indicates whether the access list is enabled.
     #[clap(long)]
     pub enable_access_list: bool,
+
+    /// The maximum number of attempts to send a bundle when a transient error occurs.
+    ///
+    /// By default, this option is set to 3.
+    #[clap(long, default_value_t = SEND_BUNDLE_MAX_ATTEMPTS)]
+    pub send_bundle_max_attempts: u64,
+
+    /// The base delay (in milliseconds) between send bundle attempts, doubled on every retry.
+    ///
+    /// By default, this option is set to 500.
+    #[clap(long, default_value_t = SEND_BUNDLE_RETRY_DELAY)]
+    pub send_bundle_retry_delay: u64,
+
+    /// The maximum time (in seconds) to wait for the bundle transaction receipt when a bundle is
+    /// sent manually.
+    ///
+    /// By default, this option is set to 120.
+    #[clap(long, default_value_t = RECEIPT_TIMEOUT)]
+    pub receipt_timeout: u64,
+
+    /// The number of blocks after which a submitted bundle transaction that is not mined is
+    /// resubmitted with the same nonce and a higher gas price.
+    ///
+    /// By default, this option is set to 10.
+    #[clap(long, default_value_t = STUCK_BUNDLE_BLOCKS)]
+    pub stuck_bundle_blocks: u64,
+
+    /// The maximum number of user operations in a bundle, the user operations beyond the limit
+    /// are deferred to the next bundle.
+    ///
+    /// By default, the number of user operations is not limited.
+    #[clap(long)]
+    pub max_bundle_size: Option<usize>,
+
+    /// The maximum gas of a bundle as a percentage of the block gas limit, the user operations
+    /// beyond the limit are deferred to the next bundle.
+    ///
+    /// By default, this option is set to 90.
+    #[clap(long, default_value_t = MAX_BUNDLE_GAS_PERC, value_parser = clap::value_parser!(u64).range(1..=100))]
+    pub max_bundle_gas_perc: u64,
+
+    /// The maximum gas of the validation phase of a bundle (the verification gas limits of the
+    /// user operations and the overhead of the entry point) as a percentage of the block gas
+    /// limit, regardless of the call gas limits. The user operations beyond the limit are
+    /// deferred to the next bundle.
+    ///
+    /// By default, this option is set to 50.
+    #[clap(long, default_value_t = MAX_BUNDLE_VALIDATION_GAS_PERC, value_parser = clap::value_parser!(u64).range(1..=100))]
+    pub max_bundle_validation_gas_perc: u64,
+
+    /// The maximum number of user operations of the same sender in a bundle, the remaining user
+    /// operations of the sender are deferred to the next bundle.
+    ///
+    /// By default, this option is set to 4.
+    #[clap(long, default_value_t = MAX_USER_OPERATIONS_PER_SENDER)]
+    pub max_bundle_ops_per_sender: usize,
+
+    /// Indicates whether bundles are only simulated against the entry point and never submitted.
+    #[clap(long)]
+    pub dry_run: bool,
+
+    /// Sets how the fees of the bundle transactions are set: estimated by the Ethereum execution
+    /// client (`provider`) or targeting the inclusion in the next block (`next-block`), capped by
+    /// the fees of the user operations in the bundle.
+    ///
+    /// By default, this option is set to `provider`.
+    #[clap(long, default_value = "provider", value_parser=parse_fee_strategy)]
+    pub bundle_fee_strategy: FeeStrategy,
+
+    /// Address of the multicall contract (Multicall3 `aggregate3`, deployed by the operator) used
+    /// to send the bundles of all entry points in one transaction.
+    ///
+    /// By default, each entry point is bundled in a separate transaction.
+    #[clap(long, value_parser=parse_address)]
+    pub multicall: Option<Address>,
+
+    /// The maximum estimated loss (in wei) of a bundle, i.e., how much more the bundle transaction
+    /// may cost than the fees the user operations refund to the beneficiary. The lowest priority
+    /// user operations of the bundles losing more are trimmed (they stay in the mempool).
+    ///
+    /// By default, the unprofitable bundles are sent (and their profit is not estimated).
+    #[clap(long, value_parser=parse_u256)]
+    pub max_bundle_loss: Option<U256>,
+
+    /// The number of block confirmations after which a mined bundle is considered final (the
+    /// block including the bundle counts as the first one). The bundle receipt is only returned
+    /// and the bundled user operations are only notified once the bundle is confirmed, until then
+    /// the user operations are restored into the mempool if the bundle is reorged out.
+    ///
+    /// By default, this option is set to 1.
+    #[clap(long, default_value_t = BUNDLE_CONFIRMATIONS, value_parser = clap::value_parser!(u64).range(1..=REORG_DEPTH as i64))]
+    pub confirmations: u64,
+
+    /// The deposit (in wei) of the bundler's signer in the entry points below which the deposit
+    /// is topped up to `--deposit-top-up-target` with a `depositTo` transaction.
+    ///
+    /// By default, the deposit is not topped up.
+    #[clap(long, value_parser=parse_u256, requires = "deposit_top_up_target")]
+    pub deposit_top_up_threshold: Option<U256>,
+
+    /// The amount (in wei) the deposit of the bundler's signer in the entry points is topped up
+    /// to once it's below `--deposit-top-up-threshold`.
+    #[clap(long, value_parser=parse_u256, requires = "deposit_top_up_threshold")]
+    pub deposit_top_up_target: Option<U256>,
 }
 
 /// UoPool CLI args
@@ -92,35 +269,258 @@ pub struct UoPoolArgs {
     pub datadir: Option<ExpandedPathBuf>,
 
     /// The storage type which is used for mempool and repution
-    /// Currently, silius support `databse` and `memory` type
+    /// Currently, silius support `databse`, `memory` and `rocks-db` type
     #[clap(value_enum, default_value_t = StorageType::Database)]
     pub storage_type: StorageType,
 
     /// Max allowed verification gas.
-    #[clap(long, default_value="5000000", value_parser=parse_u256)]
+    ///
+    /// By default, this option is set to 5000000.
+    #[clap(long, default_value_t = U256::from(MAX_VERIFICATION_GAS), value_parser=parse_u256)]
     pub max_verification_gas: U256,
 
+    /// Max allowed pre-verification gas as a percentage of the calculated pre-verification gas
+    /// (e.g., 200 allows twice the calculated value).
+    ///
+    /// By default, the pre-verification gas is not bounded.
+    #[clap(long)]
+    pub max_pre_verification_gas_perc: Option<u64>,
+
     /// Minimum stake required for entities.
     #[clap(long, value_parser=parse_u256, default_value = "1")]
     pub min_stake: U256,
 
-    /// Minimum priority fee per gas.
-    #[clap(long, value_parser=parse_u256, default_value = "0")]
-    pub min_priority_fee_per_gas: U256,
+    /// Minimum max fee per gas as a percentage of the current base fee per gas (e.g., 80 accepts
+    /// user operations paying at least 80% of the base fee).
+    ///
+    /// By default, this option is set to 100.
+    #[clap(long, default_value_t = MIN_BASE_FEE_PERC)]
+    pub min_base_fee_perc: u64,
+
+    /// Percentage by which the base fee floor is lowered for staked senders.
+    ///
+    /// By default, this option is set to 0.
+    #[clap(long, default_value_t = STAKED_GRACE_PERC)]
+    pub staked_grace_perc: u64,
+
+    /// Max allowed size of the encoded user operation (in bytes).
+    ///
+    /// By default, this option is set to 131072 (128 KiB).
+    #[clap(long, default_value_t = MAX_UO_SIZE)]
+    pub max_uo_size: usize,
+
+    /// Percentage increase of fees required to replace a user operation in the mempool.
+    ///
+    /// By default, this option is set to 10.
+    #[clap(long, default_value_t = GAS_INCREASE_PERC)]
+    pub gas_increase_perc: u64,
+
+    /// How a user operation with the same sender, nonce and fees as a user operation in the
+    /// mempool is handled: rejected as a replacement whose fees aren't bumped (`reject`) or
+    /// accepted if only the signature changed (`signature-refresh`).
+    ///
+    /// By default, this option is set to `reject`.
+    #[clap(long, default_value = "reject", value_parser=parse_equal_fee_replacement)]
+    pub equal_fee_replacement: EqualFeeReplacement,
+
+    /// Max number of user operations an unstaked sender can have in the mempool.
+    ///
+    /// By default, this option is set to 4.
+    #[clap(long, default_value_t = SAME_SENDER_MEMPOOL_COUNT)]
+    pub max_uos_per_sender: usize,
 
     /// Addresses of whitelisted entities.
     #[clap(long, value_delimiter=',', value_parser = parse_address)]
     pub whitelist: Vec<Address>,
 
+    /// Addresses of blocklisted entities, the user operations with a blocklisted sender, factory
+    /// or paymaster are refused (unless the entity is also whitelisted).
+    #[clap(long, value_delimiter=',', value_parser = parse_address)]
+    pub blocklist: Vec<Address>,
+
+    /// Addresses of the paymasters allowed to sponsor user operations (sponsored-only mode), the
+    /// user operations with any other paymaster are refused.
+    ///
+    /// By default, any paymaster is allowed. The allowlist can be updated at runtime through the
+    /// admin methods of the uopool gRPC service.
+    #[clap(long, value_delimiter=',', value_parser = parse_address)]
+    pub paymaster_allowlist: Vec<Address>,
+
+    /// Whether the user operations without a paymaster are refused.
+    #[clap(long)]
+    pub require_paymaster: bool,
+
+    /// Addresses of the trusted senders, whose user operations are always bundled first
+    /// regardless of their fees.
+    ///
+    /// The trusted senders can be updated at runtime through the admin methods of the uopool gRPC
+    /// service.
+    #[clap(long, value_delimiter=',', value_parser = parse_address)]
+    pub trusted_senders: Vec<Address>,
+
     /// User operation mempool mode
     #[clap(long, default_value = "standard", value_parser=parse_uopool_mode)]
     pub uopool_mode: UoPoolMode,
 
+    /// Indicates whether the validation is not traced, i.e., only the sanity checks and the
+    /// checks of the `simulateValidation` result are performed (the same as `--uopool-mode
+    /// unsafe`). Meant for trusted environments and for Ethereum execution clients without
+    /// `debug_traceCall`, the opcode and storage access rules are NOT enforced.
+    #[clap(long = "unsafe")]
+    pub unsafe_mode: bool,
+
+    /// Time (in seconds) after which a user operation that was not bundled is evicted from the
+    /// mempool, counted from the time it was added to the mempool.
+    ///
+    /// By default, the user operations are never evicted.
+    #[clap(long)]
+    pub uo_ttl: Option<u64>,
+
+    /// Max number of user operations in the mempool. Once the mempool is full, a new user
+    /// operation is only admitted if its effective gas price exceeds the lowest one in the
+    /// mempool, which is evicted (the user operations of staked senders are not evicted).
+    ///
+    /// By default, the mempool is unbounded.
+    #[clap(long)]
+    pub max_uos: Option<usize>,
+
+    /// Number of consecutive re-validations (on request or right before bundling) a user
+    /// operation may fail because of a possibly transient condition (e.g., the paymaster deposit
+    /// is too low) before it's evicted. Such a user operation is kept but not bundled meanwhile,
+    /// the permanent failures always evict the user operation immediately.
+    ///
+    /// By default, the user operations are evicted on the first failure.
+    #[clap(long, default_value_t = 0)]
+    pub max_transient_failures: u64,
+
+    /// Number of failed bundle attempts a user operation may be kept in the mempool for before
+    /// it's evicted and the entity that caused the failure (if the `FailedOp` reason names it) is
+    /// penalized as if it crashed `handleOps`. Unlike the transient failures, the attempts aren't
+    /// reset when the user operation passes the validation again.
+    ///
+    /// Only the user operations kept by the transient failure grace are attempted again, so this
+    /// has no effect unless `--max-transient-failures` is set above 0.
+    ///
+    /// By default, the number of attempts is unlimited.
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_bundle_attempts: Option<u64>,
+
+    /// Max number of user operations validated concurrently (the user operations of one sender
+    /// are always validated one after another).
+    ///
+    /// By default, this option is set to 16.
+    #[clap(long, default_value_t = VALIDATION_CONCURRENCY)]
+    pub validation_concurrency: usize,
+
+    /// Order in which the user operations are bundled: by max priority fee per gas
+    /// (`priority-fee`), by effective gas price (`gas-price`), by arrival in the mempool (`fifo`)
+    /// or by expected profit (`profitability`).
+    ///
+    /// By default, the user operations are sorted by max priority fee per gas.
+    #[clap(long, default_value = "priority-fee", value_parser=parse_sort_strategy)]
+    pub sort_strategy: SortStrategy,
+
+    /// Max number of contract codes cached by the sanity checks until the next block.
+    ///
+    /// By default, the codes are not cached.
+    #[clap(long)]
+    pub code_cache_size: Option<usize>,
+
+    /// Block number the sanity checks read the chain state at (e.g., for reproducible validation
+    /// or for replaying user operations against historical state).
+    ///
+    /// By default, the latest block is used.
+    #[clap(long)]
+    pub validation_block: Option<u64>,
+
+    /// Time (in milliseconds) the sanity checks wait for each call to the Ethereum execution
+    /// client before failing the validation.
+    ///
+    /// By default, this option is set to 5000.
+    #[clap(long, default_value_t = RPC_TIMEOUT)]
+    pub validation_rpc_timeout: u64,
+
+    /// Enables the admin methods of the uopool gRPC service (e.g., evicting a user operation from
+    /// the mempool).
+    ///
+    /// By default, the admin methods are disabled.
+    #[clap(long)]
+    pub enable_admin: bool,
+
+    /// Tracer used to trace the validation of the user operations: the JavaScript tracer (`js`),
+    /// the native `bundlerCollectorTracer` (`native`) or any native tracer with the same output
+    /// (`native:<name>`), depending on the support of the Ethereum execution client.
+    #[clap(long, default_value = "js", value_parser=parse_validation_tracer)]
+    pub validation_tracer: ValidationTracer,
+
+    /// Opcodes the entities are forbidden to use during the validation of the user operations of
+    /// EntryPoint v0.6.
+    ///
+    /// By default, the opcodes forbidden by ERC-4337 are used.
+    #[clap(long = "forbidden-opcodes-v06", value_delimiter = ',')]
+    pub forbidden_opcodes_v06: Vec<String>,
+
+    /// Opcodes the entities are forbidden to use during the validation of the user operations of
+    /// EntryPoint v0.7.
+    ///
+    /// By default, the opcodes forbidden by ERC-4337 are used.
+    #[clap(long = "forbidden-opcodes-v07", value_delimiter = ',')]
+    pub forbidden_opcodes_v07: Vec<String>,
+
     /// P2P configuration
     #[clap(flatten)]
     pub p2p_opts: P2PArgs,
 }
 
+impl UoPoolArgs {
+    /// Gets the effective user operation mempool mode.
+    ///
+    /// # Returns
+    /// * `UoPoolMode` - `Unsafe` if the unsafe mode is enabled, otherwise the configured mode
+    pub fn mode(&self) -> UoPoolMode {
+        if self.unsafe_mode {
+            UoPoolMode::Unsafe
+        } else {
+            self.uopool_mode
+        }
+    }
+
+    /// Collects the configured forbidden opcodes by the entry point version.
+    ///
+    /// # Returns
+    /// * `HashMap<EntryPointVersion, HashSet<String>>` - The forbidden opcodes (the versions
+    ///   without configured opcodes are missing)
+    pub fn forbidden_opcodes(&self) -> HashMap<EntryPointVersion, HashSet<String>> {
+        [
+            (EntryPointVersion::V0_6, &self.forbidden_opcodes_v06),
+            (EntryPointVersion::V0_7, &self.forbidden_opcodes_v07),
+        ]
+        .into_iter()
+        .filter(|(_, opcodes)| !opcodes.is_empty())
+        .map(|(version, opcodes)| {
+            (version, opcodes.iter().map(|opcode| opcode.to_uppercase()).collect())
+        })
+        .collect()
+    }
+
+    /// Creates the [PaymasterAllowlist](PaymasterAllowlist) of the paymasters allowed to sponsor
+    /// user operations.
+    ///
+    /// # Returns
+    /// * `PaymasterAllowlist` - The [PaymasterAllowlist](PaymasterAllowlist)
+    pub fn paymaster_allowlist(&self) -> PaymasterAllowlist {
+        PaymasterAllowlist::new(self.paymaster_allowlist.clone(), self.require_paymaster)
+    }
+
+    /// Creates the [TrustedSenders](TrustedSenders) whose user operations are bundled first.
+    ///
+    /// # Returns
+    /// * `TrustedSenders` - The [TrustedSenders](TrustedSenders)
+    pub fn trusted_senders(&self) -> TrustedSenders {
+        TrustedSenders::new(self.trusted_senders.clone())
+    }
+}
+
 /// Common CLI args for bundler and uopool
 #[derive(Debug, Clone, Parser, PartialEq)]
 pub struct BundlerAndUoPoolArgs {
@@ -128,6 +528,12 @@ pub struct BundlerAndUoPoolArgs {
     #[clap(long, default_value = "http://127.0.0.1:8545")]
     pub eth_client_address: String,
 
+    /// Fallback Ethereum execution client RPC endpoints (HTTP only), separated by commas. The
+    /// requests fail over to the healthy endpoint with the lowest latency when the main endpoint
+    /// is not available.
+    #[clap(long, value_delimiter = ',')]
+    pub eth_client_fallback_addresses: Vec<String>,
+
     /// Chain information.
     #[clap(long)]
     pub chain: Option<NamedChain>,
@@ -136,14 +542,87 @@ pub struct BundlerAndUoPoolArgs {
     #[clap(long, value_delimiter=',', value_parser=parse_address)]
     pub entry_points: Vec<Address>,
 
+    /// Versions of the entry points deployed at non-canonical addresses (e.g., custom or testnet
+    /// deployments), in the form of `entry_point=version` separated by commas.
+    #[clap(long, value_delimiter=',', value_parser=parse_entry_point_version)]
+    pub entry_point_versions: Vec<(Address, EntryPointVersion)>,
+
     /// Poll interval event filters and pending transactions in milliseconds.
     #[clap(long, default_value = "500", value_parser= parse_duration)]
     pub poll_interval: Duration,
 
+    /// Minimum priority fee per gas. The user operations paying a lower priority fee are rejected
+    /// by the mempool and the bundle transactions pay at least this priority fee.
+    ///
+    /// By default, this option is set to 0 (or the minimum priority fee of the selected chain
+    /// profile).
+    #[clap(long, value_parser=parse_u256)]
+    pub min_priority_fee_per_gas: Option<U256>,
+
+    /// Selects the built-in profile of the connected chain, which provides the defaults of the
+    /// minimum balance, the bundle interval, the minimum priority fee and the pre-verification gas
+    /// overhead that are not set explicitly.
+    ///
+    /// By default, this option is set to false, i.e., the generic defaults are used.
+    #[clap(long)]
+    pub chain_profile: bool,
+
+    /// Path to a JSON file with custom chain profiles by chain id (e.g., `{"1": {"minBalance":
+    /// "0x16345785d8a0000", "bundleInterval": 12}}`). The custom profile of the connected chain is
+    /// selected and takes precedence over the built-in profile.
+    #[clap(long)]
+    pub chain_profiles: Option<ExpandedPathBuf>,
+
     #[clap(flatten)]
     pub metrics: MetricsArgs,
 }
 
+impl BundlerAndUoPoolArgs {
+    /// Returns the Ethereum execution client RPC endpoints, the main endpoint first.
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The RPC endpoints.
+    pub fn eth_client_addresses(&self) -> Vec<String> {
+        std::iter::once(self.eth_client_address.clone())
+            .chain(self.eth_client_fallback_addresses.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the Ethereum execution client WebSockets endpoint. The fallback endpoints are only
+    /// supported over HTTP.
+    ///
+    /// # Returns
+    /// * `&str` - The WebSockets endpoint (an error if fallback endpoints are set).
+    pub fn ws_eth_client_address(&self) -> eyre::Result<&str> {
+        if !self.eth_client_fallback_addresses.is_empty() {
+            return Err(eyre::eyre!(
+                "--eth-client-fallback-addresses is only supported with an HTTP eth client address"
+            ));
+        }
+        Ok(&self.eth_client_address)
+    }
+
+    /// Returns the versions declared for the entry points deployed at non-canonical addresses.
+    ///
+    /// # Returns
+    /// * `HashMap<Address, EntryPointVersion>` - The declared versions by entry point.
+    pub fn declared_entry_point_versions(&self) -> HashMap<Address, EntryPointVersion> {
+        self.entry_point_versions.iter().cloned().collect()
+    }
+
+    /// Returns the chain profiles selected by `--chain-profile` and `--chain-profiles`.
+    ///
+    /// # Returns
+    /// * `ChainProfiles` - The selected chain profiles (none if neither option is set).
+    pub fn chain_profiles(&self) -> eyre::Result<ChainProfiles> {
+        let custom: HashMap<u64, ChainProfile> = match self.chain_profiles.as_ref() {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+        Ok(ChainProfiles::new(self.chain_profile, custom))
+    }
+}
+
 /// RPC CLI args
 #[derive(Debug, Clone, Parser, PartialEq)]
 pub struct RpcArgs {
@@ -210,6 +689,21 @@ pub struct RpcArgs {
     /// Ethereum execution client proxy HTTP RPC endpoint
     #[clap(long)]
     pub eth_client_proxy_address: Option<String>,
+
+    /// Max number of user operations each client submits through `eth_sendUserOperation` of the
+    /// HTTP RPC server within the rate limit window. The clients are identified by the `x-api-key`
+    /// header or by the IP address in the `x-forwarded-for` header set by the reverse proxy, the
+    /// other requests share one limit.
+    ///
+    /// By default, the submissions are not rate limited.
+    #[clap(long)]
+    pub rate_limit: Option<u64>,
+
+    /// Duration (in seconds) of the rate limit window.
+    ///
+    /// By default, this option is set to 60.
+    #[clap(long, default_value_t = RATE_LIMIT_WINDOW)]
+    pub rate_limit_window: u64,
 }
 
 impl RpcArgs {
@@ -231,6 +725,15 @@ impl RpcArgs {
     pub fn is_api_method_enabled(&self, method: &str) -> bool {
         self.http_api.contains(&method.into()) || self.ws_api.contains(&method.into())
     }
+
+    /// Creates the [RateLimiter](RateLimiter) of the user operations submitted by each client.
+    ///
+    /// # Returns
+    /// * `Option<RateLimiter>` - The [RateLimiter](RateLimiter) (`None` if not rate limited)
+    pub fn rate_limiter(&self) -> Option<RateLimiter> {
+        self.rate_limit
+            .map(|limit| RateLimiter::new(limit, Duration::from_secs(self.rate_limit_window)))
+    }
 }
 
 /// Create wallet CLI args
@@ -335,6 +838,22 @@ impl MetricsArgs {
     }
 }
 
+/// OpenTelemetry args (the export requires the `otel` feature)
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct OtelArgs {
+    /// Endpoint of the OpenTelemetry collector (OTLP over gRPC) the spans of the user operation
+    /// lifecycle (validation, mempool insertion, bundle build, submission and confirmation) are
+    /// exported to, e.g., `http://127.0.0.1:4317`.
+    ///
+    /// By default, the spans are not exported.
+    #[clap(long = "otel.endpoint", global = true)]
+    pub endpoint: Option<String>,
+
+    /// Name of the service the exported spans are reported under.
+    #[clap(long = "otel.service-name", global = true, default_value = "silius")]
+    pub service_name: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -366,19 +885,67 @@ mod tests {
                 mnemonic_file: PathBuf::from(
                     "~/.silius/0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990"
                 ),
-                beneficiary: Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990")
-                    .unwrap(),
-                min_balance: U256::from(100000000000000000_u64),
-                bundle_interval: 10,
+                beneficiaries: vec![Address::from_str(
+                    "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990"
+                )
+                .unwrap()],
+                beneficiary_strategy: BeneficiaryStrategy::RoundRobin,
+                min_balance: Some(U256::from(100000000000000000_u64)),
+                bundle_interval: Some(10),
+                bundle_intervals: vec![],
                 send_bundle_mode: SendStrategy::EthereumClient,
+                flashbots_relay_endpoint: None,
+                flashbots_key_file: None,
+                flashbots_target_blocks: FLASHBOTS_TARGET_BLOCKS,
+                flashbots_refund_percent: None,
+                flashbots_refund_recipient: None,
+                broadcast_endpoints: vec![],
+                broadcast_relay_endpoints: vec![],
                 bundler_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 bundler_port: 3002,
                 enable_access_list: false,
+                send_bundle_max_attempts: SEND_BUNDLE_MAX_ATTEMPTS,
+                send_bundle_retry_delay: SEND_BUNDLE_RETRY_DELAY,
+                receipt_timeout: RECEIPT_TIMEOUT,
+                stuck_bundle_blocks: STUCK_BUNDLE_BLOCKS,
+                max_bundle_size: None,
+                max_bundle_gas_perc: MAX_BUNDLE_GAS_PERC,
+                max_bundle_validation_gas_perc: MAX_BUNDLE_VALIDATION_GAS_PERC,
+                max_bundle_ops_per_sender: MAX_USER_OPERATIONS_PER_SENDER,
+                dry_run: false,
+                bundle_fee_strategy: FeeStrategy::Provider,
+                multicall: None,
+                max_bundle_loss: None,
+                confirmations: BUNDLE_CONFIRMATIONS,
+                deposit_top_up_threshold: None,
+                deposit_top_up_target: None,
             },
             BundlerArgs::try_parse_from(args).unwrap()
         );
     }
 
+    #[test]
+    fn bundler_args_with_multiple_beneficiaries() {
+        let args = vec![
+            "bundlerargs",
+            "--mnemonic-file",
+            "~/.silius/0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--beneficiary",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990,0x0000000071727De22E5E9d8BAf0edAc6f37da032",
+            "--beneficiary-strategy",
+            "lowest-balance",
+        ];
+        let args = BundlerArgs::try_parse_from(args).unwrap();
+        assert_eq!(
+            args.beneficiaries,
+            vec![
+                Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990").unwrap(),
+                Address::from_str("0x0000000071727De22E5E9d8BAf0edAc6f37da032").unwrap()
+            ]
+        );
+        assert_eq!(args.beneficiary_strategy, BeneficiaryStrategy::LowestBalance);
+    }
+
     #[test]
     fn bundler_and_uopool_args() {
         let args = vec![
@@ -389,17 +956,27 @@ mod tests {
             "holesky",
             "--entry-points",
             "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--entry-point-versions",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990=0.7.0",
             "--poll-interval",
             "5000",
         ];
         assert_eq!(
             BundlerAndUoPoolArgs {
                 eth_client_address: String::from("http://127.0.0.1:8545"),
+                eth_client_fallback_addresses: vec![],
                 chain: Some(NamedChain::Holesky),
                 entry_points: vec![
                     Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990").unwrap()
                 ],
+                entry_point_versions: vec![(
+                    Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990").unwrap(),
+                    EntryPointVersion::V0_7
+                )],
                 poll_interval: Duration::from_millis(5000),
+                min_priority_fee_per_gas: None,
+                chain_profile: false,
+                chain_profiles: None,
                 metrics: MetricsArgs {
                     enable_metrics: false,
                     custom_label_value: None,
@@ -411,6 +988,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ws_eth_client_address_with_fallbacks() {
+        let args = BundlerAndUoPoolArgs::try_parse_from([
+            "bundleranduopoolargs",
+            "--eth-client-address",
+            "ws://127.0.0.1:8546",
+        ])
+        .unwrap();
+        assert_eq!(args.ws_eth_client_address().unwrap(), "ws://127.0.0.1:8546");
+
+        let args = BundlerAndUoPoolArgs::try_parse_from([
+            "bundleranduopoolargs",
+            "--eth-client-address",
+            "ws://127.0.0.1:8546",
+            "--eth-client-fallback-addresses",
+            "http://127.0.0.1:8547",
+        ])
+        .unwrap();
+        assert!(args.ws_eth_client_address().is_err());
+    }
+
     #[test]
     fn rpc_args_when_http_and_ws_flag() {
         let args = vec![
@@ -447,6 +1045,8 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -479,6 +1079,8 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -511,6 +1113,8 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -542,6 +1146,8 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -562,6 +1168,8 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             }
             .is_enabled(),
             true
@@ -583,6 +1191,8 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             }
             .is_enabled(),
             true
@@ -604,6 +1214,8 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             }
             .is_enabled(),
             true
@@ -625,6 +1237,8 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                rate_limit: None,
+                rate_limit_window: RATE_LIMIT_WINDOW,
             }
             .is_enabled(),
             false
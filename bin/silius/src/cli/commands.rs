@@ -1,7 +1,10 @@
 use super::args::{
     BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, MetricsArgs, RpcArgs, UoPoolArgs,
 };
-use crate::bundler::{create_wallet, launch_bundler, launch_bundling, launch_rpc, launch_uopool};
+use crate::{
+    bundler::{create_wallet, launch_bundler, launch_bundling, launch_rpc, launch_uopool},
+    utils::wait_for_shutdown_signal,
+};
 use clap::{Parser, Subcommand};
 use ethers::types::Address;
 use silius_mempool::{
@@ -10,9 +13,10 @@ use silius_mempool::{
 };
 use silius_metrics::ethers::MetricsMiddleware;
 use silius_primitives::provider::{
-    create_http_block_streams, create_http_provider, create_ws_block_streams, create_ws_provider,
+    create_failover_provider, create_http_block_streams, create_ws_block_streams,
+    create_ws_provider,
 };
-use std::{future::pending, path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc};
 
 /// Start the bundler with all components (bundling component, user operation mempool, RPC server)
 #[derive(Debug, Parser)]
@@ -37,10 +41,12 @@ pub struct NodeCommand {
 impl NodeCommand {
     /// Execute the command
     pub async fn execute(self) -> eyre::Result<()> {
-        if self.common.eth_client_address.clone().starts_with("http") {
-            let http_client =
-                create_http_provider(&self.common.eth_client_address, self.common.poll_interval)
-                    .await?;
+        let bundling = if self.common.eth_client_address.clone().starts_with("http") {
+            let http_client = create_failover_provider(
+                &self.common.eth_client_addresses(),
+                self.common.poll_interval,
+            )
+            .await?;
             let eth_client = Arc::new(MetricsMiddleware::new(http_client));
 
             let block_streams =
@@ -54,9 +60,9 @@ impl NodeCommand {
                 eth_client,
                 block_streams,
             )
-            .await?;
+            .await?
         } else {
-            let http_client = create_ws_provider(&self.common.eth_client_address).await?;
+            let http_client = create_ws_provider(self.common.ws_eth_client_address()?).await?;
             let eth_client = Arc::new(MetricsMiddleware::new(http_client));
             let block_streams =
                 create_ws_block_streams(eth_client.clone(), self.common.entry_points.len()).await;
@@ -69,10 +75,12 @@ impl NodeCommand {
                 eth_client,
                 block_streams,
             )
-            .await?;
-        }
+            .await?
+        };
 
-        pending().await
+        // waits for the in-flight bundles once the shutdown signal is received
+        bundling.await?;
+        Ok(())
     }
 }
 
@@ -95,34 +103,46 @@ pub struct BundlerCommand {
 impl BundlerCommand {
     /// Execute the command
     pub async fn execute(self) -> eyre::Result<()> {
-        if self.common.eth_client_address.clone().starts_with("http") {
+        let bundling = if self.common.eth_client_address.clone().starts_with("http") {
             let eth_client = Arc::new(
-                create_http_provider(&self.common.eth_client_address, self.common.poll_interval)
-                    .await?,
+                create_failover_provider(
+                    &self.common.eth_client_addresses(),
+                    self.common.poll_interval,
+                )
+                .await?,
             );
             launch_bundling(
                 self.bundler,
                 eth_client,
                 self.common.chain,
-                self.common.entry_points,
+                self.common.entry_points.clone(),
+                self.common.declared_entry_point_versions(),
+                self.common.min_priority_fee_per_gas,
+                self.common.chain_profiles()?,
                 self.uopool_grpc_listen_address,
                 self.common.metrics,
             )
-            .await?;
+            .await?
         } else {
-            let eth_client = Arc::new(create_ws_provider(&self.common.eth_client_address).await?);
+            let eth_client =
+                Arc::new(create_ws_provider(self.common.ws_eth_client_address()?).await?);
             launch_bundling(
                 self.bundler,
                 eth_client,
                 self.common.chain,
-                self.common.entry_points,
+                self.common.entry_points.clone(),
+                self.common.declared_entry_point_versions(),
+                self.common.min_priority_fee_per_gas,
+                self.common.chain_profiles()?,
                 self.uopool_grpc_listen_address,
                 self.common.metrics,
             )
-            .await?;
-        }
+            .await?
+        };
 
-        pending().await
+        // waits for the in-flight bundles once the shutdown signal is received
+        bundling.await?;
+        Ok(())
     }
 }
 
@@ -143,8 +163,11 @@ impl UoPoolCommand {
     pub async fn execute(self) -> eyre::Result<()> {
         if self.common.eth_client_address.clone().starts_with("http") {
             let eth_client = Arc::new(
-                create_http_provider(&self.common.eth_client_address, self.common.poll_interval)
-                    .await?,
+                create_failover_provider(
+                    &self.common.eth_client_addresses(),
+                    self.common.poll_interval,
+                )
+                .await?,
             );
             let block_streams =
                 create_http_block_streams(eth_client.clone(), self.common.entry_points.len()).await;
@@ -153,12 +176,16 @@ impl UoPoolCommand {
                 eth_client,
                 block_streams,
                 self.common.chain,
-                self.common.entry_points,
+                self.common.entry_points.clone(),
+                self.common.declared_entry_point_versions(),
+                self.common.min_priority_fee_per_gas,
+                self.common.chain_profiles()?,
                 self.common.metrics,
             )
             .await?;
         } else {
-            let eth_client = Arc::new(create_ws_provider(&self.common.eth_client_address).await?);
+            let eth_client =
+                Arc::new(create_ws_provider(self.common.ws_eth_client_address()?).await?);
             let block_streams =
                 create_ws_block_streams(eth_client.clone(), self.common.entry_points.len()).await;
             launch_uopool(
@@ -166,13 +193,17 @@ impl UoPoolCommand {
                 eth_client,
                 block_streams,
                 self.common.chain,
-                self.common.entry_points,
+                self.common.entry_points.clone(),
+                self.common.declared_entry_point_versions(),
+                self.common.min_priority_fee_per_gas,
+                self.common.chain_profiles()?,
                 self.common.metrics,
             )
             .await?;
         }
 
-        pending().await
+        wait_for_shutdown_signal().await?;
+        Ok(())
     }
 }
 
@@ -206,7 +237,8 @@ impl RpcCommand {
             self.metrics,
         )
         .await?;
-        pending().await
+        wait_for_shutdown_signal().await?;
+        Ok(())
     }
 }
 
@@ -1,4 +1,5 @@
-use crate::utils::run_until_ctrl_c;
+use crate::telemetry::{init_tracing, shutdown_tracing};
+use args::OtelArgs;
 use clap::{value_parser, Parser, Subcommand};
 use std::panic;
 
@@ -16,6 +17,10 @@ pub struct Cli {
     /// The verbosity level
     #[clap(long, short, global = true, default_value_t = 2, value_parser = value_parser!(u8).range(..=4))]
     verbosity: u8,
+
+    /// The export of the spans to OpenTelemetry
+    #[clap(flatten)]
+    otel: OtelArgs,
 }
 
 impl Cli {
@@ -69,7 +74,6 @@ pub fn run() -> eyre::Result<()> {
         Err(_) => format!("silius={}", cli.get_log_level()),
     };
     std::env::set_var("RUST_LOG", rust_log);
-    tracing_subscriber::fmt::init();
 
     std::thread::Builder::new()
         .stack_size(128 * 1024 * 1024)
@@ -79,6 +83,9 @@ pub fn run() -> eyre::Result<()> {
                 .thread_stack_size(128 * 1024 * 1024)
                 .build()?;
 
+            // the spans are exported in the background by the runtime
+            rt.block_on(async { init_tracing(&cli.otel) })?;
+
             let task = async move {
                 match cli.command {
                     Commands::Node(command) => command.execute().await,
@@ -90,8 +97,10 @@ pub fn run() -> eyre::Result<()> {
                 }
             };
 
-            rt.block_on(run_until_ctrl_c(task))?;
-            Ok(())
+            // the commands run until the shutdown signal is received
+            let res = rt.block_on(task);
+            shutdown_tracing();
+            res
         })?
         .join()
         .unwrap_or_else(|e| panic::resume_unwind(e))
@@ -1,3 +1,4 @@
 pub mod bundler;
 pub mod cli;
+pub mod telemetry;
 pub mod utils;
@@ -0,0 +1,67 @@
+//! Logs and the export of the spans to OpenTelemetry (with the `otel` feature)
+
+use crate::cli::args::OtelArgs;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+/// Initializes the logs (filtered by `RUST_LOG`) and the export of the spans to the OpenTelemetry
+/// collector if its endpoint is configured. `RUST_LOG` only filters the logs, the spans of silius
+/// are exported up to the debug level. The spans are exported in batches by the Tokio runtime, so
+/// it has to be called within the runtime.
+///
+/// # Arguments
+/// * `otel` - The [OpenTelemetry args](OtelArgs)
+///
+/// # Returns
+/// * `eyre::Result<()>` - Error if the subscriber or the exporter couldn't be set up
+pub fn init_tracing(otel: &OtelArgs) -> eyre::Result<()> {
+    let registry = tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()));
+
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &otel.endpoint {
+        let tracer = otel_tracer(endpoint, &otel.service_name)?;
+        let filter =
+            tracing_subscriber::filter::Targets::new().with_target("silius", tracing::Level::DEBUG);
+        registry
+            .with(tracing_opentelemetry::layer().with_tracer(tracer).with_filter(filter))
+            .try_init()?;
+        return Ok(());
+    }
+
+    #[cfg(not(feature = "otel"))]
+    if otel.endpoint.is_some() {
+        return Err(eyre::eyre!(
+            "Spans can't be exported to OpenTelemetry, silius is built without the otel feature"
+        ));
+    }
+
+    registry.try_init()?;
+    Ok(())
+}
+
+/// Flushes the spans that weren't exported yet (no-op if the export is disabled)
+pub fn shutdown_tracing() {
+    #[cfg(feature = "otel")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+/// Creates the tracer that exports the spans to the OpenTelemetry collector over gRPC
+#[cfg(feature = "otel")]
+fn otel_tracer(
+    endpoint: &str,
+    service_name: &str,
+) -> eyre::Result<opentelemetry_sdk::trace::Tracer> {
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{runtime, trace, Resource};
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(endpoint))
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            service_name.to_string(),
+        )])))
+        .install_batch(runtime::Tokio)?;
+    Ok(tracer)
+}
@@ -2,10 +2,14 @@ use dirs::home_dir;
 use discv5::Enr;
 use ethers::types::{Address, U256};
 use expanded_pathbuf::ExpandedPathBuf;
-use pin_utils::pin_mut;
 use silius_metrics::label::LabelValue;
-use silius_primitives::{bundler::SendStrategy, UoPoolMode};
-use std::{future::Future, str::FromStr, time::Duration};
+use silius_primitives::{
+    bundler::{BeneficiaryStrategy, FeeStrategy, SendStrategy},
+    mempool::{EqualFeeReplacement, SortStrategy},
+    simulation::ValidationTracer,
+    EntryPointVersion, UoPoolMode,
+};
+use std::{str::FromStr, time::Duration};
 use tracing::info;
 
 /// Unwrap path or returns home directory
@@ -30,16 +34,63 @@ pub fn parse_u256(s: &str) -> Result<U256, String> {
     U256::from_str_radix(s, 10).map_err(|_| format!("String {s} is not a valid U256"))
 }
 
+/// Parses entry point bundle interval from string (in the form of `entry_point=interval`)
+pub fn parse_bundle_interval(s: &str) -> Result<(Address, u64), String> {
+    let (ep, interval) = s
+        .split_once('=')
+        .ok_or_else(|| format!("String {s} is not a valid entry_point=interval"))?;
+    let ep = parse_address(ep)?;
+    let interval: u64 = interval.parse().map_err(|_| format!("{interval} must be unsigned int"))?;
+    Ok((ep, interval))
+}
+
+/// Parses entry point version from string (in the form of `entry_point=version`)
+pub fn parse_entry_point_version(s: &str) -> Result<(Address, EntryPointVersion), String> {
+    let (ep, version) = s
+        .split_once('=')
+        .ok_or_else(|| format!("String {s} is not a valid entry_point=version"))?;
+    let ep = parse_address(ep)?;
+    let version = EntryPointVersion::from_str(version)?;
+    Ok((ep, version))
+}
+
 /// Parses SendBundleMode from string
 pub fn parse_send_bundle_mode(s: &str) -> Result<SendStrategy, String> {
     SendStrategy::from_str(s).map_err(|_| format!("String {s} is not a valid SendBundleMode"))
 }
 
+/// Parses BeneficiaryStrategy from string
+pub fn parse_beneficiary_strategy(s: &str) -> Result<BeneficiaryStrategy, String> {
+    BeneficiaryStrategy::from_str(s)
+        .map_err(|_| format!("String {s} is not a valid BeneficiaryStrategy"))
+}
+
+/// Parses FeeStrategy from string
+pub fn parse_fee_strategy(s: &str) -> Result<FeeStrategy, String> {
+    FeeStrategy::from_str(s).map_err(|_| format!("String {s} is not a valid FeeStrategy"))
+}
+
 /// Parses UoPoolMode from string
 pub fn parse_uopool_mode(s: &str) -> Result<UoPoolMode, String> {
     UoPoolMode::from_str(s).map_err(|_| format!("String {s} is not a valid UoPoolMode"))
 }
 
+/// Parses SortStrategy from string
+pub fn parse_sort_strategy(s: &str) -> Result<SortStrategy, String> {
+    SortStrategy::from_str(s).map_err(|_| format!("String {s} is not a valid SortStrategy"))
+}
+
+/// Parses EqualFeeReplacement from string
+pub fn parse_equal_fee_replacement(s: &str) -> Result<EqualFeeReplacement, String> {
+    EqualFeeReplacement::from_str(s)
+        .map_err(|_| format!("String {s} is not a valid EqualFeeReplacement"))
+}
+
+/// Parses ValidationTracer from string
+pub fn parse_validation_tracer(s: &str) -> Result<ValidationTracer, String> {
+    ValidationTracer::from_str(s).map_err(|_| format!("String {s} is not a valid ValidationTracer"))
+}
+
 /// Parses ENR record
 pub fn parse_enr(enr: &str) -> Result<Enr, String> {
     Enr::from_str(enr).map_err(|_| format!("Enr {enr} is not a valid enr."))
@@ -61,28 +112,20 @@ pub fn parse_label_value(label_value: &str) -> Result<LabelValue, String> {
     Ok(LabelValue::new(label.to_string(), value.to_string()))
 }
 
-/// Runs the future to completion or until:
+/// Waits until:
 /// - `ctrl-c` is received.
 /// - `SIGTERM` is received (unix only).
-pub async fn run_until_ctrl_c<F, E>(fut: F) -> Result<(), E>
-where
-    F: Future<Output = Result<(), E>>,
-    E: Send + Sync + 'static + From<std::io::Error>,
-{
-    let ctrl_c = tokio::signal::ctrl_c();
-
+pub async fn wait_for_shutdown_signal() -> std::io::Result<()> {
     let mut stream = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
-    let sigterm = stream.recv();
-    pin_mut!(sigterm, ctrl_c, fut);
 
     tokio::select! {
-        _ = ctrl_c => {
+        res = tokio::signal::ctrl_c() => {
+            res?;
             info!("Received ctrl-c signal.");
         },
-        _ = sigterm => {
+        _ = stream.recv() => {
             info!("Received SIGTERM signal.");
         },
-        res = fut => res?,
     }
 
     Ok(())
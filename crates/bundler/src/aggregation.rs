@@ -0,0 +1,147 @@
+use ethers::{
+    types::{Address, Bytes, H256},
+    utils::keccak256,
+};
+use silius_primitives::{UserOperation, UserOperationHash};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Number of lookups of the [AggregationCache](AggregationCache) that were served from the cache
+/// and that had to aggregate the signatures
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AggregationCacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that had to aggregate the signatures
+    pub misses: u64,
+}
+
+/// The signatures of a group of user operations of an aggregator passed to `handleAggregatedOps`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Aggregation {
+    /// The aggregated signature of the group
+    pub signature: Bytes,
+    /// The signature field of each user operation of the group, i.e., the value the aggregator
+    /// returned for it when validating its signature (`sigForUserOp`, usually empty)
+    pub uo_signatures: Vec<Bytes>,
+}
+
+/// The hashes of the user operations of a group (in the order they were aggregated) together with
+/// the hashes of their signatures (the hash of a user operation doesn't cover its signature)
+type Group = Vec<(UserOperationHash, H256)>;
+
+/// Caches the [Aggregation](Aggregation) of the group of user operations of each aggregator, so
+/// that the signatures of the same group are not aggregated again on every bundle attempt. The
+/// entry of an aggregator is invalidated once the group changes, including a changed signature of a
+/// user operation (e.g., a signature refresh). The clones share the same cache.
+#[derive(Clone, Debug, Default)]
+pub struct AggregationCache {
+    /// The group of user operations and its aggregation by aggregator
+    signatures: Arc<Mutex<HashMap<Address, (Group, Aggregation)>>>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl AggregationCache {
+    /// Returns the [Group](Group) of the user operations
+    fn group(uos: &[UserOperation]) -> Group {
+        uos.iter().map(|uo| (uo.hash, H256::from(keccak256(&uo.signature)))).collect()
+    }
+
+    /// Gets the cached [Aggregation](Aggregation) of the group of user operations
+    ///
+    /// # Arguments
+    /// * `aggregator` - The signature aggregator of the group
+    /// * `uos` - The user operations of the group
+    ///
+    /// # Returns
+    /// * `Option<Aggregation>` - The [Aggregation](Aggregation) (`None` if the group changed)
+    pub fn get(&self, aggregator: &Address, uos: &[UserOperation]) -> Option<Aggregation> {
+        let group = Self::group(uos);
+        let signatures = self.signatures.lock().expect("aggregation cache lock poisoned");
+        let aggregation = signatures
+            .get(aggregator)
+            .filter(|(group_prev, _)| *group_prev == group)
+            .map(|(_, aggregation)| aggregation.clone());
+
+        let counter = if aggregation.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        aggregation
+    }
+
+    /// Caches the [Aggregation](Aggregation) of the group of user operations (replaces the
+    /// aggregation of the previous group of the aggregator)
+    ///
+    /// # Arguments
+    /// * `aggregator` - The signature aggregator of the group
+    /// * `uos` - The user operations of the group
+    /// * `aggregation` - The [Aggregation](Aggregation) of the group
+    pub fn insert(&self, aggregator: Address, uos: &[UserOperation], aggregation: Aggregation) {
+        self.signatures
+            .lock()
+            .expect("aggregation cache lock poisoned")
+            .insert(aggregator, (Self::group(uos), aggregation));
+    }
+
+    /// Returns the number of cache hits and misses since the cache was created
+    pub fn stats(&self) -> AggregationCacheStats {
+        AggregationCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::UserOperationSigned;
+
+    #[test]
+    fn aggregation_cache_invalidated_on_group_change() {
+        let cache = AggregationCache::default();
+        let aggregator = Address::random();
+        let uo = |signature: u8| {
+            UserOperation::from_user_operation_signed(
+                UserOperationHash(H256::random()),
+                UserOperationSigned {
+                    signature: Bytes::from(vec![signature; 65]),
+                    ..Default::default()
+                },
+            )
+        };
+        let (first, second) = (uo(1), uo(2));
+        let aggregation =
+            Aggregation { signature: Bytes::from(vec![1; 96]), uo_signatures: vec![Bytes::new()] };
+
+        assert_eq!(cache.get(&aggregator, &[first.clone()]), None);
+        cache.insert(aggregator, &[first.clone()], aggregation.clone());
+        assert_eq!(cache.get(&aggregator, &[first.clone()]), Some(aggregation.clone()));
+
+        // the group changed
+        assert_eq!(cache.get(&aggregator, &[first.clone(), second.clone()]), None);
+        assert_eq!(cache.get(&Address::random(), &[first.clone()]), None);
+
+        // the signature of a user operation changed (the hash stays the same)
+        let refreshed = UserOperation::from_user_operation_signed(
+            first.hash,
+            UserOperationSigned {
+                signature: Bytes::from(vec![3; 65]),
+                ..first.user_operation.clone()
+            },
+        );
+        assert_eq!(cache.get(&aggregator, &[refreshed]), None);
+
+        // the clones share the cache
+        cache.clone().insert(aggregator, &[first.clone(), second.clone()], aggregation.clone());
+        assert_eq!(cache.get(&aggregator, &[first.clone(), second]), Some(aggregation));
+        assert_eq!(cache.get(&aggregator, &[first]), None);
+
+        assert_eq!(cache.stats(), AggregationCacheStats { hits: 2, misses: 5 });
+    }
+}
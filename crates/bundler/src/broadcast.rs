@@ -0,0 +1,182 @@
+use crate::bundler::SendBundleOp;
+use ethers::types::{transaction::eip2718::TypedTransaction, H256};
+use silius_primitives::bundler::TransactionConditional;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use tracing::{debug, trace, warn};
+
+/// A client that sends the same bundle through several [clients](SendBundleOp) at once (e.g., the
+/// public mempool of the Ethereum execution client and private relays). The bundle transaction is
+/// fully populated before it's sent, so all the clients sign the same transaction and the first
+/// confirmed inclusion settles the bundle; the remaining submissions are redundant and dropped.
+#[derive(Clone)]
+pub struct BroadcastClient {
+    /// The clients the bundle is sent through (the first one is the primary client)
+    clients: Vec<Arc<dyn SendBundleOp>>,
+}
+
+#[async_trait::async_trait]
+impl SendBundleOp for BroadcastClient {
+    /// Send a bundle of [UserOperations](UserOperation) through all the clients
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// * `H256` - The transaction hash of the first confirmed inclusion
+    async fn send_bundle(&self, bundle: TypedTransaction) -> eyre::Result<H256> {
+        self.broadcast(bundle, None).await
+    }
+
+    /// Send a bundle of [UserOperations](UserOperation) that is only included if the conditions
+    /// hold through all the clients
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of [UserOperations](UserOperation)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `H256` - The transaction hash of the first confirmed inclusion
+    async fn send_bundle_conditional(
+        &self,
+        bundle: TypedTransaction,
+        conditional: TransactionConditional,
+    ) -> eyre::Result<H256> {
+        self.broadcast(bundle, Some(conditional)).await
+    }
+}
+
+impl BroadcastClient {
+    /// Create a broadcast client
+    ///
+    /// # Arguments
+    /// * `client` - The primary client (its error is returned if all the clients fail)
+    ///
+    /// # Returns
+    /// * `BroadcastClient` - A [BroadcastClient](BroadcastClient) with the primary client only
+    pub fn new(client: Arc<dyn SendBundleOp>) -> Self {
+        Self { clients: vec![client] }
+    }
+
+    /// Add a client the bundle is sent through as well
+    ///
+    /// # Arguments
+    /// * `client` - The additional client
+    ///
+    /// # Returns
+    /// * `BroadcastClient` - The [BroadcastClient](BroadcastClient) with the additional client
+    pub fn with_client(mut self, client: Arc<dyn SendBundleOp>) -> Self {
+        self.clients.push(client);
+        self
+    }
+
+    /// Send the bundle through all the clients concurrently and return on the first success
+    async fn broadcast(
+        &self,
+        bundle: TypedTransaction,
+        conditional: Option<TransactionConditional>,
+    ) -> eyre::Result<H256> {
+        if let [client] = self.clients.as_slice() {
+            return send(client, bundle, conditional).await;
+        }
+
+        let mut ts: JoinSet<(usize, eyre::Result<H256>)> = JoinSet::new();
+        for (i, client) in self.clients.iter().cloned().enumerate() {
+            let (bundle, conditional) = (bundle.clone(), conditional.clone());
+            ts.spawn(async move { (i, send(&client, bundle, conditional).await) });
+        }
+
+        let mut errors = vec![];
+        while let Some(res) = ts.join_next().await {
+            match res {
+                Ok((i, Ok(tx_hash))) => {
+                    trace!("Bundle {tx_hash:?} included through client {i}");
+                    // dropping the join set aborts the redundant submissions
+                    return Ok(tx_hash);
+                }
+                Ok((i, Err(err))) if is_known_transaction(&err.to_string()) => {
+                    // the same transaction already arrived through another client
+                    debug!("Bundle already known to client {i}: {err:?}");
+                }
+                Ok((i, Err(err))) => {
+                    warn!("Sending bundle through client {i} failed: {err:?}");
+                    errors.push((i, err));
+                }
+                Err(err) => warn!("Sending bundle task failed: {err:?}"),
+            }
+        }
+
+        // none of the clients saw the bundle included, report the error of the primary client
+        // (or the first client that failed otherwise)
+        errors.sort_by_key(|(i, _)| *i);
+        Err(errors
+            .into_iter()
+            .next()
+            .map(|(_, err)| err)
+            .unwrap_or_else(|| eyre::eyre!("Bundle was not included by any client")))
+    }
+}
+
+/// Send the bundle through the client (with the conditions if any)
+async fn send(
+    client: &Arc<dyn SendBundleOp>,
+    bundle: TypedTransaction,
+    conditional: Option<TransactionConditional>,
+) -> eyre::Result<H256> {
+    match conditional {
+        Some(conditional) => client.send_bundle_conditional(bundle, conditional).await,
+        None => client.send_bundle(bundle).await,
+    }
+}
+
+/// Whether the error is caused by the transaction being already known to the client
+fn is_known_transaction(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    msg.contains("already known") ||
+        msg.contains("known transaction") ||
+        msg.contains("already imported")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Eip1559TransactionRequest;
+    use std::time::Duration;
+
+    struct MockClient {
+        res: Result<H256, String>,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl SendBundleOp for MockClient {
+        async fn send_bundle(&self, _bundle: TypedTransaction) -> eyre::Result<H256> {
+            tokio::time::sleep(self.delay).await;
+            self.res.clone().map_err(|err| eyre::eyre!(err))
+        }
+    }
+
+    fn mock(res: Result<H256, &str>, delay: u64) -> Arc<dyn SendBundleOp> {
+        Arc::new(MockClient {
+            res: res.map_err(|err| err.to_string()),
+            delay: Duration::from_millis(delay),
+        })
+    }
+
+    #[tokio::test]
+    async fn broadcast_first_inclusion() {
+        let tx_hash = H256::random();
+
+        // the relay includes the bundle while the public mempool already knows the transaction
+        let client = BroadcastClient::new(mock(Err("already known"), 0))
+            .with_client(mock(Ok(tx_hash), 10))
+            .with_client(mock(Ok(H256::random()), 1_000));
+        let bundle: TypedTransaction = Eip1559TransactionRequest::new().into();
+        assert_eq!(client.send_bundle(bundle.clone()).await.unwrap(), tx_hash);
+
+        // all the clients failed
+        let client = BroadcastClient::new(mock(Err("nonce too low"), 10))
+            .with_client(mock(Err("bundle not included"), 0));
+        assert_eq!(client.send_bundle(bundle).await.unwrap_err().to_string(), "nonce too low");
+    }
+}
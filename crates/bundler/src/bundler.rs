@@ -1,15 +1,43 @@
+use crate::{
+    aggregation::{Aggregation, AggregationCache},
+    error::BundleError,
+    multicall::{multicall_data, EntryPointBundle},
+    nonce::{NonceManager, PendingBundle},
+};
 use alloy_chains::Chain;
 use ethers::{
     providers::Middleware,
     signers::Signer,
     types::{
-        transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, H256, U256, U64,
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes,
+        Eip1559TransactionRequest, TransactionRequest, H256, I256, U256, U64,
+    },
+};
+use silius_contracts::{
+    entry_point::{EntryPointAPI, UserOpsPerAggregator},
+    AggregatorAPI, EntryPoint, EntryPointError,
+};
+use silius_primitives::{
+    bundler::{BeneficiaryStrategy, BundleInclusion, FeeStrategy, TransactionConditional},
+    constants::bundler::{
+        BASE_FEE_MAX_CHANGE_DENOMINATOR, BUNDLE_CONFIRMATIONS, ELASTICITY_MULTIPLIER,
+        MAX_BUNDLE_GAS_PERC, MAX_BUNDLE_VALIDATION_GAS_PERC, MAX_USER_OPERATIONS_PER_SENDER,
+        REPLACE_BUNDLE_GAS_INCREASE_PERC, SEND_BUNDLE_MAX_ATTEMPTS, SEND_BUNDLE_RETRY_DELAY,
+        STUCK_BUNDLE_BLOCKS, VALIDATION_GAS_OVERHEAD,
     },
+    simulation::StorageAccesses,
+    BundleSigner, EntryPointVersion, PackedUserOperation, UserOperation, UserOperationHash,
+    UserOperationSigned,
 };
-use silius_contracts::entry_point::EntryPointAPI;
-use silius_primitives::{UserOperation, UserOperationHash, Wallet};
-use std::sync::Arc;
-use tracing::{info, trace};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tracing::{field, info, instrument, trace, warn, Span};
 
 /// A trait for sending the bundler of user operations
 #[async_trait::async_trait]
@@ -22,6 +50,579 @@ pub trait SendBundleOp: Send + Sync + 'static {
     /// # Returns
     /// * `H256` - The hash
     async fn send_bundle(&self, bundle: TypedTransaction) -> eyre::Result<H256>;
+
+    /// Send a bundle of [UserOperations](UserOperation) that is only included if the conditions
+    /// hold. The conditions are ignored by default.
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of [UserOperations](UserOperation)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `H256` - The hash
+    async fn send_bundle_conditional(
+        &self,
+        bundle: TypedTransaction,
+        _conditional: TransactionConditional,
+    ) -> eyre::Result<H256> {
+        self.send_bundle(bundle).await
+    }
+}
+
+/// Retry policy used when sending a bundle fails with a transient error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts (including the first one)
+    pub max_attempts: u64,
+    /// Delay before the first retry, doubled on every subsequent retry
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a new retry policy
+    ///
+    /// # Arguments
+    /// * `max_attempts` - Maximum number of attempts (including the first one)
+    /// * `base_delay` - Delay before the first retry
+    ///
+    /// # Returns
+    /// * `Self` - A new `RetryPolicy` instance
+    pub fn new(max_attempts: u64, base_delay: Duration) -> Self {
+        Self { max_attempts: max_attempts.max(1), base_delay }
+    }
+
+    /// Returns the delay to wait after the given (1-indexed) failed attempt
+    pub fn delay(&self, attempt: u64) -> Duration {
+        let exp = attempt.saturating_sub(1).min(16) as u32;
+        self.base_delay.saturating_mul(2u32.pow(exp))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(SEND_BUNDLE_MAX_ATTEMPTS, Duration::from_millis(SEND_BUNDLE_RETRY_DELAY))
+    }
+}
+
+/// Limits on the size of a bundle, the user operations beyond the limits are deferred to the next
+/// bundle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BundleLimits {
+    /// Maximum number of user operations in a bundle (unlimited if not set)
+    pub max_user_operations: Option<usize>,
+    /// Maximum gas of a bundle as a percentage of the block gas limit
+    pub max_gas_perc: u64,
+    /// Maximum gas of the validation phase of a bundle as a percentage of the block gas limit
+    /// (only the gas of the whole bundle is limited if not set)
+    pub max_validation_gas_perc: Option<u64>,
+    /// Maximum number of user operations of the same sender in a bundle
+    pub max_user_operations_per_sender: usize,
+}
+
+impl BundleLimits {
+    /// Create new bundle limits
+    ///
+    /// # Arguments
+    /// * `max_user_operations` - Maximum number of user operations in a bundle
+    /// * `max_gas_perc` - Maximum gas of a bundle as a percentage of the block gas limit
+    /// * `max_user_operations_per_sender` - Maximum number of user operations of the same sender in
+    ///   a bundle
+    ///
+    /// # Returns
+    /// * `Self` - A new `BundleLimits` instance
+    pub fn new(
+        max_user_operations: Option<usize>,
+        max_gas_perc: u64,
+        max_user_operations_per_sender: usize,
+    ) -> Self {
+        Self {
+            max_user_operations,
+            max_gas_perc: max_gas_perc.clamp(1, 100),
+            max_validation_gas_perc: None,
+            max_user_operations_per_sender: max_user_operations_per_sender.max(1),
+        }
+    }
+
+    /// Sets the maximum gas of the validation phase of a bundle
+    ///
+    /// # Arguments
+    /// * `max_validation_gas_perc` - Maximum gas of the validation phase of a bundle as a
+    ///   percentage of the block gas limit
+    ///
+    /// # Returns
+    /// * `Self` - The `BundleLimits` instance
+    pub fn with_max_validation_gas_perc(mut self, max_validation_gas_perc: u64) -> Self {
+        self.max_validation_gas_perc = Some(max_validation_gas_perc.clamp(1, 100));
+        self
+    }
+
+    /// Returns the gas the user operation may use in the bundle (in v0.6, the verification gas
+    /// limit is counted three times if the user operation has a paymaster, as it also applies to
+    /// `postOp`, while v0.7 sets the paymaster gas limits separately)
+    fn gas(uo: &UserOperation, entry_point_version: &EntryPointVersion) -> U256 {
+        let verification_gas_limit = if uo.paymaster_and_data.is_empty() {
+            uo.verification_gas_limit
+        } else {
+            match entry_point_version {
+                EntryPointVersion::V0_6 => uo.verification_gas_limit.saturating_mul(3.into()),
+                EntryPointVersion::V0_7 => {
+                    let (verification_gas_limit, post_op_gas_limit) =
+                        PackedUserOperation::from(uo.user_operation.clone()).paymaster_gas_limits();
+                    uo.verification_gas_limit
+                        .saturating_add(verification_gas_limit)
+                        .saturating_add(post_op_gas_limit)
+                }
+            }
+        };
+        uo.pre_verification_gas
+            .saturating_add(verification_gas_limit)
+            .saturating_add(uo.call_gas_limit)
+    }
+
+    /// Returns the gas the validation phase of `handleOps` may use for the user operation (in
+    /// v0.6, the verification gas limit is counted twice if the user operation has a paymaster, as
+    /// it also applies to `validatePaymasterUserOp`, while v0.7 sets the paymaster verification
+    /// gas limit separately), including the overhead of the entry point
+    fn validation_gas(uo: &UserOperation, entry_point_version: &EntryPointVersion) -> U256 {
+        let verification_gas_limit = if uo.paymaster_and_data.is_empty() {
+            uo.verification_gas_limit
+        } else {
+            match entry_point_version {
+                EntryPointVersion::V0_6 => uo.verification_gas_limit.saturating_mul(2.into()),
+                EntryPointVersion::V0_7 => {
+                    let (verification_gas_limit, _) =
+                        PackedUserOperation::from(uo.user_operation.clone()).paymaster_gas_limits();
+                    uo.verification_gas_limit.saturating_add(verification_gas_limit)
+                }
+            }
+        };
+        verification_gas_limit.saturating_add(VALIDATION_GAS_OVERHEAD.into())
+    }
+
+    /// Truncates the user operations (sorted by priority) to the limits. A user operation that
+    /// doesn't fit is skipped and the bundle is filled with the next ones that fit (the following
+    /// user operations of its sender are skipped as well, so that no nonce gap is created). The
+    /// validation phase of all the user operations runs before any of them is executed, so its gas
+    /// is limited on its own (regardless of the call gas limits) to avoid bundles that run out of
+    /// gas during the validation.
+    ///
+    /// # Arguments
+    /// * `uos` - The [UserOperations](UserOperation) sorted by priority
+    /// * `block_gas_limit` - The gas limit of the latest block
+    /// * `entry_point_version` - The version of the entry point the bundle is sent to
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<UserOperation>)` - The user operations that fit in the bundle
+    ///   and the ones that don't
+    pub fn truncate(
+        &self,
+        uos: Vec<UserOperation>,
+        block_gas_limit: U256,
+        entry_point_version: &EntryPointVersion,
+    ) -> (Vec<UserOperation>, Vec<UserOperation>) {
+        let max_gas = block_gas_limit.saturating_mul(self.max_gas_perc.into()) / 100;
+        let max_validation_gas = self
+            .max_validation_gas_perc
+            .map_or(U256::MAX, |perc| block_gas_limit.saturating_mul(perc.into()) / 100);
+        let max_uos = self.max_user_operations.unwrap_or(usize::MAX);
+
+        let mut gas_total = U256::zero();
+        let mut validation_gas_total = U256::zero();
+        let mut skipped_senders = HashSet::new();
+        let mut fitting = vec![];
+        let mut truncated = vec![];
+        for uo in uos {
+            let gas_total_new = gas_total.saturating_add(Self::gas(&uo, entry_point_version));
+            let validation_gas_total_new =
+                validation_gas_total.saturating_add(Self::validation_gas(&uo, entry_point_version));
+            if fitting.len() >= max_uos ||
+                skipped_senders.contains(&uo.sender) ||
+                gas_total_new > max_gas ||
+                validation_gas_total_new > max_validation_gas
+            {
+                skipped_senders.insert(uo.sender);
+                truncated.push(uo);
+                continue;
+            }
+
+            gas_total = gas_total_new;
+            validation_gas_total = validation_gas_total_new;
+            fitting.push(uo);
+        }
+
+        (fitting, truncated)
+    }
+
+    /// Limits the number of user operations of the same sender, so that one sender can't fill the
+    /// whole bundle. The user operations of each sender are kept in the order of priority.
+    ///
+    /// # Arguments
+    /// * `uos` - The [UserOperations](UserOperation) sorted by priority
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<UserOperation>)` - The user operations within the limit and the
+    ///   user operations beyond the limit
+    pub fn limit_per_sender(
+        &self,
+        uos: Vec<UserOperation>,
+    ) -> (Vec<UserOperation>, Vec<UserOperation>) {
+        let mut counts: HashMap<Address, usize> = HashMap::new();
+        uos.into_iter().partition(|uo| {
+            let count = counts.entry(uo.sender).or_default();
+            *count += 1;
+            *count <= self.max_user_operations_per_sender
+        })
+    }
+}
+
+impl Default for BundleLimits {
+    fn default() -> Self {
+        Self::new(None, MAX_BUNDLE_GAS_PERC, MAX_USER_OPERATIONS_PER_SENDER)
+            .with_max_validation_gas_perc(MAX_BUNDLE_VALIDATION_GAS_PERC)
+    }
+}
+
+/// Automatic top-up of the deposit of the bundler's signer in the entry point (e.g., for the flows
+/// that require the signer to pay from its deposit)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositTopUp {
+    /// The deposit is topped up once it's below the threshold (in wei)
+    pub threshold: U256,
+    /// Amount the deposit is topped up to (in wei)
+    pub target: U256,
+}
+
+impl DepositTopUp {
+    /// Create a new deposit top-up configuration
+    ///
+    /// # Arguments
+    /// * `threshold` - The deposit is topped up once it's below the threshold (in wei)
+    /// * `target` - Amount the deposit is topped up to (in wei, at least the threshold)
+    ///
+    /// # Returns
+    /// * `Self` - A new `DepositTopUp` instance
+    pub fn new(threshold: U256, target: U256) -> Self {
+        Self { threshold, target: target.max(threshold) }
+    }
+
+    /// Returns the amount to deposit so that the deposit reaches the target, at most the available
+    /// balance of the signer (`None` if the deposit is not below the threshold or nothing is
+    /// available)
+    ///
+    /// # Arguments
+    /// * `deposit` - The current deposit (in wei)
+    /// * `available` - The balance of the signer that can be deposited (in wei)
+    ///
+    /// # Returns
+    /// * `Option<U256>` - The amount to deposit (in wei)
+    pub fn amount(&self, deposit: U256, available: U256) -> Option<U256> {
+        (deposit < self.threshold)
+            .then(|| (self.target - deposit).min(available))
+            .filter(|amount| !amount.is_zero())
+    }
+}
+
+/// Calculates the base fee of the next block from the parent block (EIP-1559)
+///
+/// # Arguments
+/// * `base_fee` - The base fee per gas of the parent block
+/// * `gas_used` - The gas used by the parent block
+/// * `gas_limit` - The gas limit of the parent block
+///
+/// # Returns
+/// * `U256` - The base fee per gas of the next block
+pub fn next_block_base_fee(base_fee: U256, gas_used: U256, gas_limit: U256) -> U256 {
+    let gas_target = gas_limit / ELASTICITY_MULTIPLIER;
+    if gas_target.is_zero() || gas_used == gas_target {
+        return base_fee;
+    }
+
+    if gas_used > gas_target {
+        let delta = base_fee.saturating_mul(gas_used - gas_target) /
+            gas_target /
+            BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_add(delta.max(U256::one()))
+    } else {
+        let delta = base_fee.saturating_mul(gas_target - gas_used) /
+            gas_target /
+            BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_sub(delta)
+    }
+}
+
+/// Splits off the user operations whose max fee per gas doesn't cover the base fee of the next
+/// block, so they are deferred instead of capping the fees of the bundle transaction below the
+/// base fee (the bundle would never be included)
+///
+/// # Arguments
+/// * `base_fee` - The base fee per gas of the next block
+/// * `uos` - The [UserOperations](UserOperation) sorted by priority
+///
+/// # Returns
+/// * `(Vec<UserOperation>, Vec<UserOperation>)` - The user operations that cover the base fee and
+///   the underpriced ones
+pub fn split_underpriced(
+    base_fee: U256,
+    uos: Vec<UserOperation>,
+) -> (Vec<UserOperation>, Vec<UserOperation>) {
+    uos.into_iter().partition(|uo| uo.max_fee_per_gas >= base_fee)
+}
+
+/// Calculates the fees of the bundle transaction targeting the inclusion in the next block, i.e.,
+/// the base fee of the next block plus the priority fee. The fees are capped by the lowest fees of
+/// the user operations, as the bundler would pay more than the user operations refund otherwise.
+/// The user operations that don't cover the base fee are deferred (see
+/// [split_underpriced](split_underpriced)), so they don't cap the fees below the base fee.
+///
+/// # Arguments
+/// * `base_fee` - The base fee per gas of the next block
+/// * `priority_fee` - The estimated priority fee per gas
+/// * `uos` - The [UserOperations](UserOperation) of the bundle
+///
+/// # Returns
+/// * `(U256, U256)` - The max fee per gas and the max priority fee per gas
+pub fn next_block_fees(base_fee: U256, priority_fee: U256, uos: &[UserOperation]) -> (U256, U256) {
+    let uos = uos.iter().filter(|uo| uo.max_fee_per_gas >= base_fee);
+    let max_fee_cap = uos.clone().map(|uo| uo.max_fee_per_gas).min().unwrap_or(U256::MAX);
+    let max_priority_fee_cap = uos.map(|uo| uo.max_priority_fee_per_gas).min().unwrap_or(U256::MAX);
+
+    let max_priority_fee = priority_fee.min(max_priority_fee_cap);
+    let max_fee = base_fee.saturating_add(max_priority_fee).min(max_fee_cap);
+
+    (max_fee, max_priority_fee.min(max_fee))
+}
+
+/// Estimates the profit of the bundle, i.e., the fees the user operations refund to the
+/// beneficiary minus the cost of the bundle transaction. The entry point charges each user
+/// operation for the gas it actually uses, so the gas of the bundle transaction is shared among
+/// the user operations in proportion to their gas limits.
+///
+/// # Arguments
+/// * `uos` - The [UserOperations](UserOperation) of the bundle
+/// * `gas` - The estimated gas of the bundle transaction
+/// * `base_fee` - The base fee per gas of the next block
+/// * `max_fee_per_gas` - The max fee per gas of the bundle transaction
+/// * `max_priority_fee_per_gas` - The max priority fee per gas of the bundle transaction
+/// * `entry_point_version` - The version of the entry point the bundle is sent to
+///
+/// # Returns
+/// * `I256` - The estimated profit (in wei), negative if the bundle is unprofitable
+pub fn bundle_profit(
+    uos: &[UserOperation],
+    gas: U256,
+    base_fee: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    entry_point_version: &EntryPointVersion,
+) -> I256 {
+    let gas_price = |max_fee: U256, max_priority_fee: U256| {
+        max_fee.min(base_fee.saturating_add(max_priority_fee))
+    };
+
+    let (uos_gas, uos_fees) = uos.iter().fold((U256::zero(), U256::zero()), |(gas, fees), uo| {
+        let uo_gas = BundleLimits::gas(uo, entry_point_version);
+        let uo_fees =
+            uo_gas.saturating_mul(gas_price(uo.max_fee_per_gas, uo.max_priority_fee_per_gas));
+        (gas.saturating_add(uo_gas), fees.saturating_add(uo_fees))
+    });
+
+    let refund =
+        if uos_gas.is_zero() { U256::zero() } else { gas.saturating_mul(uos_fees) / uos_gas };
+    let cost = gas.saturating_mul(gas_price(max_fee_per_gas, max_priority_fee_per_gas));
+
+    I256::try_from(refund)
+        .unwrap_or(I256::MAX)
+        .saturating_sub(I256::try_from(cost).unwrap_or(I256::MAX))
+}
+
+/// Finds the number of user operations (sorted by priority) that keep the loss of the bundle
+/// within the tolerance, i.e., how much of the underpriced tail has to be trimmed. The gas of the
+/// trimmed bundle is estimated in proportion to the gas limits of the remaining user operations.
+///
+/// # Arguments
+/// * `uos` - The [UserOperations](UserOperation) of the bundle
+/// * `gas` - The estimated gas of the bundle transaction
+/// * `base_fee` - The base fee per gas of the next block
+/// * `max_fee_per_gas` - The max fee per gas of the bundle transaction
+/// * `max_priority_fee_per_gas` - The max priority fee per gas of the bundle transaction
+/// * `max_loss` - The maximum loss (in wei) of the bundle
+/// * `entry_point_version` - The version of the entry point the bundle is sent to
+///
+/// # Returns
+/// * `usize` - The number of user operations to keep (0 if even the first one loses too much)
+pub fn profitable_len(
+    uos: &[UserOperation],
+    gas: U256,
+    base_fee: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    max_loss: U256,
+    entry_point_version: &EntryPointVersion,
+) -> usize {
+    let max_loss = I256::try_from(max_loss).unwrap_or(I256::MAX);
+    let uos_gas = uos.iter().fold(U256::zero(), |total, uo| {
+        total.saturating_add(BundleLimits::gas(uo, entry_point_version))
+    });
+
+    let mut prefix_gas = uos_gas;
+    for len in (1..=uos.len()).rev() {
+        let gas = if uos_gas.is_zero() { gas } else { gas.saturating_mul(prefix_gas) / uos_gas };
+        let profit = bundle_profit(
+            &uos[..len],
+            gas,
+            base_fee,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            entry_point_version,
+        );
+        if !profit.saturating_add(max_loss).is_negative() {
+            return len;
+        }
+        prefix_gas =
+            prefix_gas.saturating_sub(BundleLimits::gas(&uos[len - 1], entry_point_version));
+    }
+
+    0
+}
+
+/// Trims the bundles to the first user operations (in the order of the bundles)
+///
+/// # Arguments
+/// * `bundles` - The [EntryPointBundles](EntryPointBundle)
+/// * `len` - The number of user operations to keep
+fn trim_bundles(bundles: &mut Vec<EntryPointBundle>, len: usize) {
+    let mut left = len;
+    for bundle in bundles.iter_mut() {
+        bundle.uos.truncate(left);
+        left -= bundle.uos.len();
+    }
+    bundles.retain(|bundle| !bundle.uos.is_empty());
+}
+
+/// The estimated profit of a bundle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BundleProfit {
+    /// Estimated profit of the bundle (in wei)
+    profit: I256,
+    /// The number of user operations to keep if the bundle loses more than the tolerance
+    trimmed_len: Option<usize>,
+}
+
+/// A storage slot accessed by a user operation that was already accessed by another user operation
+/// of the same bundle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StorageConflict {
+    /// Hash of the deferred user operation
+    pub uo_hash: UserOperationHash,
+    /// Hash of the user operation in the bundle that accessed the slot first
+    pub conflicts_with: UserOperationHash,
+    /// Address of the contract
+    pub address: Address,
+    /// The storage slot
+    pub slot: H256,
+}
+
+/// Splits the user operations into the ones whose storage accesses don't conflict with a user
+/// operation with a higher priority and the conflicting ones. A user operation conflicts if it
+/// writes a slot that another one reads or writes, or reads a slot that another one writes (the
+/// slots that are only read don't conflict). The slots of the entry point are ignored, it keeps the
+/// deposits and nonces of the senders apart.
+///
+/// # Arguments
+/// * `uos` - The [UserOperations](UserOperation) sorted by priority
+/// * `storage_accesses` - The storage slots read and written during the validation of each user
+///   operation
+/// * `entry_point` - The entry point address
+///
+/// # Returns
+/// * `(Vec<UserOperation>, Vec<UserOperation>, Vec<StorageConflict>)` - The user operations to
+///   bundle, the deferred user operations and the conflicts that caused the deferral
+pub fn storage_conflicts(
+    uos: Vec<UserOperation>,
+    storage_accesses: &HashMap<UserOperationHash, StorageAccesses>,
+    entry_point: &Address,
+) -> (Vec<UserOperation>, Vec<UserOperation>, Vec<StorageConflict>) {
+    // the user operation that accessed the slot first and whether the slot was written
+    let mut accessed: HashMap<(Address, &str), (UserOperationHash, bool)> = HashMap::new();
+    let mut uos_valid = vec![];
+    let mut uos_deferred = vec![];
+    let mut conflicts = vec![];
+
+    for uo in uos {
+        let Some(accesses) = storage_accesses.get(&uo.hash) else {
+            uos_valid.push(uo);
+            continue;
+        };
+
+        let slots = accesses
+            .writes
+            .iter()
+            .map(|(address, slots)| (address, slots, true))
+            .chain(accesses.reads.iter().map(|(address, slots)| (address, slots, false)))
+            .filter(|(address, _, _)| *address != entry_point)
+            .flat_map(|(address, slots, write)| {
+                slots.iter().map(move |slot| ((*address, slot.as_str()), write))
+            })
+            .collect::<Vec<_>>();
+
+        let conflicts_uo = slots
+            .iter()
+            .filter_map(|((address, slot), write)| {
+                accessed
+                    .get(&(*address, *slot))
+                    .filter(|(_, write_prev)| *write || *write_prev)
+                    .map(|(conflicts_with, _)| StorageConflict {
+                        uo_hash: uo.hash,
+                        conflicts_with: *conflicts_with,
+                        address: *address,
+                        slot: U256::from_str_radix(slot.trim_start_matches("0x"), 16)
+                            .map(|slot| H256::from(<[u8; 32]>::from(slot)))
+                            .unwrap_or_default(),
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        if conflicts_uo.is_empty() {
+            for (slot, write) in slots {
+                let (_, write_prev) = accessed.entry(slot).or_insert((uo.hash, write));
+                *write_prev |= write;
+            }
+            uos_valid.push(uo);
+        } else {
+            conflicts.extend(conflicts_uo);
+            uos_deferred.push(uo);
+        }
+    }
+
+    (uos_valid, uos_deferred, conflicts)
+}
+
+/// The outcome of simulating a bundle without submitting it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BundleSimulation {
+    /// Hashes of the user operations that would be included in the bundle
+    pub uos: Vec<UserOperationHash>,
+    /// Estimated gas of the bundle transaction (not set if it reverts)
+    pub gas: Option<U256>,
+    /// Revert reason of the bundle transaction
+    pub revert_reason: Option<String>,
+}
+
+/// The outcome of sending a bundle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleResult {
+    /// Hash of the bundle transaction
+    pub tx_hash: H256,
+    /// Nonce of the bundle transaction (a replacement of a stuck bundle keeps the nonce)
+    pub nonce: U256,
+    /// Hashes of the user operations included in the bundle
+    pub uos: Vec<UserOperationHash>,
+    /// Estimated gas of the bundle transaction
+    pub estimated_gas: U256,
+    /// The address where the gas is refunded after execution
+    pub beneficiary: Address,
+    /// Estimated profit of the bundle (in wei, only estimated if the maximum loss is set)
+    pub profit: Option<I256>,
 }
 
 /// The `Bundler` struct is used to represent a bundler with necessary properties
@@ -31,12 +632,18 @@ where
     M: Middleware + 'static,
     S: SendBundleOp,
 {
-    /// Bundler's wallet
-    pub wallet: Wallet,
-    /// Beneficiary address where the gas is refunded after execution
-    pub beneficiary: Address,
+    /// Signer of the bundle transactions (the local wallet or a remote signer)
+    pub signer: BundleSigner,
+    /// Beneficiary addresses where the gas is refunded after execution (never empty)
+    pub beneficiaries: Vec<Address>,
+    /// Strategy used to select the beneficiary of each bundle
+    pub beneficiary_strategy: BeneficiaryStrategy,
+    /// Index of the next beneficiary in the round-robin selection (shared by the clones)
+    beneficiary_index: Arc<AtomicUsize>,
     /// Entry point contract address
     pub entry_point: Address,
+    /// Version of the entry point contract
+    pub entry_point_version: EntryPointVersion,
     /// Chain the bundler is running on
     pub chain: Chain,
     /// Minimum balance required
@@ -47,6 +654,39 @@ where
     pub client: Arc<S>,
     /// Whether add access list into tx
     pub enable_access_list: bool,
+    /// Retry policy for sending bundles
+    pub retry_policy: RetryPolicy,
+    /// Auto bundling interval (in seconds) for this entry point, the global interval is used if
+    /// not set
+    pub bundle_interval: Option<u64>,
+    /// Local tracking of the wallet nonce (shared by the bundlers using the same wallet)
+    pub nonce_manager: NonceManager,
+    /// Number of blocks after which a submitted bundle that is not mined is replaced with a
+    /// higher gas price
+    pub stuck_bundle_blocks: u64,
+    /// Limits on the size of a bundle
+    pub bundle_limits: BundleLimits,
+    /// Whether bundles are only simulated and never submitted
+    pub dry_run: bool,
+    /// Strategy used to set the fees of the bundle transactions
+    pub fee_strategy: FeeStrategy,
+    /// Multicall contract used to send the bundles of multiple entry points in one transaction
+    /// (each entry point is bundled in a separate transaction if `None`)
+    pub multicall: Option<Address>,
+    /// Maximum estimated loss (in wei) of a bundle, the underpriced tail of the bundles losing
+    /// more is trimmed (the unprofitable bundles are sent if not set)
+    pub max_bundle_loss: Option<U256>,
+    /// Number of block confirmations after which a mined bundle is considered final
+    pub confirmations: u64,
+    /// Cache of the aggregated signatures of the groups of user operations (shared by the clones)
+    pub aggregation_cache: AggregationCache,
+    /// Minimum priority fee per gas of the bundle transactions (the mempool rejects the user
+    /// operations paying less)
+    pub min_priority_fee: U256,
+    /// Automatic top-up of the signer's deposit in the entry point (disabled if `None`)
+    pub deposit_top_up: Option<DepositTopUp>,
+    /// Nonce of the deposit top-up transaction that is not mined yet (shared by the clones)
+    deposit_top_up_nonce: Arc<Mutex<Option<U256>>>,
 }
 
 impl<M, S> Bundler<M, S>
@@ -60,7 +700,7 @@ where
     /// * `Self` - A new `Bundler` instance
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        wallet: Wallet,
+        signer: impl Into<BundleSigner>,
         beneficiary: Address,
         entry_point: Address,
         chain: Chain,
@@ -68,105 +708,1737 @@ where
         eth_client: Arc<M>,
         client: Arc<S>,
         enable_access_list: bool,
+        retry_policy: RetryPolicy,
+        bundle_interval: Option<u64>,
     ) -> Self {
         Self {
-            wallet,
-            beneficiary,
+            signer: signer.into(),
+            beneficiaries: vec![beneficiary],
+            beneficiary_strategy: BeneficiaryStrategy::default(),
+            beneficiary_index: Arc::new(AtomicUsize::new(0)),
             entry_point,
+            entry_point_version: EntryPointVersion::from_address(&entry_point),
             chain,
             min_balance,
             eth_client,
             client,
             enable_access_list,
+            retry_policy,
+            bundle_interval,
+            nonce_manager: NonceManager::default(),
+            stuck_bundle_blocks: STUCK_BUNDLE_BLOCKS,
+            bundle_limits: BundleLimits::default(),
+            dry_run: false,
+            fee_strategy: FeeStrategy::default(),
+            multicall: None,
+            max_bundle_loss: None,
+            confirmations: BUNDLE_CONFIRMATIONS,
+            aggregation_cache: AggregationCache::default(),
+            min_priority_fee: U256::zero(),
+            deposit_top_up: None,
+            deposit_top_up_nonce: Default::default(),
         }
     }
 
-    /// Functions that generates a bundle of user operations (i.e.,
-    /// [TypedTransaction](TypedTransaction)).
+    /// Sets the beneficiaries the gas is refunded to and the strategy used to select the
+    /// beneficiary of each bundle (an empty list keeps the current beneficiaries)
     ///
     /// # Arguments
-    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `beneficiaries` - The beneficiary addresses
+    /// * `strategy` - The [BeneficiaryStrategy](BeneficiaryStrategy)
     ///
     /// # Returns
-    /// * `TypedTransaction` - A [TypedTransaction](TypedTransaction)
-    async fn create_bundle(&self, uos: &[UserOperation]) -> eyre::Result<TypedTransaction> {
-        let ep = EntryPointAPI::new(self.entry_point, self.eth_client.clone());
+    /// * `Self` - The `Bundler` instance
+    pub fn with_beneficiaries(
+        mut self,
+        beneficiaries: Vec<Address>,
+        strategy: BeneficiaryStrategy,
+    ) -> Self {
+        if !beneficiaries.is_empty() {
+            self.beneficiaries = beneficiaries;
+        }
+        self.beneficiary_strategy = strategy;
+        self
+    }
 
-        let nonce =
-            self.eth_client.get_transaction_count(self.wallet.signer.address(), None).await?;
-        let balance = self.eth_client.get_balance(self.wallet.signer.address(), None).await?;
-        let beneficiary = if balance < self.min_balance {
-            self.wallet.signer.address()
-        } else {
-            self.beneficiary
-        };
+    /// Sets the [NonceManager](NonceManager), so that the nonce is shared with the other bundlers
+    /// using the same wallet
+    ///
+    /// # Arguments
+    /// * `nonce_manager` - The [NonceManager](NonceManager)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_nonce_manager(mut self, nonce_manager: NonceManager) -> Self {
+        self.nonce_manager = nonce_manager;
+        self
+    }
 
-        let mut tx: TypedTransaction = ep
-            .handle_ops(
-                uos.iter().cloned().map(|uo| uo.user_operation.into()).collect(),
-                beneficiary,
-            )
-            .tx;
+    /// Sets the number of blocks after which a submitted bundle that is not mined is replaced
+    ///
+    /// # Arguments
+    /// * `stuck_bundle_blocks` - Number of blocks
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_stuck_bundle_blocks(mut self, stuck_bundle_blocks: u64) -> Self {
+        self.stuck_bundle_blocks = stuck_bundle_blocks.max(1);
+        self
+    }
 
-        let accesslist = if self.enable_access_list {
-            let accesslist = self.eth_client.create_access_list(&tx, None).await?.access_list;
-            tx.set_access_list(accesslist.clone());
-            accesslist
-        } else {
-            Default::default()
-        };
-        let estimated_gas = self.eth_client.estimate_gas(&tx, None).await?;
+    /// Sets the limits on the size of a bundle
+    ///
+    /// # Arguments
+    /// * `bundle_limits` - The [BundleLimits](BundleLimits)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_bundle_limits(mut self, bundle_limits: BundleLimits) -> Self {
+        self.bundle_limits = bundle_limits;
+        self
+    }
 
-        let (max_fee_per_gas, max_priority_fee) =
-            self.eth_client.estimate_eip1559_fees(None).await?;
+    /// Sets whether bundles are only simulated and never submitted
+    ///
+    /// # Arguments
+    /// * `dry_run` - Whether the dry run mode is enabled
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
 
-        tx = TypedTransaction::Eip1559(Eip1559TransactionRequest {
-            to: tx.to().cloned(),
-            from: Some(self.wallet.signer.address()),
-            data: tx.data().cloned(),
-            chain_id: Some(U64::from(self.chain.id())),
-            max_priority_fee_per_gas: Some(max_priority_fee),
-            max_fee_per_gas: Some(max_fee_per_gas),
-            gas: Some(estimated_gas),
-            nonce: Some(nonce),
-            value: None,
-            access_list: accesslist,
-        });
+    /// Sets the strategy used to set the fees of the bundle transactions
+    ///
+    /// # Arguments
+    /// * `fee_strategy` - The [FeeStrategy](FeeStrategy)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_fee_strategy(mut self, fee_strategy: FeeStrategy) -> Self {
+        self.fee_strategy = fee_strategy;
+        self
+    }
 
-        Ok(tx)
+    /// Sets the version of the entry point contract, e.g., the declared version of an entry point
+    /// deployed at a non-canonical address
+    ///
+    /// # Arguments
+    /// * `entry_point_version` - The [EntryPointVersion](EntryPointVersion)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_entry_point_version(mut self, entry_point_version: EntryPointVersion) -> Self {
+        self.entry_point_version = entry_point_version;
+        self
     }
 
-    /// Send a bundle of [UserOperations](UserOperation)
+    /// Sets the multicall contract (deployed by the operator, e.g., Multicall3) used to send the
+    /// bundles of multiple entry points in one transaction
     ///
     /// # Arguments
-    /// * `uos` - An array of [UserOperations](UserOperation)
+    /// * `multicall` - The address of the multicall contract (disabled if `None`)
     ///
     /// # Returns
-    /// * `H256` - The hash
-    pub async fn send_bundle(&self, uos: &Vec<UserOperation>) -> eyre::Result<Option<H256>> {
+    /// * `Self` - The `Bundler` instance
+    pub fn with_multicall(mut self, multicall: Option<Address>) -> Self {
+        self.multicall = multicall;
+        self
+    }
+
+    /// Sets the maximum estimated loss of a bundle, i.e., how much more the bundle transaction may
+    /// cost than the user operations refund to the beneficiary
+    ///
+    /// # Arguments
+    /// * `max_bundle_loss` - The maximum loss in wei (the unprofitable bundles are sent if `None`)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_max_bundle_loss(mut self, max_bundle_loss: Option<U256>) -> Self {
+        self.max_bundle_loss = max_bundle_loss;
+        self
+    }
+
+    /// Sets the number of block confirmations after which a mined bundle is considered final
+    /// (i.e., the block including the bundle counts as the first confirmation)
+    ///
+    /// # Arguments
+    /// * `confirmations` - The number of confirmations (at least 1)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations.max(1);
+        self
+    }
+
+    /// Sets the minimum priority fee per gas of the bundle transactions, so that the bundler never
+    /// pays a lower priority fee than the one it collects from the user operations
+    ///
+    /// # Arguments
+    /// * `min_priority_fee` - The minimum priority fee per gas
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_min_priority_fee(mut self, min_priority_fee: U256) -> Self {
+        self.min_priority_fee = min_priority_fee;
+        self
+    }
+
+    /// Sets the automatic top-up of the signer's deposit in the entry point
+    ///
+    /// # Arguments
+    /// * `deposit_top_up` - The [DepositTopUp](DepositTopUp) (disabled if `None`)
+    ///
+    /// # Returns
+    /// * `Self` - The `Bundler` instance
+    pub fn with_deposit_top_up(mut self, deposit_top_up: Option<DepositTopUp>) -> Self {
+        self.deposit_top_up = deposit_top_up;
+        self
+    }
+
+    /// Splits off the user operations that do not target the bundler's entry point, i.e., whose
+    /// hash does not match the hash computed for the entry point and the chain. Bundling them
+    /// would make the whole `handleOps` call revert (e.g., v0.6 user operations sent to a v0.7
+    /// entry point).
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<UserOperation>)` - The user operations of the entry point and
+    ///   the user operations that target another entry point
+    pub fn split_foreign_user_operations(
+        &self,
+        uos: &[UserOperation],
+    ) -> (Vec<UserOperation>, Vec<UserOperation>) {
+        uos.iter().cloned().partition(|uo| {
+            uo.user_operation.hash_with_version(
+                &self.entry_point,
+                self.chain.id(),
+                self.entry_point_version,
+            ) == uo.hash
+        })
+    }
+
+    /// Truncates the user operations (sorted by priority) to the [BundleLimits](BundleLimits), so
+    /// the bundle (and its validation phase) does not exceed the gas budget and no sender has more
+    /// than the allowed number of user operations in it. The user operations beyond the limits and
+    /// the ones whose max fee per gas doesn't cover the base fee of the next block stay in the
+    /// mempool for the next bundle.
+    ///
+    /// # Arguments
+    /// * `uos` - The [UserOperations](UserOperation) sorted by priority
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<UserOperation>)` - The user operations to bundle and the
+    ///   deferred user operations
+    pub async fn limit_user_operations(
+        &self,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<(Vec<UserOperation>, Vec<UserOperation>)> {
         if uos.is_empty() {
-            info!("Skipping creating a new bundle, no user operations");
-            return Ok(None);
-        };
+            return Ok((uos, vec![]));
+        }
 
-        info!(
-            "Creating a new bundle with {} user operations: {:?}",
-            uos.len(),
-            uos.iter().map(|uo| uo.hash).collect::<Vec<UserOperationHash>>()
-        );
-        trace!("Bundle content: {uos:?}");
+        let base_fee = self.next_block_base_fee().await?;
+        let (uos, mut underpriced) = split_underpriced(base_fee, uos);
+        if !underpriced.is_empty() {
+            info!(
+                "Deferring {} user operations whose max fee per gas is below the base fee {base_fee}: {:?}",
+                underpriced.len(),
+                underpriced.iter().map(|uo| uo.hash).collect::<Vec<_>>()
+            );
+        }
 
-        let bundle = self.create_bundle(uos).await?;
-        let hash = self.client.send_bundle(bundle).await?;
+        let (uos, mut deferred) = self.bundle_limits.limit_per_sender(uos);
 
-        info!(
-            "Bundle successfully sent, hash: {:?}, account: {:?}, entry point: {:?}, beneficiary: {:?}",
-            hash,
-            self.wallet.signer.address(),
-            self.entry_point,
-            self.beneficiary
-        );
+        let block = self
+            .eth_client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or(eyre::format_err!("No block found"))?;
 
-        Ok(Some(hash))
+        let (uos, mut truncated) =
+            self.bundle_limits.truncate(uos, block.gas_limit, &self.entry_point_version);
+        truncated.append(&mut deferred);
+        truncated.append(&mut underpriced);
+        Ok((uos, truncated))
+    }
+
+    /// Detects the user operations whose storage accesses conflict with a user operation with a
+    /// higher priority (based on the storage slots accessed during the validation of each user
+    /// operation), so that they are deferred to the next bundle instead of invalidating each
+    /// other. The detection only runs if access lists are enabled.
+    ///
+    /// # Arguments
+    /// * `uos` - The [UserOperations](UserOperation) sorted by priority
+    /// * `storage_accesses` - The storage slots read and written during the validation of each user
+    ///   operation
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<UserOperation>, Vec<StorageConflict>)` - The user operations to
+    ///   bundle, the deferred user operations and the conflicts that caused the deferral
+    pub fn detect_storage_conflicts(
+        &self,
+        uos: Vec<UserOperation>,
+        storage_accesses: &HashMap<UserOperationHash, StorageAccesses>,
+    ) -> (Vec<UserOperation>, Vec<UserOperation>, Vec<StorageConflict>) {
+        if !self.enable_access_list || uos.len() < 2 {
+            return (uos, vec![], vec![]);
+        }
+
+        storage_conflicts(uos, storage_accesses, &self.entry_point)
+    }
+
+    /// Explains whether the user operation would be included in the next bundle, going through
+    /// the same steps as the bundle assembly (fees, storage conflicts and bundle limits) without
+    /// re-simulating the user operations or sending anything
+    ///
+    /// # Arguments
+    /// * `uo` - The [UserOperation](UserOperation) to explain
+    /// * `uos` - The [UserOperations](UserOperation) the mempool selected for the next bundle,
+    ///   sorted by priority
+    /// * `storage_accesses` - The storage slots read and written during the validation of each user
+    ///   operation
+    ///
+    /// # Returns
+    /// * `BundleInclusion` - Whether the user operation would be included and why not otherwise
+    pub async fn explain_user_operation(
+        &self,
+        uo: &UserOperation,
+        uos: Vec<UserOperation>,
+        storage_accesses: &HashMap<UserOperationHash, StorageAccesses>,
+    ) -> eyre::Result<BundleInclusion> {
+        let base_fee = self.next_block_base_fee().await?;
+        if uo.max_fee_per_gas < base_fee {
+            return Ok(BundleInclusion::FeeBelowFloor {
+                max_fee_per_gas: uo.max_fee_per_gas,
+                base_fee,
+            });
+        }
+
+        // the mempool selects one user operation per sender
+        if !uos.iter().any(|u| u.hash == uo.hash) {
+            return Ok(if uos.iter().any(|u| u.sender == uo.sender) {
+                BundleInclusion::SenderLimit
+            } else {
+                BundleInclusion::NotSelected
+            });
+        }
+
+        let (uos, _, conflicts) = self.detect_storage_conflicts(uos, storage_accesses);
+        if let Some(conflict) = conflicts.iter().find(|c| c.uo_hash == uo.hash) {
+            return Ok(BundleInclusion::StorageConflict {
+                conflicts_with: conflict.conflicts_with,
+            });
+        }
+
+        let (uos, deferred) = self.bundle_limits.limit_per_sender(uos);
+        if deferred.iter().any(|u| u.hash == uo.hash) {
+            return Ok(BundleInclusion::SenderLimit);
+        }
+
+        let block = self
+            .eth_client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .ok_or(eyre::format_err!("No block found"))?;
+        let (uos, _) = self.bundle_limits.truncate(uos, block.gas_limit, &self.entry_point_version);
+
+        Ok(if uos.iter().any(|u| u.hash == uo.hash) {
+            BundleInclusion::Included
+        } else {
+            BundleInclusion::BundleFull
+        })
+    }
+
+    /// Returns the deposit of the bundler's signer in the entry point
+    ///
+    /// # Returns
+    /// * `U256` - The deposit (in wei)
+    pub async fn deposit(&self) -> eyre::Result<U256> {
+        let ep = EntryPoint::new(self.eth_client.clone(), self.entry_point)
+            .with_version(self.entry_point_version);
+        Ok(ep.balance_of(&self.signer.address()).await?)
+    }
+
+    /// Tops up the deposit of the bundler's signer in the entry point to the target of the
+    /// [DepositTopUp](DepositTopUp) once it's below the threshold. The `depositTo` transaction
+    /// shares the nonce with the bundles. No other top-up is sent until it's mined, and the
+    /// signer's balance never drops below the minimum balance because of it.
+    ///
+    /// # Returns
+    /// * `Option<H256>` - The hash of the `depositTo` transaction (`None` if the top-up is
+    ///   disabled, the deposit is not below the threshold or the previous top-up is not mined yet)
+    pub async fn top_up_deposit(&self) -> eyre::Result<Option<H256>> {
+        let Some(deposit_top_up) = self.deposit_top_up else {
+            return Ok(None);
+        };
+
+        // the deposit doesn't reflect the top-up in flight (or its replacement) until it's mined
+        let in_flight = *self.deposit_top_up_nonce.lock().expect("top-up nonce lock poisoned");
+        if let Some(nonce) = in_flight {
+            let mined = self
+                .eth_client
+                .get_transaction_count(self.signer.address(), Some(BlockNumber::Latest.into()))
+                .await?;
+            if mined <= nonce {
+                trace!("Deposit top-up with nonce {nonce} is not mined yet");
+                return Ok(None);
+            }
+            *self.deposit_top_up_nonce.lock().expect("top-up nonce lock poisoned") = None;
+        }
+
+        let deposit = self.deposit().await?;
+        if deposit >= deposit_top_up.threshold {
+            return Ok(None);
+        }
+
+        let ep = EntryPointAPI::new(self.entry_point, self.eth_client.clone());
+        let mut tx: TypedTransaction =
+            ep.deposit_to(self.signer.address()).value(deposit_top_up.target - deposit).tx;
+        tx.set_from(self.signer.address());
+        let gas = self.eth_client.estimate_gas(&tx, None).await?;
+        let (max_fee_per_gas, max_priority_fee) = self.bundle_fees(&[]).await?;
+
+        let balance = self.eth_client.get_balance(self.signer.address(), None).await?;
+        let available = balance
+            .saturating_sub(self.min_balance)
+            .saturating_sub(gas.saturating_mul(max_fee_per_gas));
+        let Some(amount) = deposit_top_up.amount(deposit, available) else {
+            warn!(
+                "Deposit of the bundler's signer {:?} in entry point {:?} ({deposit}) is below the threshold ({}), but the balance ({balance}) is not above the minimum balance ({})",
+                self.signer.address(),
+                self.entry_point,
+                deposit_top_up.threshold,
+                self.min_balance
+            );
+            return Ok(None);
+        };
+        info!(
+            "Deposit of the bundler's signer {:?} in entry point {:?} ({deposit}) is below the threshold ({}), depositing {amount}",
+            self.signer.address(),
+            self.entry_point,
+            deposit_top_up.threshold
+        );
+        if self.dry_run {
+            return Ok(None);
+        }
+
+        let nonce = self.reserve_nonce().await?;
+        let tx = TypedTransaction::Eip1559(Eip1559TransactionRequest {
+            to: tx.to().cloned(),
+            from: Some(self.signer.address()),
+            data: tx.data().cloned(),
+            chain_id: Some(U64::from(self.chain.id())),
+            max_priority_fee_per_gas: Some(max_priority_fee),
+            max_fee_per_gas: Some(max_fee_per_gas),
+            gas: Some(gas),
+            nonce: Some(nonce),
+            value: Some(amount),
+            access_list: Default::default(),
+        });
+
+        match self.submit_bundle(nonce, tx, &None, &[]).await {
+            Ok(hash) => {
+                info!("Deposit top-up sent, hash: {hash:?}");
+                *self.deposit_top_up_nonce.lock().expect("top-up nonce lock poisoned") =
+                    Some(nonce);
+                Ok(Some(hash))
+            }
+            Err(err) => {
+                self.nonce_manager.release(nonce);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Reserves the wallet nonce for the next bundle. The pending nonce is fetched from the chain
+    /// only if no bundle is in flight, otherwise the nonce is incremented locally.
+    ///
+    /// # Returns
+    /// * `U256` - The nonce
+    async fn reserve_nonce(&self) -> eyre::Result<U256> {
+        let address = self.signer.address();
+        let mined = self
+            .eth_client
+            .get_transaction_count(address, Some(BlockNumber::Latest.into()))
+            .await?;
+        let pending = if self.nonce_manager.needs_sync() {
+            Some(
+                self.eth_client
+                    .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        Ok(self.nonce_manager.reserve(mined, pending))
+    }
+
+    /// Resubmits the bundles that were not mined for
+    /// [stuck_bundle_blocks](Bundler::stuck_bundle_blocks) blocks with the same nonce and a higher
+    /// gas price
+    ///
+    /// # Returns
+    /// * `Vec<U256>` - The nonces of the replaced bundles
+    pub async fn replace_stuck_bundles(&self) -> eyre::Result<Vec<U256>> {
+        if !self.nonce_manager.has_submitted() {
+            return Ok(vec![]);
+        }
+
+        let mined = self
+            .eth_client
+            .get_transaction_count(self.signer.address(), Some(BlockNumber::Latest.into()))
+            .await?;
+        let block = self.eth_client.get_block_number().await?;
+
+        let stuck = self.nonce_manager.stuck(mined, block, self.stuck_bundle_blocks);
+        if stuck.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let (max_fee_per_gas, max_priority_fee) =
+            self.eth_client.estimate_eip1559_fees(None).await?;
+        let bump = |fee: Option<U256>| {
+            let fee = fee.unwrap_or_default();
+            (fee * (100 + REPLACE_BUNDLE_GAS_INCREASE_PERC) / 100).max(fee + 1)
+        };
+
+        let mut nonces = vec![];
+        for (nonce, bundle) in stuck {
+            let mut tx = bundle.tx;
+            if let Some(req) = tx.as_eip1559_mut() {
+                req.max_priority_fee_per_gas =
+                    Some(bump(req.max_priority_fee_per_gas).max(max_priority_fee));
+                req.max_fee_per_gas = Some(bump(req.max_fee_per_gas).max(max_fee_per_gas));
+            }
+
+            warn!(
+                "Bundle with nonce {nonce} was not mined for {} blocks, replacing it with a higher gas price",
+                block.saturating_sub(bundle.block)
+            );
+
+            self.nonce_manager
+                .submit(nonce, PendingBundle { tx: tx.clone(), block, tx_hash: bundle.tx_hash });
+
+            let client = self.client.clone();
+            let nonce_manager = self.nonce_manager.clone();
+            tokio::spawn(async move {
+                match client.send_bundle(tx).await {
+                    Ok(hash) => {
+                        nonce_manager.sent(nonce, hash);
+                        info!("Replacement bundle with nonce {nonce} successfully sent, hash: {hash:?}");
+                    }
+                    Err(err) => {
+                        warn!("Sending replacement bundle with nonce {nonce} failed: {err:?}")
+                    }
+                }
+            });
+
+            nonces.push(nonce);
+        }
+
+        Ok(nonces)
+    }
+
+    /// Records the bundle as submitted with the reserved nonce and sends it
+    ///
+    /// # Arguments
+    /// * `nonce` - The reserved nonce
+    /// * `bundle` - The bundle [TypedTransaction](TypedTransaction)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// * `uo_hashes` - The hashes of the user operations in the order of the bundle
+    ///
+    /// # Returns
+    /// * `H256` - The hash
+    #[instrument(
+        name = "bundle_submission",
+        skip_all,
+        fields(nonce = %nonce, tx_hash = field::Empty)
+    )]
+    async fn submit_bundle(
+        &self,
+        nonce: U256,
+        bundle: TypedTransaction,
+        conditional: &Option<TransactionConditional>,
+        uo_hashes: &[UserOperationHash],
+    ) -> Result<H256, BundleError> {
+        let block = self
+            .eth_client
+            .get_block_number()
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+        self.nonce_manager
+            .submit(nonce, PendingBundle { tx: bundle.clone(), block, tx_hash: None });
+
+        let tx_hash = match conditional {
+            Some(conditional) => {
+                self.client.send_bundle_conditional(bundle, conditional.clone()).await
+            }
+            None => self.client.send_bundle(bundle).await,
+        }
+        .map_err(|err| BundleError::from_report::<M>(err, uo_hashes))?;
+        self.nonce_manager.sent(nonce, tx_hash);
+        Span::current().record("tx_hash", field::debug(tx_hash));
+        Ok(tx_hash)
+    }
+
+    /// Selects the beneficiary of the next bundle according to the
+    /// [BeneficiaryStrategy](BeneficiaryStrategy)
+    ///
+    /// # Returns
+    /// * `Address` - The beneficiary address
+    async fn select_beneficiary(&self) -> eyre::Result<Address> {
+        match self.beneficiary_strategy {
+            BeneficiaryStrategy::RoundRobin => {
+                let index = self.beneficiary_index.fetch_add(1, Ordering::Relaxed);
+                Ok(self.beneficiaries[index % self.beneficiaries.len()])
+            }
+            BeneficiaryStrategy::LowestBalance => {
+                let mut lowest = (self.beneficiaries[0], U256::MAX);
+                for beneficiary in self.beneficiaries.iter() {
+                    let balance = self.eth_client.get_balance(*beneficiary, None).await?;
+                    if balance < lowest.1 {
+                        lowest = (*beneficiary, balance);
+                    }
+                }
+                Ok(lowest.0)
+            }
+        }
+    }
+
+    /// Re-simulates the validation of the user operations right before they are bundled, as the
+    /// state could have changed since they were sorted (e.g., nonce consumed, paymaster out of
+    /// funds).
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// * `(Vec<UserOperation>, Vec<(UserOperation, String)>)` - The user operations that are still
+    ///   valid and the user operations that now revert paired with the revert reason
+    pub async fn simulate_user_operations(
+        &self,
+        uos: &[UserOperation],
+    ) -> eyre::Result<(Vec<UserOperation>, Vec<(UserOperation, String)>)> {
+        let ep = EntryPoint::new(self.eth_client.clone(), self.entry_point)
+            .with_version(self.entry_point_version);
+
+        let mut uos_valid = vec![];
+        let mut uos_dropped = vec![];
+
+        for uo in uos {
+            match ep.simulate_validation(uo.user_operation.clone()).await {
+                Ok(_) => uos_valid.push(uo.clone()),
+                Err(EntryPointError::FailedOp(op)) => uos_dropped.push((uo.clone(), op.reason)),
+                Err(EntryPointError::ExecutionReverted(reason)) => {
+                    uos_dropped.push((uo.clone(), reason))
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok((uos_valid, uos_dropped))
+    }
+
+    /// Groups the user operations by their signature aggregator and aggregates the signatures of
+    /// each group (unless the group is unchanged since its signatures were last aggregated). The
+    /// signature of each aggregated user operation is replaced with the value the aggregator
+    /// returns for it (`sigForUserOp` of `validateUserOpSignature`). The user operations without
+    /// an aggregator are grouped under the zero address.
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    ///
+    /// # Returns
+    /// * `Vec<UserOpsPerAggregator>` - The user operations per aggregator
+    async fn aggregate_user_operations(
+        &self,
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+    ) -> eyre::Result<Vec<UserOpsPerAggregator>> {
+        let mut uos_per_aggregator = vec![];
+        for (aggregator, uos) in Self::group_user_operations(uos, aggregated) {
+            let aggregation = if aggregator.is_zero() {
+                Aggregation {
+                    signature: Bytes::default(),
+                    uo_signatures: uos.iter().map(|uo| uo.signature.clone()).collect(),
+                }
+            } else if let Some(aggregation) = self.aggregation_cache.get(&aggregator, &uos) {
+                aggregation
+            } else {
+                let aggregator_api = AggregatorAPI::new(aggregator, self.eth_client.clone());
+                let mut uo_signatures = vec![];
+                for uo in uos.iter() {
+                    uo_signatures.push(
+                        aggregator_api
+                            .validate_user_op_signature(uo.user_operation.clone().into())
+                            .call()
+                            .await?,
+                    );
+                }
+                let signature = aggregator_api
+                    .aggregate_signatures(
+                        uos.iter().cloned().map(|uo| uo.user_operation.into()).collect(),
+                    )
+                    .call()
+                    .await?;
+
+                let aggregation = Aggregation { signature, uo_signatures };
+                self.aggregation_cache.insert(aggregator, &uos, aggregation.clone());
+                aggregation
+            };
+
+            uos_per_aggregator.push(UserOpsPerAggregator {
+                user_ops: uos
+                    .into_iter()
+                    .zip(aggregation.uo_signatures)
+                    .map(|(uo, signature)| {
+                        UserOperationSigned { signature, ..uo.user_operation }.into()
+                    })
+                    .collect(),
+                aggregator,
+                signature: aggregation.signature,
+            });
+        }
+
+        Ok(uos_per_aggregator)
+    }
+
+    /// Groups the user operations by their signature aggregator (in the order of the first user
+    /// operation of each aggregator), the user operations without an aggregator are grouped under
+    /// the zero address
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    ///
+    /// # Returns
+    /// * `Vec<(Address, Vec<UserOperation>)>` - The user operations by aggregator
+    fn group_user_operations(
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+    ) -> Vec<(Address, Vec<UserOperation>)> {
+        let aggregators: HashMap<UserOperationHash, Address> = aggregated
+            .iter()
+            .flat_map(|(aggregator, uo_hashes)| {
+                uo_hashes.iter().map(move |uo_hash| (*uo_hash, *aggregator))
+            })
+            .collect();
+
+        let mut groups: Vec<(Address, Vec<UserOperation>)> = vec![];
+        for uo in uos {
+            let aggregator = aggregators.get(&uo.hash).copied().unwrap_or_default();
+            match groups.iter_mut().find(|(a, _)| *a == aggregator) {
+                Some((_, uos)) => uos.push(uo.clone()),
+                None => groups.push((aggregator, vec![uo.clone()])),
+            }
+        }
+
+        groups
+    }
+
+    /// Returns the hashes of the user operations in the order they are passed to the entry point
+    /// (the index of `FailedOp` refers to this order)
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    ///
+    /// # Returns
+    /// * `Vec<UserOperationHash>` - The hashes of the user operations
+    fn bundle_order(
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+    ) -> Vec<UserOperationHash> {
+        if aggregated.is_empty() {
+            return uos.iter().map(|uo| uo.hash).collect();
+        }
+
+        Self::group_user_operations(uos, aggregated)
+            .into_iter()
+            .flat_map(|(_, uos)| uos.into_iter().map(|uo| uo.hash))
+            .collect()
+    }
+
+    /// Returns the hashes of the user operations of the bundles in the order they are passed to
+    /// the entry points (see [bundle_order](Bundler::bundle_order))
+    ///
+    /// # Arguments
+    /// * `bundles` - Slice of [EntryPointBundles](EntryPointBundle)
+    ///
+    /// # Returns
+    /// * `Vec<UserOperationHash>` - The hashes of the user operations
+    fn bundles_order(bundles: &[EntryPointBundle]) -> Vec<UserOperationHash> {
+        bundles
+            .iter()
+            .flat_map(|bundle| Self::bundle_order(&bundle.uos, &bundle.aggregated))
+            .collect()
+    }
+
+    /// Creates the call of the entry point that executes the user operations, i.e.,
+    /// `handleAggregatedOps` if some user operations use a signature aggregator, `handleOps`
+    /// otherwise
+    ///
+    /// # Arguments
+    /// * `entry_point` - The entry point contract address
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    /// * `beneficiary` - The address where the gas is refunded after execution
+    ///
+    /// # Returns
+    /// * `TypedTransaction` - The [TypedTransaction](TypedTransaction) calling the entry point
+    async fn handle_ops_tx(
+        &self,
+        entry_point: Address,
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+        beneficiary: Address,
+    ) -> eyre::Result<TypedTransaction> {
+        let ep = EntryPointAPI::new(entry_point, self.eth_client.clone());
+
+        let tx = if aggregated.is_empty() {
+            ep.handle_ops(
+                uos.iter().cloned().map(|uo| uo.user_operation.into()).collect(),
+                beneficiary,
+            )
+            .tx
+        } else {
+            ep.handle_aggregated_ops(
+                self.aggregate_user_operations(uos, aggregated).await?,
+                beneficiary,
+            )
+            .tx
+        };
+
+        Ok(tx)
+    }
+
+    /// Creates the transaction that executes the bundles, i.e., the call of the entry point if
+    /// there is only one bundle, otherwise the call of the [multicall](Bundler::multicall)
+    /// contract that calls every entry point
+    ///
+    /// # Arguments
+    /// * `bundles` - Slice of [EntryPointBundles](EntryPointBundle)
+    /// * `beneficiary` - The address where the gas is refunded after execution
+    ///
+    /// # Returns
+    /// * `TypedTransaction` - The [TypedTransaction](TypedTransaction) executing the bundles
+    async fn bundles_tx(
+        &self,
+        bundles: &[EntryPointBundle],
+        beneficiary: Address,
+    ) -> Result<TypedTransaction, BundleError> {
+        if let [bundle] = bundles {
+            let uo_hashes = Self::bundle_order(&bundle.uos, &bundle.aggregated);
+            return self
+                .handle_ops_tx(bundle.entry_point, &bundle.uos, &bundle.aggregated, beneficiary)
+                .await
+                .map_err(|err| BundleError::from_report::<M>(err, &uo_hashes));
+        }
+
+        let multicall = self
+            .multicall
+            .ok_or(BundleError::Other { inner: "No multicall contract configured".into() })?;
+
+        let mut calls = vec![];
+        for bundle in bundles {
+            let uo_hashes = Self::bundle_order(&bundle.uos, &bundle.aggregated);
+            let mut tx = self
+                .handle_ops_tx(bundle.entry_point, &bundle.uos, &bundle.aggregated, beneficiary)
+                .await
+                .map_err(|err| BundleError::from_report::<M>(err, &uo_hashes))?;
+
+            // the multicall contract doesn't forward the revert reason, so each call is estimated
+            // on its own to find the failed user operation
+            tx.set_from(self.signer.address());
+            self.eth_client
+                .estimate_gas(&tx, None)
+                .await
+                .map_err(|err| BundleError::from_middleware_error::<M>(err, &uo_hashes))?;
+
+            calls.push((bundle.entry_point, tx.data().cloned().unwrap_or_default()));
+        }
+
+        Ok(TransactionRequest::new().to(multicall).data(multicall_data(&calls)).into())
+    }
+
+    /// Simulates the bundle of user operations with `eth_estimateGas` without submitting it, so
+    /// that the wallet and the entry point can be checked before spending gas
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    ///
+    /// # Returns
+    /// * `BundleSimulation` - The [BundleSimulation](BundleSimulation)
+    pub async fn simulate_bundle(
+        &self,
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+    ) -> eyre::Result<BundleSimulation> {
+        let mut simulation =
+            BundleSimulation { uos: uos.iter().map(|uo| uo.hash).collect(), ..Default::default() };
+        if uos.is_empty() {
+            return Ok(simulation);
+        }
+
+        let mut tx =
+            self.handle_ops_tx(self.entry_point, uos, aggregated, self.signer.address()).await?;
+        tx.set_from(self.signer.address());
+
+        match self.eth_client.estimate_gas(&tx, None).await {
+            Ok(gas) => simulation.gas = Some(gas),
+            Err(err) => {
+                let reason = match EntryPointError::from_middleware_error::<M>(err) {
+                    Ok(err) => err.to_string(),
+                    Err(err) => err.to_string(),
+                };
+                simulation.revert_reason = Some(reason);
+            }
+        }
+
+        Ok(simulation)
+    }
+
+    /// Functions that generates a bundle of user operations (i.e.,
+    /// [TypedTransaction](TypedTransaction)). The bundle calls `handleAggregatedOps` if some
+    /// user operations use a signature aggregator, `handleOps` otherwise. The bundles of multiple
+    /// entry points are sent through the [multicall](Bundler::multicall) contract. If the
+    /// [max_bundle_loss](Bundler::max_bundle_loss) is set, the underpriced tail of the bundle is
+    /// trimmed until the loss is within the tolerance (the trimmed user operations stay in the
+    /// mempool).
+    ///
+    /// # Arguments
+    /// * `bundles` - The [EntryPointBundles](EntryPointBundle), trimmed in place
+    /// * `beneficiary` - The address where the gas is refunded after execution
+    /// * `nonce` - The nonce of the bundle transaction
+    ///
+    /// # Returns
+    /// * `(TypedTransaction, Address, Option<I256>)` - A [TypedTransaction](TypedTransaction), the
+    ///   beneficiary (the bundler's wallet if its balance is below the minimum balance) and the
+    ///   estimated profit of the bundle (only estimated if the maximum loss is set)
+    #[instrument(name = "bundle_build", skip_all, fields(nonce = %nonce))]
+    async fn create_bundle(
+        &self,
+        bundles: &mut Vec<EntryPointBundle>,
+        beneficiary: Address,
+        nonce: U256,
+    ) -> Result<(TypedTransaction, Address, Option<I256>), BundleError> {
+        let balance = self
+            .eth_client
+            .get_balance(self.signer.address(), None)
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+        let beneficiary =
+            if balance < self.min_balance { self.signer.address() } else { beneficiary };
+
+        loop {
+            let (tx, profit) = self.build_bundle(bundles, beneficiary, nonce).await?;
+            let Some(profit) = profit else {
+                return Ok((tx, beneficiary, None));
+            };
+            let Some(len) = profit.trimmed_len else {
+                return Ok((tx, beneficiary, Some(profit.profit)));
+            };
+
+            if len == 0 {
+                return Err(BundleError::Unprofitable { profit: profit.profit });
+            }
+            info!(
+                "Bundle is unprofitable (estimated profit: {} wei), trimming it to {len} user operations",
+                profit.profit
+            );
+            trim_bundles(bundles, len);
+        }
+    }
+
+    /// Builds the bundle transaction and estimates its profit if the
+    /// [max_bundle_loss](Bundler::max_bundle_loss) is set
+    ///
+    /// # Arguments
+    /// * `bundles` - Slice of [EntryPointBundles](EntryPointBundle)
+    /// * `beneficiary` - The address where the gas is refunded after execution
+    /// * `nonce` - The nonce of the bundle transaction
+    ///
+    /// # Returns
+    /// * `(TypedTransaction, Option<BundleProfit>)` - A [TypedTransaction](TypedTransaction) and
+    ///   the [BundleProfit](BundleProfit) (`None` if the maximum loss is not set)
+    async fn build_bundle(
+        &self,
+        bundles: &[EntryPointBundle],
+        beneficiary: Address,
+        nonce: U256,
+    ) -> Result<(TypedTransaction, Option<BundleProfit>), BundleError> {
+        let uo_hashes = Self::bundles_order(bundles);
+
+        let mut tx = self.bundles_tx(bundles, beneficiary).await?;
+
+        let accesslist = if self.enable_access_list {
+            let accesslist = self
+                .eth_client
+                .create_access_list(&tx, None)
+                .await
+                .map_err(|err| BundleError::from_middleware_error::<M>(err, &uo_hashes))?
+                .access_list;
+            tx.set_access_list(accesslist.clone());
+            accesslist
+        } else {
+            Default::default()
+        };
+        let estimated_gas = self.eth_client.estimate_gas(&tx, None).await.map_err(|err| {
+            match BundleError::from_middleware_error::<M>(err, &uo_hashes) {
+                BundleError::Provider { inner } => BundleError::GasEstimation { inner },
+                err => err,
+            }
+        })?;
+
+        let uos: Vec<UserOperation> =
+            bundles.iter().flat_map(|bundle| bundle.uos.iter().cloned()).collect();
+        let (max_fee_per_gas, max_priority_fee) = self.bundle_fees(&uos).await?;
+
+        let profit = match self.max_bundle_loss {
+            Some(max_loss) => {
+                let base_fee = self.next_block_base_fee().await?;
+                let profit = bundle_profit(
+                    &uos,
+                    estimated_gas,
+                    base_fee,
+                    max_fee_per_gas,
+                    max_priority_fee,
+                    &self.entry_point_version,
+                );
+                trace!("Estimated profit of the bundle is {profit} wei");
+                let len = profitable_len(
+                    &uos,
+                    estimated_gas,
+                    base_fee,
+                    max_fee_per_gas,
+                    max_priority_fee,
+                    max_loss,
+                    &self.entry_point_version,
+                );
+                Some(BundleProfit { profit, trimmed_len: (len < uos.len()).then_some(len) })
+            }
+            None => None,
+        };
+
+        tx = TypedTransaction::Eip1559(Eip1559TransactionRequest {
+            to: tx.to().cloned(),
+            from: Some(self.signer.address()),
+            data: tx.data().cloned(),
+            chain_id: Some(U64::from(self.chain.id())),
+            max_priority_fee_per_gas: Some(max_priority_fee),
+            max_fee_per_gas: Some(max_fee_per_gas),
+            gas: Some(estimated_gas),
+            nonce: Some(nonce),
+            value: None,
+            access_list: accesslist,
+        });
+
+        Ok((tx, profit))
+    }
+
+    /// Calculates the fees of the bundle transaction with the [FeeStrategy](FeeStrategy), the
+    /// priority fee is at least the [min_priority_fee](Bundler::min_priority_fee)
+    ///
+    /// # Arguments
+    /// * `uos` - Slice of [UserOperations](UserOperation) in the bundle
+    ///
+    /// # Returns
+    /// * `(U256, U256)` - The max fee per gas and the max priority fee per gas
+    async fn bundle_fees(&self, uos: &[UserOperation]) -> Result<(U256, U256), BundleError> {
+        let (max_fee_per_gas, max_priority_fee) = self
+            .eth_client
+            .estimate_eip1559_fees(None)
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+
+        let priority_fee = max_priority_fee.max(self.min_priority_fee);
+
+        match self.fee_strategy {
+            FeeStrategy::Provider => {
+                Ok((max_fee_per_gas.saturating_add(priority_fee - max_priority_fee), priority_fee))
+            }
+            FeeStrategy::NextBlock => {
+                let base_fee = self.next_block_base_fee().await?;
+                trace!("Base fee of the next block is {base_fee:?}");
+                Ok(next_block_fees(base_fee, priority_fee, uos))
+            }
+        }
+    }
+
+    /// Returns the base fee of the next block, i.e., the base fee of the pending block or the one
+    /// derived from the latest block if the Ethereum execution client doesn't serve the pending
+    /// block
+    async fn next_block_base_fee(&self) -> Result<U256, BundleError> {
+        let pending = self
+            .eth_client
+            .get_block(BlockNumber::Pending)
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+        if let Some(base_fee) = pending.and_then(|block| block.base_fee_per_gas) {
+            return Ok(base_fee);
+        }
+
+        let latest = self
+            .eth_client
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+        latest
+            .and_then(|block| {
+                block
+                    .base_fee_per_gas
+                    .map(|base_fee| next_block_base_fee(base_fee, block.gas_used, block.gas_limit))
+            })
+            .ok_or(BundleError::Provider { inner: "No base fee in the latest block".into() })
+    }
+
+    /// Send a bundle of [UserOperations](UserOperation)
+    ///
+    /// # Arguments
+    /// * `uos` - An array of [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) (`None` if there was nothing to
+    ///   bundle)
+    pub async fn send_bundle(
+        &self,
+        uos: &Vec<UserOperation>,
+    ) -> Result<Option<BundleResult>, BundleError> {
+        self.send_bundle_conditional(uos, None).await
+    }
+
+    /// Send a bundle of [UserOperations](UserOperation) with the conditions under which it can be
+    /// included (only used by the clients that support conditional transactions)
+    ///
+    /// # Arguments
+    /// * `uos` - An array of [UserOperations](UserOperation)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) (`None` if there was nothing to
+    ///   bundle)
+    pub async fn send_bundle_conditional(
+        &self,
+        uos: &Vec<UserOperation>,
+        conditional: Option<TransactionConditional>,
+    ) -> Result<Option<BundleResult>, BundleError> {
+        self.send_bundle_aggregated(uos, &HashMap::new(), conditional).await
+    }
+
+    /// Send a bundle of [UserOperations](UserOperation) some of which use a signature aggregator
+    ///
+    /// # Arguments
+    /// * `uos` - An array of [UserOperations](UserOperation)
+    /// * `aggregated` - The hashes of the aggregated user operations by aggregator
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) (`None` if there was nothing to
+    ///   bundle)
+    pub async fn send_bundle_aggregated(
+        &self,
+        uos: &Vec<UserOperation>,
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+        conditional: Option<TransactionConditional>,
+    ) -> Result<Option<BundleResult>, BundleError> {
+        let bundle = EntryPointBundle {
+            entry_point: self.entry_point,
+            uos: uos.clone(),
+            aggregated: aggregated.clone(),
+        };
+        self.send_bundles(&[bundle], conditional).await
+    }
+
+    /// Send the bundles of multiple entry points in one transaction through the
+    /// [multicall](Bundler::multicall) contract (a single bundle is sent to its entry point
+    /// directly)
+    ///
+    /// # Arguments
+    /// * `bundles` - Slice of [EntryPointBundles](EntryPointBundle), the empty ones are skipped
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) with the user operations of all
+    ///   the entry points (`None` if there was nothing to bundle)
+    pub async fn send_bundle_multicall(
+        &self,
+        bundles: &[EntryPointBundle],
+        conditional: Option<TransactionConditional>,
+    ) -> Result<Option<BundleResult>, BundleError> {
+        let bundles: Vec<EntryPointBundle> =
+            bundles.iter().filter(|bundle| !bundle.uos.is_empty()).cloned().collect();
+        self.send_bundles(&bundles, conditional).await
+    }
+
+    /// Sends the bundles in one transaction, retrying according to the
+    /// [RetryPolicy](RetryPolicy)
+    ///
+    /// # Arguments
+    /// * `bundles` - Slice of [EntryPointBundles](EntryPointBundle)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) (`None` if there was nothing to
+    ///   bundle)
+    #[instrument(
+        name = "bundle",
+        skip_all,
+        fields(
+            uo_hashes = ?Self::bundles_order(bundles),
+            eps = ?bundles.iter().map(|bundle| bundle.entry_point).collect::<Vec<_>>()
+        )
+    )]
+    async fn send_bundles(
+        &self,
+        bundles: &[EntryPointBundle],
+        conditional: Option<TransactionConditional>,
+    ) -> Result<Option<BundleResult>, BundleError> {
+        if let Err(err) = self.replace_stuck_bundles().await {
+            warn!("Replacing stuck bundles failed: {err:?}");
+        }
+
+        let uos: Vec<&UserOperation> =
+            bundles.iter().flat_map(|bundle| bundle.uos.iter()).collect();
+        if uos.is_empty() {
+            info!("Skipping creating a new bundle, no user operations");
+            return Ok(None);
+        };
+
+        info!(
+            "Creating a new bundle with {} user operations: {:?}",
+            uos.len(),
+            uos.iter().map(|uo| uo.hash).collect::<Vec<UserOperationHash>>()
+        );
+        trace!("Bundle content: {uos:?}");
+
+        let beneficiary = self
+            .select_beneficiary()
+            .await
+            .map_err(|err| BundleError::Provider { inner: err.to_string() })?;
+        let nonce = self
+            .reserve_nonce()
+            .await
+            .map_err(|err| BundleError::Nonce { inner: err.to_string() })?;
+        let mut bundles = bundles.to_vec();
+
+        let mut attempt = 1;
+        let (hash, estimated_gas, beneficiary, profit) = loop {
+            let res = match self.create_bundle(&mut bundles, beneficiary, nonce).await {
+                Ok((bundle, beneficiary, profit)) => {
+                    let uo_hashes = Self::bundles_order(&bundles);
+                    let estimated_gas = bundle.gas().cloned().unwrap_or_default();
+                    self.submit_bundle(nonce, bundle, &conditional, &uo_hashes)
+                        .await
+                        .map(|hash| (hash, estimated_gas, beneficiary, profit))
+                }
+                Err(err) => Err(err),
+            };
+
+            match res {
+                Ok(res) => break res,
+                Err(err) => {
+                    if attempt >= self.retry_policy.max_attempts || err.is_permanent() {
+                        self.nonce_manager.release(nonce);
+                        return Err(err);
+                    }
+
+                    let delay = self.retry_policy.delay(attempt);
+                    warn!(
+                        "Sending bundle failed (attempt {attempt}/{}), retrying in {delay:?}: {err:?}",
+                        self.retry_policy.max_attempts
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        };
+
+        info!(
+            "Bundle successfully sent, hash: {:?}, account: {:?}, nonce: {:?}, entry points: {:?}, beneficiary: {:?}",
+            hash,
+            self.signer.address(),
+            nonce,
+            bundles.iter().map(|bundle| bundle.entry_point).collect::<Vec<_>>(),
+            beneficiary
+        );
+
+        Ok(Some(BundleResult {
+            tx_hash: hash,
+            nonce,
+            uos: bundles.iter().flat_map(|bundle| bundle.uos.iter().map(|uo| uo.hash)).collect(),
+            estimated_gas,
+            beneficiary,
+            profit,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::{pack_paymaster_and_data, UserOperationSigned};
+
+    fn uo(gas: u64, paymaster: bool) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned {
+                pre_verification_gas: gas.into(),
+                verification_gas_limit: gas.into(),
+                call_gas_limit: gas.into(),
+                paymaster_and_data: if paymaster { vec![1u8; 20].into() } else { Bytes::default() },
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn bundle_limits_truncate() {
+        let with_hashes = |uos: Vec<UserOperation>| {
+            uos.into_iter()
+                .enumerate()
+                .map(|(i, uo)| UserOperation {
+                    hash: UserOperationHash(H256::from_low_u64_be(i as u64)),
+                    user_operation: UserOperationSigned {
+                        sender: Address::random(),
+                        ..uo.user_operation
+                    },
+                })
+                .collect::<Vec<_>>()
+        };
+        let truncate = |uos: &Vec<UserOperation>,
+                        limits: BundleLimits,
+                        block_gas_limit: u64,
+                        entry_point_version: EntryPointVersion| {
+            let (fitting, truncated) =
+                limits.truncate(uos.clone(), block_gas_limit.into(), &entry_point_version);
+            assert_eq!(fitting.len() + truncated.len(), uos.len());
+            fitting.iter().map(|uo| uo.hash.0.to_low_u64_be()).collect::<Vec<_>>()
+        };
+
+        let uos = with_hashes(vec![uo(100, false), uo(100, true), uo(100, false)]);
+        let v06 = |limits: BundleLimits, block_gas_limit: u64| {
+            truncate(&uos, limits, block_gas_limit, EntryPointVersion::V0_6)
+        };
+
+        // 300 + 500 + 300 gas
+        assert_eq!(v06(BundleLimits::new(None, 100, 4), 1100), vec![0, 1, 2]);
+        assert_eq!(v06(BundleLimits::new(None, 100, 4), 1099), vec![0, 1]);
+        assert_eq!(v06(BundleLimits::new(None, 50, 4), 1600), vec![0, 1]);
+        assert_eq!(v06(BundleLimits::new(None, 100, 4), 299), Vec::<u64>::new());
+        assert_eq!(v06(BundleLimits::new(Some(1), 100, 4), 1100), vec![0]);
+
+        // the user operation that doesn't fit is skipped, the next one still fits
+        assert_eq!(v06(BundleLimits::new(None, 100, 4), 700), vec![0, 2]);
+
+        let uos = with_hashes(vec![uo(100_000, false), uo(100_000, true), uo(100_000, false)]);
+        let v06 = |limits: BundleLimits, block_gas_limit: u64| {
+            truncate(&uos, limits, block_gas_limit, EntryPointVersion::V0_6)
+        };
+
+        // 110k + 210k + 110k validation gas (with the overhead of the entry point)
+        let limits = |perc: u64| BundleLimits::new(None, 100, 4).with_max_validation_gas_perc(perc);
+        assert_eq!(v06(limits(40), 1_100_000), vec![0, 1, 2]);
+        assert_eq!(v06(limits(39), 1_100_000), vec![0, 1]);
+        assert_eq!(v06(limits(20), 1_100_000), vec![0, 2]);
+
+        // 90% of the block for the bundle and 50% for its validation phase by default
+        assert_eq!(v06(BundleLimits::default(), 2_000_000), vec![0, 1, 2]);
+        assert_eq!(v06(BundleLimits::default(), 800_000), vec![0, 2]);
+
+        // v0.7 sets the paymaster gas limits separately: 300k + 380k + 300k gas and 110k + 160k +
+        // 110k validation gas
+        let uos = with_hashes(vec![
+            uo(100_000, false),
+            UserOperation::from_user_operation_signed(
+                UserOperationHash::default(),
+                UserOperationSigned {
+                    paymaster_and_data: pack_paymaster_and_data(
+                        Some(Address::random()),
+                        50_000.into(),
+                        30_000.into(),
+                        &Bytes::default(),
+                    ),
+                    ..uo(100_000, false).user_operation
+                },
+            ),
+            uo(100_000, false),
+        ]);
+        assert_eq!(truncate(&uos, limits(38), 1_000_000, EntryPointVersion::V0_7), vec![0, 1, 2]);
+        assert_eq!(truncate(&uos, limits(37), 1_000_000, EntryPointVersion::V0_7), vec![0, 1]);
+        assert_eq!(truncate(&uos, limits(100), 979_999, EntryPointVersion::V0_7), vec![0, 1]);
+    }
+
+    #[test]
+    fn bundle_limits_per_sender() {
+        let (sender, other) = (Address::random(), Address::random());
+        let uos = [sender, sender, other, sender, other]
+            .into_iter()
+            .enumerate()
+            .map(|(i, addr)| {
+                UserOperation::from_user_operation_signed(
+                    UserOperationHash(H256::from_low_u64_be(i as u64)),
+                    UserOperationSigned {
+                        sender: addr,
+                        nonce: (i as u64).into(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let hashes = |uos: &[UserOperation]| {
+            uos.iter().map(|uo| uo.hash.0.to_low_u64_be()).collect::<Vec<_>>()
+        };
+
+        let (uos_sender, deferred) = BundleLimits::new(None, 100, 2).limit_per_sender(uos.clone());
+        assert_eq!(hashes(&uos_sender), vec![0, 1, 2, 4]);
+        assert_eq!(hashes(&deferred), vec![3]);
+
+        let (uos_sender, deferred) = BundleLimits::new(None, 100, 0).limit_per_sender(uos);
+        assert_eq!(hashes(&uos_sender), vec![0, 2]);
+        assert_eq!(hashes(&deferred), vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn storage_conflicts_by_access() {
+        let (entry_point, token) = (Address::random(), Address::random());
+        let uos = (0..4)
+            .map(|_| {
+                UserOperation::from_user_operation_signed(
+                    UserOperationHash(H256::random()),
+                    UserOperationSigned::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let slots = |slots: &[&str]| slots.iter().map(|slot| slot.to_string()).collect();
+        let access = |reads: &[&str], writes: &[&str]| StorageAccesses {
+            reads: HashMap::from([(token, slots(reads)), (entry_point, slots(&["0x1"]))]),
+            writes: HashMap::from([(token, slots(writes)), (entry_point, slots(&["0x2"]))]),
+        };
+        let storage_accesses = HashMap::from([
+            (uos[0].hash, access(&["0x1"], &["0x2"])),
+            // read/read doesn't conflict
+            (uos[1].hash, access(&["0x1"], &[])),
+            // read/write conflicts
+            (uos[2].hash, access(&["0x2"], &[])),
+            // write/read conflicts
+            (uos[3].hash, access(&[], &["0x1"])),
+        ]);
+
+        let (valid, deferred, conflicts) =
+            storage_conflicts(uos.clone(), &storage_accesses, &entry_point);
+        assert_eq!(
+            valid.iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            vec![uos[0].hash, uos[1].hash]
+        );
+        assert_eq!(
+            deferred.iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            vec![uos[2].hash, uos[3].hash]
+        );
+        assert_eq!(
+            conflicts,
+            vec![
+                StorageConflict {
+                    uo_hash: uos[2].hash,
+                    conflicts_with: uos[0].hash,
+                    address: token,
+                    slot: H256::from_low_u64_be(2),
+                },
+                StorageConflict {
+                    uo_hash: uos[3].hash,
+                    conflicts_with: uos[0].hash,
+                    address: token,
+                    slot: H256::from_low_u64_be(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deposit_top_up_amount() {
+        let deposit_top_up = DepositTopUp::new(100.into(), 500.into());
+        assert_eq!(deposit_top_up.amount(99.into(), U256::MAX), Some(401.into()));
+        assert_eq!(deposit_top_up.amount(100.into(), U256::MAX), None);
+        assert_eq!(deposit_top_up.amount(600.into(), U256::MAX), None);
+
+        // the target is at least the threshold
+        let deposit_top_up = DepositTopUp::new(100.into(), 50.into());
+        assert_eq!(deposit_top_up.amount(40.into(), U256::MAX), Some(60.into()));
+
+        // the signer's balance above the minimum balance is the limit
+        assert_eq!(deposit_top_up.amount(40.into(), 20.into()), Some(20.into()));
+        assert_eq!(deposit_top_up.amount(40.into(), U256::zero()), None);
+    }
+
+    #[test]
+    fn next_block_fees_capped() {
+        // the base fee changes by at most 1/8
+        assert_eq!(next_block_base_fee(800.into(), 15.into(), 30.into()), 800.into());
+        assert_eq!(next_block_base_fee(800.into(), 30.into(), 30.into()), 900.into());
+        assert_eq!(next_block_base_fee(800.into(), 0.into(), 30.into()), 700.into());
+        assert_eq!(next_block_base_fee(1.into(), 16.into(), 30.into()), 2.into());
+
+        let uo = |max_fee: u64, max_priority_fee: u64| {
+            UserOperation::from_user_operation_signed(
+                UserOperationHash::default(),
+                UserOperationSigned {
+                    max_fee_per_gas: max_fee.into(),
+                    max_priority_fee_per_gas: max_priority_fee.into(),
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(next_block_fees(100.into(), 2.into(), &[]), (102.into(), 2.into()));
+        assert_eq!(
+            next_block_fees(100.into(), 2.into(), &[uo(200, 10), uo(150, 5)]),
+            (102.into(), 2.into())
+        );
+        // the user operations don't pay more than their fees
+        assert_eq!(
+            next_block_fees(100.into(), 10.into(), &[uo(200, 10), uo(150, 5)]),
+            (105.into(), 5.into())
+        );
+        assert_eq!(next_block_fees(100.into(), 10.into(), &[uo(103, 10)]), (103.into(), 10.into()));
+        // the user operations that don't cover the base fee don't cap the fees below it
+        assert_eq!(next_block_fees(100.into(), 10.into(), &[uo(90, 10)]), (110.into(), 10.into()));
+        assert_eq!(
+            next_block_fees(100.into(), 10.into(), &[uo(90, 1), uo(103, 10)]),
+            (103.into(), 10.into())
+        );
+
+        let (uos, underpriced) = split_underpriced(100.into(), vec![uo(90, 10), uo(100, 10)]);
+        assert_eq!(uos.iter().map(|uo| uo.max_fee_per_gas).collect::<Vec<_>>(), vec![100.into()]);
+        assert_eq!(
+            underpriced.iter().map(|uo| uo.max_fee_per_gas).collect::<Vec<_>>(),
+            vec![90.into()]
+        );
+    }
+
+    #[test]
+    fn bundle_profit_estimate() {
+        let priced = |max_fee: u64, max_priority_fee: u64| {
+            let mut uo = uo(100, false);
+            uo.user_operation.max_fee_per_gas = max_fee.into();
+            uo.user_operation.max_priority_fee_per_gas = max_priority_fee.into();
+            uo
+        };
+        // 300 gas each, paying 110 and 105 per gas
+        let uos = vec![priced(200, 10), priced(105, 10)];
+        let base_fee = U256::from(100);
+
+        let v06 = EntryPointVersion::V0_6;
+
+        // the 1000 gas of the bundle refund 107.5 per gas
+        assert_eq!(
+            bundle_profit(&uos, 1000.into(), base_fee, 105.into(), 5.into(), &v06),
+            I256::from(2500)
+        );
+        assert_eq!(
+            bundle_profit(&uos, 1000.into(), base_fee, 200.into(), 50.into(), &v06),
+            I256::from(-42500)
+        );
+        assert_eq!(
+            bundle_profit(&[], 1000.into(), base_fee, 105.into(), 5.into(), &v06),
+            I256::from(-105000)
+        );
+
+        // the paymaster of the second user operation sets 50 + 50 gas limits, which v0.7 counts
+        // instead of the verification gas limit twice more: 300 + 400 gas (300 + 500 gas in v0.6)
+        let mut sponsored = priced(105, 10);
+        sponsored.user_operation.paymaster_and_data = pack_paymaster_and_data(
+            Some(Address::random()),
+            50.into(),
+            50.into(),
+            &Bytes::default(),
+        );
+        let uos = vec![priced(200, 10), sponsored];
+        assert_eq!(
+            bundle_profit(&uos, 1000.into(), base_fee, 105.into(), 5.into(), &v06),
+            I256::from(1875)
+        );
+        assert_eq!(
+            bundle_profit(
+                &uos,
+                1000.into(),
+                base_fee,
+                105.into(),
+                5.into(),
+                &EntryPointVersion::V0_7
+            ),
+            I256::from(2142)
+        );
+    }
+
+    #[test]
+    fn bundle_profitable_len() {
+        let priced = |max_fee: u64, max_priority_fee: u64| {
+            let mut uo = uo(100, false);
+            uo.user_operation.max_fee_per_gas = max_fee.into();
+            uo.user_operation.max_priority_fee_per_gas = max_priority_fee.into();
+            uo
+        };
+        let uos = vec![priced(200, 10), priced(105, 10)];
+        let base_fee = U256::from(100);
+        let len = |max_fee: u64, max_priority_fee: u64, max_loss: u64| {
+            profitable_len(
+                &uos,
+                1000.into(),
+                base_fee,
+                max_fee.into(),
+                max_priority_fee.into(),
+                max_loss.into(),
+                &EntryPointVersion::V0_6,
+            )
+        };
+
+        // profitable, nothing is trimmed
+        assert_eq!(len(105, 5, 0), 2);
+
+        // loses 42500 wei, the first user operation alone (500 gas) loses 20000 wei
+        assert_eq!(len(200, 50, 42500), 2);
+        assert_eq!(len(200, 50, 20000), 1);
+        assert_eq!(len(200, 50, 19999), 0);
+    }
+
+    #[test]
+    fn trim_bundles_tail() {
+        let bundle = |n: usize| EntryPointBundle {
+            entry_point: Address::random(),
+            uos: vec![uo(100, false); n],
+            aggregated: HashMap::new(),
+        };
+        let mut bundles = vec![bundle(2), bundle(3)];
+
+        trim_bundles(&mut bundles, 4);
+        assert_eq!(bundles.iter().map(|b| b.uos.len()).collect::<Vec<_>>(), vec![2, 2]);
+        trim_bundles(&mut bundles, 1);
+        assert_eq!(bundles.iter().map(|b| b.uos.len()).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn explain_user_operation_inclusion() {
+        use crate::ethereum::EthereumClient;
+        use ethers::{providers::Provider, types::Block};
+        use silius_primitives::Wallet;
+
+        let (eth_client, mock) = Provider::mocked();
+        let eth_client = Arc::new(eth_client);
+        let wallet = Wallet::from_phrase(
+            "test test test test test test test test test test test junk",
+            1,
+            false,
+        )
+        .unwrap();
+        let bundler = Bundler::new(
+            wallet.clone(),
+            Address::random(),
+            Address::random(),
+            Chain::from(1),
+            U256::zero(),
+            eth_client.clone(),
+            Arc::new(EthereumClient::new(eth_client, wallet)),
+            true,
+            RetryPolicy::default(),
+            None,
+        )
+        .with_bundle_limits(BundleLimits::new(None, 100, 1));
+
+        let sender = Address::random();
+        let uos = [sender, sender, Address::random()]
+            .into_iter()
+            .enumerate()
+            .map(|(i, sender)| {
+                UserOperation::from_user_operation_signed(
+                    UserOperationHash(H256::from_low_u64_be(i as u64)),
+                    UserOperationSigned {
+                        sender,
+                        nonce: (i as u64).into(),
+                        max_fee_per_gas: 10.into(),
+                        ..uo(100_000, false).user_operation
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let block = |base_fee: u64, gas_limit: u64| Block::<H256> {
+            base_fee_per_gas: Some(base_fee.into()),
+            gas_limit: gas_limit.into(),
+            ..Default::default()
+        };
+        // the responses are popped in reverse order: the pending block, then the latest block
+        let push_blocks = |blocks: &[Block<H256>]| {
+            for block in blocks.iter().rev() {
+                mock.push(block.clone()).unwrap();
+            }
+        };
+        let storage_accesses = HashMap::new();
+
+        // the max fee doesn't cover the base fee of the next block
+        push_blocks(&[block(11, 0)]);
+        assert_eq!(
+            bundler.explain_user_operation(&uos[0], uos.clone(), &storage_accesses).await.unwrap(),
+            BundleInclusion::FeeBelowFloor { max_fee_per_gas: 10.into(), base_fee: 11.into() }
+        );
+
+        // the mempool selected another user operation (of the same sender or not)
+        push_blocks(&[block(10, 0)]);
+        assert_eq!(
+            bundler
+                .explain_user_operation(&uos[0], uos[1..].to_vec(), &storage_accesses)
+                .await
+                .unwrap(),
+            BundleInclusion::SenderLimit
+        );
+        push_blocks(&[block(10, 0)]);
+        assert_eq!(
+            bundler
+                .explain_user_operation(&uos[0], uos[2..].to_vec(), &storage_accesses)
+                .await
+                .unwrap(),
+            BundleInclusion::NotSelected
+        );
+
+        // the user operation conflicts with one of a higher priority
+        let slots = |slot: &str| HashMap::from([(sender, HashSet::from([slot.to_string()]))]);
+        let conflicting = HashMap::from([
+            (uos[0].hash, StorageAccesses { reads: slots("0x1"), writes: HashMap::new() }),
+            (uos[2].hash, StorageAccesses { reads: HashMap::new(), writes: slots("0x1") }),
+        ]);
+        push_blocks(&[block(10, 0)]);
+        assert_eq!(
+            bundler
+                .explain_user_operation(&uos[2], vec![uos[0].clone(), uos[2].clone()], &conflicting)
+                .await
+                .unwrap(),
+            BundleInclusion::StorageConflict { conflicts_with: uos[0].hash }
+        );
+
+        // one user operation per sender in a bundle
+        push_blocks(&[block(10, 0)]);
+        assert_eq!(
+            bundler.explain_user_operation(&uos[1], uos.clone(), &storage_accesses).await.unwrap(),
+            BundleInclusion::SenderLimit
+        );
+
+        // 300k gas each, only the first one fits in the block
+        push_blocks(&[block(10, 0), block(10, 500_000)]);
+        assert_eq!(
+            bundler.explain_user_operation(&uos[0], uos.clone(), &storage_accesses).await.unwrap(),
+            BundleInclusion::Included
+        );
+        push_blocks(&[block(10, 0), block(10, 500_000)]);
+        assert_eq!(
+            bundler.explain_user_operation(&uos[2], uos.clone(), &storage_accesses).await.unwrap(),
+            BundleInclusion::BundleFull
+        );
     }
 }
@@ -0,0 +1,153 @@
+use crate::bundler::SendBundleOp;
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{JsonRpcError, Middleware, PendingTransaction, RpcError},
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Bytes, H256},
+};
+use silius_primitives::{bundler::TransactionConditional, BundleSigner};
+use std::{sync::Arc, time::Duration};
+use tracing::{trace, warn};
+
+/// JSON-RPC error code of a method that doesn't exist or is not available
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A struct for the Ethereum Signer client that sends the bundles with
+/// `eth_sendRawTransactionConditional` (supported by some L2 sequencers)
+#[derive(Clone)]
+pub struct ConditionalClient<M>(pub Arc<SignerMiddleware<Arc<M>, BundleSigner>>);
+
+#[async_trait::async_trait]
+impl<M> SendBundleOp for ConditionalClient<M>
+where
+    M: Middleware + 'static,
+{
+    /// Send a bundle of [UserOperations](UserOperation) to the Ethereum execution client
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// * `H256` - The transaction hash
+    async fn send_bundle(&self, bundle: TypedTransaction) -> eyre::Result<H256> {
+        trace!("Sending transaction to the execution client: {bundle:?}");
+
+        let tx = self.0.send_transaction(bundle, None).await?.interval(Duration::from_millis(75));
+        let tx_hash = tx.tx_hash();
+
+        let tx_receipt = tx.await?;
+
+        trace!("Transaction receipt: {tx_receipt:?}");
+
+        Ok(tx_hash)
+    }
+
+    /// Send a bundle of [UserOperations](UserOperation) with `eth_sendRawTransactionConditional`.
+    /// Falls back to `eth_sendRawTransaction` only if the execution client doesn't support the
+    /// method, any other error (e.g., the conditions don't hold) is returned.
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of [UserOperations](UserOperation)
+    /// * `conditional` - The [TransactionConditional](TransactionConditional) of the bundle
+    ///
+    /// # Returns
+    /// * `H256` - The transaction hash
+    async fn send_bundle_conditional(
+        &self,
+        bundle: TypedTransaction,
+        conditional: TransactionConditional,
+    ) -> eyre::Result<H256> {
+        trace!("Sending conditional transaction to the execution client: {bundle:?}, conditional: {conditional:?}");
+
+        let mut tx = bundle;
+        self.0.fill_transaction(&mut tx, None).await?;
+        let signature = self.0.signer().sign_transaction(&tx).await?;
+        let raw_tx = tx.rlp_signed(&signature);
+
+        let tx_hash = match self
+            .0
+            .provider()
+            .request::<_, H256>("eth_sendRawTransactionConditional", (&raw_tx, &conditional))
+            .await
+        {
+            Ok(tx_hash) => tx_hash,
+            Err(err) if err.as_error_response().is_some_and(is_method_not_supported) => {
+                warn!("eth_sendRawTransactionConditional is not supported, falling back to eth_sendRawTransaction: {err:?}");
+                self.send_raw_transaction(raw_tx).await?
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let tx_receipt = PendingTransaction::new(tx_hash, self.0.provider())
+            .interval(Duration::from_millis(75))
+            .await?;
+
+        trace!("Transaction receipt: {tx_receipt:?}");
+
+        Ok(tx_hash)
+    }
+}
+
+impl<M> ConditionalClient<M>
+where
+    M: Middleware + 'static,
+{
+    /// Create a conditional Ethereum client
+    ///
+    /// # Arguments
+    /// * `eth_client` - Connection to the Ethereum execution client
+    /// * `signer` - The signer of the bundle transactions (e.g., a
+    ///   [Wallet](silius_primitives::Wallet) or a [BundleSigner](BundleSigner) backed by a remote
+    ///   signer)
+    ///
+    /// # Returns
+    /// * `ConditionalClient` - A [Conditional Ethereum Signer Middleware](ConditionalClient)
+    pub fn new(eth_client: Arc<M>, signer: impl Into<BundleSigner>) -> Self {
+        let signer = SignerMiddleware::new(eth_client, signer.into());
+        Self(Arc::new(signer))
+    }
+
+    /// Send the signed bundle transaction with `eth_sendRawTransaction`
+    async fn send_raw_transaction(&self, raw_tx: Bytes) -> eyre::Result<H256> {
+        Ok(self.0.inner().send_raw_transaction(raw_tx).await?.tx_hash())
+    }
+}
+
+/// Whether the JSON-RPC error means that the execution client doesn't support the method
+///
+/// # Arguments
+/// * `err` - The [JsonRpcError](JsonRpcError) returned by the execution client
+///
+/// # Returns
+/// * `bool` - True if the method is not supported
+fn is_method_not_supported(err: &JsonRpcError) -> bool {
+    if err.code == METHOD_NOT_FOUND {
+        return true;
+    }
+
+    // some clients return a generic error code for the methods they don't expose
+    let message = err.message.to_lowercase();
+    message.contains("method not found") ||
+        message.contains("not supported") ||
+        message.contains("does not exist")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn method_not_supported() {
+        let err =
+            |code: i64, message: &str| JsonRpcError { code, message: message.into(), data: None };
+
+        assert!(is_method_not_supported(&err(
+            METHOD_NOT_FOUND,
+            "the method eth_sendRawTransactionConditional does not exist/is not available"
+        )));
+        assert!(is_method_not_supported(&err(-32000, "Method not found")));
+        assert!(is_method_not_supported(&err(-32000, "method is not supported")));
+        assert!(!is_method_not_supported(&err(-32003, "storage slot value condition not met")));
+        assert!(!is_method_not_supported(&err(-32000, "nonce too low")));
+    }
+}
@@ -0,0 +1,224 @@
+use ethers::{
+    providers::{Middleware, ProviderError},
+    types::{I256, U256},
+};
+use silius_contracts::{entry_point::EntryPointAPIErrors, EntryPointError};
+use silius_primitives::UserOperationHash;
+use thiserror::Error;
+
+/// Error returned while creating or sending a bundle
+#[derive(Debug, Error)]
+pub enum BundleError {
+    /// The entry point reverted with `FailedOp(uint256,string)`, i.e., one user operation of the
+    /// bundle failed
+    #[error("user operation {index} failed: {reason}")]
+    FailedOp {
+        /// Index of the failed user operation in the bundle
+        index: usize,
+        /// Hash of the failed user operation (`None` if the index is out of the bundle)
+        uo_hash: Option<UserOperationHash>,
+        /// The revert reason
+        reason: String,
+    },
+
+    /// The entry point reverted with any other error
+    #[error("entry point reverted: {reason}")]
+    EntryPointReverted {
+        /// The revert reason
+        reason: String,
+    },
+
+    /// Error of the Ethereum execution client or relay
+    #[error("provider error: {inner}")]
+    Provider {
+        /// The inner error message
+        inner: String,
+    },
+
+    /// The gas of the bundle could not be estimated
+    #[error("gas estimation error: {inner}")]
+    GasEstimation {
+        /// The inner error message
+        inner: String,
+    },
+
+    /// The nonce of the bundle transaction could not be fetched or was rejected
+    #[error("nonce error: {inner}")]
+    Nonce {
+        /// The inner error message
+        inner: String,
+    },
+
+    /// The bundle would cost more than the user operations refund to the beneficiary (beyond the
+    /// maximum loss), so it's skipped
+    #[error("unprofitable bundle: estimated profit {profit} wei")]
+    Unprofitable {
+        /// The estimated profit (in wei)
+        profit: I256,
+    },
+
+    /// Any other error
+    #[error("other error: {inner}")]
+    Other {
+        /// The inner error message
+        inner: String,
+    },
+}
+
+impl BundleError {
+    /// Creates the error from the revert of the entry point
+    ///
+    /// # Arguments
+    /// * `err` - The decoded [EntryPointAPIErrors](EntryPointAPIErrors)
+    /// * `uo_hashes` - The hashes of the user operations in the order of the bundle
+    ///
+    /// # Returns
+    /// * `Self` - [FailedOp](BundleError::FailedOp) if a user operation failed,
+    ///   [EntryPointReverted](BundleError::EntryPointReverted) otherwise
+    pub fn from_entry_point_error(
+        err: EntryPointAPIErrors,
+        uo_hashes: &[UserOperationHash],
+    ) -> Self {
+        match err {
+            EntryPointAPIErrors::FailedOp(op) => {
+                let index = op.op_index.min(U256::from(usize::MAX)).as_usize();
+                Self::FailedOp { index, uo_hash: uo_hashes.get(index).copied(), reason: op.reason }
+            }
+            EntryPointAPIErrors::RevertString(reason) => Self::EntryPointReverted { reason },
+            err => Self::EntryPointReverted { reason: format!("{err:?}") },
+        }
+    }
+
+    /// Creates the error from the error of the middleware, decoding the revert of the entry point
+    ///
+    /// # Arguments
+    /// * `err` - The error of the middleware
+    /// * `uo_hashes` - The hashes of the user operations in the order of the bundle
+    ///
+    /// # Returns
+    /// * `Self` - The [BundleError](BundleError)
+    pub fn from_middleware_error<M: Middleware>(
+        err: M::Error,
+        uo_hashes: &[UserOperationHash],
+    ) -> Self {
+        if is_nonce_error(&err.to_string()) {
+            return Self::Nonce { inner: err.to_string() };
+        }
+
+        let inner = err.to_string();
+        match EntryPointError::from_middleware_error::<M>(err) {
+            Ok(err) => Self::from_entry_point_error(err, uo_hashes),
+            Err(_) => Self::Provider { inner },
+        }
+    }
+
+    /// Creates the error from the error returned by the client sending the bundle
+    ///
+    /// # Arguments
+    /// * `err` - The error of the client
+    /// * `uo_hashes` - The hashes of the user operations in the order of the bundle
+    ///
+    /// # Returns
+    /// * `Self` - The [BundleError](BundleError)
+    pub fn from_report<M: Middleware>(err: eyre::Report, uo_hashes: &[UserOperationHash]) -> Self {
+        let err = match err.downcast::<M::Error>() {
+            Ok(err) => return Self::from_middleware_error::<M>(err, uo_hashes),
+            Err(err) => err,
+        };
+
+        match err.downcast::<ProviderError>() {
+            Ok(err) => {
+                if is_nonce_error(&err.to_string()) {
+                    return Self::Nonce { inner: err.to_string() };
+                }
+                let inner = err.to_string();
+                match EntryPointError::from_provider_error(&err) {
+                    Ok(err) => Self::from_entry_point_error(err, uo_hashes),
+                    Err(_) => Self::Provider { inner },
+                }
+            }
+            Err(err) if is_nonce_error(&err.to_string()) => Self::Nonce { inner: err.to_string() },
+            Err(err) => Self::Other { inner: format!("{err:?}") },
+        }
+    }
+
+    /// Checks whether the error is permanent (i.e., the entry point reverted or the bundle is
+    /// unprofitable) and retrying would not help
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::FailedOp { .. } | Self::EntryPointReverted { .. } | Self::Unprofitable { .. }
+        )
+    }
+
+    /// Categorizes the error (used for metrics)
+    ///
+    /// # Returns
+    /// * `&'static str` - `entry_point` if the entry point reverted, `provider` for the errors of
+    ///   the Ethereum execution client or relay, `gas_estimation`, `nonce`, `unprofitable` or
+    ///   `other` otherwise
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::FailedOp { .. } | Self::EntryPointReverted { .. } => "entry_point",
+            Self::Provider { .. } => "provider",
+            Self::GasEstimation { .. } => "gas_estimation",
+            Self::Nonce { .. } => "nonce",
+            Self::Unprofitable { .. } => "unprofitable",
+            Self::Other { .. } => "other",
+        }
+    }
+}
+
+/// Checks whether the error message of the Ethereum execution client rejects the nonce of the
+/// transaction
+fn is_nonce_error(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    msg.contains("nonce too low") || msg.contains("nonce too high")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use silius_contracts::FailedOp;
+
+    #[test]
+    fn bundle_error_from_entry_point_error() {
+        let uo_hashes = vec![UserOperationHash(H256::random()), UserOperationHash(H256::random())];
+
+        let err = BundleError::from_entry_point_error(
+            EntryPointAPIErrors::FailedOp(FailedOp {
+                op_index: U256::from(1),
+                reason: "AA23 reverted".into(),
+            }),
+            &uo_hashes,
+        );
+        assert!(matches!(
+            err,
+            BundleError::FailedOp { index: 1, uo_hash: Some(hash), ref reason }
+                if hash == uo_hashes[1] && reason == "AA23 reverted"
+        ));
+        assert!(err.is_permanent());
+        assert_eq!(err.category(), "entry_point");
+
+        let err = BundleError::from_entry_point_error(
+            EntryPointAPIErrors::FailedOp(FailedOp {
+                op_index: U256::from(2),
+                reason: "AA25 invalid account nonce".into(),
+            }),
+            &uo_hashes,
+        );
+        assert!(matches!(err, BundleError::FailedOp { index: 2, uo_hash: None, .. }));
+
+        let err = BundleError::from_entry_point_error(
+            EntryPointAPIErrors::RevertString("AA94 gas values overflow".into()),
+            &uo_hashes,
+        );
+        assert!(matches!(err, BundleError::EntryPointReverted { .. }));
+
+        let err = BundleError::Nonce { inner: "nonce too low".into() };
+        assert!(!err.is_permanent());
+        assert_eq!(err.category(), "nonce");
+        assert!(is_nonce_error("Nonce too low: next nonce 5, tx nonce 4"));
+    }
+}
@@ -2,16 +2,15 @@ use crate::bundler::SendBundleOp;
 use ethers::{
     middleware::SignerMiddleware,
     providers::Middleware,
-    signers::LocalWallet,
     types::{transaction::eip2718::TypedTransaction, H256},
 };
-use silius_primitives::Wallet;
+use silius_primitives::BundleSigner;
 use std::{sync::Arc, time::Duration};
 use tracing::trace;
 
 /// A type alias for the Ethereum Signer client
 #[derive(Clone)]
-pub struct EthereumClient<M>(pub Arc<SignerMiddleware<Arc<M>, LocalWallet>>);
+pub struct EthereumClient<M>(pub Arc<SignerMiddleware<Arc<M>, BundleSigner>>);
 
 #[async_trait::async_trait]
 impl<M> SendBundleOp for EthereumClient<M>
@@ -47,12 +46,14 @@ where
     ///
     /// # Arguments
     /// * `eth_client` - Connection to the Ethereum execution client
-    /// * `wallet` - A [Wallet](Wallet) instance
+    /// * `signer` - The signer of the bundle transactions (e.g., a
+    ///   [Wallet](silius_primitives::Wallet) or a [BundleSigner](BundleSigner) backed by a remote
+    ///   signer)
     ///
     /// # Returns
     /// * `EthereumClient` - A [Ethereum Signer Middleware](EthereumClient)
-    pub fn new(eth_client: Arc<M>, wallet: Wallet) -> Self {
-        let signer = SignerMiddleware::new(eth_client, wallet.signer);
+    pub fn new(eth_client: Arc<M>, signer: impl Into<BundleSigner>) -> Self {
+        let signer = SignerMiddleware::new(eth_client, signer.into());
         Self(Arc::new(signer))
     }
 }
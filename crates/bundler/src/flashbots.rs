@@ -3,19 +3,61 @@ use ethers::{
     middleware::SignerMiddleware,
     providers::Middleware,
     signers::{LocalWallet, Signer},
-    types::{transaction::eip2718::TypedTransaction, H256},
+    types::{transaction::eip2718::TypedTransaction, Address, H256, U64},
 };
 use ethers_flashbots::{BundleRequest, FlashbotsMiddleware, PendingBundleError, SimulatedBundle};
-use silius_primitives::Wallet;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use silius_primitives::{constants::bundler::FLASHBOTS_TARGET_BLOCKS, Wallet};
+use std::{sync::Arc, time::Duration};
 use tracing::{info, trace};
 use url::Url;
 
+/// Options of the bundles sent to the Flashbots relay
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlashbotsOptions {
+    /// Number of consecutive blocks (starting from the next block) the bundle is submitted for
+    pub target_blocks: u64,
+    /// Percentage of the MEV refunded to the `refund_recipient` (relay default if not set)
+    pub refund_percent: Option<u8>,
+    /// Recipient of the MEV refund (the bundle signer if not set)
+    pub refund_recipient: Option<Address>,
+}
+
+impl Default for FlashbotsOptions {
+    fn default() -> Self {
+        Self {
+            target_blocks: FLASHBOTS_TARGET_BLOCKS,
+            refund_percent: None,
+            refund_recipient: None,
+        }
+    }
+}
+
+/// `eth_sendBundle` parameters with the refund configuration
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SendBundleRequest<'a> {
+    #[serde(flatten)]
+    bundle: &'a BundleRequest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refund_percent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refund_recipient: Option<Address>,
+}
+
+/// `eth_sendBundle` response
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendBundleResponse {
+    bundle_hash: H256,
+}
+
 /// A struct for the Flashbots Signer client
 #[derive(Clone)]
-pub struct FlashbotsClient<M>(
-    pub Arc<SignerMiddleware<FlashbotsMiddleware<Arc<M>, LocalWallet>, LocalWallet>>,
-);
+pub struct FlashbotsClient<M> {
+    pub client: Arc<SignerMiddleware<FlashbotsMiddleware<Arc<M>, LocalWallet>, LocalWallet>>,
+    pub options: FlashbotsOptions,
+}
 
 #[async_trait::async_trait]
 impl<M> SendBundleOp for FlashbotsClient<M>
@@ -38,9 +80,23 @@ where
             Err(e) => return Err(eyre::eyre!("Bundle simulation failed: {:?}", e)),
         };
 
-        let bundle_hash = self.send_flashbots_bundle(bundle_req.clone()).await?;
+        let tx_hash = *bundle_req
+            .transaction_hashes()
+            .first()
+            .ok_or(eyre::eyre!("Bundle has no transactions"))?;
+        let block = bundle_req.block().ok_or(eyre::eyre!("Bundle has no target block"))?;
 
-        Ok(bundle_hash)
+        let mut bundle_hashes = vec![];
+        for i in 0..self.options.target_blocks.max(1) {
+            let target = block + i;
+            let bundle_hash =
+                self.send_flashbots_bundle_request(&bundle_req.clone().set_block(target)).await?;
+            bundle_hashes.push((target, bundle_hash));
+        }
+
+        self.wait_for_bundle(tx_hash, &bundle_hashes).await?;
+
+        Ok(tx_hash)
     }
 }
 
@@ -86,7 +142,19 @@ where
 
         let client = Arc::new(SignerMiddleware::new(flashbots_middleware, wallet.signer.clone()));
 
-        Ok(Self(client))
+        Ok(Self { client, options: FlashbotsOptions::default() })
+    }
+
+    /// Sets the options of the bundles sent to the Flashbots relay
+    ///
+    /// # Arguments
+    /// * `options` - The [FlashbotsOptions](FlashbotsOptions)
+    ///
+    /// # Returns
+    /// * `FlashbotsClient` - The client with the options set
+    pub fn with_options(mut self, options: FlashbotsOptions) -> Self {
+        self.options = options;
+        self
     }
 
     /// Generate a Flashbots bundle request
@@ -106,7 +174,7 @@ where
         let mut bundle_req = BundleRequest::new();
         for tx in txs {
             let typed_tx = TypedTransaction::Eip1559(tx.into());
-            let raw_signed_tx = match self.0.signer().sign_transaction(&typed_tx).await {
+            let raw_signed_tx = match self.client.signer().sign_transaction(&typed_tx).await {
                 Ok(tx) => typed_tx.rlp_signed(&tx),
                 Err(e) => return Err(eyre::eyre!("Failed to sign transaction: {:?}", e)),
             };
@@ -119,7 +187,7 @@ where
         }
 
         // Simulate the Flashbots bundle
-        let block_num = self.0.get_block_number().await?;
+        let block_num = self.client.get_block_number().await?;
         bundle_req = bundle_req
             .set_block(block_num + 1)
             .set_simulation_block(block_num)
@@ -137,7 +205,7 @@ where
     /// * `H256` - The transaction hash of the bundle
     pub async fn send_flashbots_bundle(&self, bundle: BundleRequest) -> eyre::Result<H256> {
         // Send the Flashbots bundle and check for status
-        let pending_bundle = match self.0.inner().send_bundle(&bundle).await {
+        let pending_bundle = match self.client.inner().send_bundle(&bundle).await {
             Ok(bundle) => bundle,
             Err(e) => return Err(eyre::eyre!("Failed to send bundle: {:?}", e)),
         };
@@ -155,6 +223,77 @@ where
         }
     }
 
+    /// Send a Flashbots bundle request (with the refund configuration) to the relay
+    ///
+    /// # Arguments
+    /// * `bundle` - A [BundleRequest](BundleRequest) sent to Flashbots relay
+    ///
+    /// # Returns
+    /// * `H256` - The hash of the bundle
+    async fn send_flashbots_bundle_request(&self, bundle: &BundleRequest) -> eyre::Result<H256> {
+        let req = SendBundleRequest {
+            bundle,
+            refund_percent: self.options.refund_percent,
+            refund_recipient: self.options.refund_recipient,
+        };
+
+        let res: SendBundleResponse = self
+            .client
+            .inner()
+            .relay()
+            .request("eth_sendBundle", [req])
+            .await
+            .map_err(|e| eyre::eyre!("Failed to send bundle: {:?}", e))?;
+
+        info!("Bundle {:?} sent for block: {:?}", res.bundle_hash, bundle.block());
+
+        Ok(res.bundle_hash)
+    }
+
+    /// Wait until the bundle is included in one of the target blocks
+    ///
+    /// # Arguments
+    /// * `tx_hash` - The transaction hash of the bundle
+    /// * `bundle_hashes` - The target blocks and the hashes of the bundles sent for them
+    ///
+    /// # Returns
+    /// * `()` - If the bundle was included, otherwise an error
+    async fn wait_for_bundle(
+        &self,
+        tx_hash: H256,
+        bundle_hashes: &[(U64, H256)],
+    ) -> eyre::Result<()> {
+        let last_block = bundle_hashes.last().map(|(block, _)| *block).unwrap_or_default();
+        let mut reported = 0;
+
+        loop {
+            if self.client.get_transaction_receipt(tx_hash).await?.is_some() {
+                return Ok(());
+            }
+
+            let block = self.client.get_block_number().await?;
+
+            // bundle stats are only informative, the inclusion is checked by the receipt
+            while let Some((target, bundle_hash)) =
+                bundle_hashes.get(reported).filter(|(target, _)| *target <= block)
+            {
+                match self.client.inner().get_bundle_stats(*bundle_hash, *target).await {
+                    Ok(stats) => {
+                        trace!("Bundle {bundle_hash:?} stats at block {target}: {stats:?}")
+                    }
+                    Err(e) => trace!("Failed to get bundle {bundle_hash:?} stats: {e:?}"),
+                }
+                reported += 1;
+            }
+
+            if block > last_block {
+                return Err(eyre::eyre!("Bundle not included in the target blocks"));
+            }
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
     /// Simulate a Flashbots bundle
     ///
     /// # Arguments
@@ -166,7 +305,7 @@ where
         &self,
         bundle: &BundleRequest,
     ) -> eyre::Result<SimulatedBundle> {
-        let simulated_bundle = self.0.inner().simulate_bundle(bundle).await?;
+        let simulated_bundle = self.client.inner().simulate_bundle(bundle).await?;
 
         // Currently there's only 1 tx per bundle
         for tx in &simulated_bundle.transactions {
@@ -1,10 +1,26 @@
 //! Bundler is a crate for bundling transactions and sending them to the Ethereum execution client
 #![allow(dead_code)]
 
+mod aggregation;
+mod broadcast;
 mod bundler;
+mod conditional;
+mod error;
 mod ethereum;
 mod flashbots;
+mod multicall;
+mod nonce;
 
-pub use bundler::{Bundler, SendBundleOp};
+pub use aggregation::{Aggregation, AggregationCache, AggregationCacheStats};
+pub use broadcast::BroadcastClient;
+pub use bundler::{
+    bundle_profit, next_block_base_fee, next_block_fees, profitable_len, split_underpriced,
+    BundleLimits, BundleResult, BundleSimulation, Bundler, DepositTopUp, RetryPolicy, SendBundleOp,
+    StorageConflict,
+};
+pub use conditional::ConditionalClient;
+pub use error::BundleError;
 pub use ethereum::EthereumClient;
-pub use flashbots::FlashbotsClient;
+pub use flashbots::{FlashbotsClient, FlashbotsOptions};
+pub use multicall::{multicall_data, EntryPointBundle};
+pub use nonce::{NonceManager, PendingBundle};
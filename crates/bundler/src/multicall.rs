@@ -0,0 +1,84 @@
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, Bytes},
+    utils::id,
+};
+use silius_contracts::utils::AGGREGATE3;
+use silius_primitives::{UserOperation, UserOperationHash};
+use std::collections::HashMap;
+
+/// The user operations bundled for one entry point, several of which can be sent in one
+/// transaction through the multicall contract
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryPointBundle {
+    /// Entry point contract address
+    pub entry_point: Address,
+    /// The user operations of the bundle
+    pub uos: Vec<UserOperation>,
+    /// The hashes of the aggregated user operations by aggregator
+    pub aggregated: HashMap<Address, Vec<UserOperationHash>>,
+}
+
+/// Encodes the call of `aggregate3` of the multicall contract that executes the calls in order
+/// (none of them is allowed to fail)
+///
+/// # Arguments
+/// * `calls` - The target contracts paired with the call data
+///
+/// # Returns
+/// * `Bytes` - The call data of the multicall contract
+pub fn multicall_data(calls: &[(Address, Bytes)]) -> Bytes {
+    let calls = calls
+        .iter()
+        .map(|(target, data)| {
+            Token::Tuple(vec![
+                Token::Address(*target),
+                Token::Bool(false),
+                Token::Bytes(data.to_vec()),
+            ])
+        })
+        .collect();
+
+    [id(AGGREGATE3).to_vec(), encode(&[Token::Array(calls)])].concat().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::{decode, ParamType};
+
+    #[test]
+    fn multicall_data_encoding() {
+        let calls = vec![
+            (Address::random(), Bytes::from(vec![1, 2, 3])),
+            (Address::random(), Bytes::from(vec![4])),
+        ];
+        let data = multicall_data(&calls);
+
+        // selector of `aggregate3` of Multicall3
+        assert_eq!(data[..4], [0x82, 0xad, 0x56, 0xcb]);
+
+        let tokens = decode(
+            &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::Bool,
+                ParamType::Bytes,
+            ])))],
+            &data[4..],
+        )
+        .expect("call data should decode");
+        assert_eq!(
+            tokens,
+            vec![Token::Array(
+                calls
+                    .iter()
+                    .map(|(target, data)| Token::Tuple(vec![
+                        Token::Address(*target),
+                        Token::Bool(false),
+                        Token::Bytes(data.to_vec())
+                    ]))
+                    .collect()
+            )]
+        );
+    }
+}
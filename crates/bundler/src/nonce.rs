@@ -0,0 +1,289 @@
+use ethers::types::{transaction::eip2718::TypedTransaction, H256, U256, U64};
+use silius_primitives::constants::bundler::REPLACED_BUNDLES_CACHE_SIZE;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+/// A bundle transaction that was submitted but is not confirmed yet
+#[derive(Clone, Debug)]
+pub struct PendingBundle {
+    /// The signed bundle transaction request
+    pub tx: TypedTransaction,
+    /// Block number at which the transaction was (re)submitted
+    pub block: U64,
+    /// Hash of the transaction once it was sent
+    pub tx_hash: Option<H256>,
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    /// Next nonce that is handed out (unset until synced with the chain)
+    next: Option<U256>,
+    /// Nonces handed out that are not confirmed yet, with the bundle once it is submitted
+    in_flight: BTreeMap<U256, Option<PendingBundle>>,
+    /// Nonces that were handed out and released, reused before the next nonce
+    gaps: BTreeSet<U256>,
+    /// Hashes of the replaced bundle transactions with the hash of their replacement
+    replaced: HashMap<H256, H256>,
+    /// Order in which the bundle transactions were replaced (oldest entries are evicted first)
+    replaced_order: VecDeque<H256>,
+}
+
+/// Tracks the nonce of the bundler's wallet locally, so that bundles created concurrently (e.g.,
+/// for several entry points or by manual bundling) never collide. A nonce stays in flight until
+/// the bundle is mined, i.e., the transaction count of the wallet passes it or its receipt is
+/// seen. The clones share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct NonceManager {
+    state: Arc<Mutex<NonceState>>,
+}
+
+impl NonceManager {
+    /// Whether the nonce has to be synced with the pending nonce of the chain, i.e., it was never
+    /// fetched or no bundle is in flight
+    pub fn needs_sync(&self) -> bool {
+        let state = self.state.lock().expect("nonce state lock poisoned");
+        state.next.is_none() || state.in_flight.is_empty()
+    }
+
+    /// Reserves the nonce for the next bundle
+    ///
+    /// # Arguments
+    /// * `mined` - The transaction count of the wallet in the latest block
+    /// * `pending` - The transaction count of the wallet including the pending transactions (only
+    ///   used if [needs_sync](NonceManager::needs_sync))
+    ///
+    /// # Returns
+    /// * `U256` - The reserved nonce
+    pub fn reserve(&self, mined: U256, pending: Option<U256>) -> U256 {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+
+        state.in_flight.retain(|nonce, _| *nonce >= mined);
+        state.gaps.retain(|nonce| *nonce >= mined);
+
+        if let Some(pending) = pending {
+            if state.in_flight.is_empty() {
+                state.next = Some(pending.max(mined));
+                state.gaps.clear();
+            }
+        }
+
+        let nonce = match state.gaps.pop_first() {
+            Some(nonce) => nonce,
+            None => {
+                let nonce = state.next.unwrap_or(mined).max(mined);
+                state.next = Some(nonce + 1);
+                nonce
+            }
+        };
+        state.in_flight.insert(nonce, None);
+
+        nonce
+    }
+
+    /// Records the bundle transaction submitted with the reserved nonce
+    ///
+    /// # Arguments
+    /// * `nonce` - The reserved nonce
+    /// * `bundle` - The [PendingBundle](PendingBundle)
+    pub fn submit(&self, nonce: U256, bundle: PendingBundle) {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+        state.in_flight.insert(nonce, Some(bundle));
+    }
+
+    /// Records the hash of the bundle transaction sent with the nonce. If a transaction was sent
+    /// with the nonce before, it's recorded as replaced by this one.
+    ///
+    /// # Arguments
+    /// * `nonce` - The nonce of the bundle transaction
+    /// * `tx_hash` - The hash of the sent bundle transaction
+    pub fn sent(&self, nonce: U256, tx_hash: H256) {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+        let Some(Some(bundle)) = state.in_flight.get_mut(&nonce) else {
+            return;
+        };
+        let Some(replaced) = bundle.tx_hash.replace(tx_hash).filter(|hash| *hash != tx_hash) else {
+            return;
+        };
+
+        if state.replaced.insert(replaced, tx_hash).is_none() {
+            state.replaced_order.push_back(replaced);
+        }
+        while state.replaced_order.len() > REPLACED_BUNDLES_CACHE_SIZE {
+            if let Some(hash) = state.replaced_order.pop_front() {
+                state.replaced.remove(&hash);
+            }
+        }
+    }
+
+    /// Follows the replacements of the bundle transaction to the latest one sent
+    ///
+    /// # Arguments
+    /// * `tx_hash` - The hash of the bundle transaction
+    ///
+    /// # Returns
+    /// * `H256` - The hash of the latest replacement (the given hash if it was never replaced)
+    pub fn latest_tx_hash(&self, tx_hash: H256) -> H256 {
+        let state = self.state.lock().expect("nonce state lock poisoned");
+        let mut latest = tx_hash;
+        // bounded by the number of replacements, in case of a cycle
+        for _ in 0..state.replaced.len() {
+            match state.replaced.get(&latest) {
+                Some(hash) => latest = *hash,
+                None => break,
+            }
+        }
+        latest
+    }
+
+    /// Marks the bundle transaction with the nonce as mined
+    ///
+    /// # Arguments
+    /// * `nonce` - The nonce of the bundle transaction
+    pub fn confirm(&self, nonce: U256) {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+        state.in_flight.remove(&nonce);
+    }
+
+    /// Releases the reserved nonce after the bundle could not be sent, so it's reused by the next
+    /// bundle
+    ///
+    /// # Arguments
+    /// * `nonce` - The reserved nonce
+    pub fn release(&self, nonce: U256) {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+        state.in_flight.remove(&nonce);
+        if state.next == Some(nonce + 1) {
+            state.next = Some(nonce);
+        } else {
+            state.gaps.insert(nonce);
+        }
+    }
+
+    /// Returns the submitted bundles that were not mined for the given number of blocks
+    ///
+    /// # Arguments
+    /// * `mined` - The transaction count of the wallet in the latest block
+    /// * `block` - The latest block number
+    /// * `stuck_blocks` - Number of blocks after which a bundle is considered stuck
+    ///
+    /// # Returns
+    /// * `Vec<(U256, PendingBundle)>` - The nonces and the stuck bundles
+    pub fn stuck(&self, mined: U256, block: U64, stuck_blocks: u64) -> Vec<(U256, PendingBundle)> {
+        let mut state = self.state.lock().expect("nonce state lock poisoned");
+        state.in_flight.retain(|nonce, _| *nonce >= mined);
+        state
+            .in_flight
+            .iter()
+            .filter_map(|(nonce, bundle)| bundle.clone().map(|bundle| (*nonce, bundle)))
+            .filter(|(_, bundle)| block.saturating_sub(bundle.block) >= stuck_blocks.into())
+            .collect()
+    }
+
+    /// Whether any submitted bundle is not confirmed yet
+    pub fn has_submitted(&self) -> bool {
+        let state = self.state.lock().expect("nonce state lock poisoned");
+        state.in_flight.values().any(Option::is_some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Eip1559TransactionRequest;
+
+    fn bundle(block: u64) -> PendingBundle {
+        PendingBundle {
+            tx: Eip1559TransactionRequest::new().into(),
+            block: block.into(),
+            tx_hash: None,
+        }
+    }
+
+    #[test]
+    fn nonces_are_incremented_locally() {
+        let manager = NonceManager::default();
+        assert!(manager.needs_sync());
+
+        assert_eq!(manager.reserve(5.into(), Some(6.into())), 6.into());
+        assert!(!manager.needs_sync());
+        assert_eq!(manager.reserve(5.into(), None), 7.into());
+        assert_eq!(manager.reserve(5.into(), None), 8.into());
+    }
+
+    #[test]
+    fn released_nonces_are_reused() {
+        let manager = NonceManager::default();
+        let first = manager.reserve(0.into(), Some(0.into()));
+        let second = manager.reserve(0.into(), None);
+        let third = manager.reserve(0.into(), None);
+
+        manager.release(third);
+        assert_eq!(manager.reserve(0.into(), None), third);
+
+        manager.release(first);
+        assert_eq!(manager.reserve(0.into(), None), first);
+
+        manager.confirm(first);
+        manager.confirm(second);
+        manager.confirm(third);
+        assert!(manager.needs_sync());
+    }
+
+    #[test]
+    fn nonces_are_reconciled_with_chain() {
+        let manager = NonceManager::default();
+        manager.reserve(0.into(), Some(0.into()));
+        manager.reserve(0.into(), None);
+
+        // both bundles were mined, the in flight nonces are dropped and the nonce is resynced
+        assert_eq!(manager.reserve(2.into(), None), 2.into());
+        manager.confirm(2.into());
+        assert_eq!(manager.reserve(4.into(), Some(5.into())), 5.into());
+    }
+
+    #[test]
+    fn stuck_bundles() {
+        let manager = NonceManager::default();
+        let first = manager.reserve(0.into(), Some(0.into()));
+        let second = manager.reserve(0.into(), None);
+        manager.submit(first, bundle(10));
+        manager.submit(second, bundle(12));
+        assert!(manager.has_submitted());
+
+        assert!(manager.stuck(0.into(), 11.into(), 3).is_empty());
+        assert_eq!(
+            manager.stuck(0.into(), 13.into(), 3).into_iter().map(|(n, _)| n).collect::<Vec<_>>(),
+            vec![first]
+        );
+        assert_eq!(
+            manager.stuck(1.into(), 15.into(), 3).into_iter().map(|(n, _)| n).collect::<Vec<_>>(),
+            vec![second]
+        );
+    }
+
+    #[test]
+    fn replaced_bundles() {
+        let manager = NonceManager::default();
+        let nonce = manager.reserve(0.into(), Some(0.into()));
+        let (first, second) = (H256::random(), H256::random());
+
+        manager.submit(nonce, bundle(10));
+        manager.sent(nonce, first);
+        assert_eq!(manager.latest_tx_hash(first), first);
+
+        // the stuck bundle is resubmitted and stays in flight until it's mined
+        let (_, stuck) = manager.stuck(0.into(), 20.into(), 3).remove(0);
+        assert_eq!(stuck.tx_hash, Some(first));
+        manager.submit(nonce, PendingBundle { block: 20.into(), ..stuck });
+        manager.sent(nonce, second);
+        assert_eq!(manager.latest_tx_hash(first), second);
+        assert_eq!(manager.latest_tx_hash(second), second);
+        assert!(manager.has_submitted());
+
+        assert!(manager.stuck(1.into(), 30.into(), 3).is_empty());
+        assert!(!manager.has_submitted());
+        assert_eq!(manager.latest_tx_hash(first), second);
+    }
+}
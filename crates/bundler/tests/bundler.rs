@@ -15,7 +15,7 @@ use ethers::{
     utils::{parse_units, Anvil, AnvilInstance},
 };
 use jsonrpsee::server::{ServerBuilder, ServerHandle};
-use silius_bundler::{Bundler, FlashbotsClient, SendBundleOp};
+use silius_bundler::{Bundler, FlashbotsClient, RetryPolicy, SendBundleOp};
 use silius_primitives::{
     constants::{entry_point::ADDRESS, flashbots_relay_endpoints},
     Wallet,
@@ -56,6 +56,7 @@ where
     S: SendBundleOp,
 {
     pub bundler: Bundler<M, S>,
+    pub wallet: Wallet,
     pub _entry_point: Address,
     pub _anvil: AnvilInstance,
 }
@@ -94,9 +95,11 @@ async fn setup() -> eyre::Result<TestContext<Provider<Ws>, FlashbotsClient<Provi
         eth_client,
         client,
         true,
+        RetryPolicy::default(),
+        None,
     );
 
-    Ok(TestContext { bundler, _entry_point: ep_address, _anvil: anvil })
+    Ok(TestContext { bundler, wallet, _entry_point: ep_address, _anvil: anvil })
 }
 
 async fn start_mock_server() -> eyre::Result<(ServerHandle, MockFlashbotsBlockBuilderRelay)> {
@@ -175,7 +178,7 @@ async fn test_send_bundle_flashbots() -> eyre::Result<()> {
 
     let bundler = ctx.bundler;
     let depositor = mock_relay.mock_eth_client.clone();
-    let address = bundler.wallet.signer.address();
+    let address = bundler.signer.address();
 
     let eth_client = Arc::new(Provider::<Http>::try_from("http://127.0.0.1:8545".to_string())?);
 
@@ -183,7 +186,7 @@ async fn test_send_bundle_flashbots() -> eyre::Result<()> {
     let client = FlashbotsClient::new(
         eth_client.clone(),
         Some(vec!["http://127.0.0.1:3001".into()]),
-        bundler.wallet.clone(),
+        ctx.wallet.clone(),
     )?;
 
     let depositor_weth_instance =
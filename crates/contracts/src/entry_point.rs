@@ -1,33 +1,56 @@
 pub use super::{
     error::EntryPointError,
     gen::{
-        EntryPointAPI, EntryPointAPIEvents, StakeManagerAPI, UserOperationEventFilter,
-        ValidatePaymasterUserOpReturn, SELECTORS_INDICES, SELECTORS_NAMES,
+        entry_point_api::{EntryPointAPIErrors, UserOpsPerAggregator},
+        AccountDeployedFilter, EntryPointAPI, EntryPointAPIEvents, StakeManagerAPI,
+        UserOperationEventFilter, ValidatePaymasterUserOpReturn, SELECTORS_INDICES,
+        SELECTORS_NAMES,
     },
 };
 use super::{
     gen::{
         entry_point_api::{
-            EntryPointAPIErrors, SenderAddressResult, UserOperation, ValidationResult,
-            ValidationResultWithAggregation,
+            SenderAddressResult, UserOperation, ValidationResult, ValidationResultWithAggregation,
         },
         stake_manager_api::DepositInfo,
     },
     tracer::JS_TRACER,
 };
-use crate::{error::decode_revert_error, executor_tracer::EXECUTOR_TRACER, gen::ExecutionResult};
+use crate::{
+    error::{decode_revert_error, decode_revert_string},
+    executor_tracer::EXECUTOR_TRACER,
+    gen::ExecutionResult,
+};
 use ethers::{
+    abi::{encode, Tokenizable},
     prelude::{ContractError, Event},
-    providers::Middleware,
+    providers::{Middleware, MiddlewareError},
     types::{
-        spoof, transaction::eip2718::TypedTransaction, Address, Bytes, GethDebugTracerType,
-        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TransactionRequest, U256,
+        spoof, transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, CallFrame,
+        GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions,
+        GethDebugTracingOptions, GethTrace, GethTraceFrame, TransactionRequest, H256, U256,
     },
+    utils::id,
+};
+use silius_primitives::{
+    simulation::ValidationTracer, EntryPointVersion, PackedUserOperation, UserOperationSigned,
 };
 use std::sync::Arc;
 
 const UINT96_MAX: u128 = 5192296858534827628530496329220095;
 
+/// Signature of `getUserOpHash` of EntryPoint v0.7 (takes the `PackedUserOperation`)
+const GET_USER_OP_HASH_V07: &str =
+    "getUserOpHash((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes))";
+
+/// Signature of `validateUserOp` of the accounts of EntryPoint v0.6
+const VALIDATE_USER_OP_V06: &str = "validateUserOp((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes),bytes32,uint256)";
+
+/// Signature of `validateUserOp` of the accounts of EntryPoint v0.7 (takes the
+/// `PackedUserOperation`)
+const VALIDATE_USER_OP_V07: &str =
+    "validateUserOp((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes),bytes32,uint256)";
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SimulateValidationResult {
     ValidationResult(ValidationResult),
@@ -40,13 +63,67 @@ pub struct EntryPoint<M: Middleware + 'static> {
     address: Address,
     entry_point_api: EntryPointAPI<M>,
     stake_manager_api: StakeManagerAPI<M>,
+    validation_tracer: ValidationTracer,
+    version: EntryPointVersion,
 }
 
 impl<M: Middleware + 'static> EntryPoint<M> {
     pub fn new(eth_client: Arc<M>, address: Address) -> Self {
         let entry_point_api = EntryPointAPI::new(address, eth_client.clone());
         let stake_manager_api = StakeManagerAPI::new(address, eth_client.clone());
-        Self { eth_client, address, entry_point_api, stake_manager_api }
+        Self {
+            eth_client,
+            address,
+            entry_point_api,
+            stake_manager_api,
+            validation_tracer: ValidationTracer::default(),
+            version: EntryPointVersion::from_address(&address),
+        }
+    }
+
+    /// Sets the version of the entry point smart contract, e.g., the declared version of an entry
+    /// point deployed at a non-canonical address (the version of the canonical address by default)
+    ///
+    /// # Arguments
+    /// * `version` - The [EntryPointVersion](EntryPointVersion)
+    ///
+    /// # Returns
+    /// * `Self` - The entry point with the version
+    pub fn with_version(mut self, version: EntryPointVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Creates the entry point deployed at another address with the same Ethereum execution
+    /// client and validation tracer
+    ///
+    /// # Arguments
+    /// * `address` - The address of the entry point smart contract
+    /// * `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+    ///
+    /// # Returns
+    /// * `Self` - The entry point at the address
+    pub fn at(&self, address: Address, version: EntryPointVersion) -> Self {
+        Self::new(self.eth_client.clone(), address)
+            .with_validation_tracer(self.validation_tracer.clone())
+            .with_version(version)
+    }
+
+    /// Sets the tracer used to trace the validation of the user operations, e.g., a native tracer
+    /// for the Ethereum execution clients without the support of the JavaScript tracers
+    ///
+    /// # Arguments
+    /// * `validation_tracer` - The [ValidationTracer](ValidationTracer)
+    ///
+    /// # Returns
+    /// * `Self` - The entry point with the tracer
+    pub fn with_validation_tracer(mut self, validation_tracer: ValidationTracer) -> Self {
+        self.validation_tracer = validation_tracer;
+        self
+    }
+
+    pub fn validation_tracer(&self) -> &ValidationTracer {
+        &self.validation_tracer
     }
 
     pub fn entry_point_api(&self) -> &EntryPointAPI<M> {
@@ -65,6 +142,10 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         self.address
     }
 
+    pub fn version(&self) -> EntryPointVersion {
+        self.version
+    }
+
     fn deserialize_error_msg(
         err: ContractError<M>,
     ) -> Result<EntryPointAPIErrors, EntryPointError> {
@@ -109,6 +190,12 @@ impl<M: Middleware + 'static> EntryPoint<M> {
     ) -> Result<GethTrace, EntryPointError> {
         let call = self.entry_point_api.simulate_validation(uo.into());
 
+        // the native tracers are selected by name like the JavaScript tracers
+        let tracer = match &self.validation_tracer {
+            ValidationTracer::Js => JS_TRACER.to_string(),
+            ValidationTracer::Native(name) => name.clone(),
+        };
+
         let res = self
             .eth_client
             .debug_trace_call(
@@ -120,7 +207,7 @@ impl<M: Middleware + 'static> EntryPoint<M> {
                         disable_stack: None,
                         enable_memory: None,
                         enable_return_data: None,
-                        tracer: Some(GethDebugTracerType::JsTracer(JS_TRACER.into())),
+                        tracer: Some(GethDebugTracerType::JsTracer(tracer)),
                         tracer_config: None,
                         timeout: None,
                     },
@@ -172,6 +259,58 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         Ok(res)
     }
 
+    /// Traces `simulateHandleOp` with the built-in call tracer, so that the gas used by each call
+    /// (e.g., the validation of the paymaster) is known
+    ///
+    /// # Arguments
+    /// * `uo` - The user operation to simulate
+    ///
+    /// # Returns
+    /// * `CallFrame` - The top level call frame of the simulation
+    pub async fn simulate_handle_op_call_trace<U: Into<UserOperation>>(
+        &self,
+        uo: U,
+    ) -> Result<CallFrame, EntryPointError> {
+        let uo = uo.into();
+        let max_fee_per_gas = uo.max_fee_per_gas;
+        let call = self.entry_point_api.simulate_handle_op(uo, Address::zero(), Bytes::default());
+        let mut tx: TypedTransaction = call.tx;
+        tx.set_from(Address::zero());
+        tx.set_gas_price(max_fee_per_gas);
+        tx.set_gas(u64::MAX);
+        let res = self
+            .eth_client
+            .debug_trace_call(
+                tx,
+                None,
+                GethDebugTracingCallOptions {
+                    tracing_options: GethDebugTracingOptions {
+                        disable_storage: None,
+                        disable_stack: None,
+                        enable_memory: None,
+                        enable_return_data: None,
+                        tracer: Some(GethDebugTracerType::BuiltInTracer(
+                            GethDebugBuiltInTracerType::CallTracer,
+                        )),
+                        tracer_config: None,
+                        timeout: None,
+                    },
+                    state_overrides: Some(spoof::balance(Address::zero(), UINT96_MAX.into())),
+                },
+            )
+            .await
+            .map_err(|e| {
+                EntryPointError::from_middleware_error::<M>(e).expect_err("trace err is expected")
+            })?;
+
+        match res {
+            GethTrace::Known(GethTraceFrame::CallTracer(frame)) => Ok(frame),
+            _ => Err(EntryPointError::Other {
+                inner: format!("Unexpected trace of simulate handle op: {res:?}"),
+            }),
+        }
+    }
+
     pub async fn handle_ops<U: Into<UserOperation>>(
         &self,
         uos: Vec<U>,
@@ -190,7 +329,20 @@ impl<M: Middleware + 'static> EntryPoint<M> {
     }
 
     pub async fn get_deposit_info(&self, addr: &Address) -> Result<DepositInfo, EntryPointError> {
-        let res = self.stake_manager_api.get_deposit_info(*addr).call().await;
+        self.get_deposit_info_at(addr, None).await
+    }
+
+    /// Gets the deposit info of the address at the given block (the latest block if `None`)
+    pub async fn get_deposit_info_at(
+        &self,
+        addr: &Address,
+        block: Option<BlockNumber>,
+    ) -> Result<DepositInfo, EntryPointError> {
+        let mut call = self.stake_manager_api.get_deposit_info(*addr);
+        if let Some(block) = block {
+            call = call.block(block);
+        }
+        let res = call.call().await;
 
         match res {
             Ok(deposit_info) => Ok(deposit_info),
@@ -276,6 +428,133 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         }
     }
 
+    /// Verifies that the entry point smart contract is deployed and implements the interface of
+    /// the version, i.e., `getUserOpHash` of the declared version returns the same hash as the
+    /// one calculated by the bundler
+    ///
+    /// # Arguments
+    /// * `version` - The declared [EntryPointVersion](EntryPointVersion)
+    /// * `chain_id` - The chain id
+    ///
+    /// # Returns
+    /// * `()` - If the entry point matches the version, otherwise an [EntryPointError]
+    pub async fn verify_version(
+        &self,
+        version: EntryPointVersion,
+        chain_id: u64,
+    ) -> Result<(), EntryPointError> {
+        let code = self
+            .eth_client
+            .get_code(self.address, None)
+            .await
+            .map_err(|err| EntryPointError::Provider { inner: err.to_string() })?;
+        if code.is_empty() {
+            return Err(EntryPointError::Other {
+                inner: format!("entry point {:?} has no code", self.address),
+            });
+        }
+
+        let uo = UserOperationSigned::default()
+            .sender(Address::random())
+            .verification_gas_limit(100_000.into())
+            .pre_verification_gas(21_000.into())
+            .max_priority_fee_per_gas(1_000_000_000.into());
+        let expected = uo.hash_with_version(&self.address, chain_id, version);
+
+        let hash = match version {
+            EntryPointVersion::V0_6 => self
+                .entry_point_api
+                .get_user_op_hash(uo.into())
+                .call()
+                .await
+                .map(H256::from)
+                .map_err(|err| EntryPointError::Other {
+                    inner: format!("get user operation hash error: {err:?}"),
+                })?,
+            EntryPointVersion::V0_7 => {
+                let data = [
+                    &id(GET_USER_OP_HASH_V07)[..],
+                    &encode(&[PackedUserOperation::from(uo).into_token()]),
+                ]
+                .concat();
+                let res = self
+                    .eth_client
+                    .call(&TransactionRequest::new().to(self.address).data(data).into(), None)
+                    .await
+                    .map_err(|err| EntryPointError::Provider { inner: err.to_string() })?;
+                if res.len() != 32 {
+                    return Err(EntryPointError::Decode {
+                        inner: format!("invalid user operation hash {res:?}"),
+                    });
+                }
+                H256::from_slice(&res)
+            }
+        };
+
+        if hash != expected.0 {
+            return Err(EntryPointError::Other {
+                inner: format!(
+                    "entry point {:?} doesn't match the interface of version {}",
+                    self.address,
+                    version.as_str()
+                ),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Calls `validateUserOp` of the sender of the user operation on behalf of the entry point
+    /// (without missing account funds, so the sender doesn't pay the prefund), with the
+    /// verification gas limit of the user operation as the gas limit of the call
+    ///
+    /// # Arguments
+    /// * `uo` - The user operation
+    /// * `uo_hash` - The hash of the user operation
+    /// * `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+    /// * `block` - The block to call the sender at (the latest block if `None`)
+    ///
+    /// # Returns
+    /// * `U256` - The validation data returned by the sender, or an
+    ///   [ExecutionReverted](EntryPointError::ExecutionReverted) error if the call reverted
+    pub async fn validate_user_op(
+        &self,
+        uo: UserOperationSigned,
+        uo_hash: H256,
+        version: EntryPointVersion,
+        block: Option<BlockNumber>,
+    ) -> Result<U256, EntryPointError> {
+        let (sender, gas) = (uo.sender, uo.verification_gas_limit);
+        let (signature, uo) = match version {
+            EntryPointVersion::V0_6 => (VALIDATE_USER_OP_V06, UserOperation::from(uo).into_token()),
+            EntryPointVersion::V0_7 => {
+                (VALIDATE_USER_OP_V07, PackedUserOperation::from(uo).into_token())
+            }
+        };
+        let data =
+            [&id(signature)[..], &encode(&[uo, uo_hash.into_token(), U256::zero().into_token()])]
+                .concat();
+
+        let tx = TransactionRequest::new().from(self.address).to(sender).gas(gas).data(data);
+        match self.eth_client.call(&tx.into(), block.map(Into::into)).await {
+            Ok(res) if res.len() >= 32 => Ok(U256::from_big_endian(&res[..32])),
+            Ok(res) => Err(EntryPointError::Decode {
+                inner: format!("invalid validation data {res:?} returned by sender {sender:?}"),
+            }),
+            Err(err) => match err.as_error_response() {
+                Some(res) if res.is_revert() => {
+                    let data = res.as_revert_data().unwrap_or_default();
+                    let reason =
+                        if data.len() >= 4 { decode_revert_string(data.clone()) } else { None };
+                    Err(EntryPointError::ExecutionReverted(
+                        reason.unwrap_or_else(|| data.to_string()),
+                    ))
+                }
+                _ => Err(EntryPointError::Provider { inner: err.to_string() }),
+            },
+        }
+    }
+
     pub async fn handle_aggregated_ops<U: Into<UserOperation>>(
         &self,
         _uos_per_aggregator: Vec<U>,
@@ -6,7 +6,12 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 
 abigen!(AccountAPI, "$OUT_DIR/IAccount.sol/IAccount.json");
+abigen!(AggregatorAPI, "$OUT_DIR/IAggregator.sol/IAggregator.json");
 abigen!(EntryPointAPI, "$OUT_DIR/IEntryPoint.sol/IEntryPoint.json");
+abigen!(
+    GasPriceOracleAPI,
+    r#"[function getL1Fee(bytes memory _data) external view returns (uint256)]"#
+);
 abigen!(PaymasterAPI, "$OUT_DIR/IPaymaster.sol/IPaymaster.json");
 abigen!(SenderCreatorAPI, "$OUT_DIR/SenderCreator.sol/SenderCreator.json");
 abigen!(StakeManagerAPI, "$OUT_DIR/IStakeManager.sol/IStakeManager.json");
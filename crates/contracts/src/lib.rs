@@ -10,5 +10,6 @@ pub mod utils;
 pub use entry_point::EntryPoint;
 pub use error::{decode_revert_string, EntryPointError};
 pub use gen::{
-    ExecutionResult, FailedOp, UserOperationEventFilter, UserOperationRevertReasonFilter,
+    AggregatorAPI, ExecutionResult, FailedOp, GasPriceOracleAPI, UserOperationEventFilter,
+    UserOperationRevertReasonFilter,
 };
@@ -1,7 +1,20 @@
-use crate::gen::entry_point_api::{self, EntryPointAPICalls};
-use ethers::{abi::AbiDecode, types::Bytes};
+use crate::gen::{
+    aggregator_api,
+    entry_point_api::{self, EntryPointAPICalls},
+    paymaster_api,
+};
+use ethers::{
+    abi::{decode, AbiDecode, ParamType},
+    contract::EthCall,
+    types::{Address, Bytes, CallFrame, NameOrAddress, U256},
+    utils::id,
+};
 use silius_primitives::UserOperationSigned;
 
+/// Signature of `aggregate3` of the multicall contract (Multicall3) the bundles of several entry
+/// points are sent through, which reverts if any of the calls reverts
+pub const AGGREGATE3: &str = "aggregate3((address,bool,bytes)[])";
+
 impl From<UserOperationSigned> for entry_point_api::UserOperation {
     fn from(uo: UserOperationSigned) -> Self {
         Self {
@@ -20,6 +33,24 @@ impl From<UserOperationSigned> for entry_point_api::UserOperation {
     }
 }
 
+impl From<UserOperationSigned> for aggregator_api::UserOperation {
+    fn from(uo: UserOperationSigned) -> Self {
+        Self {
+            sender: uo.sender,
+            nonce: uo.nonce,
+            init_code: uo.init_code,
+            call_data: uo.call_data,
+            call_gas_limit: uo.call_gas_limit,
+            verification_gas_limit: uo.verification_gas_limit,
+            pre_verification_gas: uo.pre_verification_gas,
+            max_fee_per_gas: uo.max_fee_per_gas,
+            max_priority_fee_per_gas: uo.max_priority_fee_per_gas,
+            paymaster_and_data: uo.paymaster_and_data,
+            signature: uo.signature,
+        }
+    }
+}
+
 impl From<entry_point_api::UserOperation> for UserOperationSigned {
     fn from(uo: entry_point_api::UserOperation) -> Self {
         Self {
@@ -47,10 +78,102 @@ pub fn parse_from_input_data(data: Bytes) -> Option<Vec<UserOperationSigned>> {
     })
 }
 
+/// Parses the user operations the transaction bundled for the entry point, whether it calls
+/// `handleOps` of the entry point directly or through `aggregate3` of the multicall contract
+///
+/// # Arguments
+/// * `to` - The recipient of the transaction
+/// * `data` - The input data of the transaction
+/// * `entry_point` - The entry point address
+///
+/// # Returns
+/// * `Option<Vec<UserOperationSigned>>` - The user operations (`None` if the transaction doesn't
+///   call `handleOps` of the entry point)
+pub fn parse_from_transaction(
+    to: Option<Address>,
+    data: Bytes,
+    entry_point: &Address,
+) -> Option<Vec<UserOperationSigned>> {
+    if to == Some(*entry_point) {
+        return parse_from_input_data(data);
+    }
+
+    if data.len() < 4 || data[..4] != id(AGGREGATE3) {
+        return None;
+    }
+    let calls = decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &data[4..],
+    )
+    .ok()?
+    .into_iter()
+    .next()?
+    .into_array()?;
+
+    let bundles = calls
+        .into_iter()
+        .filter_map(|call| {
+            let mut call = call.into_tuple()?.into_iter();
+            let target = call.next()?.into_address()?;
+            let data = call.nth(1)?.into_bytes()?;
+            (target == *entry_point).then(|| parse_from_input_data(data.into())).flatten()
+        })
+        .collect::<Vec<_>>();
+
+    (!bundles.is_empty()).then(|| bundles.into_iter().flatten().collect())
+}
+
+/// Parses the user operations the entry point executed from the call trace (`callTracer`) of the
+/// transaction, i.e., from the `handleOps` calls to the entry point at any depth, so that the
+/// bundles sent through any contract are found. The reverted calls are skipped.
+///
+/// # Arguments
+/// * `frame` - The call frame of the transaction
+/// * `entry_point` - The entry point address
+///
+/// # Returns
+/// * `Vec<UserOperationSigned>` - The user operations
+pub fn parse_from_call_frame(frame: &CallFrame, entry_point: &Address) -> Vec<UserOperationSigned> {
+    if frame.error.is_some() {
+        return vec![];
+    }
+
+    if frame.to == Some(NameOrAddress::Address(*entry_point)) {
+        if let Some(uos) = parse_from_input_data(frame.input.clone()) {
+            return uos;
+        }
+    }
+
+    frame.calls.iter().flatten().flat_map(|call| parse_from_call_frame(call, entry_point)).collect()
+}
+
+/// Finds the gas used by the validation of the paymaster (`validatePaymasterUserOp`) in the call
+/// trace of the simulation
+///
+/// # Arguments
+/// * `frame` - The [call frame](CallFrame) of the simulation
+/// * `paymaster` - The address of the paymaster
+///
+/// # Returns
+/// * `Option<U256>` - The gas used by the validation of the paymaster (`None` if it wasn't called)
+pub fn paymaster_validation_gas_used(frame: &CallFrame, paymaster: &Address) -> Option<U256> {
+    if frame.to == Some(NameOrAddress::Address(*paymaster)) &&
+        frame.input.starts_with(&paymaster_api::ValidatePaymasterUserOpCall::selector())
+    {
+        return Some(frame.gas_used);
+    }
+
+    frame.calls.iter().flatten().find_map(|call| paymaster_validation_gas_used(call, paymaster))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::types::Bytes;
+    use ethers::abi::{encode, AbiEncode, Token};
     use std::str::FromStr;
 
     #[test]
@@ -60,4 +183,109 @@ mod tests {
         let res = parse_from_input_data(data);
         assert!(matches!(res, Some(..)), "No user operation found")
     }
+
+    #[test]
+    fn parse_transaction_through_multicall() {
+        let entry_point = Address::random();
+        let uo = UserOperationSigned::default().sender(Address::random()).nonce(1.into());
+        let handle_ops = Bytes::from(
+            EntryPointAPICalls::HandleOps(entry_point_api::HandleOpsCall {
+                ops: vec![uo.clone().into()],
+                beneficiary: Address::random(),
+            })
+            .encode(),
+        );
+        let multicall = Address::random();
+        let data: Bytes = [
+            id(AGGREGATE3).to_vec(),
+            encode(&[Token::Array(
+                [(Address::random(), Bytes::from(vec![1u8])), (entry_point, handle_ops.clone())]
+                    .into_iter()
+                    .map(|(target, data)| {
+                        Token::Tuple(vec![
+                            Token::Address(target),
+                            Token::Bool(false),
+                            Token::Bytes(data.to_vec()),
+                        ])
+                    })
+                    .collect(),
+            )]),
+        ]
+        .concat()
+        .into();
+
+        assert_eq!(
+            parse_from_transaction(Some(entry_point), handle_ops, &entry_point),
+            Some(vec![uo.clone()])
+        );
+        assert_eq!(
+            parse_from_transaction(Some(multicall), data.clone(), &entry_point),
+            Some(vec![uo])
+        );
+        assert_eq!(parse_from_transaction(Some(multicall), data, &Address::random()), None);
+    }
+
+    #[test]
+    fn parse_call_frame() {
+        let entry_point = Address::random();
+        let uo = UserOperationSigned::default().sender(Address::random()).nonce(1.into());
+        let call = |to: Address, input: Bytes, calls: Vec<CallFrame>| CallFrame {
+            to: Some(to.into()),
+            input,
+            calls: (!calls.is_empty()).then_some(calls),
+            ..Default::default()
+        };
+        let handle_ops = Bytes::from(
+            EntryPointAPICalls::HandleOps(entry_point_api::HandleOpsCall {
+                ops: vec![uo.clone().into()],
+                beneficiary: Address::random(),
+            })
+            .encode(),
+        );
+
+        // the bundle is sent through a contract calling the entry point
+        let frame = call(
+            Address::random(),
+            Bytes::from(vec![1u8]),
+            vec![
+                call(Address::random(), Bytes::default(), vec![]),
+                call(entry_point, handle_ops.clone(), vec![]),
+            ],
+        );
+        assert_eq!(parse_from_call_frame(&frame, &entry_point), vec![uo]);
+        assert!(parse_from_call_frame(&frame, &Address::random()).is_empty());
+
+        // the reverted calls didn't execute the user operations
+        let frame = CallFrame {
+            error: Some("execution reverted".into()),
+            ..call(entry_point, handle_ops, vec![])
+        };
+        assert!(parse_from_call_frame(&frame, &entry_point).is_empty());
+    }
+
+    #[test]
+    fn paymaster_validation_gas_used_in_call_frame() {
+        let (entry_point, paymaster) = (Address::random(), Address::random());
+        let validate_paymaster = Bytes::from(
+            [paymaster_api::ValidatePaymasterUserOpCall::selector().to_vec(), vec![0; 32]].concat(),
+        );
+        let call = |to: Address, input: Bytes, gas_used: u64| CallFrame {
+            to: Some(to.into()),
+            input,
+            gas_used: gas_used.into(),
+            ..Default::default()
+        };
+
+        // the post operation of the paymaster is not part of the validation
+        let frame = CallFrame {
+            calls: Some(vec![
+                call(Address::random(), validate_paymaster.clone(), 10_000),
+                call(paymaster, Bytes::from(vec![1u8; 4]), 20_000),
+                call(paymaster, validate_paymaster, 30_000),
+            ]),
+            ..call(entry_point, Bytes::default(), 100_000)
+        };
+        assert_eq!(paymaster_validation_gas_used(&frame, &paymaster), Some(30_000.into()));
+        assert_eq!(paymaster_validation_gas_used(&frame, &Address::random()), None);
+    }
 }
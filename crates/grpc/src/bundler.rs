@@ -1,34 +1,243 @@
 use crate::{
-    proto::{bundler::*, uopool::GetSortedRequest},
+    proto::{
+        bundler::*,
+        uopool::{FailedUserOperation, GetAllRequest, GetSortedRequest, RemoveFailedRequest},
+    },
     uo_pool_client::UoPoolClient,
+    utils::{parse_addr, parse_hash},
 };
 use alloy_chains::Chain;
 use async_trait::async_trait;
 use ethers::{
     providers::Middleware,
-    types::{Address, H256, U256},
+    signers::Signer,
+    types::{Address, TransactionReceipt, H256, U256},
 };
+use futures::Stream;
 use parking_lot::Mutex;
-use silius_bundler::{Bundler, SendBundleOp};
-use silius_metrics::grpc::MetricsLayer;
-use silius_primitives::{UserOperation, Wallet};
-use std::{net::SocketAddr, sync::Arc, time::Duration};
-use tonic::{Request, Response, Status};
-use tracing::{error, info};
+use silius_bundler::{
+    BundleError, BundleLimits, BundleResult, BundleSimulation, Bundler, DepositTopUp,
+    EntryPointBundle, NonceManager, RetryPolicy, SendBundleOp, StorageConflict,
+};
+use silius_mempool::find_user_operation_logs;
+use silius_metrics::{
+    bundler::{
+        record_aggregation_cache, record_bundle_profit, record_bundle_user_operations,
+        record_send_bundle_duration, record_send_bundle_error, record_signer_balance,
+        record_uopool_unavailable, record_user_operations_pulled,
+    },
+    grpc::MetricsLayer,
+};
+use silius_primitives::{
+    bundler::{BeneficiaryStrategy, BundleInclusion, FeeStrategy, TransactionConditional},
+    constants::bundler::{
+        BUNDLED_EVENTS_CHANNEL_SIZE, DEPOSIT_TOP_UP_INTERVAL, FAILED_OP_MAX_RETRIES,
+        HEALTH_CHECK_TIMEOUT, SHUTDOWN_TIMEOUT, SUBMITTED_USER_OPERATIONS_CACHE_SIZE,
+        UOPOOL_RECONNECT_DELAY, UOPOOL_RECONNECT_MAX_ATTEMPTS,
+    },
+    simulation::{StorageAccesses, StorageMap},
+    BundleSigner, BundledUserOperation, EntryPointVersion, UserOperation, UserOperationHash,
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{broadcast, broadcast::error::RecvError, Notify},
+    task::JoinHandle,
+};
+use tonic::{Code, Request, Response, Status};
+use tracing::{error, info, instrument, warn, Instrument};
+
+/// Recently submitted user operations with the entry point and the hash of the bundle transaction
+/// they were included in (oldest entries are evicted first)
+#[derive(Debug)]
+pub struct SubmittedUserOperations {
+    txs: HashMap<UserOperationHash, (Address, H256)>,
+    order: VecDeque<UserOperationHash>,
+    capacity: usize,
+}
+
+impl SubmittedUserOperations {
+    pub fn new(capacity: usize) -> Self {
+        Self { txs: HashMap::new(), order: VecDeque::new(), capacity }
+    }
 
+    pub fn insert(&mut self, uo_hash: UserOperationHash, ep: Address, tx_hash: H256) {
+        if self.txs.insert(uo_hash, (ep, tx_hash)).is_none() {
+            self.order.push_back(uo_hash);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(uo_hash) = self.order.pop_front() {
+                self.txs.remove(&uo_hash);
+            }
+        }
+    }
+
+    pub fn get(&self, uo_hash: &UserOperationHash) -> Option<(Address, H256)> {
+        self.txs.get(uo_hash).copied()
+    }
+}
+
+impl Default for SubmittedUserOperations {
+    fn default() -> Self {
+        Self::new(SUBMITTED_USER_OPERATIONS_CACHE_SIZE)
+    }
+}
+
+#[derive(Clone)]
 pub struct BundlerService<M, S>
 where
     M: Middleware + Clone + 'static,
     S: SendBundleOp + Clone + 'static,
 {
     pub bundlers: Vec<Bundler<M, S>>,
-    pub running: Arc<Mutex<bool>>,
+    /// Whether auto bundling is running per entry point. An entry point without an entry has no
+    /// auto bundling task, the task of a paused (or stopped) entry point exits on its next wake up
+    /// and removes the entry.
+    pub running: Arc<Mutex<HashMap<Address, bool>>>,
+    /// Whether the bundler is in auto bundling mode (the entry points may all be paused)
+    pub auto: Arc<Mutex<bool>>,
     pub uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
+    /// Handles of the spawned auto bundling tasks
+    pub handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// Wakes up the auto bundling tasks when bundling is stopped
+    pub notify: Arc<Notify>,
+    /// Effective auto bundling interval (in seconds) per entry point
+    pub intervals: Arc<Mutex<HashMap<Address, u64>>>,
+    /// Maximum time to wait for the bundle transaction receipt in `send_bundle_now`
+    pub receipt_timeout: Duration,
+    /// Bundle transactions of the recently submitted user operations
+    pub submitted: Arc<Mutex<SubmittedUserOperations>>,
+    /// Storage conflicts that deferred user operations in the last bundle of each entry point
+    pub conflicts: Arc<Mutex<HashMap<Address, Vec<StorageConflict>>>>,
+    /// Whether auto bundling is paused because the signer's balance is below the minimum balance
+    pub low_balance: Arc<Mutex<bool>>,
+    /// Channel the submitted user operations are published to once their bundle is mined
+    pub bundled: broadcast::Sender<BundledUserOperation>,
 }
 
-fn is_running(running: Arc<Mutex<bool>>) -> bool {
+/// Checks whether auto bundling is running for any of the entry points of an auto bundling task.
+/// Otherwise the entries of the entry points are removed, since the task exits.
+///
+/// # Arguments
+/// * `running` - Whether auto bundling is running per entry point
+/// * `eps` - The entry points of the auto bundling task
+///
+/// # Returns
+/// * `bool` - Whether the auto bundling task keeps running
+fn is_running(running: &Mutex<HashMap<Address, bool>>, eps: &[Address]) -> bool {
+    let mut r = running.lock();
+    if eps.iter().any(|ep| r.get(ep).copied().unwrap_or(false)) {
+        return true;
+    }
+    for ep in eps.iter() {
+        r.remove(ep);
+    }
+    false
+}
+
+/// Marks the entry points as running. The entry points of the auto bundling task that are not
+/// started are left paused.
+///
+/// # Arguments
+/// * `running` - Whether auto bundling is running per entry point
+/// * `eps` - The entry points to start
+/// * `task_eps` - The entry points of the auto bundling task
+///
+/// # Returns
+/// * `bool` - Whether the auto bundling task has to be spawned (false if it didn't exit yet)
+fn start_running(
+    running: &Mutex<HashMap<Address, bool>>,
+    eps: &[Address],
+    task_eps: &[Address],
+) -> bool {
+    let mut r = running.lock();
+    let alive = task_eps.iter().any(|ep| r.contains_key(ep));
+    for ep in task_eps.iter() {
+        let start = eps.contains(ep);
+        r.entry(*ep).and_modify(|running| *running |= start).or_insert(start);
+    }
+    !alive
+}
+
+/// Marks the entry point as paused
+///
+/// # Arguments
+/// * `running` - Whether auto bundling is running per entry point
+/// * `ep` - The entry point to pause
+///
+/// # Returns
+/// * `bool` - Whether auto bundling was running for the entry point
+fn pause_running(running: &Mutex<HashMap<Address, bool>>, ep: &Address) -> bool {
+    match running.lock().get_mut(ep) {
+        Some(running) if *running => {
+            *running = false;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns the entry points of the auto bundling task that are not paused
+///
+/// # Arguments
+/// * `running` - Whether auto bundling is running per entry point
+/// * `eps` - The entry points of the auto bundling task
+///
+/// # Returns
+/// * `Vec<Address>` - The running entry points
+fn running_entry_points(running: &Mutex<HashMap<Address, bool>>, eps: &[Address]) -> Vec<Address> {
     let r = running.lock();
-    *r
+    eps.iter().filter(|ep| r.get(ep).copied().unwrap_or(false)).copied().collect()
+}
+
+/// Calls the uopool gRPC service, retrying with exponential backoff while the service is
+/// unreachable (the channel re-establishes the connection on the next call), so that a restart of
+/// the uopool doesn't cost a whole bundle interval
+///
+/// # Arguments
+/// * `ep` - The entry point the call is made for (used for metrics)
+/// * `call` - Makes the call to the uopool gRPC service
+///
+/// # Returns
+/// * `T` - The response of the uopool gRPC service
+async fn call_uopool<T, F, Fut>(ep: &Address, mut call: F) -> Result<T, Status>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response<T>, Status>>,
+{
+    let mut delay = Duration::from_millis(UOPOOL_RECONNECT_DELAY);
+    let mut attempt = 1;
+    loop {
+        match call().await {
+            Ok(res) => return Ok(res.into_inner()),
+            Err(status) if status.code() == Code::Unavailable => {
+                record_uopool_unavailable(ep);
+                if attempt >= UOPOOL_RECONNECT_MAX_ATTEMPTS {
+                    return Err(status);
+                }
+                warn!(
+                    "Uopool gRPC service unreachable (attempt {attempt}), reconnecting in {delay:?}: {}",
+                    status.message()
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(status) => return Err(status),
+        }
+    }
+}
+
+/// Checks whether the error is caused by the uopool gRPC service being unreachable (as opposed to
+/// e.g. the mempool having no user operations)
+fn is_uopool_unavailable(err: &eyre::Report) -> bool {
+    err.downcast_ref::<Status>().is_some_and(|status| status.code() == Code::Unavailable)
 }
 
 impl<M, S> BundlerService<M, S>
@@ -39,89 +248,1031 @@ where
     pub fn new(
         bundlers: Vec<Bundler<M, S>>,
         uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
+        receipt_timeout: Duration,
     ) -> Self {
-        Self { bundlers, running: Arc::new(Mutex::new(false)), uopool_grpc_client }
+        Self {
+            bundlers,
+            running: Arc::new(Mutex::new(HashMap::new())),
+            auto: Arc::new(Mutex::new(false)),
+            uopool_grpc_client,
+            handles: Arc::new(Mutex::new(vec![])),
+            notify: Arc::new(Notify::new()),
+            intervals: Arc::new(Mutex::new(HashMap::new())),
+            receipt_timeout,
+            submitted: Arc::new(Mutex::new(SubmittedUserOperations::default())),
+            conflicts: Arc::new(Mutex::new(HashMap::new())),
+            low_balance: Arc::new(Mutex::new(false)),
+            bundled: broadcast::channel(BUNDLED_EVENTS_CHANNEL_SIZE).0,
+        }
+    }
+
+    /// Checks the balance of the bundler's signer against the minimum balance and pauses (or
+    /// resumes) auto bundling when the balance crosses it
+    ///
+    /// # Arguments
+    /// * `bundler` - The [Bundler](Bundler) whose signer is checked
+    /// * `low_balance` - Whether auto bundling is currently paused due to low funds
+    ///
+    /// # Returns
+    /// * `bool` - True if auto bundling is paused due to low funds
+    async fn check_balance(
+        bundler: &Bundler<M, S>,
+        low_balance: &Mutex<bool>,
+    ) -> eyre::Result<bool> {
+        let address = bundler.signer.address();
+        let balance = bundler.eth_client.get_balance(address, None).await?;
+        let low = balance < bundler.min_balance;
+        record_signer_balance(&address, balance, low);
+
+        let was_low = std::mem::replace(&mut *low_balance.lock(), low);
+        match (was_low, low) {
+            (false, true) => warn!(
+                "Balance of the bundler's signer {address:?} ({balance}) is below the minimum balance ({}), pausing auto bundling",
+                bundler.min_balance
+            ),
+            (true, false) => info!(
+                "Balance of the bundler's signer {address:?} ({balance}) was topped up, resuming auto bundling"
+            ),
+            _ => {}
+        }
+
+        Ok(low)
     }
 
+    /// Waits for the bundle transaction (or its latest replacement, if it was stuck) to be mined
+    /// and confirmed by the given number of blocks. The receipt is fetched again on every poll, so
+    /// a bundle reorged out before it's confirmed is waited for again. The nonce of the bundle is
+    /// released once the bundle is mined.
+    ///
+    /// # Arguments
+    /// * `eth_client` - The Ethereum execution client the bundle was sent through
+    /// * `nonce_manager` - The [NonceManager](NonceManager) that tracks the replacements
+    /// * `bundle` - The [BundleResult](BundleResult) of the sent bundle
+    /// * `confirmations` - Number of block confirmations (the block including the bundle counts as
+    ///   the first one)
+    /// * `timeout` - Maximum time to wait for the confirmed receipt
+    ///
+    /// # Returns
+    /// * `SendBundleStatus` - `Mined` if the receipt is available and confirmed, `Dropped` if the
+    ///   transaction disappeared from the mempool without being mined, `NotMined` if the timeout
+    ///   elapsed
+    /// * `Option<TransactionReceipt>` - The receipt of the mined transaction
+    #[instrument(
+        name = "bundle_confirmation",
+        skip_all,
+        fields(tx_hash = ?bundle.tx_hash, confirmations = confirmations)
+    )]
+    async fn wait_for_receipt(
+        eth_client: &Arc<M>,
+        nonce_manager: &NonceManager,
+        bundle: &BundleResult,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> (SendBundleStatus, Option<TransactionReceipt>) {
+        let start = Instant::now();
+        let mut tx_hash = bundle.tx_hash;
+        let mut seen = false;
+
+        loop {
+            let latest = nonce_manager.latest_tx_hash(bundle.tx_hash);
+            if latest != tx_hash {
+                info!("Bundle transaction {tx_hash:?} was replaced by {latest:?}");
+                tx_hash = latest;
+                seen = false;
+            }
+
+            match eth_client.get_transaction_receipt(tx_hash).await {
+                Ok(Some(receipt)) => {
+                    seen = true;
+                    nonce_manager.confirm(bundle.nonce);
+                    if Self::is_confirmed(eth_client, &receipt, confirmations).await {
+                        return (SendBundleStatus::Mined, Some(receipt));
+                    }
+                }
+                // transactions sent through private relays are never visible in the public
+                // mempool, so the transaction is only considered dropped if it was seen before
+                _ => match eth_client.get_transaction(tx_hash).await {
+                    Ok(Some(_)) => seen = true,
+                    Ok(None) if seen => {
+                        // the transaction could have been mined in the meantime (the
+                        // confirmations are checked on the next poll)
+                        let receipt = eth_client.get_transaction_receipt(tx_hash).await;
+                        if !matches!(receipt, Ok(Some(_))) {
+                            warn!("Bundle transaction {tx_hash:?} was dropped from the mempool");
+                            return (SendBundleStatus::Dropped, None);
+                        }
+                    }
+                    _ => {}
+                },
+            }
+
+            if start.elapsed() >= timeout {
+                warn!(
+                    "Bundle transaction {tx_hash:?} was not mined with {confirmations} confirmations within {timeout:?}"
+                );
+                return (SendBundleStatus::NotMined, None);
+            }
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Checks whether the block that included the transaction has the given number of
+    /// confirmations
+    async fn is_confirmed(
+        eth_client: &Arc<M>,
+        receipt: &TransactionReceipt,
+        confirmations: u64,
+    ) -> bool {
+        if confirmations <= 1 {
+            return true;
+        }
+        let Some(block_number) = receipt.block_number else {
+            return false;
+        };
+        eth_client
+            .get_block_number()
+            .await
+            .is_ok_and(|latest| latest + 1 >= block_number + confirmations)
+    }
+
+    /// Waits for the bundle transaction in the background and publishes the user operations of
+    /// the bundle (with whether their execution succeeded) once it is mined and confirmed
+    ///
+    /// # Arguments
+    /// * `bundler` - The [Bundler](Bundler) that sent the bundle
+    /// * `bundle` - The [BundleResult](BundleResult) of the sent bundle
+    /// * `bundled` - The channel the [BundledUserOperations](BundledUserOperation) are published to
+    /// * `timeout` - Maximum time to wait for the receipt
+    fn notify_bundled(
+        bundler: &Bundler<M, S>,
+        bundle: &BundleResult,
+        bundled: &broadcast::Sender<BundledUserOperation>,
+        timeout: Duration,
+    ) {
+        // nobody to notify
+        if bundled.receiver_count() == 0 {
+            return;
+        }
+
+        let eth_client = bundler.eth_client.clone();
+        let nonce_manager = bundler.nonce_manager.clone();
+        let ep = bundler.entry_point;
+        let confirmations = bundler.confirmations;
+        let bundle = bundle.clone();
+        let bundled = bundled.clone();
+
+        tokio::spawn(
+            async move {
+                let (_, receipt) = Self::wait_for_receipt(
+                    &eth_client,
+                    &nonce_manager,
+                    &bundle,
+                    confirmations,
+                    timeout,
+                )
+                .await;
+                if let Some(receipt) = receipt {
+                    let tx_hash = receipt.transaction_hash;
+                    for uo_hash in bundle.uos {
+                        let success = find_user_operation_logs(&receipt.logs, &ep, &uo_hash)
+                            .is_some_and(|(event, _)| event.success);
+                        // sending only fails if there are no subscribers
+                        let _ = bundled.send(BundledUserOperation {
+                            user_operation_hash: uo_hash,
+                            entry_point: ep,
+                            transaction_hash: tx_hash,
+                            success,
+                        });
+                    }
+                }
+            }
+            .in_current_span(),
+        );
+    }
+
+    /// Gets the user operations to bundle, the hashes of the aggregated user operations by
+    /// aggregator, the conditions under which the bundle stays valid (derived from the storage
+    /// slots read during the validation) and the storage slots read and written during the
+    /// validation of each user operation
     async fn get_user_operations(
         uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
         ep: &Address,
-    ) -> eyre::Result<Vec<UserOperation>> {
-        let req = Request::new(GetSortedRequest { ep: Some((*ep).into()) });
-        let res = uopool_grpc_client.clone().get_sorted_user_operations(req).await?;
+    ) -> eyre::Result<(
+        Vec<UserOperation>,
+        HashMap<Address, Vec<UserOperationHash>>,
+        Option<TransactionConditional>,
+        HashMap<UserOperationHash, StorageAccesses>,
+    )> {
+        let res = call_uopool(ep, || {
+            let mut client = uopool_grpc_client.clone();
+            let req = Request::new(GetSortedRequest { ep: Some((*ep).into()) });
+            async move { client.get_sorted_user_operations(req).await }
+        })
+        .await?;
+
+        let uos: Vec<UserOperation> = res.uos.into_iter().map(|u| u.into()).collect();
+        record_user_operations_pulled(ep, uos.len());
+
+        let aggregated = res
+            .aggregated
+            .into_iter()
+            .filter_map(|a| {
+                a.aggregator.map(|aggregator| {
+                    (Address::from(aggregator), a.uos.into_iter().map(Into::into).collect())
+                })
+            })
+            .collect();
+
+        let conditional = serde_json::from_str::<StorageMap>(&res.storage_map)
+            .ok()
+            .filter(|storage_map| !storage_map.is_empty())
+            .map(|storage_map| TransactionConditional::from_storage_map(&storage_map));
+        let storage_accesses = serde_json::from_str(&res.storage_accesses).unwrap_or_default();
+        Ok((uos, aggregated, conditional, storage_accesses))
+    }
+
+    /// Drops the user operations that revert when re-simulated right before bundling (the mempool
+    /// removes them and updates the reputation of the responsible entities, unless `read_only` is
+    /// set)
+    ///
+    /// # Returns
+    /// * `Vec<UserOperation>` - The user operations that are still valid
+    /// * `Vec<UserOperationHash>` - The hashes of the dropped user operations
+    async fn drop_failed_user_operations(
+        bundler: &Bundler<M, S>,
+        uos: &[UserOperation],
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        read_only: bool,
+    ) -> eyre::Result<(Vec<UserOperation>, Vec<UserOperationHash>)> {
+        let (uos, uos_dropped) = bundler.simulate_user_operations(uos).await?;
+
+        if !uos_dropped.is_empty() && !read_only {
+            warn!(
+                "Dropping {} user operations that failed re-simulation: {:?}",
+                uos_dropped.len(),
+                uos_dropped.iter().map(|(uo, reason)| (uo.hash, reason)).collect::<Vec<_>>()
+            );
+
+            Self::remove_failed_user_operations(
+                bundler,
+                uos_dropped.iter().map(|(uo, reason)| (uo.hash, reason.clone())).collect(),
+                uopool_grpc_client,
+            )
+            .await;
+        }
+
+        Ok((uos, uos_dropped.into_iter().map(|(uo, _)| uo.hash).collect()))
+    }
+
+    /// Removes the failed user operations from the mempool (the mempool updates the reputation of
+    /// the responsible entities)
+    ///
+    /// # Arguments
+    /// * `uos` - The hashes of the failed user operations paired with the revert reason
+    async fn remove_failed_user_operations(
+        bundler: &Bundler<M, S>,
+        uos: Vec<(UserOperationHash, String)>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+    ) {
+        let uos = uos
+            .into_iter()
+            .map(|(uo_hash, reason)| FailedUserOperation { hash: Some(uo_hash.into()), reason })
+            .collect::<Vec<_>>();
+        let res = call_uopool(&bundler.entry_point, || {
+            let mut client = uopool_grpc_client.clone();
+            let req = Request::new(RemoveFailedRequest {
+                uos: uos.clone(),
+                ep: Some(bundler.entry_point.into()),
+            });
+            async move { client.remove_failed(req).await }
+        })
+        .await;
+        if let Err(err) = res {
+            error!("Error while removing failed user operations: {err:?}");
+        }
+    }
+
+    /// Selects the user operations of the bundle, i.e., the ones that pass the re-simulation (up
+    /// to the bundle limits and without storage conflicts, the rest is deferred to the next
+    /// bundle). If `read_only` is set (simulations and the dry run mode), the failed user
+    /// operations are left in the mempool and the storage conflicts are not recorded.
+    ///
+    /// # Returns
+    /// * `Vec<UserOperation>` - The user operations of the bundle
+    /// * `Vec<UserOperationHash>` - The hashes of the user operations dropped from the bundle
+    async fn prepare_bundle(
+        bundler: &Bundler<M, S>,
+        uos: &[UserOperation],
+        storage_accesses: &HashMap<UserOperationHash, StorageAccesses>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        conflicts: &Mutex<HashMap<Address, Vec<StorageConflict>>>,
+        read_only: bool,
+    ) -> eyre::Result<(Vec<UserOperation>, Vec<UserOperationHash>)> {
+        let (uos, foreign) = bundler.split_foreign_user_operations(uos);
+        if !foreign.is_empty() {
+            warn!(
+                "Skipping {} user operations that do not target entry point {:?}: {:?}",
+                foreign.len(),
+                bundler.entry_point,
+                foreign.iter().map(|uo| uo.hash).collect::<Vec<_>>()
+            );
+        }
+
+        let (uos, dropped) =
+            Self::drop_failed_user_operations(bundler, &uos, uopool_grpc_client, read_only).await?;
+
+        let (uos, deferred, conflicts_bundle) =
+            bundler.detect_storage_conflicts(uos, storage_accesses);
+        if !deferred.is_empty() {
+            info!(
+                "Deferring {} user operations with storage conflicts to the next bundle: {:?}",
+                deferred.len(),
+                conflicts_bundle
+            );
+        }
+        if !read_only {
+            conflicts.lock().insert(bundler.entry_point, conflicts_bundle);
+        }
+
+        let (uos, deferred) = bundler.limit_user_operations(uos).await?;
+        if !deferred.is_empty() {
+            info!(
+                "Deferring {} user operations beyond the bundle limits to the next bundle: {:?}",
+                deferred.len(),
+                deferred.iter().map(|uo| uo.hash).collect::<Vec<_>>()
+            );
+        }
+
+        Ok((uos, dropped))
+    }
+
+    /// Sends the bundle of the user operations that pass the re-simulation, remembers the bundle
+    /// transaction of the submitted user operations and records the bundler metrics (duration,
+    /// size, and failures). If the entry point reverts with `FailedOp`, the failed user operation
+    /// is dropped (penalizing the responsible entity) and the rest of the user operations are
+    /// resubmitted right away, up to [FAILED_OP_MAX_RETRIES](FAILED_OP_MAX_RETRIES) times.
+    ///
+    /// # Returns
+    /// * `Option<BundleResult>` - The [BundleResult](BundleResult) (`None` if there was nothing to
+    ///   bundle, in dry run mode or if the retries were exhausted)
+    /// * `Vec<UserOperationHash>` - The hashes of the user operations dropped from the bundle
+    #[allow(clippy::too_many_arguments)]
+    #[instrument(skip_all, fields(ep = ?bundler.entry_point))]
+    async fn send_bundle(
+        bundler: &Bundler<M, S>,
+        uos: &[UserOperation],
+        aggregated: &HashMap<Address, Vec<UserOperationHash>>,
+        conditional: Option<TransactionConditional>,
+        storage_accesses: &HashMap<UserOperationHash, StorageAccesses>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        submitted: &Mutex<SubmittedUserOperations>,
+        conflicts: &Mutex<HashMap<Address, Vec<StorageConflict>>>,
+        bundled: &broadcast::Sender<BundledUserOperation>,
+        receipt_timeout: Duration,
+    ) -> eyre::Result<(Option<BundleResult>, Vec<UserOperationHash>)> {
+        let (mut uos, mut dropped) = Self::prepare_bundle(
+            bundler,
+            uos,
+            storage_accesses,
+            uopool_grpc_client,
+            conflicts,
+            bundler.dry_run,
+        )
+        .await?;
+
+        if bundler.dry_run {
+            let simulation = bundler.simulate_bundle(&uos, aggregated).await?;
+            info!("Dry run of bundle for entry point {:?}: {simulation:?}", bundler.entry_point);
+            return Ok((None, dropped));
+        }
+
+        let mut retries = 0;
+        loop {
+            let start = Instant::now();
+            let res = bundler.send_bundle_aggregated(&uos, aggregated, conditional.clone()).await;
+            record_send_bundle_duration(&bundler.entry_point, start.elapsed());
+            let stats = bundler.aggregation_cache.stats();
+            record_aggregation_cache(&bundler.entry_point, stats.hits, stats.misses);
+
+            match &res {
+                Ok(Some(bundle)) => {
+                    record_bundle_user_operations(&bundler.entry_point, bundle.uos.len());
+                    if let Some(profit) = bundle.profit {
+                        record_bundle_profit(&bundler.signer.address(), profit);
+                    }
+                    let mut submitted = submitted.lock();
+                    for uo_hash in bundle.uos.iter() {
+                        submitted.insert(*uo_hash, bundler.entry_point, bundle.tx_hash);
+                    }
+                    Self::notify_bundled(bundler, bundle, bundled, receipt_timeout);
+                }
+                Ok(None) => {}
+                Err(BundleError::Unprofitable { profit }) => {
+                    record_bundle_profit(&bundler.signer.address(), *profit);
+                    info!(
+                        "Skipping unprofitable bundle for entry point {:?}, estimated profit: {profit} wei",
+                        bundler.entry_point
+                    );
+                    return Ok((None, dropped));
+                }
+                Err(err) => {
+                    record_send_bundle_error(&bundler.entry_point, err.category());
+
+                    if let BundleError::FailedOp { uo_hash: Some(uo_hash), reason, .. } = err {
+                        warn!(uo_hash = %uo_hash, "Dropping user operation that failed in the bundle: {reason}");
+                        Self::remove_failed_user_operations(
+                            bundler,
+                            vec![(*uo_hash, reason.clone())],
+                            uopool_grpc_client,
+                        )
+                        .await;
+                        dropped.push(*uo_hash);
+                        uos.retain(|uo| uo.hash != *uo_hash);
+
+                        if retries >= FAILED_OP_MAX_RETRIES || uos.is_empty() {
+                            return Ok((None, dropped));
+                        }
+
+                        retries += 1;
+                        info!(
+                            "Resubmitting the bundle without the failed user operation (retry {retries}/{FAILED_OP_MAX_RETRIES})"
+                        );
+                        continue;
+                    }
+                }
+            }
+
+            return Ok((res?, dropped));
+        }
+    }
+
+    /// Checks whether the bundles of all entry points are sent in one transaction through the
+    /// multicall contract (only if there are multiple entry points and not in dry run mode)
+    fn use_multicall(bundlers: &[Bundler<M, S>]) -> bool {
+        let Some(bundler) = bundlers.first() else {
+            return false;
+        };
+        bundlers.len() > 1 && bundler.multicall.is_some() && !bundler.dry_run
+    }
+
+    /// Sends the bundles of all entry points in one transaction through the multicall contract,
+    /// remembers the bundle transaction of the submitted user operations and records the bundler
+    /// metrics. As with [send_bundle](BundlerService::send_bundle), the user operation that fails
+    /// in the bundle is dropped and the rest is resubmitted, up to
+    /// [FAILED_OP_MAX_RETRIES](FAILED_OP_MAX_RETRIES) times. The bundlers share the signer, so
+    /// the first one sends the transaction.
+    ///
+    /// # Returns
+    /// * `Vec<(Address, Option<BundleResult>, Vec<UserOperationHash>)>` - The entry point address
+    ///   of each bundler with the [BundleResult](BundleResult) of its user operations in the
+    ///   transaction (`None` if there was nothing to bundle) and the hashes of the user operations
+    ///   dropped from the bundle
+    #[instrument(skip_all)]
+    async fn send_bundle_multicall(
+        bundlers: &[Bundler<M, S>],
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        submitted: &Mutex<SubmittedUserOperations>,
+        conflicts: &Mutex<HashMap<Address, Vec<StorageConflict>>>,
+        bundled: &broadcast::Sender<BundledUserOperation>,
+        receipt_timeout: Duration,
+    ) -> eyre::Result<Vec<(Address, Option<BundleResult>, Vec<UserOperationHash>)>> {
+        let mut bundles = vec![];
+        let mut dropped = vec![];
+        for bundler in bundlers.iter() {
+            let (uos, aggregated, _, storage_accesses) =
+                Self::get_user_operations(uopool_grpc_client, &bundler.entry_point).await?;
+            let (uos, dropped_ep) = Self::prepare_bundle(
+                bundler,
+                &uos,
+                &storage_accesses,
+                uopool_grpc_client,
+                conflicts,
+                false,
+            )
+            .await?;
+            bundles.push(EntryPointBundle { entry_point: bundler.entry_point, uos, aggregated });
+            dropped.push(dropped_ep);
+        }
+
+        let Some(bundler) = bundlers.first() else {
+            return Ok(vec![]);
+        };
+
+        let mut retries = 0;
+        let bundle = loop {
+            let start = Instant::now();
+            let res = bundler.send_bundle_multicall(&bundles, None).await;
+            let eps: Vec<Address> = bundles
+                .iter()
+                .filter(|bundle| !bundle.uos.is_empty())
+                .map(|bundle| bundle.entry_point)
+                .collect();
+            for ep in eps.iter() {
+                record_send_bundle_duration(ep, start.elapsed());
+            }
+            let stats = bundler.aggregation_cache.stats();
+            record_aggregation_cache(&bundler.entry_point, stats.hits, stats.misses);
+
+            let err = match res {
+                Ok(bundle) => break bundle,
+                Err(BundleError::Unprofitable { profit }) => {
+                    record_bundle_profit(&bundler.signer.address(), profit);
+                    info!(
+                        "Skipping unprofitable bundle for entry points {eps:?}, estimated profit: {profit} wei"
+                    );
+                    break None;
+                }
+                Err(err) => err,
+            };
+            for ep in eps.iter() {
+                record_send_bundle_error(ep, err.category());
+            }
 
-        let uos: Vec<UserOperation> = res.into_inner().uos.into_iter().map(|u| u.into()).collect();
-        Ok(uos)
+            let BundleError::FailedOp { uo_hash: Some(uo_hash), reason, .. } = &err else {
+                return Err(err.into());
+            };
+            let Some(index) =
+                bundles.iter().position(|bundle| bundle.uos.iter().any(|uo| uo.hash == *uo_hash))
+            else {
+                return Err(err.into());
+            };
+
+            warn!(uo_hash = %uo_hash, "Dropping user operation that failed in the bundle: {reason}");
+            Self::remove_failed_user_operations(
+                &bundlers[index],
+                vec![(*uo_hash, reason.clone())],
+                uopool_grpc_client,
+            )
+            .await;
+            dropped[index].push(*uo_hash);
+            bundles[index].uos.retain(|uo| uo.hash != *uo_hash);
+
+            let empty = bundles.iter().all(|bundle| bundle.uos.is_empty());
+            if retries >= FAILED_OP_MAX_RETRIES || empty {
+                break None;
+            }
+
+            retries += 1;
+            info!(
+                "Resubmitting the bundle without the failed user operation (retry {retries}/{FAILED_OP_MAX_RETRIES})"
+            );
+        };
+
+        if let Some(bundle) = &bundle {
+            if let Some(profit) = bundle.profit {
+                record_bundle_profit(&bundler.signer.address(), profit);
+            }
+        }
+
+        let mut results = vec![];
+        for ((bundler, ep_bundle), dropped) in bundlers.iter().zip(bundles).zip(dropped) {
+            let bundle =
+                bundle.as_ref().filter(|_| !ep_bundle.uos.is_empty()).map(|bundle| BundleResult {
+                    uos: ep_bundle.uos.iter().map(|uo| uo.hash).collect(),
+                    ..bundle.clone()
+                });
+
+            if let Some(bundle) = &bundle {
+                record_bundle_user_operations(&bundler.entry_point, bundle.uos.len());
+                let mut submitted = submitted.lock();
+                for uo_hash in bundle.uos.iter() {
+                    submitted.insert(*uo_hash, bundler.entry_point, bundle.tx_hash);
+                }
+                Self::notify_bundled(bundler, bundle, bundled, receipt_timeout);
+            }
+
+            results.push((bundler.entry_point, bundle, dropped));
+        }
+
+        Ok(results)
     }
 
-    pub async fn send_bundles(&self) -> eyre::Result<Option<H256>> {
-        let mut tx_hashes: Vec<Option<H256>> = vec![];
+    /// Sends a bundle for every entry point the service is configured with (in one transaction if
+    /// the multicall contract is configured)
+    ///
+    /// # Returns
+    /// * `Vec<(Address, Option<BundleResult>, Vec<UserOperationHash>)>` - The entry point address
+    ///   of each bundler with the [BundleResult](BundleResult) of the bundle it submitted (`None`
+    ///   if there was nothing to bundle) and the hashes of the user operations dropped because they
+    ///   failed re-simulation
+    pub async fn send_bundles(
+        &self,
+    ) -> eyre::Result<Vec<(Address, Option<BundleResult>, Vec<UserOperationHash>)>> {
+        if Self::use_multicall(&self.bundlers) {
+            return Self::send_bundle_multicall(
+                &self.bundlers,
+                &self.uopool_grpc_client,
+                &self.submitted,
+                &self.conflicts,
+                &self.bundled,
+                self.receipt_timeout,
+            )
+            .await;
+        }
+
+        let mut bundles: Vec<(Address, Option<BundleResult>, Vec<UserOperationHash>)> = vec![];
 
         for bundler in self.bundlers.iter() {
-            let uos =
+            let (uos, aggregated, conditional, storage_accesses) =
                 Self::get_user_operations(&self.uopool_grpc_client, &bundler.entry_point).await?;
-            let tx_hash = bundler.send_bundle(&uos).await?;
+            let (bundle, dropped) = Self::send_bundle(
+                bundler,
+                &uos,
+                &aggregated,
+                conditional,
+                &storage_accesses,
+                &self.uopool_grpc_client,
+                &self.submitted,
+                &self.conflicts,
+                &self.bundled,
+                self.receipt_timeout,
+            )
+            .await?;
 
-            tx_hashes.push(tx_hash)
+            bundles.push((bundler.entry_point, bundle, dropped))
         }
 
-        // FIXME: Because currently the bundler support multiple bundler and
-        // we don't have a way to know which bundler is the one that is
-        Ok(tx_hashes.into_iter().next().expect("At least one bundler must be present"))
+        Ok(bundles)
+    }
+
+    /// Simulates a bundle for every entry point the service is configured with, without
+    /// submitting it. The simulation is read-only, i.e., the user operations that fail the
+    /// re-simulation are reported but not removed from the mempool.
+    ///
+    /// # Returns
+    /// * `Vec<(Address, BundleSimulation, Vec<UserOperationHash>)>` - The entry point address of
+    ///   each bundler with the [BundleSimulation](BundleSimulation) and the hashes of the user
+    ///   operations dropped because they failed re-simulation
+    pub async fn simulate_bundles(
+        &self,
+    ) -> eyre::Result<Vec<(Address, BundleSimulation, Vec<UserOperationHash>)>> {
+        let mut simulations = vec![];
+
+        for bundler in self.bundlers.iter() {
+            let (uos, aggregated, _, storage_accesses) =
+                Self::get_user_operations(&self.uopool_grpc_client, &bundler.entry_point).await?;
+            let (uos, dropped) = Self::prepare_bundle(
+                bundler,
+                &uos,
+                &storage_accesses,
+                &self.uopool_grpc_client,
+                &self.conflicts,
+                true,
+            )
+            .await?;
+            let simulation = bundler.simulate_bundle(&uos, &aggregated).await?;
+
+            simulations.push((bundler.entry_point, simulation, dropped));
+        }
+
+        Ok(simulations)
+    }
+
+    /// Explains whether the user operation of the mempool would be included in the next bundle of
+    /// its entry point, and why not otherwise
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the user operation
+    ///
+    /// # Returns
+    /// * `Option<(Address, BundleInclusion)>` - The entry point of the user operation with the
+    ///   [BundleInclusion](BundleInclusion) (`None` if the user operation is not in the mempool)
+    pub async fn explain_user_operation(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> eyre::Result<Option<(Address, BundleInclusion)>> {
+        for bundler in self.bundlers.iter() {
+            let ep = bundler.entry_point;
+            let res = call_uopool(&ep, || {
+                let mut client = self.uopool_grpc_client.clone();
+                let req = Request::new(GetAllRequest { ep: Some(ep.into()), with_queued: false });
+                async move { client.get_all(req).await }
+            })
+            .await?;
+
+            let uo = res.uos.into_iter().map(UserOperation::from).find(|uo| uo.hash == *uo_hash);
+            if let Some(uo) = uo {
+                let (uos, _, _, storage_accesses) =
+                    Self::get_user_operations(&self.uopool_grpc_client, &ep).await?;
+                let inclusion = bundler.explain_user_operation(&uo, uos, &storage_accesses).await?;
+                return Ok(Some((ep, inclusion)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Starts the background task of every bundler with the [DepositTopUp](DepositTopUp) enabled,
+    /// which checks the signer's deposit in the entry point every
+    /// [DEPOSIT_TOP_UP_INTERVAL](DEPOSIT_TOP_UP_INTERVAL) seconds and tops it up once it's below
+    /// the threshold
+    pub fn start_deposit_top_up(&self) {
+        for bundler in self.bundlers.iter().filter(|bundler| bundler.deposit_top_up.is_some()) {
+            let bundler = bundler.clone();
+            info!("Starting deposit top-up for entry point {:?}", bundler.entry_point);
+
+            tokio::spawn(async move {
+                let mut interval =
+                    tokio::time::interval(Duration::from_secs(DEPOSIT_TOP_UP_INTERVAL));
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = bundler.top_up_deposit().await {
+                        error!(
+                            "Error while topping up the deposit in entry point {:?}: {e:?}",
+                            bundler.entry_point
+                        );
+                    }
+                }
+            });
+        }
     }
 
     pub fn stop_bundling(&self) {
         info!("Stopping auto bundling");
-        let mut r = self.running.lock();
-        *r = false;
+        *self.auto.lock() = false;
+        self.running.lock().values_mut().for_each(|running| *running = false);
+        self.notify.notify_waiters();
     }
 
+    /// Stops auto bundling and waits for the bundles that are currently being sent to finish
+    ///
+    /// # Arguments
+    /// * `timeout` - Maximum time to wait for the in-flight bundles
+    pub async fn shutdown(&self, timeout: Duration) {
+        self.stop_bundling();
+
+        let handles: Vec<JoinHandle<()>> = self.handles.lock().drain(..).collect();
+        if handles.is_empty() {
+            return;
+        }
+
+        info!("Waiting for {} bundling task(s) to finish", handles.len());
+        match tokio::time::timeout(timeout, futures::future::join_all(handles)).await {
+            Ok(_) => info!("All bundling tasks finished"),
+            Err(_) => warn!("Bundling tasks did not finish within {timeout:?}"),
+        }
+    }
+
+    /// Returns whether auto bundling is running for any entry point
     pub fn is_running(&self) -> bool {
-        is_running(self.running.clone())
+        self.running.lock().values().any(|running| *running)
+    }
+
+    /// Returns whether auto bundling is running for the entry point
+    pub fn is_running_for(&self, ep: &Address) -> bool {
+        self.running.lock().get(ep).copied().unwrap_or(false)
+    }
+
+    /// Returns the entry points the service is configured with
+    pub fn entry_points(&self) -> Vec<Address> {
+        self.bundlers.iter().map(|bundler| bundler.entry_point).collect()
+    }
+
+    /// Returns the chain the service is running on (`None` if no bundler is configured)
+    pub fn chain(&self) -> Option<Chain> {
+        self.bundlers.first().map(|bundler| bundler.chain)
     }
 
-    pub fn start_bundling(&self, int: u64) {
+    /// Starts auto bundling for every bundler
+    ///
+    /// # Arguments
+    /// * `int` - The global bundle interval (in seconds)
+    /// * `intervals` - Per entry point bundle intervals that take precedence over the interval the
+    ///   bundler was configured with and the global interval
+    pub fn start_bundling(&self, int: u64, intervals: &HashMap<Address, u64>) {
+        *self.auto.lock() = true;
         if !self.is_running() {
             info!("Starting auto bundling");
+            self.handles.lock().retain(|h| !h.is_finished());
 
-            {
-                let mut r = self.running.lock();
-                *r = true;
+            if Self::use_multicall(&self.bundlers) {
+                info!("Auto bundling interval for all entry points (multicall): {int}s");
+                for bundler in self.bundlers.iter() {
+                    self.intervals.lock().insert(bundler.entry_point, int);
+                }
+                self.start_bundling_multicall(int, &self.entry_points());
+                return;
             }
 
             for bundler in self.bundlers.iter() {
-                let bundler_own = bundler.clone();
-                let running_lock = self.running.clone();
-                let uopool_grpc_client = self.uopool_grpc_client.clone();
+                let int = intervals
+                    .get(&bundler.entry_point)
+                    .copied()
+                    .or(bundler.bundle_interval)
+                    .unwrap_or(int);
+                info!("Auto bundling interval for entry point {:?}: {int}s", bundler.entry_point);
+                self.intervals.lock().insert(bundler.entry_point, int);
+                self.start_bundling_entry_point(bundler, int);
+            }
+        }
+    }
 
-                tokio::spawn(async move {
-                    let mut interval = tokio::time::interval(Duration::from_secs(int));
-                    loop {
-                        interval.tick().await;
+    /// Pauses auto bundling for the entry point, the other entry points keep being bundled
+    ///
+    /// # Arguments
+    /// * `ep` - The entry point
+    ///
+    /// # Returns
+    /// * `bool` - Whether auto bundling was running for the entry point
+    pub fn pause_bundling(&self, ep: &Address) -> bool {
+        if !pause_running(&self.running, ep) {
+            return false;
+        }
+        info!("Pausing auto bundling for entry point {ep:?}");
+        true
+    }
 
-                        if !is_running(running_lock.clone()) {
-                            break;
-                        }
+    /// Resumes auto bundling for the entry point with the interval it was last started with
+    ///
+    /// # Arguments
+    /// * `ep` - The entry point
+    ///
+    /// # Returns
+    /// * `bool` - Whether auto bundling was resumed (false if the bundler is not in auto bundling
+    ///   mode or auto bundling was never started for the entry point)
+    pub fn resume_bundling(&self, ep: &Address) -> bool {
+        if !*self.auto.lock() {
+            return false;
+        }
+        let int = match self.intervals.lock().get(ep) {
+            Some(int) => *int,
+            None => return false,
+        };
+        let bundler = match self.bundlers.iter().find(|bundler| bundler.entry_point == *ep) {
+            Some(bundler) => bundler,
+            None => return false,
+        };
 
-                        match Self::get_user_operations(
+        info!("Resuming auto bundling for entry point {ep:?} (interval {int}s)");
+        self.handles.lock().retain(|h| !h.is_finished());
+        if Self::use_multicall(&self.bundlers) {
+            self.start_bundling_multicall(int, &[*ep]);
+        } else {
+            self.start_bundling_entry_point(bundler, int);
+        }
+        true
+    }
+
+    /// Starts auto bundling for the entry point of the bundler, the task of the entry point is
+    /// reused if it didn't exit yet
+    ///
+    /// # Arguments
+    /// * `bundler` - The [Bundler](Bundler) of the entry point
+    /// * `int` - The bundle interval (in seconds)
+    fn start_bundling_entry_point(&self, bundler: &Bundler<M, S>, int: u64) {
+        if !start_running(&self.running, &[bundler.entry_point], &[bundler.entry_point]) {
+            return;
+        }
+
+        let bundler_own = bundler.clone();
+        let running_lock = self.running.clone();
+        let uopool_grpc_client = self.uopool_grpc_client.clone();
+        let notify = self.notify.clone();
+        let submitted = self.submitted.clone();
+        let conflicts = self.conflicts.clone();
+        let low_balance = self.low_balance.clone();
+        let bundled = self.bundled.clone();
+        let receipt_timeout = self.receipt_timeout;
+
+        let handle = tokio::spawn(async move {
+            let eps = [bundler_own.entry_point];
+            let mut interval = tokio::time::interval(Duration::from_secs(int));
+            loop {
+                if !is_running(&running_lock, &eps) {
+                    break;
+                }
+
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = notify.notified() => {}
+                }
+
+                if !is_running(&running_lock, &eps) {
+                    break;
+                }
+
+                match Self::check_balance(&bundler_own, &low_balance).await {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!("Error while checking the balance of the bundler's signer: {e:?}");
+                    }
+                }
+
+                match Self::get_user_operations(&uopool_grpc_client, &bundler_own.entry_point).await
+                {
+                    Ok((bundle, aggregated, conditional, storage_accesses)) => {
+                        if let Err(e) = Self::send_bundle(
+                            &bundler_own,
+                            &bundle,
+                            &aggregated,
+                            conditional,
+                            &storage_accesses,
                             &uopool_grpc_client,
-                            &bundler_own.entry_point,
+                            &submitted,
+                            &conflicts,
+                            &bundled,
+                            receipt_timeout,
                         )
                         .await
                         {
-                            Ok(bundle) => {
-                                if let Err(e) = bundler_own.send_bundle(&bundle).await {
-                                    error!("Error while sending bundle: {e:?}");
-                                }
-                            }
-                            Err(e) => {
-                                error!("Error while creating bundle: {e:?}");
-                            }
+                            error!("Error while sending bundle: {e:?}");
                         }
                     }
-                });
+                    Err(e) if is_uopool_unavailable(&e) => {
+                        warn!(
+                            "Skipping the bundle of entry point {:?}, uopool gRPC service unreachable: {e:?}",
+                            bundler_own.entry_point
+                        );
+                    }
+                    Err(e) => {
+                        error!("Error while creating bundle: {e:?}");
+                    }
+                }
             }
+        });
+
+        self.handles.lock().push(handle);
+    }
+
+    /// Starts auto bundling of the entry points in one transaction through the multicall
+    /// contract (the per entry point bundle intervals are not used). The paused entry points are
+    /// left out of the bundle, the task is reused if it didn't exit yet.
+    ///
+    /// # Arguments
+    /// * `int` - The global bundle interval (in seconds)
+    /// * `eps` - The entry points to start auto bundling for
+    fn start_bundling_multicall(&self, int: u64, eps: &[Address]) {
+        if !start_running(&self.running, eps, &self.entry_points()) {
+            return;
         }
+
+        let bundlers = self.bundlers.clone();
+        let running_lock = self.running.clone();
+        let uopool_grpc_client = self.uopool_grpc_client.clone();
+        let notify = self.notify.clone();
+        let submitted = self.submitted.clone();
+        let conflicts = self.conflicts.clone();
+        let low_balance = self.low_balance.clone();
+        let bundled = self.bundled.clone();
+        let receipt_timeout = self.receipt_timeout;
+
+        let handle = tokio::spawn(async move {
+            let eps = bundlers.iter().map(|bundler| bundler.entry_point).collect::<Vec<_>>();
+            let mut interval = tokio::time::interval(Duration::from_secs(int));
+            loop {
+                if !is_running(&running_lock, &eps) {
+                    break;
+                }
+
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = notify.notified() => {}
+                }
+
+                if !is_running(&running_lock, &eps) {
+                    break;
+                }
+
+                let eps_running = running_entry_points(&running_lock, &eps);
+                let bundlers_running = bundlers
+                    .iter()
+                    .filter(|bundler| eps_running.contains(&bundler.entry_point))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if bundlers_running.is_empty() {
+                    continue;
+                }
+
+                match Self::check_balance(&bundlers_running[0], &low_balance).await {
+                    Ok(true) => continue,
+                    Ok(false) => {}
+                    Err(e) => {
+                        error!("Error while checking the balance of the bundler's signer: {e:?}");
+                    }
+                }
+
+                if let Err(e) = Self::send_bundle_multicall(
+                    &bundlers_running,
+                    &uopool_grpc_client,
+                    &submitted,
+                    &conflicts,
+                    &bundled,
+                    receipt_timeout,
+                )
+                .await
+                {
+                    if is_uopool_unavailable(&e) {
+                        warn!("Skipping the bundle, uopool gRPC service unreachable: {e:?}");
+                    } else {
+                        error!("Error while sending bundle: {e:?}");
+                    }
+                }
+            }
+        });
+
+        self.handles.lock().push(handle);
     }
 }
 
@@ -143,13 +1294,64 @@ where
                 Ok(Response::new(SetModeResponse { res: SetModeResult::Ok.into() }))
             }
             Mode::Auto => {
-                let int = req.interval;
-                self.start_bundling(int);
+                let intervals = req
+                    .intervals
+                    .iter()
+                    .filter_map(|i| i.ep.clone().map(|ep| (Address::from(ep), i.interval)))
+                    .collect::<HashMap<Address, u64>>();
+                self.start_bundling(req.interval, &intervals);
                 Ok(Response::new(SetModeResponse { res: SetModeResult::Ok.into() }))
             }
         }
     }
 
+    async fn get_bundler_mode(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetModeResponse>, Status> {
+        if !*self.auto.lock() {
+            return Ok(Response::new(GetModeResponse {
+                mode: Mode::Manual.into(),
+                intervals: vec![],
+                paused: vec![],
+            }));
+        }
+
+        let (running, paused): (Vec<_>, Vec<_>) =
+            self.intervals.lock().clone().into_iter().partition(|(ep, _)| self.is_running_for(ep));
+        let intervals = running
+            .into_iter()
+            .map(|(ep, int)| BundleInterval { ep: Some(ep.into()), interval: int })
+            .collect();
+        let paused = paused.into_iter().map(|(ep, _)| ep.into()).collect();
+
+        Ok(Response::new(GetModeResponse { mode: Mode::Auto.into(), intervals, paused }))
+    }
+
+    async fn pause_bundling(&self, req: Request<BundlingRequest>) -> Result<Response<()>, Status> {
+        let ep = parse_addr(req.into_inner().ep)?;
+
+        if !self.pause_bundling(&ep) {
+            return Err(Status::failed_precondition(format!(
+                "Auto bundling is not running for entry point {ep:?}"
+            )));
+        }
+
+        Ok(Response::new(()))
+    }
+
+    async fn resume_bundling(&self, req: Request<BundlingRequest>) -> Result<Response<()>, Status> {
+        let ep = parse_addr(req.into_inner().ep)?;
+
+        if !self.resume_bundling(&ep) {
+            return Err(Status::failed_precondition(format!(
+                "Auto bundling is not enabled or was never started for entry point {ep:?}"
+            )));
+        }
+
+        Ok(Response::new(()))
+    }
+
     async fn send_bundle_now(
         &self,
         _req: Request<()>,
@@ -159,52 +1361,331 @@ where
             .await
             .map_err(|e| tonic::Status::internal(format!("Send bundle now with error: {e:?}")))?;
 
-        if let Some(tx_hash) = res {
-            // wait for the tx to be mined
-            loop {
-                let tx_receipt = self
+        let mut results = vec![];
+        for (bundler, (ep, bundle, dropped)) in self.bundlers.iter().zip(res) {
+            let (status, receipt) = match &bundle {
+                Some(bundle) => {
+                    Self::wait_for_receipt(
+                        &bundler.eth_client,
+                        &bundler.nonce_manager,
+                        bundle,
+                        bundler.confirmations,
+                        self.receipt_timeout,
+                    )
+                    .await
+                }
+                None => (SendBundleStatus::Empty, None),
+            };
+            // the bundle could have been mined as a replacement with another hash
+            let tx_hash = receipt
+                .as_ref()
+                .map(|receipt| receipt.transaction_hash)
+                .or(bundle.as_ref().map(|b| b.tx_hash))
+                .unwrap_or_default();
+
+            results.push(SendBundleResult {
+                ep: Some(ep.into()),
+                tx_hash: Some(tx_hash.into()),
+                status: status.into(),
+                dropped: dropped.into_iter().map(Into::into).collect(),
+                uos: bundle
+                    .as_ref()
+                    .map(|b| b.uos.iter().map(|uo_hash| (*uo_hash).into()).collect())
+                    .unwrap_or_default(),
+                estimated_gas: bundle.as_ref().map(|b| b.estimated_gas.into()),
+                gas_used: receipt.and_then(|receipt| receipt.gas_used).map(Into::into),
+                beneficiary: bundle.as_ref().map(|b| b.beneficiary.into()),
+            });
+        }
+
+        Ok(Response::new(SendBundleNowResponse {
+            res: Some(
+                results
+                    .iter()
+                    .find(|r| r.status() != SendBundleStatus::Empty)
+                    .and_then(|r| r.tx_hash.clone())
+                    .unwrap_or_else(|| H256::default().into()),
+            ),
+            results,
+        }))
+    }
+
+    async fn simulate_bundle(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<SimulateBundleResponse>, Status> {
+        let res = self
+            .simulate_bundles()
+            .await
+            .map_err(|e| Status::internal(format!("Simulate bundle with error: {e:?}")))?;
+
+        let results = res
+            .into_iter()
+            .map(|(ep, simulation, dropped)| BundleSimulationResult {
+                ep: Some(ep.into()),
+                uos: simulation.uos.into_iter().map(Into::into).collect(),
+                gas: simulation.gas.map(Into::into),
+                revert_reason: simulation.revert_reason.unwrap_or_default(),
+                dropped: dropped.into_iter().map(Into::into).collect(),
+            })
+            .collect();
+
+        Ok(Response::new(SimulateBundleResponse { results }))
+    }
+
+    async fn explain_user_operation(
+        &self,
+        req: Request<ExplainUserOperationRequest>,
+    ) -> Result<Response<ExplainUserOperationResponse>, Status> {
+        let uo_hash = parse_hash(req.into_inner().hash)?.into();
+
+        let (ep, inclusion) = self
+            .explain_user_operation(&uo_hash)
+            .await
+            .map_err(|e| Status::internal(format!("Explain user operation with error: {e:?}")))?
+            .ok_or_else(|| Status::not_found("User operation is not in the mempool"))?;
+
+        Ok(Response::new(ExplainUserOperationResponse { ep: Some(ep.into()), ..inclusion.into() }))
+    }
+
+    async fn get_supported_entry_points(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetSupportedEntryPointsResponse>, Status> {
+        let chain =
+            self.chain().ok_or_else(|| Status::failed_precondition("No bundler is configured"))?;
+
+        Ok(Response::new(GetSupportedEntryPointsResponse {
+            eps: self.entry_points().into_iter().map(Into::into).collect(),
+            chain_id: chain.id(),
+        }))
+    }
+
+    async fn get_storage_conflicts(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetStorageConflictsResponse>, Status> {
+        let conflicts = self
+            .conflicts
+            .lock()
+            .iter()
+            .flat_map(|(ep, conflicts)| {
+                conflicts.iter().map(|c| crate::proto::bundler::StorageConflict {
+                    ep: Some((*ep).into()),
+                    uo: Some(c.uo_hash.into()),
+                    conflicts_with: Some(c.conflicts_with.into()),
+                    address: Some(c.address.into()),
+                    slot: Some(c.slot.into()),
+                })
+            })
+            .collect();
+
+        Ok(Response::new(GetStorageConflictsResponse { conflicts }))
+    }
+
+    async fn get_user_operation_transaction(
+        &self,
+        req: Request<GetUserOperationTransactionRequest>,
+    ) -> Result<Response<GetUserOperationTransactionResponse>, Status> {
+        let req = req.into_inner();
+
+        let uo_hash = parse_hash(req.hash)?;
+        let submitted = self.submitted.lock().get(&uo_hash.into());
+        match submitted {
+            Some((ep, tx_hash)) => {
+                // a stuck bundle is replaced with the same nonce and another hash
+                let tx_hash = self
                     .bundlers
-                    .first()
-                    .expect("Must have at least one bundler")
-                    .eth_client
-                    .get_transaction_receipt(tx_hash)
-                    .await;
-                if let Ok(tx_receipt) = tx_receipt {
-                    if tx_receipt.is_some() {
-                        break;
+                    .iter()
+                    .find(|bundler| bundler.entry_point == ep)
+                    .map(|bundler| bundler.nonce_manager.latest_tx_hash(tx_hash))
+                    .unwrap_or(tx_hash);
+                Ok(Response::new(GetUserOperationTransactionResponse {
+                    ep: Some(ep.into()),
+                    tx_hash: Some(tx_hash.into()),
+                }))
+            }
+            None => Err(Status::not_found("User operation was not submitted by this bundler")),
+        }
+    }
+
+    async fn get_balance(&self, _req: Request<()>) -> Result<Response<GetBalanceResponse>, Status> {
+        let bundler = self
+            .bundlers
+            .first()
+            .ok_or_else(|| Status::failed_precondition("No bundler is configured"))?;
+
+        let address = bundler.signer.address();
+        let balance = bundler
+            .eth_client
+            .get_balance(address, None)
+            .await
+            .map_err(|e| Status::internal(format!("Failed to get the balance: {e:?}")))?;
+
+        let mut deposits = vec![];
+        for bundler in self.bundlers.iter() {
+            let deposit = bundler
+                .deposit()
+                .await
+                .map_err(|e| Status::internal(format!("Failed to get the deposit: {e:?}")))?;
+            deposits.push(EntryPointDeposit {
+                ep: Some(bundler.entry_point.into()),
+                deposit: Some(deposit.into()),
+                top_up_threshold: bundler.deposit_top_up.map(|t| t.threshold.into()),
+                top_up_target: bundler.deposit_top_up.map(|t| t.target.into()),
+            });
+        }
+
+        Ok(Response::new(GetBalanceResponse {
+            address: Some(address.into()),
+            balance: Some(balance.into()),
+            min_balance: Some(bundler.min_balance.into()),
+            paused: *self.low_balance.lock(),
+            deposits,
+        }))
+    }
+
+    async fn get_health(&self, _req: Request<()>) -> Result<Response<GetHealthResponse>, Status> {
+        let timeout = Duration::from_secs(HEALTH_CHECK_TIMEOUT);
+
+        let block_number = match self.bundlers.first() {
+            Some(bundler) => {
+                match tokio::time::timeout(timeout, bundler.eth_client.get_block_number()).await {
+                    Ok(Ok(block_number)) => Some(block_number.as_u64()),
+                    Ok(Err(e)) => {
+                        warn!("Health check failed to get the block number: {e:?}");
+                        None
+                    }
+                    Err(_) => {
+                        warn!("Health check timed out getting the block number");
+                        None
                     }
                 }
-                tokio::time::sleep(Duration::from_millis(50)).await;
             }
-        }
+            None => None,
+        };
+
+        let uopool = match tokio::time::timeout(
+            timeout,
+            self.uopool_grpc_client.clone().get_chain_id(Request::new(())),
+        )
+        .await
+        {
+            Ok(Ok(_)) => true,
+            Ok(Err(e)) => {
+                warn!("Health check failed to reach the user operation pool: {e:?}");
+                false
+            }
+            Err(_) => {
+                warn!("Health check timed out reaching the user operation pool");
+                false
+            }
+        };
+
+        let eth_client = block_number.is_some();
+        let bundling = self.is_running();
+        let status = match (eth_client, uopool, bundling) {
+            (true, true, true) => HealthStatus::Healthy,
+            (false, false, _) => HealthStatus::Unhealthy,
+            _ => HealthStatus::Degraded,
+        };
+
+        Ok(Response::new(GetHealthResponse {
+            status: status as i32,
+            eth_client,
+            uopool,
+            bundling,
+            block_number: block_number.unwrap_or_default(),
+        }))
+    }
+
+    type SubscribeBundledStream =
+        Pin<Box<dyn Stream<Item = Result<BundledUserOperationEvent, Status>> + Send + 'static>>;
 
-        Ok(Response::new(SendBundleNowResponse { res: Some(res.unwrap_or_default().into()) }))
+    async fn subscribe_bundled(
+        &self,
+        req: Request<SubscribeBundledRequest>,
+    ) -> Result<Response<Self::SubscribeBundledStream>, Status> {
+        let req = req.into_inner();
+
+        let uos: HashSet<UserOperationHash> =
+            req.uos.into_iter().map(|h| H256::from(h).into()).collect();
+        let rx = self.bundled.subscribe();
+
+        let stream = futures::stream::unfold((rx, uos), |(mut rx, uos)| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(uo) => {
+                        if uos.is_empty() || uos.contains(&uo.user_operation_hash) {
+                            return Some((Ok(uo.into()), (rx, uos)));
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(
+                            "Bundled user operations subscriber lagged behind, {n} events skipped"
+                        )
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
     }
 }
 
+/// Starts the bundler gRPC service and auto bundling. Once the shutdown future completes, auto
+/// bundling is stopped and the in-flight bundles are given [SHUTDOWN_TIMEOUT] seconds to finish.
+///
+/// # Returns
+/// * `JoinHandle<()>` - The handle of the task that finishes once the bundler is shut down (an
+///   error if no beneficiary is set)
 #[allow(clippy::too_many_arguments)]
 pub fn bundler_service_run<M, S>(
     addr: SocketAddr,
-    wallet: Wallet,
+    signer: impl Into<BundleSigner>,
     eps: Vec<Address>,
+    entry_point_versions: HashMap<Address, EntryPointVersion>,
     chain: Chain,
-    beneficiary: Address,
+    beneficiaries: Vec<Address>,
+    beneficiary_strategy: BeneficiaryStrategy,
     min_balance: U256,
     bundle_interval: u64,
+    bundle_intervals: HashMap<Address, u64>,
     eth_client: Arc<M>,
     client: Arc<S>,
     uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
     enable_metrics: bool,
     enable_access_list: bool,
-) where
+    retry_policy: RetryPolicy,
+    receipt_timeout: Duration,
+    stuck_bundle_blocks: u64,
+    bundle_limits: BundleLimits,
+    dry_run: bool,
+    fee_strategy: FeeStrategy,
+    multicall: Option<Address>,
+    max_bundle_loss: Option<U256>,
+    confirmations: u64,
+    min_priority_fee: U256,
+    deposit_top_up: Option<DepositTopUp>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> eyre::Result<JoinHandle<()>>
+where
     M: Middleware + Clone + 'static,
     S: SendBundleOp + Clone + 'static,
 {
+    let beneficiary = *beneficiaries
+        .first()
+        .ok_or_else(|| eyre::eyre!("At least one beneficiary is required"))?;
+
+    // the bundlers share the signer, so they have to share the nonce as well
+    let nonce_manager = NonceManager::default();
+    let signer = signer.into();
     let bundlers: Vec<Bundler<M, S>> = eps
         .into_iter()
         .map(|ep| {
             Bundler::new(
-                wallet.clone(),
+                signer.clone(),
                 beneficiary,
                 ep,
                 chain,
@@ -212,13 +1693,29 @@ pub fn bundler_service_run<M, S>(
                 eth_client.clone(),
                 client.clone(),
                 enable_access_list,
+                retry_policy,
+                bundle_intervals.get(&ep).copied(),
             )
+            .with_entry_point_version(EntryPointVersion::from_declared(&entry_point_versions, &ep))
+            .with_beneficiaries(beneficiaries.clone(), beneficiary_strategy)
+            .with_nonce_manager(nonce_manager.clone())
+            .with_stuck_bundle_blocks(stuck_bundle_blocks)
+            .with_bundle_limits(bundle_limits)
+            .with_dry_run(dry_run)
+            .with_fee_strategy(fee_strategy)
+            .with_multicall(multicall)
+            .with_max_bundle_loss(max_bundle_loss)
+            .with_confirmations(confirmations)
+            .with_min_priority_fee(min_priority_fee)
+            .with_deposit_top_up(deposit_top_up)
         })
         .collect();
 
-    let bundler_service = BundlerService::new(bundlers, uopool_grpc_client);
-    bundler_service.start_bundling(bundle_interval);
+    let bundler_service = BundlerService::new(bundlers, uopool_grpc_client, receipt_timeout);
+    bundler_service.start_bundling(bundle_interval, &HashMap::new());
+    bundler_service.start_deposit_top_up();
 
+    let bundler_service_shutdown = bundler_service.clone();
     tokio::spawn(async move {
         let mut builder = tonic::transport::Server::builder();
         let svc = bundler_server::BundlerServer::new(bundler_service);
@@ -229,4 +1726,164 @@ pub fn bundler_service_run<M, S>(
         }
         // let route = builder.add_service(svc)
     });
+
+    Ok(tokio::spawn(async move {
+        shutdown.await;
+        bundler_service_shutdown.shutdown(Duration::from_secs(SHUTDOWN_TIMEOUT)).await;
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_inclusion_to_and_from_proto() {
+        for inclusion in [
+            BundleInclusion::Included,
+            BundleInclusion::FeeBelowFloor { max_fee_per_gas: 1.into(), base_fee: 2.into() },
+            BundleInclusion::SenderLimit,
+            BundleInclusion::StorageConflict { conflicts_with: UserOperationHash(H256::random()) },
+            BundleInclusion::BundleFull,
+            BundleInclusion::NotSelected,
+        ] {
+            let res = ExplainUserOperationResponse::from(inclusion);
+            assert_eq!(res.reason, inclusion.to_string());
+            assert_eq!(BundleInclusion::try_from(res).unwrap(), inclusion);
+        }
+
+        // the status is missing (e.g., a response of a newer bundler)
+        assert!(BundleInclusion::try_from(ExplainUserOperationResponse::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn call_uopool_reconnects() {
+        let ep = Address::random();
+
+        // the service becomes reachable again
+        let mut calls = 0;
+        let res = call_uopool(&ep, || {
+            calls += 1;
+            let calls = calls;
+            async move {
+                if calls < 3 {
+                    Err(Status::unavailable("connection refused"))
+                } else {
+                    Ok(Response::new(calls))
+                }
+            }
+        })
+        .await;
+        assert_eq!(res.unwrap(), 3);
+
+        // the other errors are not retried
+        let mut calls = 0;
+        let res = call_uopool(&ep, || {
+            calls += 1;
+            async move { Err::<Response<()>, _>(Status::not_found("mempool not found")) }
+        })
+        .await;
+        assert_eq!(res.unwrap_err().code(), Code::NotFound);
+        assert_eq!(calls, 1);
+
+        assert!(is_uopool_unavailable(&Status::unavailable("connection refused").into()));
+        assert!(!is_uopool_unavailable(&eyre::eyre!("no user operations")));
+    }
+
+    #[test]
+    fn pause_and_resume_before_task_exits() {
+        let running = Mutex::new(HashMap::new());
+        let ep = Address::random();
+
+        assert!(start_running(&running, &[ep], &[ep]));
+        assert!(is_running(&running, &[ep]));
+
+        // resumed before the task wakes up, the task is reused
+        assert!(pause_running(&running, &ep));
+        assert!(!pause_running(&running, &ep));
+        assert!(!start_running(&running, &[ep], &[ep]));
+        assert!(is_running(&running, &[ep]));
+
+        // the task of the paused entry point exits, so a new one is spawned on resume
+        assert!(pause_running(&running, &ep));
+        assert!(!is_running(&running, &[ep]));
+        assert!(running.lock().is_empty());
+        assert!(!pause_running(&running, &ep));
+        assert!(start_running(&running, &[ep], &[ep]));
+    }
+
+    #[test]
+    fn pause_and_resume_multicall_subset() {
+        let running = Mutex::new(HashMap::new());
+        let (ep, ep_other) = (Address::random(), Address::random());
+        let eps = [ep, ep_other];
+
+        // the entry points that are not started are left out of the bundle
+        assert!(start_running(&running, &[ep], &eps));
+        assert_eq!(running_entry_points(&running, &eps), vec![ep]);
+
+        assert!(!start_running(&running, &[ep_other], &eps));
+        assert_eq!(running_entry_points(&running, &eps), eps.to_vec());
+
+        assert!(pause_running(&running, &ep));
+        assert!(is_running(&running, &eps));
+        assert_eq!(running_entry_points(&running, &eps), vec![ep_other]);
+
+        // the task exits once all the entry points are paused
+        assert!(pause_running(&running, &ep_other));
+        assert!(!is_running(&running, &eps));
+        assert!(running_entry_points(&running, &eps).is_empty());
+        assert!(start_running(&running, &[ep_other], &eps));
+        assert_eq!(running_entry_points(&running, &eps), vec![ep_other]);
+    }
+
+    #[tokio::test]
+    async fn bundler_service_run_without_beneficiaries() {
+        use ethers::providers::Provider;
+        use silius_bundler::EthereumClient;
+        use silius_primitives::Wallet;
+
+        let (eth_client, _mock) = Provider::mocked();
+        let eth_client = Arc::new(eth_client);
+        let wallet = Wallet::from_phrase(
+            "test test test test test test test test test test test junk",
+            1,
+            false,
+        )
+        .unwrap();
+        let uopool_grpc_client = UoPoolClient::new(
+            tonic::transport::Channel::from_static("http://127.0.0.1:3002").connect_lazy(),
+        );
+
+        let res = bundler_service_run(
+            "127.0.0.1:3003".parse().unwrap(),
+            wallet.clone(),
+            vec![Address::random()],
+            HashMap::new(),
+            Chain::from(1),
+            vec![],
+            BeneficiaryStrategy::default(),
+            U256::zero(),
+            10,
+            HashMap::new(),
+            eth_client.clone(),
+            Arc::new(EthereumClient::new(eth_client, wallet)),
+            uopool_grpc_client,
+            false,
+            false,
+            RetryPolicy::default(),
+            Duration::from_secs(60),
+            10,
+            BundleLimits::default(),
+            false,
+            FeeStrategy::default(),
+            None,
+            None,
+            1,
+            U256::zero(),
+            None,
+            async {},
+        );
+        assert!(res.is_err());
+    }
 }
@@ -384,14 +384,123 @@ pub mod types {
 }
 
 pub mod uopool {
+    use silius_mempool::{MempoolEvent, RemovalReason};
+
     tonic::include_proto!("uopool");
+
+    impl From<RemovalReason> for MempoolRemovalReason {
+        fn from(value: RemovalReason) -> Self {
+            match value {
+                RemovalReason::Bundled => Self::Bundled,
+                RemovalReason::Invalidated => Self::Invalidated,
+                RemovalReason::Requested => Self::Requested,
+                RemovalReason::Expired => Self::Expired,
+                RemovalReason::Evicted => Self::Evicted,
+            }
+        }
+    }
+
+    impl From<MempoolEvent> for MempoolEventResponse {
+        fn from(value: MempoolEvent) -> Self {
+            match value {
+                MempoolEvent::Added { uo_hash, sender } => Self {
+                    kind: MempoolEventKind::Added.into(),
+                    hash: Some(uo_hash.into()),
+                    sender: Some(sender.into()),
+                    ..Default::default()
+                },
+                MempoolEvent::Replaced { uo_hash, prev_hash, sender } => Self {
+                    kind: MempoolEventKind::Replaced.into(),
+                    hash: Some(uo_hash.into()),
+                    sender: Some(sender.into()),
+                    prev_hash: Some(prev_hash.into()),
+                    ..Default::default()
+                },
+                MempoolEvent::Removed { uo_hash, sender, reason } => Self {
+                    kind: MempoolEventKind::Removed.into(),
+                    hash: Some(uo_hash.into()),
+                    sender: Some(sender.into()),
+                    reason: MempoolRemovalReason::from(reason).into(),
+                    ..Default::default()
+                },
+            }
+        }
+    }
 }
 
 pub mod bundler {
-    use silius_primitives::BundlerMode;
+    use silius_primitives::{bundler::BundleInclusion, BundledUserOperation, BundlerMode};
 
     tonic::include_proto!("bundler");
 
+    impl From<BundledUserOperation> for BundledUserOperationEvent {
+        fn from(value: BundledUserOperation) -> Self {
+            Self {
+                uo_hash: Some(value.user_operation_hash.into()),
+                ep: Some(value.entry_point.into()),
+                tx_hash: Some(value.transaction_hash.into()),
+                success: value.success,
+            }
+        }
+    }
+
+    impl From<BundledUserOperationEvent> for BundledUserOperation {
+        fn from(value: BundledUserOperationEvent) -> Self {
+            Self {
+                user_operation_hash: value.uo_hash.map(Into::into).unwrap_or_default(),
+                entry_point: value.ep.map(Into::into).unwrap_or_default(),
+                transaction_hash: value.tx_hash.map(Into::into).unwrap_or_default(),
+                success: value.success,
+            }
+        }
+    }
+
+    impl From<BundleInclusion> for ExplainUserOperationResponse {
+        fn from(value: BundleInclusion) -> Self {
+            let mut res = Self { reason: value.to_string(), ..Default::default() };
+            let status = match value {
+                BundleInclusion::Included => BundleInclusionStatus::Included,
+                BundleInclusion::FeeBelowFloor { max_fee_per_gas, base_fee } => {
+                    res.max_fee_per_gas = Some(max_fee_per_gas.into());
+                    res.base_fee = Some(base_fee.into());
+                    BundleInclusionStatus::FeeBelowFloor
+                }
+                BundleInclusion::SenderLimit => BundleInclusionStatus::SenderLimit,
+                BundleInclusion::StorageConflict { conflicts_with } => {
+                    res.conflicts_with = Some(conflicts_with.into());
+                    BundleInclusionStatus::StorageConflict
+                }
+                BundleInclusion::BundleFull => BundleInclusionStatus::BundleFull,
+                BundleInclusion::NotSelected => BundleInclusionStatus::NotSelected,
+            };
+            res.set_status(status);
+            res
+        }
+    }
+
+    impl TryFrom<ExplainUserOperationResponse> for BundleInclusion {
+        type Error = tonic::Status;
+
+        fn try_from(value: ExplainUserOperationResponse) -> Result<Self, Self::Error> {
+            Ok(match value.status() {
+                BundleInclusionStatus::Unspecified => {
+                    return Err(tonic::Status::internal("Bundle inclusion status is unspecified"))
+                }
+                BundleInclusionStatus::Included => Self::Included,
+                BundleInclusionStatus::FeeBelowFloor => Self::FeeBelowFloor {
+                    max_fee_per_gas: value.max_fee_per_gas.map(Into::into).unwrap_or_default(),
+                    base_fee: value.base_fee.map(Into::into).unwrap_or_default(),
+                },
+                BundleInclusionStatus::SenderLimit => Self::SenderLimit,
+                BundleInclusionStatus::StorageConflict => Self::StorageConflict {
+                    conflicts_with: value.conflicts_with.map(Into::into).unwrap_or_default(),
+                },
+                BundleInclusionStatus::BundleFull => Self::BundleFull,
+                BundleInclusionStatus::NotSelected => Self::NotSelected,
+            })
+        }
+    }
+
     impl From<Mode> for BundlerMode {
         fn from(value: Mode) -> Self {
             match value {
@@ -12,23 +12,36 @@ use ethers::{
     types::{Address, U256},
 };
 use eyre::Result;
-use futures::{channel::mpsc::unbounded, StreamExt};
-use parking_lot::RwLock;
+use futures::{channel::mpsc::unbounded, future::join_all, Stream, StreamExt};
+use parking_lot::{Mutex, RwLock};
 use silius_mempool::{
-    mempool_id, validate::validator::StandardUserOperationValidator, HashSetOp, Mempool,
-    MempoolErrorKind, MempoolId, Reputation, ReputationEntryOp, SanityCheck, SimulationCheck,
-    SimulationTraceCheck, UoPool as UserOperationPool, UoPoolBuilder, UserOperationAct,
+    mempool_id,
+    validate::{validator::StandardUserOperationValidator, PaymasterAllowlist},
+    FeeOracle, HashSetOp, Mempool, MempoolError, MempoolErrorKind, MempoolId, RemovalReason,
+    Reputation, ReputationEntryOp, SanityCheck, SimulationCheck, SimulationTraceCheck,
+    TrustedSenders, UoPool as UserOperationPool, UoPoolBuilder, UserOperationAct,
     UserOperationAddrAct, UserOperationCodeHashAct,
 };
-use silius_metrics::grpc::MetricsLayer;
+use silius_metrics::{grpc::MetricsLayer, mempool::record_validation_duration};
 use silius_p2p::{
     config::Config,
     service::{MempoolChannels, Network},
 };
-use silius_primitives::{provider::BlockStream, UserOperation};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use silius_primitives::{
+    mempool::{MempoolSnapshot, SortStrategy},
+    provider::BlockStream,
+    EntryPointVersion, UserOperation, UserOperationHash,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast::error::RecvError, Mutex as AsyncMutex, Semaphore};
 use tonic::{Code, Request, Response, Status};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, info_span, instrument, warn, Instrument};
 
 type StandardUserPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk> = UserOperationPool<
     M,
@@ -59,6 +72,23 @@ where
 {
     pub uopools: UoPoolMaps<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>,
     pub chain: Chain,
+    /// Bounds the number of user operations validated concurrently
+    validations: Arc<Semaphore>,
+    /// Locks held while a user operation of the sender is validated and added, so that the user
+    /// operations of one sender are processed one after another
+    sender_locks: Arc<Mutex<HashMap<Address, Arc<AsyncMutex<()>>>>>,
+    /// Lock held while a validated user operation is added, so that the limits on the number of
+    /// user operations of the factory and the paymaster are checked against the mempool it's
+    /// added to
+    insert_lock: Arc<AsyncMutex<()>>,
+    /// Whether the admin methods (e.g., evicting a user operation) are enabled
+    enable_admin: bool,
+    /// Suggests the fees of the user operations
+    fee_oracle: FeeOracle,
+    /// Paymasters allowed to sponsor the user operations (shared with the validators)
+    paymaster_allowlist: PaymasterAllowlist,
+    /// Senders whose user operations are bundled first (shared with the user operation pools)
+    trusted_senders: TrustedSenders,
 }
 
 impl<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>
@@ -78,8 +108,70 @@ where
     pub fn new(
         uopools: UoPoolMaps<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>,
         chain: Chain,
+        validation_concurrency: usize,
     ) -> Self {
-        Self { uopools, chain }
+        Self {
+            uopools,
+            chain,
+            validations: Arc::new(Semaphore::new(validation_concurrency.max(1))),
+            sender_locks: Default::default(),
+            insert_lock: Default::default(),
+            enable_admin: false,
+            fee_oracle: FeeOracle::default(),
+            paymaster_allowlist: PaymasterAllowlist::default(),
+            trusted_senders: TrustedSenders::default(),
+        }
+    }
+
+    /// Sets the [FeeOracle](FeeOracle) suggesting the fees of the user operations (it should use
+    /// the same floors as the sanity checks)
+    ///
+    /// # Arguments
+    /// * `fee_oracle` - The [FeeOracle](FeeOracle)
+    ///
+    /// # Returns
+    /// * `Self` - The `UoPoolService` instance
+    pub fn with_fee_oracle(mut self, fee_oracle: FeeOracle) -> Self {
+        self.fee_oracle = fee_oracle;
+        self
+    }
+
+    /// Enables the admin methods (e.g., evicting a user operation), which are disabled by default
+    ///
+    /// # Arguments
+    /// * `enable_admin` - Whether the admin methods are enabled
+    ///
+    /// # Returns
+    /// * `Self` - The `UoPoolService` instance
+    pub fn with_admin(mut self, enable_admin: bool) -> Self {
+        self.enable_admin = enable_admin;
+        self
+    }
+
+    /// Sets the [PaymasterAllowlist](PaymasterAllowlist) updated by the admin methods (it should
+    /// be shared with the validators)
+    ///
+    /// # Arguments
+    /// * `paymaster_allowlist` - The [PaymasterAllowlist](PaymasterAllowlist)
+    ///
+    /// # Returns
+    /// * `Self` - The `UoPoolService` instance
+    pub fn with_paymaster_allowlist(mut self, paymaster_allowlist: PaymasterAllowlist) -> Self {
+        self.paymaster_allowlist = paymaster_allowlist;
+        self
+    }
+
+    /// Sets the [TrustedSenders](TrustedSenders) updated by the admin methods (it should be shared
+    /// with the user operation pools)
+    ///
+    /// # Arguments
+    /// * `trusted_senders` - The [TrustedSenders](TrustedSenders)
+    ///
+    /// # Returns
+    /// * `Self` - The `UoPoolService` instance
+    pub fn with_trusted_senders(mut self, trusted_senders: TrustedSenders) -> Self {
+        self.trusted_senders = trusted_senders;
+        self
     }
 
     #[allow(clippy::type_complexity)]
@@ -94,6 +186,85 @@ where
             .map(|b| b.uopool())
             .ok_or(Status::new(Code::Unavailable, "User operation pool is not available"))
     }
+
+    /// Validates the user operation and adds it into the mempool. User operations of different
+    /// senders are validated concurrently (up to the validation concurrency), the ones of the same
+    /// sender one after another. The limits of the factory and the paymaster are checked again
+    /// when the user operation is added, one user operation at a time.
+    ///
+    /// # Arguments
+    /// * `ep` - The entry point address
+    /// * `uo` - The [UserOperation](UserOperation) to add
+    ///
+    /// # Returns
+    /// * `Result<UserOperationHash, MempoolError>` - The hash of the added
+    ///   [UserOperation](UserOperation)
+    #[instrument(name = "user_operation", skip_all, fields(uo_hash = %uo.hash, ep = ?ep))]
+    async fn validate_and_add(
+        &self,
+        ep: &Address,
+        uo: UserOperation,
+    ) -> tonic::Result<Result<UserOperationHash, MempoolError>> {
+        // the hash is calculated again in the format of the version of the entry point
+        let uo = UserOperation::from_user_operation_signed(
+            self.get_uopool(ep)?.user_operation_hash(&uo.user_operation),
+            uo.user_operation,
+        );
+        let sender = uo.sender;
+        let sender_lock = self.sender_locks.lock().entry(sender).or_default().clone();
+
+        let res = {
+            let _sender_guard = sender_lock.lock().await;
+
+            let res = {
+                let uopool = self.get_uopool(ep)?;
+                if uopool.mempool.contains_identical(&uo).unwrap_or_default() {
+                    // identical resubmissions are acknowledged without validating them again
+                    Ok(Default::default())
+                } else {
+                    let _permit = self
+                        .validations
+                        .acquire()
+                        .await
+                        .map_err(|_| Status::internal("Validation is not available"))?;
+                    let start = Instant::now();
+                    let res = uopool.validate_user_operation(&uo).await;
+                    record_validation_duration(ep, start.elapsed());
+                    res
+                }
+            };
+
+            let _insert_guard = self.insert_lock.lock().await;
+            let mut uopool = self.get_uopool(ep)?;
+            uopool.add_user_operation(uo, res).await
+        };
+
+        // the lock of the sender is dropped once nobody waits for it
+        drop(sender_lock);
+        let mut sender_locks = self.sender_locks.lock();
+        if sender_locks.get(&sender).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            sender_locks.remove(&sender);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Creates the [AddResponse](AddResponse) with the hash of the added
+/// [UserOperation](UserOperation) or the serialized [MempoolError](MempoolError)
+fn add_response(res: Result<UserOperationHash, MempoolError>) -> tonic::Result<AddResponse> {
+    Ok(match res {
+        Ok(uo_hash) => AddResponse {
+            res: AddResult::Added as i32,
+            data: serde_json::to_string(&uo_hash)
+                .map_err(|err| Status::internal(format!("Failed to serialize hash: {err}")))?,
+        },
+        Err(err) => AddResponse {
+            res: AddResult::NotAdded as i32,
+            data: serde_json::to_string(&err)
+                .map_err(|err| Status::internal(format!("Failed to serialize error: {err}")))?,
+        },
+    })
 }
 
 #[async_trait]
@@ -117,29 +288,36 @@ where
         let uo = parse_uo(req.uo)?;
         let ep = parse_addr(req.ep)?;
 
-        let res = {
-            let uopool = self.get_uopool(&ep)?;
-            uopool.validate_user_operation(&uo).await
-        };
+        match self.validate_and_add(&ep, uo).await? {
+            Err(err) if !matches!(err.kind, MempoolErrorKind::InvalidUserOperation(_)) => {
+                Err(Status::internal(format!("Internal error: {err}")))
+            }
+            res => Ok(Response::new(add_response(res)?)),
+        }
+    }
 
-        let mut uopool = self.get_uopool(&ep)?;
+    async fn add_batch(
+        &self,
+        req: Request<AddBatchRequest>,
+    ) -> Result<Response<AddBatchResponse>, Status> {
+        let req = req.into_inner();
 
-        match uopool.add_user_operation(uo, res).await {
-            Ok(uo_hash) => Ok(Response::new(AddResponse {
-                res: AddResult::Added as i32,
-                data: serde_json::to_string(&uo_hash)
-                    .map_err(|err| Status::internal(format!("Failed to serialize hash: {err}")))?,
-            })),
-            Err(err) => match err.kind {
-                MempoolErrorKind::InvalidUserOperation(_) => Ok(Response::new(AddResponse {
-                    res: AddResult::NotAdded as i32,
-                    data: serde_json::to_string(&err).map_err(|err| {
-                        Status::internal(format!("Failed to serialize error: {err}"))
-                    })?,
-                })),
-                _ => Err(Status::internal(format!("Internal error: {err}"))),
-            },
-        }
+        let ep = parse_addr(req.ep)?;
+        self.get_uopool(&ep)?;
+        let uos = req.uos.into_iter().map(UserOperation::from).collect::<Vec<_>>();
+
+        // the user operations are validated concurrently, the user operations of one sender are
+        // validated one after another in the order of the batch (the lock of a sender is fair)
+        let res = join_all(uos.into_iter().map(|uo| self.validate_and_add(&ep, uo))).await;
+
+        // the user operations that passed the validation are added even if others failed, the
+        // internal errors are returned as the result of the failed user operation
+        Ok(Response::new(AddBatchResponse {
+            res: res
+                .into_iter()
+                .map(|res| add_response(res?))
+                .collect::<tonic::Result<Vec<_>>>()?,
+        }))
     }
 
     async fn remove(&self, req: Request<RemoveRequest>) -> Result<Response<()>, Status> {
@@ -148,7 +326,31 @@ where
         let ep = parse_addr(req.ep)?;
         let mut uopool = self.get_uopool(&ep)?;
 
-        uopool.remove_user_operations(req.hashes.into_iter().map(Into::into).collect());
+        uopool.remove_user_operations(
+            req.hashes.into_iter().map(Into::into).collect(),
+            RemovalReason::Requested,
+        );
+
+        Ok(Response::new(()))
+    }
+
+    async fn remove_failed(
+        &self,
+        req: Request<RemoveFailedRequest>,
+    ) -> Result<Response<()>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uos_failed = req
+            .uos
+            .into_iter()
+            .map(|uo| Ok((parse_hash(uo.hash)?.into(), uo.reason)))
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let mut uopool = self.get_uopool(&ep)?;
+        uopool.remove_failed_user_operations(uos_failed).map_err(|e| {
+            tonic::Status::internal(format!("Remove failed uos internal error: {e:?}"))
+        })?;
 
         Ok(Response::new(()))
     }
@@ -184,8 +386,18 @@ where
         let ep = parse_addr(req.ep)?;
 
         let uopool = self.get_uopool(&ep)?;
+        let uo = UserOperation::from_user_operation_signed(
+            uopool.user_operation_hash(&uo.user_operation),
+            uo.user_operation,
+        );
+
+        let res = if req.validate {
+            uopool.validate_and_estimate_user_operation_gas(&uo).await
+        } else {
+            uopool.estimate_user_operation_gas(&uo).await
+        };
 
-        Ok(Response::new(match uopool.estimate_user_operation_gas(&uo).await {
+        Ok(Response::new(match res {
             Ok(gas) => EstimateUserOperationGasResponse {
                 res: EstimateUserOperationGasResult::Estimated as i32,
                 data: serde_json::to_string(&gas)
@@ -199,6 +411,30 @@ where
         }))
     }
 
+    async fn get_gas_price(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetGasPriceResponse>, Status> {
+        // all the mempools share the connection to the execution client
+        let eth_client = self
+            .uopools
+            .read()
+            .values()
+            .next()
+            .map(|b| b.uopool().entry_point.eth_client())
+            .ok_or(Status::new(Code::Unavailable, "User operation pool is not available"))?;
+
+        let gas_price =
+            self.fee_oracle.suggest(eth_client.as_ref()).await.map_err(|e| {
+                tonic::Status::internal(format!("Get gas price internal error: {e}"))
+            })?;
+
+        Ok(Response::new(GetGasPriceResponse {
+            max_fee_per_gas: Some(gas_price.max_fee_per_gas.into()),
+            max_priority_fee_per_gas: Some(gas_price.max_priority_fee_per_gas.into()),
+        }))
+    }
+
     async fn get_sorted_user_operations(
         &self,
         req: Request<GetSortedRequest>,
@@ -207,14 +443,22 @@ where
 
         let ep = parse_addr(req.ep)?;
 
+        // expired user operations are evicted on the cadence of the bundling
+        {
+            let mut uopool = self.get_uopool(&ep)?;
+            if let Err(e) = uopool.remove_expired_user_operations().await {
+                warn!("Failed to remove expired user operations: {e:?}");
+            }
+        }
+
         let uos = {
             let uopool = self.get_uopool(&ep)?;
-            uopool.get_sorted_user_operations().map_err(|e| {
+            uopool.get_sorted_user_operations().await.map_err(|e| {
                 tonic::Status::internal(format!("Get sorted uos internal error: {e:?}"))
             })?
         };
         debug!("get sorted user operation {uos:?}");
-        let uos_valid = {
+        let (uos_valid, storage_map, storage_accesses, uos_by_aggregator) = {
             let mut uopool = self.get_uopool(&ep)?;
             uopool
                 .bundle_user_operations(uos)
@@ -225,6 +469,19 @@ where
 
         Ok(Response::new(GetSortedResponse {
             uos: uos_valid.into_iter().map(Into::into).collect(),
+            storage_map: serde_json::to_string(&storage_map).map_err(|err| {
+                Status::internal(format!("Failed to serialize storage map: {err}"))
+            })?,
+            aggregated: uos_by_aggregator
+                .into_iter()
+                .map(|(aggregator, uos)| UserOperationsPerAggregator {
+                    aggregator: Some(aggregator.into()),
+                    uos: uos.into_iter().map(Into::into).collect(),
+                })
+                .collect(),
+            storage_accesses: serde_json::to_string(&storage_accesses).map_err(|err| {
+                Status::internal(format!("Failed to serialize storage accesses: {err}"))
+            })?,
         }))
     }
 
@@ -235,6 +492,7 @@ where
         let req = req.into_inner();
 
         let uo_hash = parse_hash(req.hash)?;
+        let tx_hash = req.tx_hash.map(Into::into);
 
         let keys: Vec<MempoolId> = self.uopools.read().keys().cloned().collect();
         for key in keys {
@@ -243,13 +501,15 @@ where
                 let uopool_builder = uopools_ref.get(&key).expect("key must exist");
                 uopool_builder.uopool()
             };
-            if let Ok(uo_by_hash) = uopool.get_user_operation_by_hash(&uo_hash.into()).await {
+            if let Ok(uo_by_hash) =
+                uopool.get_user_operation_by_hash(&uo_hash.into(), tx_hash).await
+            {
                 return Ok(Response::new(GetUserOperationByHashResponse {
                     user_operation: Some(uo_by_hash.user_operation.into()),
                     entry_point: Some(uo_by_hash.entry_point.into()),
-                    transaction_hash: Some(uo_by_hash.transaction_hash.into()),
-                    block_hash: Some(uo_by_hash.block_hash.into()),
-                    block_number: uo_by_hash.block_number.as_u64(),
+                    transaction_hash: uo_by_hash.transaction_hash.map(Into::into),
+                    block_hash: uo_by_hash.block_hash.map(Into::into),
+                    block_number: uo_by_hash.block_number.map(|n| n.as_u64()).unwrap_or_default(),
                 }));
             }
         }
@@ -264,6 +524,7 @@ where
         let req = req.into_inner();
 
         let uo_hash = parse_hash(req.hash)?;
+        let tx_hash = req.tx_hash.map(Into::into);
         let keys: Vec<MempoolId> = self.uopools.read().keys().cloned().collect();
         for key in keys {
             let uopool = {
@@ -271,7 +532,9 @@ where
                 let uopool_builder = uopools_ref.get(&key).expect("key must exist");
                 uopool_builder.uopool()
             };
-            if let Ok(uo_receipt) = uopool.get_user_operation_receipt(&uo_hash.into()).await {
+            if let Ok(uo_receipt) =
+                uopool.get_user_operation_receipt(&uo_hash.into(), tx_hash).await
+            {
                 return Ok(Response::new(GetUserOperationReceiptResponse {
                     user_operation_hash: Some(uo_receipt.user_operation_hash.into()),
                     sender: Some(uo_receipt.sender.into()),
@@ -298,12 +561,63 @@ where
 
         let ep = parse_addr(req.ep)?;
         let uopool = self.get_uopool(&ep)?;
-        match uopool.get_all() {
-            Ok(uos) => {
-                Ok(Response::new(GetAllResponse { uos: uos.into_iter().map(Into::into).collect() }))
-            }
-            Err(err) => Err(Status::unknown(format!("Internal error: {err:?}"))),
-        }
+        let uos = uopool
+            .get_all()
+            .await
+            .map_err(|err| Status::unknown(format!("Internal error: {err:?}")))?;
+        // the queued user operations are only looked up on request, it queries the nonce of
+        // every sender
+        let queued = if req.with_queued {
+            uopool
+                .split_queued_user_operations(uos.clone())
+                .await
+                .map_err(|err| Status::unknown(format!("Internal error: {err:?}")))?
+                .1
+        } else {
+            vec![]
+        };
+
+        Ok(Response::new(GetAllResponse {
+            uos: uos.into_iter().map(Into::into).collect(),
+            queued: queued.into_iter().map(|uo| uo.hash.into()).collect(),
+            attempts: uopool
+                .get_bundle_attempts()
+                .into_iter()
+                .map(|(hash, attempts)| UserOperationAttempts { hash: Some(hash.into()), attempts })
+                .collect(),
+        }))
+    }
+
+    async fn get_mempool_composition(
+        &self,
+        req: Request<GetMempoolCompositionRequest>,
+    ) -> Result<Response<GetMempoolCompositionResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+        let composition = uopool
+            .composition()
+            .map_err(|err| Status::unknown(format!("Internal error: {err:?}")))?;
+
+        let entities = |counts: HashMap<Address, usize>| {
+            let mut counts = counts.into_iter().collect::<Vec<_>>();
+            counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            counts
+                .into_iter()
+                .map(|(addr, count)| EntityUserOperations {
+                    addr: Some(addr.into()),
+                    count: count as u64,
+                })
+                .collect()
+        };
+
+        Ok(Response::new(GetMempoolCompositionResponse {
+            total: composition.total as u64,
+            senders: entities(composition.by_sender),
+            paymasters: entities(composition.by_paymaster),
+            factories: entities(composition.by_factory),
+        }))
     }
 
     async fn clear_mempool(&self, _req: Request<()>) -> Result<Response<()>, Status> {
@@ -320,6 +634,60 @@ where
         Ok(Response::new(()))
     }
 
+    async fn update_blocklist(
+        &self,
+        req: Request<UpdateBlocklistRequest>,
+    ) -> Result<Response<()>, Status> {
+        if !self.enable_admin {
+            return Err(Status::permission_denied("Admin methods are not enabled"));
+        }
+
+        let req = req.into_inner();
+
+        let add = req.add.into_iter().map(Address::from).collect::<Vec<_>>();
+        let remove = req.remove.into_iter().map(Address::from).collect::<Vec<_>>();
+
+        self.uopools.read().values().for_each(|uopool| {
+            let mut uopool = uopool.uopool();
+            for addr in add.iter() {
+                uopool.reputation.add_blacklist(addr);
+            }
+            for addr in remove.iter() {
+                uopool.reputation.remove_blacklist(addr);
+            }
+        });
+        info!("Blocklist updated, added: {add:?}, removed: {remove:?}");
+
+        Ok(Response::new(()))
+    }
+
+    async fn update_allowlist(
+        &self,
+        req: Request<UpdateAllowlistRequest>,
+    ) -> Result<Response<()>, Status> {
+        if !self.enable_admin {
+            return Err(Status::permission_denied("Admin methods are not enabled"));
+        }
+
+        let req = req.into_inner();
+        let kind = req.allowlist();
+        let allowlist = match kind {
+            Allowlist::Paymasters => self.paymaster_allowlist.paymasters(),
+            Allowlist::TrustedSenders => self.trusted_senders.senders(),
+            Allowlist::Unspecified => {
+                return Err(Status::invalid_argument("Allowlist is unspecified"))
+            }
+        };
+
+        let add = req.add.into_iter().map(Address::from).collect::<Vec<_>>();
+        let remove = req.remove.into_iter().map(Address::from).collect::<Vec<_>>();
+
+        allowlist.update(&add, &remove);
+        info!("Allowlist {kind:?} updated, added: {add:?}, removed: {remove:?}");
+
+        Ok(Response::new(()))
+    }
+
     async fn clear(&self, _req: Request<()>) -> Result<Response<()>, Status> {
         self.uopools.read().values().for_each(|uopool| {
             uopool.uopool().clear();
@@ -367,21 +735,83 @@ where
         let req = req.into_inner();
 
         let ep = parse_addr(req.ep)?;
-        let mut uopool = self.get_uopool(&ep)?;
+        self.get_uopool(&ep)?;
+
+        // the user operations of one sender are added in order, the senders concurrently
+        let mut senders = HashMap::<Address, usize>::new();
+        let mut uos_by_sender: Vec<Vec<UserOperation>> = vec![];
+        for uo in req.uos.into_iter().map(UserOperation::from) {
+            let index = *senders.entry(uo.sender).or_insert_with(|| {
+                uos_by_sender.push(vec![]);
+                uos_by_sender.len() - 1
+            });
+            uos_by_sender[index].push(uo);
+        }
+
+        let res = futures::future::join_all(uos_by_sender.into_iter().map(|uos| async {
+            for uo in uos {
+                if self.validate_and_add(&ep, uo).await?.is_err() {
+                    return Ok(false);
+                }
+            }
+            Ok::<bool, Status>(true)
+        }))
+        .await;
+
+        let mut added = true;
+        for res in res {
+            added &= res?;
+        }
 
         let res = Response::new(AddMempoolResponse {
-            res: match uopool
-                .add_user_operations(req.uos.into_iter().map(|uo| uo.into()).collect())
-                .await
-            {
-                Ok(_) => AddMempoolResult::AddedMempool as i32,
-                Err(_) => AddMempoolResult::NotAddedMempool as i32,
+            res: if added {
+                AddMempoolResult::AddedMempool as i32
+            } else {
+                AddMempoolResult::NotAddedMempool as i32
             },
         });
 
         Ok(res)
     }
 
+    async fn export_snapshot(
+        &self,
+        req: Request<ExportSnapshotRequest>,
+    ) -> Result<Response<ExportSnapshotResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+
+        let snapshot = uopool
+            .export_snapshot()
+            .and_then(|snapshot| Ok(serde_json::to_string(&snapshot)?))
+            .map_err(|e| tonic::Status::internal(format!("Export snapshot internal error: {e}")))?;
+
+        Ok(Response::new(ExportSnapshotResponse { snapshot }))
+    }
+
+    async fn import_snapshot(
+        &self,
+        req: Request<ImportSnapshotRequest>,
+    ) -> Result<Response<ImportSnapshotResponse>, Status> {
+        let req = req.into_inner();
+
+        let snapshot: MempoolSnapshot = serde_json::from_str(&req.snapshot)
+            .map_err(|e| Status::invalid_argument(format!("Invalid snapshot: {e}")))?;
+        let ep = snapshot.entry_point;
+        let total = snapshot.user_operations.len();
+        let mut uopool = self.get_uopool(&ep)?;
+
+        let imported = uopool
+            .import_snapshot(snapshot)
+            .await
+            .map_err(|e| tonic::Status::internal(format!("Import snapshot internal error: {e}")))?;
+        info!("Imported {imported} of {total} user operations from the snapshot of {ep:?}");
+
+        Ok(Response::new(ImportSnapshotResponse { imported: imported as u64 }))
+    }
+
     async fn get_stake_info(
         &self,
         req: Request<GetStakeInfoRequest>,
@@ -401,12 +831,97 @@ where
             is_staked: res.is_staked,
         }))
     }
+
+    type SubscribeEventsStream =
+        Pin<Box<dyn Stream<Item = Result<MempoolEventResponse, Status>> + Send + 'static>>;
+
+    async fn subscribe_events(
+        &self,
+        req: Request<SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let m_id = mempool_id(&ep, self.chain.id());
+        let rx = self
+            .uopools
+            .read()
+            .get(&m_id)
+            .map(|b| b.subscribe_events())
+            .ok_or(Status::new(Code::Unavailable, "User operation pool is not available"))?;
+
+        let stream = futures::stream::unfold(rx, |mut rx| async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => return Some((Ok(event.into()), rx)),
+                    Err(RecvError::Lagged(n)) => {
+                        warn!("Mempool events subscriber lagged behind, {n} events skipped")
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn remove_user_operation(
+        &self,
+        req: Request<RemoveUserOperationRequest>,
+    ) -> Result<Response<RemoveUserOperationResponse>, Status> {
+        if !self.enable_admin {
+            return Err(Status::permission_denied("Admin methods are not enabled"));
+        }
+
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uo_hash: UserOperationHash = parse_hash(req.hash)?.into();
+        let mut uopool = self.get_uopool(&ep)?;
+
+        let removed = uopool.evict_user_operation(&uo_hash).map_err(|e| {
+            tonic::Status::internal(format!("Remove user operation internal error: {e:?}"))
+        })?;
+        if removed {
+            info!(uo_hash = %uo_hash, "User operation evicted from the mempool on request");
+        }
+
+        Ok(Response::new(RemoveUserOperationResponse { removed }))
+    }
+
+    async fn revalidate_mempool(
+        &self,
+        req: Request<RevalidateMempoolRequest>,
+    ) -> Result<Response<RevalidateMempoolResponse>, Status> {
+        if !self.enable_admin {
+            return Err(Status::permission_denied("Admin methods are not enabled"));
+        }
+
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let mut uopool = self.get_uopool(&ep)?;
+
+        let (kept, dropped) = uopool.revalidate_user_operations().await.map_err(|e| {
+            tonic::Status::internal(format!("Revalidate mempool internal error: {e:?}"))
+        })?;
+        info!("Re-validated the mempool of {ep:?}, kept: {kept}, dropped: {}", dropped.len());
+
+        Ok(Response::new(RevalidateMempoolResponse {
+            kept: kept as u64,
+            dropped: dropped
+                .into_iter()
+                .map(|(uo_hash, reason)| FailedUserOperation { hash: Some(uo_hash.into()), reason })
+                .collect(),
+        }))
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
 pub async fn uopool_service_run<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>(
     addr: SocketAddr,
     eps: Vec<Address>,
+    entry_point_versions: HashMap<Address, EntryPointVersion>,
     eth_client: Arc<M>,
     block_streams: Vec<BlockStream>,
     chain: Chain,
@@ -416,6 +931,16 @@ pub async fn uopool_service_run<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>(
     validator: StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>,
     p2p_config: Option<Config>,
     enable_metrics: bool,
+    uo_ttl: Option<u64>,
+    max_uos: Option<usize>,
+    max_transient_failures: u64,
+    max_bundle_attempts: Option<u64>,
+    validation_concurrency: usize,
+    sort_strategy: SortStrategy,
+    enable_admin: bool,
+    fee_oracle: FeeOracle,
+    trusted_senders: TrustedSenders,
+    pre_verification_gas_overhead: U256,
 ) -> Result<()>
 where
     M: Middleware + Clone + 'static,
@@ -441,6 +966,8 @@ where
 
             for (ep, block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
                 let id = mempool_id(&ep, chain.id());
+                let entry_point_version =
+                    EntryPointVersion::from_declared(&entry_point_versions, &ep);
                 let (waiting_to_pub_sd, waiting_to_pub_rv) = unbounded::<(UserOperation, U256)>();
                 let uo_builder = UoPoolBuilder::new(
                     eth_client.clone(),
@@ -451,7 +978,19 @@ where
                     reputation.clone(),
                     validator.clone(),
                     Some(waiting_to_pub_sd),
-                );
+                )
+                .with_entry_point_version(entry_point_version)
+                .with_uo_ttl(uo_ttl)
+                .with_max_uos(max_uos)
+                .with_max_transient_failures(max_transient_failures)
+                .with_max_bundle_attempts(max_bundle_attempts)
+                .with_sort_strategy(sort_strategy)
+                .with_trusted_senders(trusted_senders.clone())
+                .with_pre_verification_gas_overhead(pre_verification_gas_overhead);
+                match uo_builder.uopool().reload_user_operations().await {
+                    Ok(count) => info!("Reloaded {count} user operations into the mempool {id:?}"),
+                    Err(e) => error!("Failed to reload user operations from the mempool: {e:?}"),
+                }
                 uo_builder.register_block_updates(block_stream);
                 uo_builder.register_reputation_updates();
 
@@ -460,11 +999,20 @@ where
                 // spawn a task which would consume the userop received from p2p network
                 tokio::spawn(async move {
                     while let Some(user_op) = p2p_userop_rv.next().await {
-                        let res = uo_pool.validate_user_operation(&user_op).await;
-                        match uo_pool.add_user_operation(user_op, res).await {
-                            Ok(_) => {}
-                            Err(e) => error!("Failed to add user operation: {:?} from p2p", e),
+                        let user_op = UserOperation::from_user_operation_signed(
+                            uo_pool.user_operation_hash(&user_op.user_operation),
+                            user_op.user_operation,
+                        );
+                        let span = info_span!("user_operation", uo_hash = %user_op.hash, ep = ?ep);
+                        async {
+                            let res = uo_pool.validate_user_operation(&user_op).await;
+                            match uo_pool.add_user_operation(user_op, res).await {
+                                Ok(_) => {}
+                                Err(e) => error!("Failed to add user operation: {:?} from p2p", e),
+                            }
                         }
+                        .instrument(span)
+                        .await;
                     }
                 });
                 m_map.insert(id, uo_builder);
@@ -497,6 +1045,8 @@ where
         } else {
             for (ep, block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
                 let id = mempool_id(&ep, chain.id());
+                let entry_point_version =
+                    EntryPointVersion::from_declared(&entry_point_versions, &ep);
                 let uo_builder = UoPoolBuilder::new(
                     eth_client.clone(),
                     ep,
@@ -506,26 +1056,37 @@ where
                     reputation.clone(),
                     validator.clone(),
                     None,
-                );
+                )
+                .with_entry_point_version(entry_point_version)
+                .with_uo_ttl(uo_ttl)
+                .with_max_uos(max_uos)
+                .with_max_transient_failures(max_transient_failures)
+                .with_max_bundle_attempts(max_bundle_attempts)
+                .with_sort_strategy(sort_strategy)
+                .with_trusted_senders(trusted_senders.clone())
+                .with_pre_verification_gas_overhead(pre_verification_gas_overhead);
+                match uo_builder.uopool().reload_user_operations().await {
+                    Ok(count) => info!("Reloaded {count} user operations into the mempool {id:?}"),
+                    Err(e) => error!("Failed to reload user operations from the mempool: {e:?}"),
+                }
                 uo_builder.register_block_updates(block_stream);
                 uo_builder.register_reputation_updates();
                 m_map.insert(id, uo_builder);
             }
         };
 
+        let paymaster_allowlist = validator.paymaster_allowlist().clone();
         let uopool_map = Arc::new(RwLock::new(m_map));
-        let svc = uo_pool_server::UoPoolServer::new(UoPoolService::<
-            M,
-            T,
-            Y,
-            X,
-            Z,
-            H,
-            R,
-            SanCk,
-            SimCk,
-            SimTrCk,
-        >::new(uopool_map, chain));
+        let uopool_service = UoPoolService::<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>::new(
+            uopool_map,
+            chain,
+            validation_concurrency,
+        )
+        .with_admin(enable_admin)
+        .with_fee_oracle(fee_oracle)
+        .with_paymaster_allowlist(paymaster_allowlist)
+        .with_trusted_senders(trusted_senders);
+        let svc = uo_pool_server::UoPoolServer::new(uopool_service);
 
         if enable_metrics {
             builder.layer(MetricsLayer).add_service(svc).serve(addr).await
@@ -538,3 +1099,59 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{MockProvider, Provider};
+    use silius_mempool::validate::simulation::signature::Signature;
+    use silius_primitives::{
+        reputation::ReputationEntry, simulation::CodeHash, UserOperationSigned,
+    };
+    use std::collections::HashSet;
+    use uo_pool_server::UoPool as _;
+
+    type Service = UoPoolService<
+        Provider<MockProvider>,
+        HashMap<UserOperationHash, UserOperationSigned>,
+        HashMap<Address, HashSet<UserOperationHash>>,
+        HashMap<Address, HashSet<UserOperationHash>>,
+        HashMap<UserOperationHash, Vec<CodeHash>>,
+        HashSet<Address>,
+        HashMap<Address, ReputationEntry>,
+        (),
+        (Signature,),
+        (),
+    >;
+
+    #[tokio::test]
+    async fn admin_methods_denied() {
+        let service = Service::new(Default::default(), Chain::from(1), 1);
+        let blocklist = || {
+            Request::new(UpdateBlocklistRequest {
+                add: vec![Address::random().into()],
+                remove: vec![],
+            })
+        };
+        let allowlist = || {
+            Request::new(UpdateAllowlistRequest {
+                allowlist: Allowlist::Paymasters.into(),
+                add: vec![Address::random().into()],
+                remove: vec![],
+            })
+        };
+
+        assert_eq!(
+            service.update_blocklist(blocklist()).await.unwrap_err().code(),
+            Code::PermissionDenied
+        );
+        assert_eq!(
+            service.update_allowlist(allowlist()).await.unwrap_err().code(),
+            Code::PermissionDenied
+        );
+
+        let service = service.with_admin(true);
+        assert!(service.update_blocklist(blocklist()).await.is_ok());
+        assert!(service.update_allowlist(allowlist()).await.is_ok());
+    }
+}
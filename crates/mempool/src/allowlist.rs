@@ -0,0 +1,102 @@
+use ethers::types::Address;
+use parking_lot::RwLock;
+use std::{collections::HashSet, sync::Arc};
+
+/// Set of addresses configured by the operator that can be updated at runtime (e.g., by the admin
+/// methods). The clones share the same set.
+#[derive(Clone, Debug, Default)]
+pub struct Allowlist {
+    /// Addresses in the allowlist (`None` until the allowlist is set or updated)
+    addresses: Arc<RwLock<Option<HashSet<Address>>>>,
+}
+
+impl Allowlist {
+    /// Creates a new [Allowlist](Allowlist)
+    ///
+    /// # Arguments
+    /// * `addresses` - The addresses in the allowlist (the allowlist is not set if empty)
+    ///
+    /// # Returns
+    /// * `Allowlist` - The [Allowlist](Allowlist)
+    pub fn new(addresses: Vec<Address>) -> Self {
+        let addresses =
+            if addresses.is_empty() { None } else { Some(addresses.into_iter().collect()) };
+        Self { addresses: Arc::new(RwLock::new(addresses)) }
+    }
+
+    /// Checks whether the address is in the allowlist
+    ///
+    /// # Arguments
+    /// * `address` - The address to check
+    ///
+    /// # Returns
+    /// * `bool` - Whether the address is in the allowlist (false if the allowlist is not set)
+    pub fn contains(&self, address: &Address) -> bool {
+        self.addresses.read().as_ref().is_some_and(|addresses| addresses.contains(address))
+    }
+
+    /// Checks whether the address is allowed
+    ///
+    /// # Arguments
+    /// * `address` - The address to check
+    ///
+    /// # Returns
+    /// * `bool` - Whether the address is allowed (any address is allowed if the allowlist is not
+    ///   set)
+    pub fn allows(&self, address: &Address) -> bool {
+        self.addresses.read().as_ref().map_or(true, |addresses| addresses.contains(address))
+    }
+
+    /// Checks whether the allowlist has no addresses
+    ///
+    /// # Returns
+    /// * `bool` - Whether the allowlist is empty (or not set)
+    pub fn is_empty(&self) -> bool {
+        self.addresses.read().as_ref().map_or(true, HashSet::is_empty)
+    }
+
+    /// Updates the addresses in the allowlist. Once updated, the allowlist is set (even if all the
+    /// addresses are removed).
+    ///
+    /// # Arguments
+    /// * `add` - The addresses to add
+    /// * `remove` - The addresses to remove
+    pub fn update(&self, add: &[Address], remove: &[Address]) {
+        let mut addresses = self.addresses.write();
+        let addresses = addresses.get_or_insert_with(HashSet::new);
+        addresses.extend(add.iter().copied());
+        for addr in remove.iter() {
+            addresses.remove(addr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlist_updates() {
+        let (first, second) = (Address::random(), Address::random());
+
+        let allowlist = Allowlist::default();
+        assert!(!allowlist.contains(&first));
+        assert!(allowlist.allows(&first));
+        assert!(allowlist.is_empty());
+
+        let allowlist = Allowlist::new(vec![first]);
+        assert!(allowlist.contains(&first) && allowlist.allows(&first));
+        assert!(!allowlist.contains(&second) && !allowlist.allows(&second));
+
+        // the clones share the allowlist
+        allowlist.clone().update(&[second], &[first]);
+        assert!(!allowlist.allows(&first));
+        assert!(allowlist.allows(&second));
+
+        // the allowlist is set once updated
+        let allowlist = Allowlist::default();
+        allowlist.update(&[], &[]);
+        assert!(allowlist.is_empty());
+        assert!(!allowlist.allows(&first));
+    }
+}
@@ -0,0 +1,97 @@
+use parking_lot::Mutex;
+use silius_primitives::UserOperationHash;
+use std::{collections::HashMap, sync::Arc};
+
+/// Failed bundle attempts of the user operations, i.e., how many times a user operation was kept
+/// in the mempool after it failed at bundle time (the re-simulation right before bundling or the
+/// simulation of the bundle). Unlike the [ValidationGrace](crate::ValidationGrace), the attempts
+/// are not reset once the user operation passes the validation again, so a user operation that
+/// keeps failing intermittently (e.g., a flaky paymaster) is evicted eventually instead of being
+/// retried forever. The clones share the same attempt counters.
+#[derive(Clone, Debug, Default)]
+pub struct BundleAttempts {
+    /// Failed bundle attempts by user operation
+    attempts: Arc<Mutex<HashMap<UserOperationHash, u64>>>,
+    /// Number of failed bundle attempts tolerated before the eviction (unlimited if not set)
+    max_attempts: Option<u64>,
+}
+
+impl BundleAttempts {
+    /// Creates a new [BundleAttempts](BundleAttempts)
+    ///
+    /// # Arguments
+    /// * `max_attempts` - Number of failed bundle attempts tolerated before the user operation is
+    ///   evicted (unlimited if not set)
+    ///
+    /// # Returns
+    /// * `BundleAttempts` - The [BundleAttempts](BundleAttempts)
+    pub fn new(max_attempts: Option<u64>) -> Self {
+        Self { attempts: Default::default(), max_attempts }
+    }
+
+    /// Records a failed bundle attempt of the user operation
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the user operation
+    ///
+    /// # Returns
+    /// * `bool` - Whether the user operation exhausted the attempts and should be evicted
+    pub fn on_failure(&self, uo_hash: &UserOperationHash) -> bool {
+        let mut attempts = self.attempts.lock();
+        let count = attempts.entry(*uo_hash).or_default();
+        *count += 1;
+        if self.max_attempts.is_some_and(|max_attempts| *count > max_attempts) {
+            attempts.remove(uo_hash);
+            return true;
+        }
+        false
+    }
+
+    /// Forgets the attempts of the user operation (it left the mempool)
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the user operation
+    pub fn reset(&self, uo_hash: &UserOperationHash) {
+        self.attempts.lock().remove(uo_hash);
+    }
+
+    /// Returns the number of failed bundle attempts of the user operation
+    pub fn attempts(&self, uo_hash: &UserOperationHash) -> u64 {
+        self.attempts.lock().get(uo_hash).copied().unwrap_or_default()
+    }
+
+    /// Returns the number of failed bundle attempts of all the user operations that failed at
+    /// least once
+    pub fn all(&self) -> HashMap<UserOperationHash, u64> {
+        self.attempts.lock().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn bundle_attempts_evict_after_max_failures() {
+        let attempts = BundleAttempts::new(Some(2));
+        let uo_hash = UserOperationHash(H256::random());
+
+        assert!(!attempts.on_failure(&uo_hash));
+        assert!(!attempts.clone().on_failure(&uo_hash));
+        assert_eq!(attempts.attempts(&uo_hash), 2);
+        assert_eq!(attempts.all(), HashMap::from([(uo_hash, 2)]));
+        assert!(attempts.on_failure(&uo_hash));
+        assert_eq!(attempts.attempts(&uo_hash), 0);
+
+        // the attempts are forgotten once the user operation leaves the mempool
+        assert!(!attempts.on_failure(&uo_hash));
+        attempts.reset(&uo_hash);
+        assert!(attempts.all().is_empty());
+
+        // unlimited attempts by default
+        let attempts = BundleAttempts::default();
+        assert!((0..100).all(|_| !attempts.on_failure(&uo_hash)));
+        assert_eq!(attempts.attempts(&uo_hash), 100);
+    }
+}
@@ -1,24 +1,38 @@
 use crate::{
     mempool::{UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    metrics::record_composition,
+    reorg::{IncludedBundle, IncludedBundles},
     reputation::{HashSetOp, ReputationEntryOp},
     validate::{
         validator::StandardUserOperationValidator, SanityCheck, SimulationCheck,
         SimulationTraceCheck,
     },
-    Mempool, Reputation, UoPool,
+    BundleAttempts, EstimationCache, Mempool, MempoolEvent, RemovalReason, Reputation,
+    TrustedSenders, UoPool, ValidationGrace,
 };
 use alloy_chains::Chain;
 use ethers::{
+    abi::RawLog,
+    contract::EthEvent,
     providers::Middleware,
-    types::{Address, H256, U256},
+    types::{Address, Filter, H256, U256, U64},
 };
 use eyre::format_err;
 use futures::channel::mpsc::UnboundedSender;
 use futures_util::StreamExt;
-use silius_contracts::EntryPoint;
-use silius_primitives::{get_address, provider::BlockStream, UserOperation, UserOperationSigned};
-use std::{sync::Arc, time::Duration};
-use tracing::warn;
+use silius_contracts::{
+    entry_point::{AccountDeployedFilter, UserOperationEventFilter},
+    EntryPoint,
+};
+use silius_primitives::{
+    constants::mempool::{ESTIMATION_CACHE_SIZE, EVENTS_CHANNEL_SIZE, REORG_DEPTH},
+    mempool::SortStrategy,
+    provider::BlockStream,
+    EntryPointVersion, UserOperation, UserOperationHash, UserOperationSigned,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
 
 type StandardUoPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk> =
     UoPool<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>, T, Y, X, Z, H, R>;
@@ -37,6 +51,8 @@ where
 {
     eth_client: Arc<M>,
     entrypoint_addr: Address,
+    // The version of the entry point (the version of the canonical address by default)
+    entry_point_version: EntryPointVersion,
     chain: Chain,
     max_verification_gas: U256,
     mempool: Mempool<T, Y, X, Z>,
@@ -44,6 +60,24 @@ where
     validator: StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>,
     // It would be None if p2p is not enabled
     publish_sd: Option<UnboundedSender<(UserOperation, U256)>>,
+    // Shared by all the user operation pools created by the builder
+    events_sd: broadcast::Sender<MempoolEvent>,
+    // Time (in seconds) after which a user operation that was not bundled is evicted
+    uo_ttl: Option<u64>,
+    // The order in which the user operations are bundled
+    sort_strategy: SortStrategy,
+    // Shared by all the user operation pools created by the builder, cleared on every new block
+    estimation_cache: EstimationCache,
+    // The senders whose user operations are bundled first
+    trusted_senders: TrustedSenders,
+    // Max number of user operations in the mempool (unbounded if not set)
+    max_uos: Option<usize>,
+    // Shared by all the user operation pools created by the builder
+    validation_grace: ValidationGrace,
+    // Shared by all the user operation pools created by the builder
+    bundle_attempts: BundleAttempts,
+    // Gas added to the calculated pre-verification gas of the estimations
+    pre_verification_gas_overhead: U256,
 }
 
 impl<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>
@@ -74,56 +108,355 @@ where
         Self {
             eth_client,
             entrypoint_addr,
+            entry_point_version: EntryPointVersion::from_address(&entrypoint_addr),
             chain,
             max_verification_gas,
             mempool,
             reputation,
             validator,
             publish_sd,
+            events_sd: broadcast::channel(EVENTS_CHANNEL_SIZE).0,
+            uo_ttl: None,
+            sort_strategy: SortStrategy::default(),
+            estimation_cache: EstimationCache::new(ESTIMATION_CACHE_SIZE),
+            trusted_senders: TrustedSenders::default(),
+            max_uos: None,
+            validation_grace: ValidationGrace::default(),
+            bundle_attempts: BundleAttempts::default(),
+            pre_verification_gas_overhead: U256::zero(),
         }
     }
 
+    /// Sets the version of the entry point, e.g., the declared version of an entry point deployed
+    /// at a non-canonical address
+    ///
+    /// # Arguments
+    /// `entry_point_version` - The [EntryPointVersion](EntryPointVersion)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_entry_point_version(mut self, entry_point_version: EntryPointVersion) -> Self {
+        self.entry_point_version = entry_point_version;
+        self
+    }
+
+    /// Sets the time after which the user operations that were not bundled are evicted from the
+    /// mempool
+    ///
+    /// # Arguments
+    /// `uo_ttl` - The TTL in seconds (never evicted if not set)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_uo_ttl(mut self, uo_ttl: Option<u64>) -> Self {
+        self.uo_ttl = uo_ttl;
+        self
+    }
+
+    /// Sets the gas added to the calculated pre-verification gas of the estimations
+    ///
+    /// # Arguments
+    /// `overhead` - The pre-verification gas overhead
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_pre_verification_gas_overhead(mut self, overhead: U256) -> Self {
+        self.pre_verification_gas_overhead = overhead;
+        self
+    }
+
+    /// Sets the order in which the user operations are bundled
+    ///
+    /// # Arguments
+    /// `sort_strategy` - The [SortStrategy](SortStrategy)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_sort_strategy(mut self, sort_strategy: SortStrategy) -> Self {
+        self.sort_strategy = sort_strategy;
+        self
+    }
+
+    /// Sets the senders whose user operations are bundled first regardless of the sort strategy
+    ///
+    /// # Arguments
+    /// `trusted_senders` - The [TrustedSenders](TrustedSenders) (shared with the user operation
+    /// pools)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_trusted_senders(mut self, trusted_senders: TrustedSenders) -> Self {
+        self.trusted_senders = trusted_senders;
+        self
+    }
+
+    /// Sets the capacity of the mempool, the user operations with the lowest fees are evicted by
+    /// the ones that pay more once it's full
+    ///
+    /// # Arguments
+    /// `max_uos` - The max number of user operations (unbounded if not set)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_max_uos(mut self, max_uos: Option<usize>) -> Self {
+        self.max_uos = max_uos;
+        self
+    }
+
+    /// Sets the number of consecutive re-validations a user operation may fail because of a
+    /// possibly transient condition before it's evicted
+    ///
+    /// # Arguments
+    /// `max_transient_failures` - The number of tolerated failures (0 evicts on the first one)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_max_transient_failures(mut self, max_transient_failures: u64) -> Self {
+        self.validation_grace = ValidationGrace::new(max_transient_failures);
+        self
+    }
+
+    /// Sets the number of failed bundle attempts a user operation may be kept in the mempool for
+    /// before it's evicted and the responsible entity is penalized (only the user operations kept
+    /// within the [max transient failures](Self::with_max_transient_failures) are attempted again)
+    ///
+    /// # Arguments
+    /// `max_bundle_attempts` - The number of tolerated failed attempts (unlimited if not set)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPoolBuilder](UoPoolBuilder) object
+    pub fn with_max_bundle_attempts(mut self, max_bundle_attempts: Option<u64>) -> Self {
+        self.bundle_attempts = BundleAttempts::new(max_bundle_attempts);
+        self
+    }
+
+    /// Subscribes to the [MempoolEvents](MempoolEvent) of the user operation pool
+    ///
+    /// # Returns
+    /// `broadcast::Receiver<MempoolEvent>` - The receiver of the events published after
+    /// subscribing
+    pub fn subscribe_events(&self) -> broadcast::Receiver<MempoolEvent> {
+        self.events_sd.subscribe()
+    }
+
+    /// Removes the user operations included in the block from the mempool (with the user
+    /// operations whose nonce they consumed) and credits the inclusion to their entities. The
+    /// included user operations are found through the `UserOperationEvent` logs of the entry point,
+    /// so bundles sent through another contract (e.g., the multicall contract) or by other
+    /// bundlers are recognized as well.
+    ///
+    /// # Arguments
+    /// * `hash` - The hash of the new block
+    /// * `uopool` - The user operation pool
+    /// * `included` - The [IncludedBundles](IncludedBundles) of the recent blocks
     async fn handle_block_update(
         hash: H256,
         uopool: &mut StandardUoPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>,
+        included: &mut IncludedBundles,
     ) -> eyre::Result<()> {
-        let txs =
-            uopool.entry_point.eth_client().get_block_with_txs(hash).await?.map(|b| b.transactions);
-
-        if let Some(txs) = txs {
-            for tx in txs {
-                if tx.to == Some(uopool.entry_point.address()) {
-                    let dec: Result<(Vec<UserOperationSigned>, Address), _> =
-                        uopool.entry_point.entry_point_api().decode("handleOps", tx.input);
-
-                    if let Ok((uos, _)) = dec {
-                        uopool.remove_user_operations(
-                            uos.iter()
-                                .map(|uo| uo.hash(&uopool.entry_point.address(), uopool.chain.id()))
-                                .collect(),
-                        );
-
-                        for uo in uos {
-                            // update reputations
-                            uopool.reputation.increment_included(&uo.sender).map_err(|e| {
-                                format_err!("Failed to increment sender reputation: {:?}", e)
-                            })?;
-
-                            if let Some(addr) = get_address(&uo.paymaster_and_data) {
-                                uopool.reputation.increment_included(&addr).map_err(|e| {
-                                    format_err!("Failed to increment paymaster reputation: {:?}", e)
-                                })?;
-                            }
-
-                            if let Some(addr) = get_address(&uo.init_code) {
-                                uopool.reputation.increment_included(&addr).map_err(|e| {
-                                    format_err!("Failed to increment factory reputation: {:?}", e)
-                                })?;
-                            }
-                        }
+        let eth_client = uopool.entry_point.eth_client();
+        let Some(block) = eth_client.get_block(hash).await? else {
+            return Ok(());
+        };
+        let block_number = block.number.unwrap_or_default();
+        let ep = uopool.entry_point.address();
+
+        let logs = eth_client
+            .get_logs(&Filter::new().at_block_hash(hash).address(ep).topic0(vec![
+                UserOperationEventFilter::signature(),
+                AccountDeployedFilter::signature(),
+            ]))
+            .await?;
+
+        // the events of the user operations grouped by the bundle transaction (in block order)
+        let mut bundles: Vec<(H256, Vec<UserOperationEventFilter>)> = vec![];
+        let mut factories = HashMap::<H256, Address>::new();
+        for log in logs {
+            let Some(tx_hash) = log.transaction_hash else { continue };
+            let topic = log.topics.first().copied();
+            let raw = RawLog { topics: log.topics, data: log.data.to_vec() };
+
+            if topic == Some(AccountDeployedFilter::signature()) {
+                if let Ok(event) = AccountDeployedFilter::decode_log(&raw) {
+                    factories.insert(event.user_op_hash.into(), event.factory);
+                }
+            } else if let Ok(event) = UserOperationEventFilter::decode_log(&raw) {
+                match bundles.last_mut() {
+                    Some((hash, events)) if *hash == tx_hash => events.push(event),
+                    _ => bundles.push((tx_hash, vec![event])),
+                }
+            }
+        }
+
+        for (tx_hash, events) in bundles {
+            let uo_hashes: Vec<UserOperationHash> =
+                events.iter().map(|event| H256::from(event.user_op_hash).into()).collect();
+
+            // the user operations are kept to add them back to the mempool if the bundle is
+            // reorged out, the ones that were not in the mempool are decoded from the bundle
+            let mut uos = vec![];
+            for uo_hash in uo_hashes.iter() {
+                if let Some(uo) = uopool.mempool.get(uo_hash)? {
+                    uos.push(uo);
+                }
+            }
+            if uos.len() < uo_hashes.len() {
+                let decoded = uopool
+                    .get_user_operations_in_transaction(tx_hash)
+                    .await
+                    .map_err(|e| warn!("Failed to decode bundle transaction {tx_hash:?}: {e:?}"))
+                    .unwrap_or_default();
+                for uo in decoded {
+                    let uo_hash = uopool.user_operation_hash(&uo);
+                    if uo_hashes.contains(&uo_hash) && !uos.iter().any(|uo| uo.hash == uo_hash) {
+                        uos.push(UserOperation::from_user_operation_signed(uo_hash, uo));
                     }
                 }
             }
+
+            uopool.remove_user_operations(uo_hashes.clone(), RemovalReason::Bundled);
+
+            // the events carry the sender and the nonce, which is all it takes to find the user
+            // operations whose nonce was consumed
+            let consumed = events
+                .iter()
+                .zip(uo_hashes.iter())
+                .map(|(event, uo_hash)| {
+                    UserOperation::from_user_operation_signed(
+                        *uo_hash,
+                        UserOperationSigned::default().sender(event.sender).nonce(event.nonce),
+                    )
+                })
+                .collect::<Vec<_>>();
+            uopool.remove_stale_user_operations(&consumed);
+
+            // update reputations
+            let mut entities = vec![];
+            for (event, uo_hash) in events.iter().zip(uo_hashes.iter()) {
+                entities.push(event.sender);
+                if !event.paymaster.is_zero() {
+                    entities.push(event.paymaster);
+                }
+                if let Some(factory) = factories.get(&uo_hash.0) {
+                    entities.push(*factory);
+                }
+            }
+            for entity in entities.iter() {
+                uopool.reputation.increment_included(entity).map_err(|e| {
+                    format_err!("Failed to increment entity {entity:?} reputation: {:?}", e)
+                })?;
+            }
+
+            // the transaction was included before in a block that was reorged out, the previous
+            // inclusion is no longer credited
+            if let Some(prev) = included.insert(IncludedBundle {
+                tx_hash,
+                block_number,
+                block_hash: hash,
+                uos,
+                entities,
+            }) {
+                Self::revert_included(uopool, &prev.entities)?;
+            }
+        }
+
+        Self::handle_reorgs(block_number, hash, block.parent_hash, uopool, included).await?;
+
+        let composition = uopool.composition()?;
+        record_composition(&uopool.entry_point.address(), &composition);
+
+        Ok(())
+    }
+
+    /// Reverts the inclusion credited to the entities of a bundle that is no longer included
+    ///
+    /// # Arguments
+    /// * `uopool` - The user operation pool
+    /// * `entities` - The entities credited with the inclusion
+    fn revert_included(
+        uopool: &mut StandardUoPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>,
+        entities: &[Address],
+    ) -> eyre::Result<()> {
+        for entity in entities {
+            uopool.reputation.decrement_included(entity).map_err(|e| {
+                format_err!("Failed to decrement entity {entity:?} reputation: {:?}", e)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Checks whether the tracked bundles are still included in the canonical chain and adds the
+    /// user operations of the bundles that were reorged out back to the mempool (after they are
+    /// validated again), so they are bundled again. The inclusion credited to their entities is
+    /// reverted. The blocks of the bundles are only fetched when the new block doesn't build on
+    /// the previous one, from the most recent one down to the first one that is still canonical.
+    ///
+    /// # Arguments
+    /// * `latest` - The latest block number
+    /// * `hash` - The hash of the latest block
+    /// * `parent_hash` - The hash of the parent of the latest block
+    /// * `uopool` - The user operation pool
+    /// * `included` - The [IncludedBundles](IncludedBundles) of the recent blocks
+    async fn handle_reorgs(
+        latest: U64,
+        hash: H256,
+        parent_hash: H256,
+        uopool: &mut StandardUoPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk>,
+        included: &mut IncludedBundles,
+    ) -> eyre::Result<()> {
+        included.prune(latest, REORG_DEPTH);
+        if included.set_head(hash, parent_hash) {
+            return Ok(());
+        }
+
+        let eth_client = uopool.entry_point.eth_client();
+        let mut block_numbers =
+            included.iter().map(|bundle| bundle.block_number).collect::<Vec<_>>();
+        block_numbers.sort_unstable_by(|a, b| b.cmp(a));
+        block_numbers.dedup();
+
+        let mut reorged = vec![];
+        for block_number in block_numbers {
+            let block_hash = eth_client.get_block(block_number).await?.and_then(|b| b.hash);
+
+            let mut canonical = false;
+            for bundle in included.iter().filter(|bundle| bundle.block_number == block_number) {
+                if Some(bundle.block_hash) == block_hash {
+                    canonical = true;
+                } else {
+                    reorged.push(bundle.tx_hash);
+                }
+            }
+            // the blocks below a canonical block are canonical as well
+            if canonical {
+                break;
+            }
+        }
+
+        for tx_hash in reorged {
+            let Some(bundle) = included.remove(&tx_hash) else { continue };
+
+            // the transaction could have been included again in another block
+            if let Some(receipt) = eth_client.get_transaction_receipt(tx_hash).await? {
+                if let (Some(block_number), Some(block_hash)) =
+                    (receipt.block_number, receipt.block_hash)
+                {
+                    included.insert(IncludedBundle { block_number, block_hash, ..bundle });
+                }
+                continue;
+            }
+
+            Self::revert_included(uopool, &bundle.entities)?;
+            info!(
+                "Bundle transaction {tx_hash:?} was reorged out, adding {} user operations back to the mempool",
+                bundle.uos.len()
+            );
+            for uo in bundle.uos {
+                let res = uopool.validate_user_operation(&uo).await;
+                if let Err(e) = uopool.add_user_operation(uo, res).await {
+                    warn!("Failed to add reorged user operation back to the mempool: {e:?}");
+                }
+            }
         }
 
         Ok(())
@@ -131,11 +464,15 @@ where
 
     pub fn register_block_updates(&self, mut block_stream: BlockStream) {
         let mut uopool = self.uopool();
+        let estimation_cache = self.estimation_cache.clone();
         tokio::spawn(async move {
+            let mut included = IncludedBundles::default();
             while let Some(hash) = block_stream.next().await {
                 if let Ok(hash) = hash {
+                    uopool.validator.on_new_block();
+                    estimation_cache.clear();
                     let h: H256 = hash;
-                    let _ = Self::handle_block_update(h, &mut uopool)
+                    let _ = Self::handle_block_update(h, &mut uopool, &mut included)
                         .await
                         .map_err(|e| warn!("Failed to handle block update: {:?}", e));
                 }
@@ -157,16 +494,26 @@ where
     }
 
     pub fn uopool(&self) -> StandardUoPool<M, T, Y, X, Z, H, R, SanCk, SimCk, SimTrCk> {
-        let entry_point = EntryPoint::<M>::new(self.eth_client.clone(), self.entrypoint_addr);
+        let entry_point = EntryPoint::<M>::new(self.eth_client.clone(), self.entrypoint_addr)
+            .with_version(self.entry_point_version);
 
         UoPool::<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>, T, Y, X, Z, H, R>::new(
             entry_point,
-            self.validator.clone(),
+            self.validator.clone().with_entry_point(self.entrypoint_addr, self.entry_point_version),
             self.mempool.clone(),
             self.reputation.clone(),
             self.max_verification_gas,
             self.chain,
             self.publish_sd.as_ref().cloned(),
+            Some(self.events_sd.clone()),
         )
+        .with_ttl(self.uo_ttl)
+        .with_sort_strategy(self.sort_strategy)
+        .with_estimation_cache(self.estimation_cache.clone())
+        .with_trusted_senders(self.trusted_senders.clone())
+        .with_max_uos(self.max_uos)
+        .with_validation_grace(self.validation_grace.clone())
+        .with_bundle_attempts(self.bundle_attempts.clone())
+        .with_pre_verification_gas_overhead(self.pre_verification_gas_overhead)
     }
 }
@@ -22,6 +22,7 @@ use reth_db::{
     transaction::{DbTx, DbTxMut},
 };
 use silius_primitives::{simulation::CodeHash, UserOperation, UserOperationHash};
+use std::collections::HashMap;
 
 impl<E: EnvironmentKind> AddRemoveUserOp for DatabaseTable<E, UserOperations> {
     fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
@@ -160,6 +161,20 @@ macro_rules! impl_user_op_addr_op {
                     })
                     .unwrap_or_else(|_| vec![])
             }
+
+            fn get_numbers_by_address(&self) -> HashMap<Address, usize> {
+                self.env
+                    .tx()
+                    .and_then(|tx| {
+                        let mut cursor = tx.cursor_read::<$table>()?;
+                        let mut numbers = HashMap::new();
+                        while let Some((address, uo_hash_set)) = cursor.next()? {
+                            numbers.insert(address.into(), uo_hash_set.len());
+                        }
+                        Ok(numbers)
+                    })
+                    .unwrap_or_default()
+            }
         }
     };
 }
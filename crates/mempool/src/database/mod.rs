@@ -1,36 +1,49 @@
 //! The database implementation of the [Mempool](crate::mempool::Mempool) trait. Primarily used for
 //! storing mempool information in a local database.
 
+#[cfg(feature = "mdbx")]
 pub use self::env::DatabaseError;
+#[cfg(feature = "mdbx")]
 use self::env::Env;
+#[cfg(feature = "mdbx")]
 use reth_libmdbx::EnvironmentKind;
+#[cfg(feature = "mdbx")]
 pub use reth_libmdbx::WriteMap;
+#[cfg(feature = "mdbx")]
 use std::{path::PathBuf, sync::Arc};
 
+#[cfg(feature = "mdbx")]
 mod env;
+#[cfg(feature = "mdbx")]
 pub mod mempool;
+#[cfg(feature = "mdbx")]
 pub mod reputation;
+// the tables and their codecs are shared with the RocksDB backend
 pub mod tables;
-mod utils;
+pub(crate) mod utils;
 
+#[cfg(feature = "mdbx")]
 pub fn init_env<E: EnvironmentKind>(path: PathBuf) -> eyre::Result<Env<E>> {
     let env = Env::open(path)?;
     env.create_tables()?;
     Ok(env)
 }
 /// The database-based implementation of the [Mempool](crate::mempool::Mempool) trait.
+#[cfg(feature = "mdbx")]
 #[derive(Debug)]
 pub struct DatabaseTable<E: EnvironmentKind, Table> {
     pub env: Arc<Env<E>>,
     _table: std::marker::PhantomData<Table>,
 }
 
+#[cfg(feature = "mdbx")]
 impl<E: EnvironmentKind, Table> Clone for DatabaseTable<E, Table> {
     fn clone(&self) -> Self {
         Self { env: self.env.clone(), _table: std::marker::PhantomData }
     }
 }
 
+#[cfg(feature = "mdbx")]
 impl<E: EnvironmentKind, Table: Clone> DatabaseTable<E, Table> {
     pub fn new(env: Arc<Env<E>>) -> Self {
         Self { env, _table: std::marker::PhantomData }
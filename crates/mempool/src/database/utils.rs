@@ -154,6 +154,10 @@ impl WrapUserOpSet {
         self.0.is_empty()
     }
 
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
     pub fn to_vec(&self) -> Vec<UserOperationHash> {
         self.0.iter().cloned().map(Into::into).collect()
     }
@@ -1,5 +1,7 @@
 #[cfg(feature = "mdbx")]
 use crate::DatabaseError;
+#[cfg(feature = "rocksdb")]
+use crate::RocksDbError;
 use ethers::types::{Address, U256};
 use serde::{Deserialize, Serialize};
 use silius_contracts::EntryPointError;
@@ -46,6 +48,10 @@ pub enum MempoolErrorKind {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// RocksDB error
+    #[cfg(feature = "rocksdb")]
+    #[error(transparent)]
+    RocksDb(RocksDbError),
     /// Any other error
     #[error("other error: {inner}")]
     Other {
@@ -79,6 +85,13 @@ impl From<reth_db::Error> for MempoolErrorKind {
     }
 }
 
+#[cfg(feature = "rocksdb")]
+impl From<RocksDbError> for MempoolErrorKind {
+    fn from(e: RocksDbError) -> Self {
+        Self::RocksDb(e)
+    }
+}
+
 /// Error when validating user operation failed
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub enum InvalidMempoolUserOperationError {
@@ -104,13 +117,20 @@ pub enum ReputationError {
     ThrottledEntity { entity: String, address: Address },
     /// Stake of the entity is too low
     #[error("{entity} {address:?} stake {stake} is too low {min_stake}")]
-    StakeTooLow { entity: String, address: Address, stake: U256, min_stake: U256 },
+    StakeTooLow {
+        entity: String,
+        address: Address,
+        stake: U256,
+        min_stake: U256,
+        min_unstake_delay: U256,
+    },
     /// Unstake delay of the entity is too low
     #[error("{entity} {address:?} unstake delay {unstake_delay} is too low {min_unstake_delay}")]
     UnstakeDelayTooLow {
         address: Address,
         entity: String,
         unstake_delay: U256,
+        min_stake: U256,
         min_unstake_delay: U256,
     },
     /// Entity is unstaked
@@ -120,6 +140,10 @@ pub enum ReputationError {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// RocksDB error
+    #[cfg(feature = "rocksdb")]
+    #[error(transparent)]
+    RocksDb(RocksDbError),
 }
 
 #[cfg(feature = "mdbx")]
@@ -129,6 +153,13 @@ impl From<reth_db::Error> for ReputationError {
     }
 }
 
+#[cfg(feature = "rocksdb")]
+impl From<RocksDbError> for ReputationError {
+    fn from(e: RocksDbError) -> Self {
+        Self::RocksDb(e)
+    }
+}
+
 /// Error when sanity check fails
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub enum SanityError {
@@ -141,12 +172,15 @@ pub enum SanityError {
     /// Pre verification gas is too low
     #[error("preVerificationGas too low: expected at least {pre_verification_gas_expected}")]
     PreVerificationGasTooLow { pre_verification_gas: U256, pre_verification_gas_expected: U256 },
+    /// Pre verification gas is too high
+    #[error("preVerificationGas {pre_verification_gas} too high: expected at most {pre_verification_gas_expected}")]
+    PreVerificationGasTooHigh { pre_verification_gas: U256, pre_verification_gas_expected: U256 },
     /// Call gas limit is too low
     #[error("callGasLimit too low: expected at least {call_gas_limit_expected}")]
     CallGasLimitTooLow { call_gas_limit: U256, call_gas_limit_expected: U256 },
-    /// Max fee per gas is too low (lower than current base fee per gas)
-    #[error("maxFeePerGas too low: expected at least {base_fee_per_gas}")]
-    MaxFeePerGasTooLow { max_fee_per_gas: U256, base_fee_per_gas: U256 },
+    /// Max fee per gas is too low (lower than the floor derived from the current base fee per gas)
+    #[error("maxFeePerGas too low: expected at least {max_fee_per_gas_expected}")]
+    MaxFeePerGasTooLow { max_fee_per_gas: U256, max_fee_per_gas_expected: U256 },
     /// Max priority fee per gas is too high (higher than max fee per gas)
     #[error("maxPriorityFeePerGas too high: expected at most {max_fee_per_gas}")]
     MaxPriorityFeePerGasTooHigh { max_priority_fee_per_gas: U256, max_fee_per_gas: U256 },
@@ -156,12 +190,42 @@ pub enum SanityError {
         max_priority_fee_per_gas: U256,
         max_priority_fee_per_gas_expected: U256,
     },
+    /// User operation is too large (encoded size is higher than this bundler accepts)
+    #[error("user operation too large: size {size} bytes, expected at most {max_size} bytes")]
+    UserOperationTooLarge { size: usize, max_size: usize },
+    /// Mempool is full and the user operation doesn't pay more than the ones it could evict
+    #[error("mempool is full ({max_uos} user operations): effective gas price {effective_gas_price} has to exceed the lowest one in the mempool")]
+    MempoolFull { max_uos: usize, effective_gas_price: U256 },
+    /// Unstaked sender has too many user operations in the mempool
+    #[error("sender {sender:?} has {count} user operations in the mempool, unstaked senders are limited to {max_uos_per_sender}")]
+    SenderLimit { sender: Address, count: usize, max_uos_per_sender: usize },
     /// Paymaster validation failed
     #[error("{inner}")]
     Paymaster { inner: String },
+    /// Paymaster deposit doesn't cover the prefund of the user operation
+    #[error("paymaster {paymaster:?} deposit {deposit} is lower than the required prefund {required_prefund}")]
+    PaymasterDeposit { paymaster: Address, deposit: U256, required_prefund: U256 },
     /// Sender validation failed
     #[error("{inner}")]
     Sender { inner: String },
+    /// Factory validation failed
+    #[error("{inner}")]
+    Factory { inner: String },
+    /// Signature of the user operation is invalid (`validateUserOp` of the sender failed)
+    #[error("invalid signature: {inner}")]
+    Signature { inner: String },
+    /// Entity is blocklisted by the operator
+    #[error("{entity} {address:?} is blocklisted")]
+    Blocklisted { entity: String, address: Address },
+    /// Paymaster is not in the allowlist of the operator
+    #[error("paymaster {address:?} is not allowed")]
+    PaymasterNotAllowed { address: Address },
+    /// User operation without a paymaster while the operator requires one
+    #[error("user operations without a paymaster are not accepted")]
+    PaymasterRequired,
+    /// Entry point version of the user operation is not supported (yet) by this bundler
+    #[error("user operations of entry point version {version} are not supported")]
+    UnsupportedEntryPointVersion { version: String },
     /// Entity role validation
     #[error("A {entity} at {address:?} in this user operation is used as a {entity_other} entity in another useroperation currently in mempool")]
     EntityRoles { entity: String, address: Address, entity_other: String },
@@ -178,6 +242,10 @@ pub enum SanityError {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// RocksDB error
+    #[cfg(feature = "rocksdb")]
+    #[error(transparent)]
+    RocksDb(RocksDbError),
     /// Any other error
     #[error("other error: {inner}")]
     Other {
@@ -219,6 +287,12 @@ pub enum SimulationError {
     /// Opcode error
     #[error("{entity} uses banned opcode: {opcode}")]
     Opcode { entity: String, opcode: String },
+    /// Contract created during the validation (other than the sender deployed by the factory)
+    #[error("{entity} creates a contract with {opcode} during the validation")]
+    ContractCreation { entity: String, opcode: String },
+    /// SELFDESTRUCT called during the validation
+    #[error("{entity} calls SELFDESTRUCT during the validation")]
+    SelfDestruct { entity: String },
     /// Storage access error
     #[error("Storage access validation failed for slot: {slot}")]
     StorageAccess { slot: String },
@@ -234,6 +308,10 @@ pub enum SimulationError {
     /// User operation out of gas
     #[error("User operation out of gas")]
     OutOfGas,
+    /// The trace of the validation could not be decoded (e.g., the tracer is not supported by the
+    /// Ethereum execution client)
+    #[error("validation tracer error: {inner}")]
+    Tracer { inner: String },
     /// Reputation error
     #[error(transparent)]
     Reputation(ReputationError),
@@ -247,6 +325,10 @@ pub enum SimulationError {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// RocksDB error
+    #[cfg(feature = "rocksdb")]
+    #[error(transparent)]
+    RocksDb(RocksDbError),
     /// Any other error
     #[error("other error: {inner}")]
     Other {
@@ -4,15 +4,16 @@ use ethers::{
     abi::{Hash, RawLog},
     contract::EthLogDecode,
     providers::Middleware,
-    types::{Bytes, U256},
+    types::{Address, Bytes, U256},
 };
 use silius_contracts::{
     decode_revert_string,
     executor_tracer::{ExecutorTracerResult, LogInfo},
+    utils::paymaster_validation_gas_used,
     EntryPoint, EntryPointError, ExecutionResult, FailedOp, UserOperationEventFilter,
     UserOperationRevertReasonFilter,
 };
-use silius_primitives::UserOperationSigned;
+use silius_primitives::{get_address, UserOperationSigned};
 use std::str::FromStr;
 
 const FALL_BACK_BINARY_SEARCH_CUT_OFF: u64 = 30000;
@@ -148,10 +149,12 @@ async fn trace_simulate_handle_op<M: Middleware>(
     Ok(TraceOutput { tracer_result, execution_result, user_op_event, user_op_revert_event })
 }
 
+/// Estimates the verification gas limit, the call gas limit and, if the user operation has a
+/// paymaster, the gas of the paymaster validation
 pub async fn estimate_user_op_gas<M: Middleware>(
     user_op_ori: &UserOperationSigned,
     entry_point: &EntryPoint<M>,
-) -> Result<(U256, U256), EntryPointError> {
+) -> Result<(U256, U256, Option<U256>), EntryPointError> {
     let mut iteration: u64 = 0;
 
     let mut user_op = user_op_ori.clone();
@@ -193,7 +196,8 @@ pub async fn estimate_user_op_gas<M: Middleware>(
         });
     }
     let out: TraceOutput;
-    let mut res: Result<(U256, U256), EntryPointError> = Ok((0u64.into(), 0u64.into()));
+    let mut res: Result<(U256, U256, Option<U256>), EntryPointError> =
+        Ok((0u64.into(), 0u64.into(), None));
     loop {
         if iteration >= MAX_RETRY {
             return res;
@@ -268,5 +272,31 @@ pub async fn estimate_user_op_gas<M: Middleware>(
             }
         }
     }
-    Ok((verification_gas_limit, call_gas_limit.into()))
+    user_op.call_gas_limit = call_gas_limit.into();
+
+    let paymaster_verification_gas_limit = match get_address(&user_op.paymaster_and_data) {
+        Some(paymaster) => {
+            Some(estimate_paymaster_verification_gas(&user_op, &paymaster, entry_point).await?)
+        }
+        None => None,
+    };
+
+    Ok((verification_gas_limit, call_gas_limit.into(), paymaster_verification_gas_limit))
+}
+
+/// Estimates the gas of the paymaster validation from the gas it uses in the simulation with the
+/// estimated gas limits. The paymaster validation gets at most the verification gas limit, so the
+/// estimation doesn't exceed it.
+async fn estimate_paymaster_verification_gas<M: Middleware>(
+    user_op: &UserOperationSigned,
+    paymaster: &Address,
+    entry_point: &EntryPoint<M>,
+) -> Result<U256, EntryPointError> {
+    let frame = entry_point.simulate_handle_op_call_trace(user_op.clone()).await?;
+    let gas_used =
+        paymaster_validation_gas_used(&frame, paymaster).ok_or(EntryPointError::Other {
+            inner: "Paymaster validation not found in the trace of simulate handle op".to_string(),
+        })?;
+
+    Ok((gas_used * (100 + BASE_VGL_BUFFER) / 100).min(user_op.verification_gas_limit))
 }
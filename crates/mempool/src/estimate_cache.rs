@@ -0,0 +1,155 @@
+use crate::metrics::record_estimation_cache;
+use ethers::{
+    abi::{encode, Token},
+    types::{H256, U256},
+    utils::keccak256,
+};
+use parking_lot::Mutex;
+use silius_primitives::UserOperationSigned;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Verification gas limit, call gas limit and paymaster verification gas limit by the key of
+    /// the user operation
+    gas: HashMap<H256, (U256, U256, Option<U256>)>,
+    /// Keys ordered from the least to the most recently used
+    order: VecDeque<H256>,
+}
+
+/// LRU cache of the gas limits estimated by simulating the user operations, so that repeated
+/// estimations of the same user operation don't simulate it again. The estimation depends on the
+/// chain state, so the cache is cleared on every new block. The clones share the same cache.
+#[derive(Clone, Debug)]
+pub struct EstimationCache {
+    entries: Arc<Mutex<Entries>>,
+    /// Max number of cached estimations (the least recently used is evicted once full)
+    capacity: usize,
+}
+
+impl EstimationCache {
+    /// Creates a new [EstimationCache](EstimationCache)
+    ///
+    /// # Arguments
+    /// * `capacity` - Max number of cached estimations
+    ///
+    /// # Returns
+    /// * `EstimationCache` - The [EstimationCache](EstimationCache)
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: Default::default(), capacity }
+    }
+
+    /// Calculates the key of the user operation from the fields that affect the simulation
+    ///
+    /// # Arguments
+    /// * `uo` - The [UserOperation](UserOperationSigned)
+    ///
+    /// # Returns
+    /// * `H256` - The key of the user operation
+    pub fn key(uo: &UserOperationSigned) -> H256 {
+        keccak256(encode(&[
+            Token::Address(uo.sender),
+            Token::Uint(uo.nonce),
+            Token::Bytes(uo.call_data.to_vec()),
+            Token::Bytes(uo.init_code.to_vec()),
+            Token::Bytes(uo.paymaster_and_data.to_vec()),
+        ]))
+        .into()
+    }
+
+    /// Gets the cached verification gas limit, call gas limit and paymaster verification gas limit
+    /// of the user operation
+    ///
+    /// # Arguments
+    /// * `key` - The key of the user operation
+    ///
+    /// # Returns
+    /// * `Option<(U256, U256, Option<U256>)>` - The gas limits (`None` if they are not cached)
+    pub fn get(&self, key: &H256) -> Option<(U256, U256, Option<U256>)> {
+        let mut entries = self.entries.lock();
+        let gas = entries.gas.get(key).copied();
+        if gas.is_some() {
+            entries.order.retain(|k| k != key);
+            entries.order.push_back(*key);
+        }
+        record_estimation_cache(gas.is_some());
+        gas
+    }
+
+    /// Caches the verification gas limit, call gas limit and paymaster verification gas limit of
+    /// the user operation
+    ///
+    /// # Arguments
+    /// * `key` - The key of the user operation
+    /// * `gas` - The verification gas limit, the call gas limit and the paymaster verification gas
+    ///   limit (`None` without paymaster)
+    pub fn insert(&self, key: H256, gas: (U256, U256, Option<U256>)) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.gas.insert(key, gas).is_some() {
+            entries.order.retain(|k| *k != key);
+        }
+        entries.order.push_back(key);
+
+        while entries.order.len() > self.capacity {
+            if let Some(key) = entries.order.pop_front() {
+                entries.gas.remove(&key);
+            }
+        }
+    }
+
+    /// Clears the cache (called on every new block)
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock();
+        entries.gas.clear();
+        entries.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+
+    #[test]
+    fn estimation_cache_evicts_least_recently_used() {
+        let cache = EstimationCache::new(2);
+        let (first, second, third) = (H256::random(), H256::random(), H256::random());
+
+        cache.insert(first, (1.into(), 1.into(), None));
+        cache.insert(second, (2.into(), 2.into(), None));
+        assert_eq!(cache.get(&first), Some((1.into(), 1.into(), None)));
+
+        // the second estimation is the least recently used
+        cache.insert(third, (3.into(), 3.into(), Some(3.into())));
+        assert_eq!(cache.get(&second), None);
+        assert_eq!(cache.get(&first), Some((1.into(), 1.into(), None)));
+        assert_eq!(cache.get(&third), Some((3.into(), 3.into(), Some(3.into()))));
+
+        cache.clone().clear();
+        assert_eq!(cache.get(&first), None);
+    }
+
+    #[test]
+    fn estimation_cache_key() {
+        let uo = UserOperationSigned::random();
+        let key = EstimationCache::key(&uo);
+
+        // the fees and the signature don't affect the simulated gas limits
+        let uo_signed = UserOperationSigned {
+            max_fee_per_gas: 100.into(),
+            signature: Bytes::from(vec![1; 65]),
+            ..uo.clone()
+        };
+        assert_eq!(EstimationCache::key(&uo_signed), key);
+
+        let uo_other = UserOperationSigned { call_data: Bytes::from(vec![1]), ..uo };
+        assert_ne!(EstimationCache::key(&uo_other), key);
+    }
+}
@@ -0,0 +1,31 @@
+//! Events published when user operations enter or leave the mempool
+use ethers::types::Address;
+use silius_primitives::UserOperationHash;
+
+/// The reason a user operation was removed from the mempool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovalReason {
+    /// The user operation was included on chain
+    Bundled,
+    /// The user operation is not valid anymore (e.g., failed the second validation or the
+    /// re-simulation, banned entity, dropped on reload)
+    Invalidated,
+    /// The user operation was removed on request (e.g., via the gRPC API)
+    Requested,
+    /// The user operation was not bundled within the TTL of the mempool
+    Expired,
+    /// The user operation was evicted by a user operation with a higher fee while the mempool
+    /// was full
+    Evicted,
+}
+
+/// An event published by the [UoPool](crate::UoPool) whenever the content of the mempool changes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MempoolEvent {
+    /// A new user operation was added
+    Added { uo_hash: UserOperationHash, sender: Address },
+    /// A user operation replaced the previous one with the same sender and nonce
+    Replaced { uo_hash: UserOperationHash, prev_hash: UserOperationHash, sender: Address },
+    /// A user operation was removed
+    Removed { uo_hash: UserOperationHash, sender: Address, reason: RemovalReason },
+}
@@ -0,0 +1,128 @@
+use ethers::{
+    providers::Middleware,
+    types::{BlockNumber, U256},
+};
+use silius_primitives::{
+    constants::{
+        mempool::{BASE_FEE_HEADROOM_PERC, FEE_HISTORY_BLOCKS, FEE_HISTORY_PERCENTILE},
+        validation::sanity::MIN_BASE_FEE_PERC,
+    },
+    UserOperationGasPrice,
+};
+
+/// Suggests the fees of the user operations from the current base fee, the priority fees paid in
+/// the recent blocks and the floors of the [MaxFee](crate::validate::sanity::max_fee::MaxFee)
+/// sanity check, so that the user operations priced with the suggested fees are accepted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeOracle {
+    /// Min priority fee per gas that the bundler would accept
+    pub min_priority_fee_per_gas: U256,
+    /// Min max fee per gas as a percentage of the current base fee per gas
+    pub min_base_fee_perc: u64,
+}
+
+impl FeeOracle {
+    /// Creates a new [FeeOracle](FeeOracle)
+    ///
+    /// # Arguments
+    /// * `min_priority_fee_per_gas` - Min priority fee per gas that the bundler would accept
+    /// * `min_base_fee_perc` - Min max fee per gas as a percentage of the current base fee per gas
+    ///
+    /// # Returns
+    /// * `FeeOracle` - The [FeeOracle](FeeOracle)
+    pub fn new(min_priority_fee_per_gas: U256, min_base_fee_perc: u64) -> Self {
+        Self { min_priority_fee_per_gas, min_base_fee_perc }
+    }
+
+    /// Suggests the fees from the fee history of the recent blocks (`eth_feeHistory`)
+    ///
+    /// # Arguments
+    /// * `eth_client` - Connection to the Ethereum execution client
+    ///
+    /// # Returns
+    /// * `UserOperationGasPrice` - The suggested [fees](UserOperationGasPrice)
+    pub async fn suggest<M: Middleware>(
+        &self,
+        eth_client: &M,
+    ) -> eyre::Result<UserOperationGasPrice> {
+        let history = eth_client
+            .fee_history(FEE_HISTORY_BLOCKS, BlockNumber::Latest, &[FEE_HISTORY_PERCENTILE])
+            .await?;
+
+        // the last base fee is the base fee of the next block
+        let base_fee = history
+            .base_fee_per_gas
+            .last()
+            .copied()
+            .ok_or(eyre::format_err!("No base fee in the fee history"))?;
+        let rewards = history.reward.iter().filter_map(|reward| reward.first().copied()).collect();
+
+        Ok(self.suggest_from(base_fee, rewards))
+    }
+
+    /// Suggests the fees from the base fee and the priority fees paid in the recent blocks, i.e.,
+    /// the median of the priority fees (at least the min priority fee) and the base fee with
+    /// headroom (at least the base fee floor) plus the priority fee
+    ///
+    /// # Arguments
+    /// * `base_fee` - The base fee per gas of the next block
+    /// * `rewards` - The priority fees paid in the recent blocks
+    ///
+    /// # Returns
+    /// * `UserOperationGasPrice` - The suggested [fees](UserOperationGasPrice)
+    pub fn suggest_from(&self, base_fee: U256, mut rewards: Vec<U256>) -> UserOperationGasPrice {
+        rewards.sort();
+        let reward = rewards.get(rewards.len() / 2).copied().unwrap_or_default();
+
+        let max_priority_fee_per_gas = reward.max(self.min_priority_fee_per_gas);
+        let base_fee_perc = self.min_base_fee_perc.max(BASE_FEE_HEADROOM_PERC);
+        let max_fee_per_gas = (base_fee.saturating_mul(base_fee_perc.into()) / 100)
+            .saturating_add(max_priority_fee_per_gas);
+
+        UserOperationGasPrice { max_fee_per_gas, max_priority_fee_per_gas }
+    }
+}
+
+impl Default for FeeOracle {
+    fn default() -> Self {
+        Self::new(U256::zero(), MIN_BASE_FEE_PERC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggest_fees() {
+        let rewards = vec![3.into(), 1.into(), 10.into(), 2.into(), 5.into()];
+
+        let oracle = FeeOracle::default();
+        assert_eq!(
+            oracle.suggest_from(100.into(), rewards.clone()),
+            UserOperationGasPrice {
+                max_fee_per_gas: 128.into(),
+                max_priority_fee_per_gas: 3.into()
+            }
+        );
+
+        // the floors of the bundler take precedence
+        let oracle = FeeOracle::new(4.into(), 150);
+        assert_eq!(
+            oracle.suggest_from(100.into(), rewards),
+            UserOperationGasPrice {
+                max_fee_per_gas: 154.into(),
+                max_priority_fee_per_gas: 4.into()
+            }
+        );
+
+        // empty blocks
+        assert_eq!(
+            FeeOracle::default().suggest_from(100.into(), vec![]),
+            UserOperationGasPrice {
+                max_fee_per_gas: 125.into(),
+                max_priority_fee_per_gas: 0.into()
+            }
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{InvalidMempoolUserOperationError, SanityError, SimulationError};
+use parking_lot::Mutex;
+use silius_primitives::UserOperationHash;
+use std::{collections::HashMap, sync::Arc};
+
+/// `FailedOp` reasons of the entry point caused by a condition that may resolve on its own (the
+/// sender or the paymaster can top up the deposit)
+const TRANSIENT_REASONS: [&str; 2] = ["AA21", "AA31"];
+
+/// Grace policy of the user operations that fail the re-validation (on request or right before
+/// being bundled) because of a possibly transient condition, e.g., the paymaster is momentarily
+/// out of deposit. Such a user operation is kept in the mempool (but not bundled) and retried in
+/// the next round, it's only evicted after more consecutive transient failures than the grace
+/// allows. The permanent failures (e.g., the nonce is consumed) evict the user operation
+/// immediately. The clones share the same failure counters.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationGrace {
+    /// Consecutive transient failures by user operation
+    failures: Arc<Mutex<HashMap<UserOperationHash, u64>>>,
+    /// Number of consecutive transient failures tolerated before the eviction
+    max_failures: u64,
+}
+
+impl ValidationGrace {
+    /// Creates a new [ValidationGrace](ValidationGrace)
+    ///
+    /// # Arguments
+    /// * `max_failures` - Number of consecutive transient failures tolerated before the user
+    ///   operation is evicted (0 evicts on the first failure)
+    ///
+    /// # Returns
+    /// * `ValidationGrace` - The [ValidationGrace](ValidationGrace)
+    pub fn new(max_failures: u64) -> Self {
+        Self { failures: Default::default(), max_failures }
+    }
+
+    /// Records a failed validation of the user operation
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the user operation
+    /// * `transient` - Whether the failure is possibly transient
+    ///
+    /// # Returns
+    /// * `bool` - Whether the user operation should be evicted
+    pub fn on_failure(&self, uo_hash: &UserOperationHash, transient: bool) -> bool {
+        let mut failures = self.failures.lock();
+        if !transient {
+            failures.remove(uo_hash);
+            return true;
+        }
+
+        let count = failures.entry(*uo_hash).or_default();
+        *count += 1;
+        if *count > self.max_failures {
+            failures.remove(uo_hash);
+            return true;
+        }
+        false
+    }
+
+    /// Resets the failures of the user operation (it passed the validation or left the mempool)
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the user operation
+    pub fn reset(&self, uo_hash: &UserOperationHash) {
+        self.failures.lock().remove(uo_hash);
+    }
+
+    /// Returns the number of consecutive transient failures of the user operation
+    pub fn failures(&self, uo_hash: &UserOperationHash) -> u64 {
+        self.failures.lock().get(uo_hash).copied().unwrap_or_default()
+    }
+}
+
+/// Checks whether the `FailedOp` reason of the entry point is possibly transient
+///
+/// # Arguments
+/// * `reason` - The revert reason
+///
+/// # Returns
+/// * `bool` - Whether the failure is possibly transient
+pub fn is_transient_reason(reason: &str) -> bool {
+    TRANSIENT_REASONS.iter().any(|code| reason.starts_with(code))
+}
+
+/// Checks whether the validation of the user operation failed because of a possibly transient
+/// condition (a deposit too low to cover the prefund or an error of the provider)
+///
+/// # Arguments
+/// * `err` - The validation error
+///
+/// # Returns
+/// * `bool` - Whether the failure is possibly transient
+pub fn is_transient_failure(err: &InvalidMempoolUserOperationError) -> bool {
+    match err {
+        InvalidMempoolUserOperationError::Sanity(
+            SanityError::PaymasterDeposit { .. } | SanityError::Provider { .. },
+        ) => true,
+        InvalidMempoolUserOperationError::Simulation(SimulationError::Provider { .. }) => true,
+        InvalidMempoolUserOperationError::Simulation(SimulationError::Validation { inner }) => {
+            is_transient_reason(inner)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn validation_grace_evicts_after_consecutive_failures() {
+        let grace = ValidationGrace::new(2);
+        let uo_hash = UserOperationHash(H256::random());
+
+        assert!(!grace.on_failure(&uo_hash, true));
+        assert!(!grace.clone().on_failure(&uo_hash, true));
+        assert_eq!(grace.failures(&uo_hash), 2);
+        assert!(grace.on_failure(&uo_hash, true));
+        assert_eq!(grace.failures(&uo_hash), 0);
+
+        // the counter starts over once the validation passes
+        assert!(!grace.on_failure(&uo_hash, true));
+        grace.reset(&uo_hash);
+        assert!(!grace.on_failure(&uo_hash, true));
+        assert!(!grace.on_failure(&uo_hash, true));
+
+        // permanent failures evict immediately
+        assert!(grace.on_failure(&uo_hash, false));
+        assert!(ValidationGrace::default().on_failure(&uo_hash, true));
+
+        assert!(is_transient_reason("AA31 paymaster deposit too low"));
+        assert!(!is_transient_reason("AA25 invalid account nonce"));
+    }
+}
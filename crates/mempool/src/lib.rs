@@ -1,19 +1,34 @@
 //! The UserOperation alternative mempool implementation according to the [ERC-4337 specifications](https://eips.ethereum.org/EIPS/eip-4337#Alternative%20Mempools).
 #![allow(dead_code)]
 
+mod allowlist;
+mod attempts;
 mod builder;
-#[cfg(feature = "mdbx")]
+#[cfg(any(feature = "mdbx", feature = "rocksdb"))]
 mod database;
 pub mod error;
 mod estimate;
+mod estimate_cache;
+mod events;
+mod fees;
+mod grace;
 mod memory;
 mod mempool;
 pub mod metrics;
+mod reorg;
 mod reputation;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb;
+mod sort;
+mod trusted_senders;
 mod uopool;
 mod utils;
 pub mod validate;
 
+#[cfg(feature = "rocksdb")]
+pub use self::rocksdb::{init_rocksdb, RocksDbError, RocksDbTable};
+pub use allowlist::Allowlist;
+pub use attempts::BundleAttempts;
 pub use builder::UoPoolBuilder;
 #[cfg(feature = "mdbx")]
 pub use database::{
@@ -28,12 +43,17 @@ pub use error::{
     InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, ReputationError, SanityError,
     SimulationError,
 };
+pub use estimate_cache::EstimationCache;
+pub use events::{MempoolEvent, RemovalReason};
+pub use fees::FeeOracle;
+pub use grace::ValidationGrace;
 pub use mempool::{
     mempool_id, AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, Mempool, MempoolId,
     UserOperationAct, UserOperationAddrAct, UserOperationAddrOp, UserOperationCodeHashAct,
     UserOperationCodeHashOp, UserOperationOp,
 };
 pub use reputation::{HashSetOp, Reputation, ReputationEntryOp};
-pub use uopool::UoPool;
+pub use trusted_senders::TrustedSenders;
+pub use uopool::{find_user_operation_logs, UoPool};
 pub use utils::Overhead;
 pub use validate::{SanityCheck, SimulationCheck, SimulationTraceCheck};
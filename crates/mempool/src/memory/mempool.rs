@@ -71,6 +71,10 @@ impl UserOperationAddrOp for HashMap<Address, HashSet<UserOperationHash>> {
             vec![]
         };
     }
+
+    fn get_numbers_by_address(&self) -> HashMap<Address, usize> {
+        self.iter().map(|(addr, uo_hashes)| (*addr, uo_hashes.len())).collect()
+    }
 }
 
 impl AddRemoveUserOpHash for HashMap<Address, HashSet<UserOperationHash>> {
@@ -1,15 +1,31 @@
-use crate::MempoolErrorKind;
+use crate::{sort::sort_user_operations, MempoolErrorKind};
 use ethers::{
     abi::AbiEncode,
     types::{Address, H256, U256},
     utils::{keccak256, to_checksum},
 };
 use parking_lot::RwLock;
-use silius_primitives::{simulation::CodeHash, UserOperation, UserOperationHash};
-use std::sync::Arc;
+use silius_primitives::{
+    mempool::{MempoolComposition, SortStrategy},
+    simulation::CodeHash,
+    UserOperation, UserOperationHash,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 pub type MempoolId = H256;
 
+/// Current unix time in seconds
+pub(crate) fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or_default()
+}
+
 pub fn mempool_id(ep: &Address, chain_id: u64) -> MempoolId {
     H256::from_slice(
         keccak256([to_checksum(ep, None).encode(), U256::from(chain_id).encode()].concat())
@@ -189,12 +205,23 @@ pub trait UserOperationAddrOp {
     fn get_number_by_address(&self, addr: &Address) -> usize {
         self.get_all_by_address(addr).len()
     }
+
+    /// Retrieves the number of user operation hashes of every address.
+    ///
+    /// # Returns
+    ///
+    /// A map of the addresses to the number of their user operation hashes.
+    fn get_numbers_by_address(&self) -> HashMap<Address, usize>;
 }
 
 impl<T: UserOperationAddrOp> UserOperationAddrOp for Arc<RwLock<T>> {
     fn get_all_by_address(&self, addr: &Address) -> Vec<UserOperationHash> {
         self.read().get_all_by_address(addr)
     }
+
+    fn get_numbers_by_address(&self) -> HashMap<Address, usize> {
+        self.read().get_numbers_by_address()
+    }
 }
 
 /// Trait for managing user operation code hashes in a memory pool.
@@ -339,6 +366,11 @@ where
     user_operations_by_sender: Y,
     user_operations_by_entity: X,
     user_operations_code_hashes: Z,
+    /// Unix time (in seconds) at which the user operations were added to the mempool
+    added_at: Arc<RwLock<HashMap<UserOperationHash, u64>>>,
+    /// Order in which the user operations were added to the mempool
+    arrivals: Arc<RwLock<HashMap<UserOperationHash, u64>>>,
+    next_arrival: Arc<AtomicU64>,
 }
 
 impl<T, Y, X, Z> Clone for Mempool<T, Y, X, Z>
@@ -354,6 +386,9 @@ where
             user_operations_by_sender: self.user_operations_by_sender.clone(),
             user_operations_by_entity: self.user_operations_by_entity.clone(),
             user_operations_code_hashes: self.user_operations_code_hashes.clone(),
+            added_at: self.added_at.clone(),
+            arrivals: self.arrivals.clone(),
+            next_arrival: self.next_arrival.clone(),
         }
     }
 }
@@ -376,11 +411,18 @@ where
             user_operations_by_sender,
             user_operations_by_entity,
             user_operations_code_hashes,
+            added_at: Default::default(),
+            arrivals: Default::default(),
+            next_arrival: Default::default(),
         }
     }
     pub fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
         let (sender, factory, paymaster) = uo.get_entities();
         let uo_hash = uo.hash;
+        // an identical resubmission is acknowledged with the hash of the existing user operation
+        if self.contains(&uo_hash)? {
+            return Ok(uo_hash);
+        }
         self.user_operations.add(uo)?;
         self.user_operations_by_sender.add(&sender, uo_hash)?;
         if let Some(factory) = factory {
@@ -389,6 +431,8 @@ where
         if let Some(paymaster) = paymaster {
             self.user_operations_by_entity.add(&paymaster, uo_hash)?;
         }
+        self.arrivals.write().insert(uo_hash, self.next_arrival.fetch_add(1, Ordering::Relaxed));
+        self.added_at.write().insert(uo_hash, unix_time());
         Ok(uo_hash)
     }
     pub fn get(
@@ -397,6 +441,14 @@ where
     ) -> Result<Option<UserOperation>, MempoolErrorKind> {
         self.user_operations.get_by_uo_hash(uo_hash)
     }
+    pub fn contains(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        Ok(self.user_operations.get_by_uo_hash(uo_hash)?.is_some())
+    }
+    /// Whether the same user operation, including the signature (which isn't part of the hash), is
+    /// already in the mempool
+    pub fn contains_identical(&self, uo: &UserOperation) -> Result<bool, MempoolErrorKind> {
+        Ok(self.get(&uo.hash)?.is_some_and(|uo_prev| uo_prev.user_operation == uo.user_operation))
+    }
     pub fn get_all_by_sender(&self, addr: &Address) -> Vec<UserOperation> {
         let uos_by_sender = self.user_operations_by_sender.get_all_by_address(addr);
         uos_by_sender
@@ -459,16 +511,44 @@ where
 
         self.user_operations_code_hashes.remove_code_hashes(uo_hash)?;
 
+        self.added_at.write().remove(uo_hash);
+        self.arrivals.write().remove(uo_hash);
+
         Ok(true)
     }
-    pub fn remove_by_entity(&mut self, entity: &Address) -> Result<(), MempoolErrorKind> {
-        let uos = self.user_operations_by_entity.get_all_by_address(entity);
+    pub fn remove_by_entity(
+        &mut self,
+        entity: &Address,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let uo_hashes = self.user_operations_by_entity.get_all_by_address(entity);
+
+        let mut uos = vec![];
+        for uo_hash in uo_hashes {
+            if let Some(uo) = self.user_operations.get_by_uo_hash(&uo_hash)? {
+                self.remove(&uo_hash)?;
+                uos.push(uo);
+            }
+        }
 
-        for uo_hash in uos {
-            self.remove(&uo_hash)?;
+        Ok(uos)
+    }
+    // Remove the UserOperations of the sender with the same nonce as the given (included)
+    // UserOperation, they can't be included anymore since the nonce is consumed
+    pub fn remove_by_nonce(
+        &mut self,
+        uo: &UserOperation,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let uos = self
+            .get_all_by_sender(&uo.sender)
+            .into_iter()
+            .filter(|uo_other| uo_other.hash != uo.hash && uo_other.nonce == uo.nonce)
+            .collect::<Vec<_>>();
+
+        for uo_stale in uos.iter() {
+            self.remove(&uo_stale.hash)?;
         }
 
-        Ok(())
+        Ok(uos)
     }
     // Get UserOperations sorted by max_priority_fee_per_gas without dup sender
     pub fn get_sorted(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
@@ -477,10 +557,57 @@ where
     pub fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
         self.user_operations.get_all()
     }
+    // Get UserOperations sorted by the strategy, the base fee is only used by the strategies
+    // based on the effective gas price
+    pub fn get_sorted_by(
+        &self,
+        strategy: SortStrategy,
+        base_fee: U256,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos = self.user_operations.get_all()?;
+        sort_user_operations(&mut uos, strategy, base_fee, &self.arrivals.read());
+        Ok(uos)
+    }
+    // Get UserOperations that are in the mempool for at least `ttl` seconds at unix time `now`.
+    // The UserOperations persisted by a previous run weren't added by this mempool, the time they
+    // are first seen by this check is taken as the time they were added
+    pub fn get_expired(&self, now: u64, ttl: u64) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let uos = self.user_operations.get_all()?;
+        let mut added_at = self.added_at.write();
+        Ok(uos
+            .into_iter()
+            .filter(|uo| now.saturating_sub(*added_at.entry(uo.hash).or_insert(now)) >= ttl)
+            .collect())
+    }
+    // Count the UserOperations by sender, by paymaster and by factory from the address indexes,
+    // only the UserOperations of the entities are read to tell the paymasters from the factories
+    pub fn composition(&self) -> Result<MempoolComposition, MempoolErrorKind> {
+        let by_sender = self.user_operations_by_sender.get_numbers_by_address();
+        let mut composition =
+            MempoolComposition { total: by_sender.values().sum(), by_sender, ..Default::default() };
+
+        for entity in self.user_operations_by_entity.get_numbers_by_address().into_keys() {
+            for uo_hash in self.user_operations_by_entity.get_all_by_address(&entity) {
+                if let Some(uo) = self.user_operations.get_by_uo_hash(&uo_hash)? {
+                    let (_, factory, paymaster) = uo.get_entities();
+                    if factory == Some(entity) {
+                        *composition.by_factory.entry(entity).or_default() += 1;
+                    }
+                    if paymaster == Some(entity) {
+                        *composition.by_paymaster.entry(entity).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(composition)
+    }
     pub fn clear(&mut self) {
         self.user_operations.clear();
         self.user_operations_by_sender.clear();
         self.user_operations_by_entity.clear();
         self.user_operations_code_hashes.clear();
+        self.added_at.write().clear();
+        self.arrivals.write().clear();
     }
 }
@@ -1 +1,59 @@
+//! Metrics recorded by the mempool (described by the metrics exporter of `silius-metrics`)
+use ethers::types::Address;
+use metrics::{counter, gauge, histogram};
+use silius_primitives::mempool::MempoolComposition;
 
+pub const MEMPOOL_REPLACEMENT_ACCEPTED: &str = "silius_mempool_replacement_accepted";
+pub const MEMPOOL_REPLACEMENT_REJECTED: &str = "silius_mempool_replacement_rejected";
+pub const MEMPOOL_REPLACEMENT_BUMP_PERC: &str = "silius_mempool_replacement_bump_perc";
+pub const MEMPOOL_ESTIMATION_CACHE_HIT: &str = "silius_mempool_estimation_cache_hit";
+pub const MEMPOOL_ESTIMATION_CACHE_MISS: &str = "silius_mempool_estimation_cache_miss";
+pub const MEMPOOL_USER_OPERATIONS: &str = "silius_mempool_user_operations";
+pub const MEMPOOL_MAX_ENTITY_USER_OPERATIONS: &str = "silius_mempool_max_entity_user_operations";
+
+/// Records a user operation replacing the previous one with the same sender and nonce
+///
+/// # Arguments
+/// * `sender` - The sender of the user operation
+/// * `accepted` - Whether the replacement was accepted (otherwise its fees were bumped too little)
+/// * `bump_perc` - By how much the fees were bumped (in percent)
+pub fn record_replacement(sender: &Address, accepted: bool, bump_perc: f64) {
+    let (name, outcome) = if accepted {
+        (MEMPOOL_REPLACEMENT_ACCEPTED, "accepted")
+    } else {
+        (MEMPOOL_REPLACEMENT_REJECTED, "rejected")
+    };
+    counter!(name, "sender" => format!("{sender:?}")).increment(1);
+    histogram!(MEMPOOL_REPLACEMENT_BUMP_PERC, "outcome" => outcome).record(bump_perc);
+}
+
+/// Records a lookup of the gas estimation cache (the hit rate is the ratio of the hits to all
+/// lookups)
+///
+/// # Arguments
+/// * `hit` - Whether the estimation was cached
+pub fn record_estimation_cache(hit: bool) {
+    let name = if hit { MEMPOOL_ESTIMATION_CACHE_HIT } else { MEMPOOL_ESTIMATION_CACHE_MISS };
+    counter!(name).increment(1);
+}
+
+/// Records the composition of the mempool of the entry point, i.e., the number of user operations
+/// and the max number of user operations of one sender, paymaster and factory (the counts of each
+/// entity are returned by the `GetMempoolComposition` gRPC method)
+///
+/// # Arguments
+/// * `ep` - The address of the entry point
+/// * `composition` - The [MempoolComposition](MempoolComposition) of the mempool
+pub fn record_composition(ep: &Address, composition: &MempoolComposition) {
+    let ep = format!("{ep:?}");
+    gauge!(MEMPOOL_USER_OPERATIONS, "entry_point" => ep.clone()).set(composition.total as f64);
+    for (entity, counts) in [
+        ("sender", &composition.by_sender),
+        ("paymaster", &composition.by_paymaster),
+        ("factory", &composition.by_factory),
+    ] {
+        let max = counts.values().max().copied().unwrap_or_default();
+        gauge!(MEMPOOL_MAX_ENTITY_USER_OPERATIONS, "entry_point" => ep.clone(), "entity" => entity)
+            .set(max as f64);
+    }
+}
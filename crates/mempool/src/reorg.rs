@@ -0,0 +1,125 @@
+use ethers::types::{Address, H256, U64};
+use silius_primitives::UserOperation;
+use std::collections::VecDeque;
+
+/// A bundle transaction that was seen included in a block
+#[derive(Clone, Debug)]
+pub struct IncludedBundle {
+    /// The hash of the bundle transaction
+    pub tx_hash: H256,
+    /// Number of the block that included the transaction
+    pub block_number: U64,
+    /// Hash of the block that included the transaction
+    pub block_hash: H256,
+    /// The user operations of the bundle
+    pub uos: Vec<UserOperation>,
+    /// The entities credited with the inclusion of the user operations (once per user operation)
+    pub entities: Vec<Address>,
+}
+
+/// Tracks the bundle transactions included in the recent blocks, so that the user operations of a
+/// bundle that is reorged out of the canonical chain can be added back to the mempool
+#[derive(Debug, Default)]
+pub struct IncludedBundles {
+    bundles: VecDeque<IncludedBundle>,
+    /// Hash of the last handled block
+    head: Option<H256>,
+}
+
+impl IncludedBundles {
+    /// Tracks the included bundle (replaces the previous inclusion of the same transaction)
+    ///
+    /// # Arguments
+    /// * `bundle` - The [IncludedBundle](IncludedBundle)
+    ///
+    /// # Returns
+    /// * `Option<IncludedBundle>` - The replaced inclusion of the same transaction
+    pub fn insert(&mut self, bundle: IncludedBundle) -> Option<IncludedBundle> {
+        let prev = self.remove(&bundle.tx_hash);
+        self.bundles.push_back(bundle);
+        prev
+    }
+
+    /// Sets the last handled block
+    ///
+    /// # Arguments
+    /// * `hash` - The hash of the block
+    /// * `parent_hash` - The hash of the parent of the block
+    ///
+    /// # Returns
+    /// * `bool` - True if the block is a child of the previous one, so no block was reorged out
+    pub fn set_head(&mut self, hash: H256, parent_hash: H256) -> bool {
+        self.head.replace(hash) == Some(parent_hash)
+    }
+
+    /// Stops tracking the bundle transaction
+    ///
+    /// # Arguments
+    /// * `tx_hash` - The hash of the bundle transaction
+    ///
+    /// # Returns
+    /// * `Option<IncludedBundle>` - The tracked [IncludedBundle](IncludedBundle)
+    pub fn remove(&mut self, tx_hash: &H256) -> Option<IncludedBundle> {
+        let idx = self.bundles.iter().position(|bundle| bundle.tx_hash == *tx_hash)?;
+        self.bundles.remove(idx)
+    }
+
+    /// Stops tracking the bundles that are deep enough to be considered final
+    ///
+    /// # Arguments
+    /// * `latest` - The latest block number
+    /// * `depth` - Number of blocks after which a bundle is considered final
+    pub fn prune(&mut self, latest: U64, depth: u64) {
+        self.bundles.retain(|bundle| latest.saturating_sub(bundle.block_number) < depth.into());
+    }
+
+    /// Returns the tracked bundles
+    pub fn iter(&self) -> impl Iterator<Item = &IncludedBundle> {
+        self.bundles.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(tx_hash: H256, block: u64) -> IncludedBundle {
+        IncludedBundle {
+            tx_hash,
+            block_number: block.into(),
+            block_hash: H256::random(),
+            uos: vec![],
+            entities: vec![],
+        }
+    }
+
+    #[test]
+    fn included_bundles() {
+        let mut included = IncludedBundles::default();
+        let (first, second) = (H256::random(), H256::random());
+        included.insert(bundle(first, 10));
+        included.insert(bundle(second, 12));
+
+        // the re-inclusion replaces the previous one
+        assert!(included.insert(bundle(first, 13)).is_some());
+        assert_eq!(included.iter().count(), 2);
+        assert_eq!(included.iter().last().map(|b| b.block_number), Some(13.into()));
+
+        included.prune(16.into(), 4);
+        assert_eq!(included.iter().map(|b| b.tx_hash).collect::<Vec<_>>(), vec![first]);
+
+        assert!(included.remove(&first).is_some());
+        assert!(included.remove(&first).is_none());
+    }
+
+    #[test]
+    fn included_bundles_head() {
+        let mut included = IncludedBundles::default();
+        let (first, second, third) = (H256::random(), H256::random(), H256::random());
+
+        assert!(!included.set_head(first, H256::random()));
+        assert!(included.set_head(second, first));
+        // the new block doesn't build on the previous one
+        assert!(!included.set_head(third, first));
+    }
+}
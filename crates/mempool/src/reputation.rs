@@ -289,6 +289,24 @@ where
         Ok(())
     }
 
+    /// Decreases the number of times an entity successfully included a
+    /// [UserOperation](UserOperation) in a block (e.g., the block was reorged out)
+    ///
+    /// # Arguments
+    /// * `addr` - The address to decrement
+    ///
+    /// # Returns
+    /// * `Ok(())` if the address was decremented successfully
+    /// * `Err(ReputationError::NotFound)` if the address does not exist
+    pub fn decrement_included(&mut self, addr: &Address) -> Result<(), ReputationError> {
+        self.set_default(addr)?;
+        if let Some(mut ent) = self.entities.get_entry(addr)? {
+            ent.uo_included = ent.uo_included.saturating_sub(1);
+            self.entities.set_entry(ent)?;
+        }
+        Ok(())
+    }
+
     /// Update an entity's status by hours
     ///
     /// # Returns
@@ -439,6 +457,7 @@ where
                     address: info.address,
                     stake: info.stake,
                     min_stake: self.min_stake,
+                    min_unstake_delay: self.min_unstake_delay,
                 }
             } else if info.unstake_delay < U256::from(2)
             // TODO: remove this when spec tests are updated!!!!
@@ -448,6 +467,7 @@ where
                     address: info.address,
                     entity: entity.into(),
                     unstake_delay: info.unstake_delay,
+                    min_stake: self.min_stake,
                     min_unstake_delay: self.min_unstake_delay,
                 }
             } else {
@@ -477,9 +497,9 @@ where
     /// Get all [reputation entries](ReputationEntries)
     ///
     /// # Returns
-    /// * All [reputation entries](ReputationEntries)
+    /// * All [reputation entries](ReputationEntries) sorted by address
     pub fn get_all(&self) -> Result<Vec<ReputationEntry>, ReputationError> {
-        Ok(self
+        let mut entries: Vec<ReputationEntry> = self
             .entities
             .get_all()
             .into_iter()
@@ -487,7 +507,9 @@ where
                 let status = self.get_status(&entry.address)?;
                 Ok::<ReputationEntry, ReputationError>(ReputationEntry { status, ..entry })
             })
-            .collect())
+            .collect();
+        entries.sort_by_key(|entry| entry.address);
+        Ok(entries)
     }
 
     // Try to get the reputation status from a sequence of bytes which the first 20 bytes should be
@@ -0,0 +1,216 @@
+use super::{
+    tables::{CodeHashes, UserOperations, UserOperationsByEntity, UserOperationsBySender},
+    RocksDbTable,
+};
+use crate::{
+    database::utils::{WrapCodeHash, WrapCodeHashVec, WrapUserOperationHash},
+    mempool::{
+        AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, UserOperationAddrOp,
+        UserOperationCodeHashOp, UserOperationOp,
+    },
+    MempoolErrorKind,
+};
+use ethers::types::Address;
+use silius_primitives::{simulation::CodeHash, UserOperation, UserOperationHash};
+use std::collections::HashMap;
+use tracing::error;
+
+impl AddRemoveUserOp for RocksDbTable<UserOperations> {
+    fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+        self.put(uo.hash.into(), uo.user_operation.into())?;
+        Ok(uo.hash)
+    }
+
+    fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo_hash_wrap: WrapUserOperationHash = (*uo_hash).into();
+        let original_value = self.get(uo_hash_wrap.clone())?;
+        self.delete(uo_hash_wrap)?;
+        Ok(original_value.is_some())
+    }
+}
+
+macro_rules! impl_add_remove_user_op_hash {
+    ($table: ident) => {
+        impl AddRemoveUserOpHash for RocksDbTable<$table> {
+            fn add(
+                &mut self,
+                address: &Address,
+                uo_hash: UserOperationHash,
+            ) -> Result<(), MempoolErrorKind> {
+                let mut uo_hash_set = self.get((*address).into())?.unwrap_or_default();
+                uo_hash_set.insert(uo_hash.into());
+                self.put((*address).into(), uo_hash_set)?;
+                Ok(())
+            }
+
+            fn remove_uo_hash(
+                &mut self,
+                address: &Address,
+                uo_hash: &UserOperationHash,
+            ) -> Result<bool, MempoolErrorKind> {
+                if let Some(mut uo_hash_set) = self.get((*address).into())? {
+                    uo_hash_set.remove(&(*uo_hash).into());
+                    if uo_hash_set.is_empty() {
+                        self.delete((*address).into())?;
+                    } else {
+                        self.put((*address).into(), uo_hash_set)?;
+                    }
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+        }
+    };
+}
+
+impl_add_remove_user_op_hash!(UserOperationsBySender);
+impl_add_remove_user_op_hash!(UserOperationsByEntity);
+
+impl UserOperationOp for RocksDbTable<UserOperations> {
+    fn get_by_uo_hash(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+        let res = self.get((*uo_hash).into())?;
+        Ok(res.map(|uo| UserOperation::from_user_operation_signed(*uo_hash, uo.into())))
+    }
+
+    fn get_sorted(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos = self.get_all()?;
+        uos.sort_by(|a, b| {
+            if a.max_priority_fee_per_gas != b.max_priority_fee_per_gas {
+                b.max_priority_fee_per_gas.cmp(&a.max_priority_fee_per_gas)
+            } else {
+                a.nonce.cmp(&b.nonce)
+            }
+        });
+        Ok(uos)
+    }
+
+    fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .map(|(hash, uo)| UserOperation::from_user_operation_signed(hash.into(), uo.into()))
+            .collect())
+    }
+}
+
+macro_rules! impl_user_op_addr_op {
+    ($table:ident) => {
+        impl UserOperationAddrOp for RocksDbTable<$table> {
+            fn get_all_by_address(&self, address: &Address) -> Vec<UserOperationHash> {
+                self.get((*address).into())
+                    .ok()
+                    .flatten()
+                    .map(|uo_hash_set| uo_hash_set.to_vec())
+                    .unwrap_or_default()
+            }
+
+            fn get_numbers_by_address(&self) -> HashMap<Address, usize> {
+                self.entries()
+                    .map(|entries| {
+                        entries
+                            .into_iter()
+                            .map(|(address, uo_hash_set)| (address.into(), uo_hash_set.len()))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+        }
+    };
+}
+
+impl_user_op_addr_op!(UserOperationsBySender);
+impl_user_op_addr_op!(UserOperationsByEntity);
+
+impl UserOperationCodeHashOp for RocksDbTable<CodeHashes> {
+    fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        Ok(self.get((*uo_hash).into())?.is_some())
+    }
+
+    fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind> {
+        let wrap_hashes: WrapCodeHashVec =
+            hashes.into_iter().map(Into::into).collect::<Vec<WrapCodeHash>>().into();
+        self.put((*uo_hash).into(), wrap_hashes)?;
+        Ok(())
+    }
+
+    fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind> {
+        Ok(self
+            .get((*uo_hash).into())?
+            .map(|hashes| {
+                let hashes: Vec<WrapCodeHash> = hashes.into();
+                hashes.into_iter().map(Into::into).collect::<Vec<CodeHash>>()
+            })
+            .unwrap_or_default())
+    }
+
+    fn remove_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let uo_hash_wrap: WrapUserOperationHash = (*uo_hash).into();
+        if self.get(uo_hash_wrap.clone())?.is_some() {
+            self.delete(uo_hash_wrap)?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+macro_rules! impl_clear {
+    ($table: ident) => {
+        impl ClearOp for RocksDbTable<$table> {
+            fn clear(&mut self) {
+                if let Err(err) = self.clear_column() {
+                    error!("Failed to clear the {} rocksdb column: {err:?}", stringify!($table));
+                }
+            }
+        }
+    };
+}
+
+impl_clear!(UserOperations);
+impl_clear!(UserOperationsBySender);
+impl_clear!(UserOperationsByEntity);
+impl_clear!(CodeHashes);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rocksdb::{
+            init_rocksdb,
+            tables::{CodeHashes, UserOperations, UserOperationsByEntity, UserOperationsBySender},
+            RocksDbTable,
+        },
+        utils::tests::mempool_test_case,
+        Mempool,
+    };
+    use std::sync::Arc;
+    use tempdir::TempDir;
+
+    #[allow(clippy::unit_cmp)]
+    #[tokio::test]
+    async fn rocksdb_mempool() {
+        let dir = TempDir::new("test-silius-rocksdb").unwrap();
+
+        let db = Arc::new(init_rocksdb(dir.into_path()).unwrap());
+        let uo_ops: RocksDbTable<UserOperations> = RocksDbTable::new(db.clone());
+        let uo_ops_sender: RocksDbTable<UserOperationsBySender> = RocksDbTable::new(db.clone());
+        let uo_ops_entity: RocksDbTable<UserOperationsByEntity> = RocksDbTable::new(db.clone());
+        let uo_ops_codehashes: RocksDbTable<CodeHashes> = RocksDbTable::new(db.clone());
+        let mempool = Mempool::new(uo_ops, uo_ops_sender, uo_ops_entity, uo_ops_codehashes);
+
+        mempool_test_case(mempool);
+    }
+}
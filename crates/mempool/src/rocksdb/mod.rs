@@ -0,0 +1,110 @@
+//! The RocksDB implementation of the [Mempool](crate::mempool::Mempool) trait. Primarily used for
+//! storing mempool information in a local database.
+
+pub use crate::database::tables;
+use reth_db::table::{Compress, Decode, Decompress, Encode, Table};
+use rocksdb::{ColumnFamily, IteratorMode, Options, DB};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, sync::Arc};
+use thiserror::Error;
+
+pub mod mempool;
+pub mod reputation;
+
+/// RocksDB error
+#[derive(Debug, Error, Serialize, Deserialize)]
+pub enum RocksDbError {
+    /// Internal database error
+    #[error("rocksdb error: {0}")]
+    Internal(String),
+    /// Value couldn't be encoded or decoded
+    #[error("rocksdb codec error: {0}")]
+    Codec(String),
+    /// Column family not found
+    #[error("rocksdb column family {0} not found")]
+    ColumnNotFound(String),
+}
+
+impl From<rocksdb::Error> for RocksDbError {
+    fn from(value: rocksdb::Error) -> Self {
+        RocksDbError::Internal(value.to_string())
+    }
+}
+
+impl From<reth_db::Error> for RocksDbError {
+    fn from(value: reth_db::Error) -> Self {
+        RocksDbError::Codec(value.to_string())
+    }
+}
+
+/// Opens (or creates) the RocksDB database with a column family for each of the
+/// [tables](tables::TABLES)
+pub fn init_rocksdb(path: PathBuf) -> eyre::Result<DB> {
+    fs::create_dir_all(&path)?;
+
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+
+    Ok(DB::open_cf(&opts, path, tables::TABLES.iter().map(|(_, name)| *name))?)
+}
+
+/// The RocksDB-based implementation of the [Mempool](crate::mempool::Mempool) trait.
+#[derive(Debug)]
+pub struct RocksDbTable<Table> {
+    pub db: Arc<DB>,
+    _table: std::marker::PhantomData<Table>,
+}
+
+impl<Table> Clone for RocksDbTable<Table> {
+    fn clone(&self) -> Self {
+        Self { db: self.db.clone(), _table: std::marker::PhantomData }
+    }
+}
+
+// keys and values are encoded the same way as in the MDBX database
+impl<T: Table> RocksDbTable<T> {
+    pub fn new(db: Arc<DB>) -> Self {
+        Self { db, _table: std::marker::PhantomData }
+    }
+
+    fn cf(&self) -> Result<&ColumnFamily, RocksDbError> {
+        self.db.cf_handle(T::NAME).ok_or(RocksDbError::ColumnNotFound(T::NAME.into()))
+    }
+
+    fn get(&self, key: T::Key) -> Result<Option<T::Value>, RocksDbError> {
+        match self.db.get_cf(self.cf()?, key.encode())? {
+            Some(value) => Ok(Some(T::Value::decompress(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, key: T::Key, value: T::Value) -> Result<(), RocksDbError> {
+        self.db.put_cf(self.cf()?, key.encode(), value.compress())?;
+        Ok(())
+    }
+
+    fn delete(&self, key: T::Key) -> Result<(), RocksDbError> {
+        self.db.delete_cf(self.cf()?, key.encode())?;
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<(T::Key, T::Value)>, RocksDbError> {
+        self.db
+            .iterator_cf(self.cf()?, IteratorMode::Start)
+            .map(|entry| {
+                let (key, value) = entry?;
+                Ok((T::Key::decode(key.into_vec())?, T::Value::decompress(value.into_vec())?))
+            })
+            .collect()
+    }
+
+    fn clear_column(&self) -> Result<(), RocksDbError> {
+        let cf = self.cf()?;
+        for entry in self.db.iterator_cf(cf, IteratorMode::Start) {
+            let (key, _) = entry?;
+            self.db.delete_cf(cf, key)?;
+        }
+        Ok(())
+    }
+}
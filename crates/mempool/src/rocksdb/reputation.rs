@@ -0,0 +1,72 @@
+use super::{tables::EntitiesReputation, RocksDbTable};
+use crate::{mempool::ClearOp, reputation::ReputationEntryOp, ReputationError};
+use ethers::types::Address;
+use silius_primitives::reputation::ReputationEntry;
+use tracing::error;
+
+impl ClearOp for RocksDbTable<EntitiesReputation> {
+    fn clear(&mut self) {
+        if let Err(err) = self.clear_column() {
+            error!("Failed to clear the EntitiesReputation rocksdb column: {err:?}");
+        }
+    }
+}
+
+impl ReputationEntryOp for RocksDbTable<EntitiesReputation> {
+    fn get_entry(&self, addr: &Address) -> Result<Option<ReputationEntry>, ReputationError> {
+        Ok(self.get((*addr).into())?.map(|entry| entry.into()))
+    }
+
+    fn set_entry(
+        &mut self,
+        entry: ReputationEntry,
+    ) -> Result<Option<ReputationEntry>, ReputationError> {
+        let original = self.get(entry.address.into())?;
+        self.put(entry.address.into(), entry.into())?;
+        Ok(original.map(|entry| entry.into()))
+    }
+
+    fn contains_entry(&self, addr: &Address) -> Result<bool, ReputationError> {
+        Ok(self.get_entry(addr)?.is_some())
+    }
+
+    fn get_all(&self) -> Vec<ReputationEntry> {
+        self.entries()
+            .map(|entries| entries.into_iter().map(|(_, entry)| entry.into()).collect())
+            .unwrap_or_else(|_| vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        rocksdb::{init_rocksdb, tables::EntitiesReputation, RocksDbTable},
+        utils::tests::reputation_test_case,
+        Reputation,
+    };
+    use ethers::types::{Address, U256};
+    use silius_primitives::constants::validation::reputation::{
+        BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+    };
+    use std::{collections::HashSet, sync::Arc};
+    use tempdir::TempDir;
+
+    #[tokio::test]
+    async fn rocksdb_reputation() {
+        let dir = TempDir::new("test-silius-rocksdb").unwrap();
+
+        let db = Arc::new(init_rocksdb(dir.into_path()).unwrap());
+        let entry: RocksDbTable<EntitiesReputation> = RocksDbTable::new(db);
+        let reputation = Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            HashSet::<Address>::default(),
+            HashSet::<Address>::default(),
+            entry,
+        );
+        reputation_test_case(reputation);
+    }
+}
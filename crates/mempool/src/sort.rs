@@ -0,0 +1,141 @@
+use ethers::types::{I256, U256};
+use silius_primitives::{mempool::SortStrategy, UserOperation, UserOperationHash};
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Calculates the effective gas price (the tip the bundler receives per gas) of the user
+/// operation, i.e., `min(maxPriorityFeePerGas, maxFeePerGas - baseFee)`
+///
+/// # Arguments
+/// * `uo` - The [UserOperation](UserOperation)
+/// * `base_fee` - The base fee per gas of the latest block
+///
+/// # Returns
+/// * `U256` - The effective gas price
+pub fn effective_gas_price(uo: &UserOperation, base_fee: U256) -> U256 {
+    uo.max_priority_fee_per_gas.min(uo.max_fee_per_gas.saturating_sub(base_fee))
+}
+
+/// Calculates the expected profit of bundling the user operation, i.e., the refund the entry point
+/// pays to the beneficiary for the total gas limit of the user operation (at
+/// `min(maxFeePerGas, baseFee + maxPriorityFeePerGas)`) minus the cost of that gas for the bundle
+/// transaction (at the base fee). The profit is negative if the user operation doesn't cover the
+/// base fee.
+///
+/// # Arguments
+/// * `uo` - The [UserOperation](UserOperation)
+/// * `base_fee` - The base fee per gas of the latest block
+///
+/// # Returns
+/// * `I256` - The expected profit
+pub fn expected_profit(uo: &UserOperation, base_fee: U256) -> I256 {
+    let gas = uo
+        .pre_verification_gas
+        .saturating_add(uo.verification_gas_limit)
+        .saturating_add(uo.call_gas_limit);
+    let gas_price = uo.max_fee_per_gas.min(base_fee.saturating_add(uo.max_priority_fee_per_gas));
+
+    let refund = I256::try_from(gas.saturating_mul(gas_price)).unwrap_or(I256::MAX);
+    let cost = I256::try_from(gas.saturating_mul(base_fee)).unwrap_or(I256::MAX);
+    refund.saturating_sub(cost)
+}
+
+/// Sorts the user operations by the [SortStrategy](SortStrategy), the ties are sorted by nonce
+///
+/// # Arguments
+/// * `uos` - The [UserOperations](UserOperation) to sort
+/// * `strategy` - The [SortStrategy](SortStrategy)
+/// * `base_fee` - The base fee per gas of the latest block (unused by the priority fee and FIFO
+///   strategies)
+/// * `arrivals` - The order in which the user operations were added to the mempool (user operations
+///   without arrival are sorted last by the FIFO strategy)
+pub fn sort_user_operations(
+    uos: &mut [UserOperation],
+    strategy: SortStrategy,
+    base_fee: U256,
+    arrivals: &HashMap<UserOperationHash, u64>,
+) {
+    match strategy {
+        SortStrategy::PriorityFee => {
+            uos.sort_by_key(|uo| (Reverse(uo.max_priority_fee_per_gas), uo.nonce))
+        }
+        SortStrategy::GasPrice => {
+            uos.sort_by_key(|uo| (Reverse(effective_gas_price(uo, base_fee)), uo.nonce))
+        }
+        SortStrategy::Fifo => {
+            uos.sort_by_key(|uo| (arrivals.get(&uo.hash).copied().unwrap_or(u64::MAX), uo.nonce))
+        }
+        SortStrategy::Profitability => {
+            uos.sort_by_key(|uo| (Reverse(expected_profit(uo, base_fee)), uo.nonce))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use silius_primitives::UserOperationSigned;
+
+    fn user_operation(
+        max_fee: u64,
+        max_priority_fee: u64,
+        call_gas: u64,
+        arrival: u64,
+        arrivals: &mut HashMap<UserOperationHash, u64>,
+    ) -> UserOperation {
+        let hash = UserOperationHash(H256::random());
+        arrivals.insert(hash, arrival);
+        UserOperation::from_user_operation_signed(
+            hash,
+            UserOperationSigned {
+                max_fee_per_gas: max_fee.into(),
+                max_priority_fee_per_gas: max_priority_fee.into(),
+                call_gas_limit: call_gas.into(),
+                verification_gas_limit: 100_000.into(),
+                pre_verification_gas: 50_000.into(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn sort_strategies() {
+        let base_fee = U256::from(100);
+        let mut arrivals = HashMap::new();
+        // effective gas price 5, profit 5 * 1_150_000
+        let first = user_operation(200, 5, 1_000_000, 1, &mut arrivals);
+        // effective gas price 20 (capped by the max fee), profit 20 * 200_000
+        let second = user_operation(120, 30, 50_000, 2, &mut arrivals);
+        // effective gas price 10, profit 10 * 250_000
+        let third = user_operation(300, 10, 100_000, 0, &mut arrivals);
+        let uos = vec![second.clone(), first.clone(), third.clone()];
+        let hashes = |uos: &[UserOperation]| uos.iter().map(|uo| uo.hash).collect::<Vec<_>>();
+
+        let mut sorted = uos.clone();
+        sort_user_operations(&mut sorted, SortStrategy::PriorityFee, base_fee, &arrivals);
+        assert_eq!(hashes(&sorted), vec![second.hash, third.hash, first.hash]);
+
+        let mut sorted = uos.clone();
+        sort_user_operations(&mut sorted, SortStrategy::GasPrice, base_fee, &arrivals);
+        assert_eq!(hashes(&sorted), vec![second.hash, third.hash, first.hash]);
+
+        let mut sorted = uos.clone();
+        sort_user_operations(&mut sorted, SortStrategy::Fifo, base_fee, &arrivals);
+        assert_eq!(hashes(&sorted), vec![third.hash, first.hash, second.hash]);
+
+        let mut sorted = uos;
+        sort_user_operations(&mut sorted, SortStrategy::Profitability, base_fee, &arrivals);
+        assert_eq!(hashes(&sorted), vec![first.hash, second.hash, third.hash]);
+
+        // the user operations priced below the base fee have no tip and lose money
+        let below = user_operation(90, 5, 1_000_000, 3, &mut arrivals);
+        let free = user_operation(100, 5, 1_000_000, 4, &mut arrivals);
+        assert_eq!(effective_gas_price(&below, base_fee), U256::zero());
+        assert_eq!(expected_profit(&below, base_fee), I256::from(-11_500_000));
+        assert_eq!(expected_profit(&free, base_fee), I256::zero());
+
+        let mut sorted = vec![below.clone(), free.clone(), third.clone()];
+        sort_user_operations(&mut sorted, SortStrategy::Profitability, base_fee, &arrivals);
+        assert_eq!(hashes(&sorted), vec![third.hash, free.hash, below.hash]);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::Allowlist;
+use ethers::types::Address;
+use silius_primitives::UserOperation;
+
+/// Senders whose user operations are always bundled first regardless of their fees (e.g., the
+/// relayer accounts of the operator). The set can be updated at runtime and the clones share the
+/// same set.
+#[derive(Clone, Debug, Default)]
+pub struct TrustedSenders {
+    senders: Allowlist,
+}
+
+impl TrustedSenders {
+    /// Creates a new [TrustedSenders](TrustedSenders)
+    ///
+    /// # Arguments
+    /// * `senders` - The trusted senders
+    ///
+    /// # Returns
+    /// * `TrustedSenders` - The [TrustedSenders](TrustedSenders)
+    pub fn new(senders: Vec<Address>) -> Self {
+        Self { senders: Allowlist::new(senders) }
+    }
+
+    /// Gets the [Allowlist](Allowlist) of the trusted senders, which is updated by the admin
+    /// methods
+    ///
+    /// # Returns
+    /// * `&Allowlist` - The [Allowlist](Allowlist) of the trusted senders
+    pub fn senders(&self) -> &Allowlist {
+        &self.senders
+    }
+
+    /// Moves the user operations of the trusted senders to the front, the relative order of the
+    /// user operations (e.g., by fees) is kept otherwise
+    ///
+    /// # Arguments
+    /// * `uos` - The sorted [UserOperations](UserOperation)
+    pub fn prioritize(&self, uos: &mut [UserOperation]) {
+        if self.senders.is_empty() {
+            return;
+        }
+        // the sort is stable
+        uos.sort_by_cached_key(|uo| !self.senders.contains(&uo.sender));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn user_operation(sender: Address) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash(H256::random()),
+            UserOperationSigned { sender, ..Default::default() },
+        )
+    }
+
+    #[test]
+    fn trusted_senders_first() {
+        let (trusted, other) = (Address::random(), Address::random());
+        let uos = vec![
+            user_operation(other),
+            user_operation(trusted),
+            user_operation(other),
+            user_operation(trusted),
+        ];
+        let hashes = |order: &[usize]| order.iter().map(|i| uos[*i].hash).collect::<Vec<_>>();
+
+        let mut sorted = uos.clone();
+        TrustedSenders::default().prioritize(&mut sorted);
+        assert_eq!(sorted.iter().map(|uo| uo.hash).collect::<Vec<_>>(), hashes(&[0, 1, 2, 3]));
+
+        let trusted_senders = TrustedSenders::new(vec![trusted]);
+        let mut sorted = uos.clone();
+        trusted_senders.prioritize(&mut sorted);
+        assert_eq!(sorted.iter().map(|uo| uo.hash).collect::<Vec<_>>(), hashes(&[1, 3, 0, 2]));
+
+        // the clones share the set
+        trusted_senders.clone().senders().update(&[other], &[trusted]);
+        let mut sorted = uos.clone();
+        trusted_senders.prioritize(&mut sorted);
+        assert_eq!(sorted.iter().map(|uo| uo.hash).collect::<Vec<_>>(), hashes(&[0, 2, 1, 3]));
+    }
+}
@@ -1,39 +1,94 @@
 use crate::{
+    attempts::BundleAttempts,
     estimate::estimate_user_op_gas,
-    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    estimate_cache::EstimationCache,
+    grace::{is_transient_failure, is_transient_reason, ValidationGrace},
+    mempool::{
+        unix_time, Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct,
+    },
     mempool_id,
+    metrics::record_replacement,
     reputation::{HashSetOp, ReputationEntryOp},
+    sort::effective_gas_price,
+    utils::{
+        calculate_fee_bump_perc, calculate_pre_verification_gas, eviction_candidates,
+        failed_validation_entity, nonce_key, split_queued,
+    },
     validate::{
-        UserOperationValidationOutcome, UserOperationValidator, UserOperationValidatorMode,
+        sanity::unstaked_entities::UnstakedEntities, UserOperationValidationOutcome,
+        UserOperationValidator, UserOperationValidatorMode,
     },
-    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, MempoolId, Overhead,
-    Reputation, ReputationError, SanityError, SimulationError,
+    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, MempoolEvent, MempoolId,
+    RemovalReason, Reputation, ReputationError, SanityError, SimulationError, TrustedSenders,
 };
 use alloy_chains::Chain;
 use ethers::{
+    abi::RawLog,
+    contract::EthEvent,
     prelude::LogMeta,
     providers::Middleware,
-    types::{Address, BlockNumber, U256},
+    types::{
+        Address, BlockNumber, GethDebugBuiltInTracerType, GethDebugTracerType,
+        GethDebugTracingOptions, GethTrace, GethTraceFrame, Log, H256, U256,
+    },
 };
 use eyre::format_err;
 use futures::channel::mpsc::UnboundedSender;
 use silius_contracts::{
-    entry_point::UserOperationEventFilter, utils::parse_from_input_data, EntryPoint,
-    EntryPointError,
+    entry_point::UserOperationEventFilter,
+    utils::{parse_from_call_frame, parse_from_transaction},
+    EntryPoint, EntryPointError,
 };
 use silius_primitives::{
-    constants::validation::reputation::THROTTLED_ENTITY_BUNDLE_COUNT,
+    constants::validation::{
+        entities::{FACTORY, NUMBER_OF_LEVELS, PAYMASTER},
+        reputation::{THROTTLED_ENTITY_BUNDLE_COUNT, THROTTLED_ENTITY_MEMPOOL_COUNT},
+    },
     get_address,
+    mempool::{MempoolComposition, MempoolSnapshot, SortStrategy},
     reputation::{ReputationEntry, StakeInfo, StakeInfoResponse, Status},
-    UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
-    UserOperationReceipt,
+    simulation::{StorageAccesses, StorageMap},
+    EntryPointVersion, UserOperation, UserOperationByHash, UserOperationGasEstimation,
+    UserOperationHash, UserOperationReceipt, UserOperationSigned,
 };
 use std::collections::{HashMap, HashSet};
-use tracing::{debug, error, info, trace};
+use tokio::sync::broadcast;
+use tracing::{debug, debug_span, error, info, instrument, trace, Instrument};
 
 const FILTER_MAX_DEPTH: u64 = 10;
 const PRE_VERIFICATION_SAFE_RESERVE: u64 = 1_000;
 
+/// Finds the `UserOperationEvent` of the user operation in the logs of the bundle transaction and
+/// the logs emitted by the user operation, i.e., the logs between the previous
+/// `UserOperationEvent` and the `UserOperationEvent` of the user operation.
+pub fn find_user_operation_logs(
+    logs: &[Log],
+    ep: &Address,
+    uo_hash: &UserOperationHash,
+) -> Option<(UserOperationEventFilter, Vec<Log>)> {
+    let topic = UserOperationEventFilter::signature();
+    let mut start = 0;
+
+    for (i, log) in logs.iter().enumerate() {
+        if log.address != *ep || log.topics.first() != Some(&topic) {
+            continue;
+        }
+
+        if log.topics.get(1) == Some(&uo_hash.0) {
+            let event = UserOperationEventFilter::decode_log(&RawLog {
+                topics: log.topics.clone(),
+                data: log.data.to_vec(),
+            })
+            .ok()?;
+            return Some((event, logs[start..i].to_vec()));
+        }
+
+        start = i + 1;
+    }
+
+    None
+}
+
 /// The alternative mempool pool implementation that provides functionalities to add, remove,
 /// validate, and serves data requests from the [RPC API](EthApiServer). Architecturally, the
 /// [UoPool](UoPool) is the backend service managed by the [UoPoolService](UoPoolService) and serves
@@ -63,6 +118,25 @@ where
     pub chain: Chain,
     // It would be None if p2p is not enabled
     p2p_channel: Option<UnboundedSender<(UserOperation, U256)>>,
+    // Channel the [MempoolEvents](MempoolEvent) are published to (None if nobody listens)
+    events: Option<broadcast::Sender<MempoolEvent>>,
+    // Time (in seconds) after which a user operation that was not bundled is evicted (never if
+    // not set)
+    ttl: Option<u64>,
+    // The order in which the user operations are bundled
+    sort_strategy: SortStrategy,
+    // Cache of the simulated gas limits (disabled if `None`)
+    estimation_cache: Option<EstimationCache>,
+    // The senders whose user operations are bundled first
+    trusted_senders: TrustedSenders,
+    // Max number of user operations in the mempool (unbounded if not set)
+    max_uos: Option<usize>,
+    // Grace policy of the user operations that fail the re-validation with a transient error
+    grace: ValidationGrace,
+    // Failed bundle attempts of the user operations kept in the mempool
+    bundle_attempts: BundleAttempts,
+    // Gas added to the calculated pre-verification gas of the estimations
+    pre_verification_gas_overhead: U256,
 }
 
 impl<M: Middleware + 'static, V: UserOperationValidator, T, Y, X, Z, H, R>
@@ -85,6 +159,8 @@ where
     /// `eth_client` - The Ethereum client [Middleware](ethers::providers::Middleware)
     /// `max_verification_gas` - The maximum gas limit for [UserOperation](UserOperation) gas
     /// verification. `chain` - The [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID
+    /// `p2p_channel` - The channel the added user operations are published to the p2p network
+    /// `events` - The channel the [MempoolEvents](MempoolEvent) are published to
     ///
     /// # Returns
     /// `Self` - The [UoPool](UoPool) object
@@ -97,6 +173,7 @@ where
         max_verification_gas: U256,
         chain: Chain,
         p2p_channel: Option<UnboundedSender<(UserOperation, U256)>>,
+        events: Option<broadcast::Sender<MempoolEvent>>,
     ) -> Self {
         Self {
             id: mempool_id(&entry_point.address(), chain.id()),
@@ -107,17 +184,171 @@ where
             max_verification_gas,
             chain,
             p2p_channel,
+            events,
+            ttl: None,
+            sort_strategy: SortStrategy::default(),
+            estimation_cache: None,
+            trusted_senders: TrustedSenders::default(),
+            max_uos: None,
+            grace: ValidationGrace::default(),
+            bundle_attempts: BundleAttempts::default(),
+            pre_verification_gas_overhead: U256::zero(),
         }
     }
 
-    /// Returns all of the [UserOperations](UserOperation) in the mempool
+    /// Sets the time after which the [UserOperations](UserOperation) that were not bundled are
+    /// evicted from the mempool
+    ///
+    /// # Arguments
+    /// `ttl` - The TTL in seconds (never evicted if not set)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_ttl(mut self, ttl: Option<u64>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets the order in which the [UserOperations](UserOperation) are bundled
+    ///
+    /// # Arguments
+    /// `sort_strategy` - The [SortStrategy](SortStrategy)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_sort_strategy(mut self, sort_strategy: SortStrategy) -> Self {
+        self.sort_strategy = sort_strategy;
+        self
+    }
+
+    /// Sets the gas added to the calculated pre-verification gas of the
+    /// [estimations](UoPool::estimate_user_operation_gas)
+    ///
+    /// # Arguments
+    /// `overhead` - The pre-verification gas overhead
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_pre_verification_gas_overhead(mut self, overhead: U256) -> Self {
+        self.pre_verification_gas_overhead = overhead;
+        self
+    }
+
+    /// Sets the [EstimationCache](EstimationCache) of the gas limits simulated by
+    /// [estimate_user_operation_gas](UoPool::estimate_user_operation_gas)
+    ///
+    /// # Arguments
+    /// `estimation_cache` - The [EstimationCache](EstimationCache) (shared by the clones)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_estimation_cache(mut self, estimation_cache: EstimationCache) -> Self {
+        self.estimation_cache = Some(estimation_cache);
+        self
+    }
+
+    /// Sets the [TrustedSenders](TrustedSenders) whose [UserOperations](UserOperation) are bundled
+    /// first regardless of the [SortStrategy](SortStrategy)
+    ///
+    /// # Arguments
+    /// `trusted_senders` - The [TrustedSenders](TrustedSenders) (shared by the clones)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_trusted_senders(mut self, trusted_senders: TrustedSenders) -> Self {
+        self.trusted_senders = trusted_senders;
+        self
+    }
+
+    /// Sets the capacity of the mempool. Once it's full, a new [UserOperation](UserOperation) is
+    /// only admitted if it pays more than the one with the lowest fee, which is evicted
+    ///
+    /// # Arguments
+    /// `max_uos` - The max number of [UserOperations](UserOperation) (unbounded if not set)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_max_uos(mut self, max_uos: Option<usize>) -> Self {
+        self.max_uos = max_uos;
+        self
+    }
+
+    /// Sets the [ValidationGrace](ValidationGrace) of the [UserOperations](UserOperation) that
+    /// fail the re-validation because of a possibly transient condition
+    ///
+    /// # Arguments
+    /// `grace` - The [ValidationGrace](ValidationGrace) (shared by the clones)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_validation_grace(mut self, grace: ValidationGrace) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Sets the [BundleAttempts](BundleAttempts) of the [UserOperations](UserOperation) that are
+    /// kept in the mempool after they failed at bundle time
+    ///
+    /// # Arguments
+    /// `bundle_attempts` - The [BundleAttempts](BundleAttempts) (shared by the clones)
+    ///
+    /// # Returns
+    /// `Self` - The [UoPool](UoPool) object
+    pub fn with_bundle_attempts(mut self, bundle_attempts: BundleAttempts) -> Self {
+        self.bundle_attempts = bundle_attempts;
+        self
+    }
+
+    /// Returns the number of failed bundle attempts of the [UserOperations](UserOperation) that
+    /// failed at bundle time at least once
+    ///
+    /// # Returns
+    /// `HashMap<UserOperationHash, u64>` - The failed bundle attempts by user operation
+    pub fn get_bundle_attempts(&self) -> HashMap<UserOperationHash, u64> {
+        self.bundle_attempts.all()
+    }
+
+    /// Calculates the hash of the [UserOperation](UserOperation) in the format of the version of
+    /// the entry point
+    ///
+    /// # Arguments
+    /// * `uo` - The [UserOperationSigned](UserOperationSigned) to hash
+    ///
+    /// # Returns
+    /// `UserOperationHash` - The hash of the user operation
+    pub fn user_operation_hash(&self, uo: &UserOperationSigned) -> UserOperationHash {
+        uo.hash_with_version(
+            &self.entry_point.address(),
+            self.chain.id(),
+            self.entry_point.version(),
+        )
+    }
+
+    /// Publishes the [MempoolEvent](MempoolEvent) to the subscribers (if any)
+    fn publish_event(&self, event: MempoolEvent) {
+        // the failures of the user operations that left the mempool are not tracked anymore
+        match &event {
+            MempoolEvent::Removed { uo_hash, .. } |
+            MempoolEvent::Replaced { prev_hash: uo_hash, .. } => {
+                self.grace.reset(uo_hash);
+                self.bundle_attempts.reset(uo_hash);
+            }
+            MempoolEvent::Added { .. } => {}
+        }
+        if let Some(ref sd) = self.events {
+            // sending only fails if there are no subscribers
+            let _ = sd.send(event);
+        }
+    }
+
+    /// Returns all of the [UserOperations](UserOperation) in the mempool (including the queued
+    /// ones), sorted in the same order as the bundles, see
+    /// [get_sorted_user_operations](UoPool::get_sorted_user_operations)
     ///
     /// # Returns
     /// `Result<Vec<UserOperation>, eyre::Error>` - An array of [UserOperations](UserOperation)
-    pub fn get_all(&self) -> eyre::Result<Vec<UserOperation>> {
-        self.mempool.get_all().map_err(|err| {
-            format_err!("Getting all user operations from mempool failed with error: {err:?}",)
-        })
+    pub async fn get_all(&self) -> eyre::Result<Vec<UserOperation>> {
+        self.sort_user_operations().await
     }
 
     /// Returns an array of [ReputationEntry](ReputationEntry) for entities.
@@ -189,6 +420,170 @@ where
         Ok(())
     }
 
+    /// Re-validates the [UserOperations](UserOperation) restored from the storage (e.g., after
+    /// restart) against the current chain state. Only the sanity checks are performed. The
+    /// [UserOperations](UserOperation) are validated in place, so the stored mempool stays intact
+    /// until the validation is done, and only the ones that are not valid anymore are removed.
+    ///
+    /// # Returns
+    /// `Result<usize, eyre::Error>` - The number of re-admitted [UserOperations](UserOperation)
+    pub async fn reload_user_operations(&mut self) -> eyre::Result<usize> {
+        let mut uos = self.mempool.get_all().map_err(|err| {
+            format_err!("Getting all user operations from mempool failed with error: {err:?}",)
+        })?;
+        uos.sort_by(|a, b| a.sender.cmp(&b.sender).then(a.nonce.cmp(&b.nonce)));
+
+        let mut invalid = vec![];
+        for uo in uos.iter() {
+            let res = self
+                .validator
+                .validate_user_operation(
+                    uo,
+                    &self.mempool,
+                    &self.reputation,
+                    UserOperationValidatorMode::Sanity.into(),
+                )
+                .await;
+
+            if let Err(err) = res {
+                debug!(uo_hash = %uo.hash, "Dropping user operation on reload: {err:?}");
+                invalid.push(uo.hash);
+            }
+        }
+
+        let count = uos.len() - invalid.len();
+        self.remove_user_operations(invalid, RemovalReason::Invalidated);
+
+        Ok(count)
+    }
+
+    /// Re-validates the pending [UserOperations](UserOperation) against the current chain state
+    /// on request of the operator (e.g., after a change of the paymaster allowlist or a large
+    /// state change). Only the sanity checks are performed. The user operations are validated in
+    /// place (they keep their position in the FIFO order and their age) and only the invalid ones
+    /// are removed, the user operations that fail because of the provider or another transient
+    /// condition (within the [ValidationGrace](ValidationGrace)) are kept.
+    ///
+    /// # Returns
+    /// `eyre::Result<(usize, Vec<(UserOperationHash, String)>)>` - The number of kept
+    /// [UserOperations](UserOperation) and the dropped ones with the reason
+    pub async fn revalidate_user_operations(
+        &mut self,
+    ) -> eyre::Result<(usize, Vec<(UserOperationHash, String)>)> {
+        let uos = self.mempool.get_sorted_by(SortStrategy::Fifo, U256::zero()).map_err(|err| {
+            format_err!("Getting all user operations from mempool failed with error: {err:?}",)
+        })?;
+
+        let mut dropped = vec![];
+        for uo in uos.iter() {
+            let uo_hash = uo.hash;
+            let res = self
+                .validator
+                .validate_user_operation(
+                    uo,
+                    &self.mempool,
+                    &self.reputation,
+                    UserOperationValidatorMode::Sanity.into(),
+                )
+                .await;
+
+            match res {
+                Err(InvalidMempoolUserOperationError::Sanity(SanityError::Provider { inner })) => {
+                    debug!(uo_hash = %uo_hash, "Keeping user operation on re-validation, provider error: {inner}");
+                }
+                Err(err) if !self.grace.on_failure(&uo_hash, is_transient_failure(&err)) => {
+                    debug!(uo_hash = %uo_hash, "Keeping user operation on re-validation until the next round, transient error: {err:?}");
+                }
+                Err(err) => {
+                    debug!(uo_hash = %uo_hash, "Dropping user operation on re-validation: {err:?}");
+                    dropped.push((uo_hash, err.to_string()));
+                }
+                Ok(_) => self.grace.reset(&uo_hash),
+            }
+        }
+
+        self.remove_user_operations(
+            dropped.iter().map(|(uo_hash, _)| *uo_hash).collect(),
+            RemovalReason::Invalidated,
+        );
+
+        Ok((uos.len() - dropped.len(), dropped))
+    }
+
+    /// Exports the [UserOperations](UserOperation) and the reputation of the entities into a
+    /// [MempoolSnapshot](MempoolSnapshot)
+    ///
+    /// # Returns
+    /// `Result<MempoolSnapshot, eyre::Error>` - The [MempoolSnapshot](MempoolSnapshot)
+    pub fn export_snapshot(&self) -> eyre::Result<MempoolSnapshot> {
+        let user_operations = self.mempool.get_all().map_err(|err| {
+            format_err!("Getting all user operations from mempool failed with error: {err:?}",)
+        })?;
+
+        Ok(MempoolSnapshot {
+            entry_point: self.entry_point.address(),
+            chain_id: self.chain.id(),
+            user_operations,
+            reputation: self.get_reputation(),
+        })
+    }
+
+    /// Counts the [UserOperations](UserOperation) in the mempool by sender, by paymaster and by
+    /// factory
+    ///
+    /// # Returns
+    /// `Result<MempoolComposition, eyre::Error>` - The [MempoolComposition](MempoolComposition)
+    pub fn composition(&self) -> eyre::Result<MempoolComposition> {
+        self.mempool.composition().map_err(|err| {
+            format_err!("Counting user operations in mempool failed with error: {err:?}",)
+        })
+    }
+
+    /// Imports the [MempoolSnapshot](MempoolSnapshot) (e.g., exported by another bundler). The
+    /// reputation of the entities is restored first, then the [UserOperations](UserOperation) are
+    /// validated against the current chain state and the invalid ones are dropped.
+    ///
+    /// # Arguments
+    /// `snapshot` - The [MempoolSnapshot](MempoolSnapshot) of the same entry point and chain
+    ///
+    /// # Returns
+    /// `Result<usize, eyre::Error>` - The number of imported [UserOperations](UserOperation)
+    pub async fn import_snapshot(&mut self, snapshot: MempoolSnapshot) -> eyre::Result<usize> {
+        let ep = self.entry_point.address();
+        if snapshot.entry_point != ep || snapshot.chain_id != self.chain.id() {
+            return Err(format_err!(
+                "Snapshot of entry point {:?} on chain {} doesn't match the mempool {:?}",
+                snapshot.entry_point,
+                snapshot.chain_id,
+                self.id
+            ));
+        }
+
+        self.set_reputation(snapshot.reputation)
+            .map_err(|err| format_err!("Setting reputation failed with error: {err:?}"))?;
+
+        let mut uos = snapshot.user_operations;
+        uos.sort_by(|a, b| a.sender.cmp(&b.sender).then(a.nonce.cmp(&b.nonce)));
+
+        let mut count = 0;
+        for uo in uos {
+            // the hash is calculated again instead of trusting the snapshot
+            let uo = UserOperation::from_user_operation_signed(
+                self.user_operation_hash(&uo.user_operation),
+                uo.user_operation,
+            );
+            let res = self.validate_user_operation(&uo).await;
+            match self.add_user_operation(uo, res).await {
+                Ok(_) => count += 1,
+                Err(err) => {
+                    debug!(uo_hash = %err.hash, "Dropping user operation on import: {err:?}")
+                }
+            }
+        }
+
+        Ok(count)
+    }
+
     /// Validates a single [UserOperation](UserOperation) and returns the validation outcome by
     /// calling [UserOperationValidator::validate_user_operation](UserOperationValidator::validate_user_operation)
     ///
@@ -198,6 +593,11 @@ where
     /// # Returns
     /// `Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError>` - The validation
     /// outcome
+    #[instrument(
+        name = "validation",
+        skip_all,
+        fields(uo_hash = %uo.hash, ep = ?self.entry_point.address())
+    )]
     pub async fn validate_user_operation(
         &self,
         uo: &UserOperation,
@@ -214,6 +614,59 @@ where
             .await
     }
 
+    /// Checks the limits on the number of user operations of the factory and the paymaster in the
+    /// mempool ([SREP-030] and [UREP-020]) against the mempool the user operation is added to. The
+    /// sanity checks see the mempool at the start of the validation, so the user operations of an
+    /// entity validated at the same time could exceed the limits together otherwise.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to add
+    /// `stake_info` - The stake infos of the entities from the simulation
+    ///
+    /// # Returns
+    /// `Result<(), InvalidMempoolUserOperationError>` - An error if an entity is over its limit
+    fn check_entity_limits(
+        &self,
+        uo: &UserOperation,
+        stake_info: &[StakeInfo; NUMBER_OF_LEVELS],
+    ) -> Result<(), InvalidMempoolUserOperationError> {
+        let (_, factory, paymaster) = uo.get_entities();
+
+        for (entity, addr, info) in
+            [(FACTORY, factory, stake_info[0]), (PAYMASTER, paymaster, stake_info[2])]
+        {
+            let Some(addr) = addr else { continue };
+            let count = self.mempool.get_number_by_entity(&addr);
+
+            let status = Status::from(self.reputation.get_status(&addr)?);
+            if status == Status::THROTTLED &&
+                self.mempool.get_number_by_sender(&addr) + count >=
+                    THROTTLED_ENTITY_MEMPOOL_COUNT
+            {
+                return Err(SanityError::from(ReputationError::ThrottledEntity {
+                    entity: entity.into(),
+                    address: addr,
+                })
+                .into());
+            }
+
+            if self.reputation.verify_stake(entity, Some(info)).is_err() &&
+                count as u64 >=
+                    UnstakedEntities::calculate_allowed_user_operations(
+                        self.reputation.get(&addr)?,
+                    )
+            {
+                return Err(SanityError::from(ReputationError::UnstakedEntity {
+                    entity: entity.into(),
+                    address: addr,
+                })
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Adds a single validated user operation into the pool
     /// Indirectly invoked by [EthApiServer::send_user_operation](EthApiServer::send_user_operation)
     /// via [UoPoolService::add](UoPoolService::add) to add a [UserOperation](UserOperation) into
@@ -230,11 +683,23 @@ where
     /// # Returns
     /// `Result<UserOperationHash, MempoolError>` - The hash of the added
     /// [UserOperation](UserOperation)
+    #[instrument(
+        name = "mempool_insertion",
+        skip_all,
+        fields(uo_hash = %uo.hash, ep = ?self.entry_point.address())
+    )]
     pub async fn add_user_operation(
         &mut self,
         uo: UserOperation,
         res: Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError>,
     ) -> Result<UserOperationHash, MempoolError> {
+        // an identical resubmission is not a replacement, it's acknowledged as is (the same hash
+        // with another signature is a signature refresh, it's subject to the validation)
+        if self.mempool.contains_identical(&uo).unwrap_or_default() {
+            debug!(uo_hash = %uo.hash, "User operation is already in the mempool {:?}", self.id);
+            return Ok(uo.hash);
+        }
+
         let res = match res {
             Ok(res) => res,
             Err(err) => {
@@ -242,14 +707,26 @@ where
                     ReputationError::BannedEntity { address, entity: _ },
                 )) = err
                 {
-                    self.remove_user_operation_by_entity(&address);
+                    self.remove_user_operation_by_entity(&address, RemovalReason::Invalidated);
                 }
                 return Err(MempoolError { hash: uo.hash, kind: err.into() });
             }
         };
 
+        if let Some(ref stake_info) = res.stake_info {
+            self.check_entity_limits(&uo, stake_info)
+                .map_err(|err| MempoolError { hash: uo.hash, kind: err.into() })?;
+        }
+
+        // a replacement takes the place of the previous user operation
+        if res.prev_hash.is_none() {
+            self.make_room(&uo).await?;
+        }
+
+        // the replaced user operation is reported together with the new one
+        let uo_prev = res.prev_hash.and_then(|uo_hash| self.mempool.get(&uo_hash).ok().flatten());
         if let Some(uo_hash) = res.prev_hash {
-            self.remove_user_operation(&uo_hash);
+            self.mempool.remove(&uo_hash).ok();
         }
         if let Some(ref sd) = self.p2p_channel {
             sd.unbounded_send((uo.clone(), res.verified_block))
@@ -259,13 +736,28 @@ where
             Ok(uo_hash) => {
                 // TODO: find better way to do it atomically
                 if let Some(code_hashes) = res.code_hashes {
-                    match self.mempool.set_code_hashes(&uo_hash, code_hashes){
+                    match self.mempool.set_code_hashes(&uo_hash, code_hashes) {
                         Ok(_) => (),
-                        Err(e) => error!("Failed to set code hashes for user operation {uo_hash:?} with error: {e:?}"),
+                        Err(e) => {
+                            error!(uo_hash = %uo_hash, "Failed to set code hashes for user operation with error: {e:?}")
+                        }
                     }
                 }
-                info!("{uo_hash:?} added to the mempool {:?}", self.id);
-                trace!("{uo:?} added to the mempool {:?}", self.id);
+                info!(uo_hash = %uo_hash, "User operation added to the mempool {:?}", self.id);
+                trace!(uo_hash = %uo_hash, "{uo:?} added to the mempool {:?}", self.id);
+                self.publish_event(match res.prev_hash {
+                    Some(prev_hash) => {
+                        MempoolEvent::Replaced { uo_hash, prev_hash, sender: uo.sender }
+                    }
+                    None => MempoolEvent::Added { uo_hash, sender: uo.sender },
+                });
+                if let Some(uo_prev) = uo_prev {
+                    record_replacement(
+                        &uo.sender,
+                        true,
+                        calculate_fee_bump_perc(&uo_prev.user_operation, &uo.user_operation),
+                    );
+                }
 
                 // update reputation
                 self.reputation
@@ -281,6 +773,11 @@ where
                         .increment_seen(&p_addr)
                         .map_err(|e| MempoolError { hash: uo_hash, kind: e.into() })?;
                 }
+                if let Some(aggregator) = res.aggregator {
+                    self.reputation
+                        .increment_seen(&aggregator.address)
+                        .map_err(|e| MempoolError { hash: uo_hash, kind: e.into() })?;
+                }
 
                 Ok(uo_hash)
             }
@@ -288,15 +785,152 @@ where
         }
     }
 
-    /// Sorts the [UserOperations](UserOperation) in the mempool by calling the
-    /// [Mempool::get_sorted](Mempool::get_sorted) function
+    /// Makes room for the [UserOperation](UserOperation) if the mempool is full, by evicting the
+    /// [UserOperation](UserOperation) of the entry point with the lowest effective gas price if
+    /// the new one pays more. The [UserOperations](UserOperation) of staked senders and of the
+    /// sender of the new one are not evicted.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to make room for
+    ///
+    /// # Returns
+    /// `Result<Option<UserOperationHash>, MempoolError>` - The hash of the evicted
+    /// [UserOperation](UserOperation) (if any), or an error if there is no room for it
+    async fn make_room(
+        &mut self,
+        uo: &UserOperation,
+    ) -> Result<Option<UserOperationHash>, MempoolError> {
+        let max_uos = match self.max_uos {
+            Some(max_uos) => max_uos,
+            None => return Ok(None),
+        };
+
+        let uos = self.mempool.get_all().map_err(|kind| MempoolError { hash: uo.hash, kind })?;
+        if uos.len() < max_uos {
+            return Ok(None);
+        }
+
+        let base_fee = self.base_fee_per_gas().await.map_err(|err| MempoolError {
+            hash: uo.hash,
+            kind: MempoolErrorKind::Provider { inner: err.to_string() },
+        })?;
+        let gas_price = effective_gas_price(uo, base_fee);
+
+        let uos = uos
+            .into_iter()
+            .filter(|uo_prev| self.user_operation_hash(uo_prev) == uo_prev.hash)
+            .collect();
+
+        let mut staked = HashMap::new();
+        for uo_prev in eviction_candidates(uos, uo, base_fee) {
+            let is_staked = match staked.get(&uo_prev.sender) {
+                Some(is_staked) => *is_staked,
+                None => {
+                    let is_staked = self
+                        .get_stake_info(&uo_prev.sender)
+                        .await
+                        .map_err(|err| MempoolError {
+                            hash: uo.hash,
+                            kind: MempoolErrorKind::Provider { inner: err.to_string() },
+                        })?
+                        .is_staked;
+                    staked.insert(uo_prev.sender, is_staked);
+                    is_staked
+                }
+            };
+            if is_staked {
+                continue;
+            }
+
+            self.mempool
+                .remove(&uo_prev.hash)
+                .map_err(|kind| MempoolError { hash: uo.hash, kind })?;
+            info!(
+                uo_hash = %uo.hash,
+                "Evicted user operation {:?} from the full mempool {:?}",
+                uo_prev.hash, self.id
+            );
+            self.publish_event(MempoolEvent::Removed {
+                uo_hash: uo_prev.hash,
+                sender: uo_prev.sender,
+                reason: RemovalReason::Evicted,
+            });
+            return Ok(Some(uo_prev.hash));
+        }
+
+        Err(MempoolError {
+            hash: uo.hash,
+            kind: SanityError::MempoolFull { max_uos, effective_gas_price: gas_price }.into(),
+        })
+    }
+
+    /// Sorts the [UserOperations](UserOperation) in the mempool by the configured
+    /// [SortStrategy](SortStrategy) by calling the
+    /// [Mempool::get_sorted_by](Mempool::get_sorted_by) function, the
+    /// [UserOperations](UserOperation) of the [TrustedSenders](TrustedSenders) are moved to the
+    /// front
+    ///
+    /// # Returns
+    /// `Result<Vec<UserOperation>, eyre::Error>` - The sorted [UserOperations](UserOperation)
+    pub async fn get_sorted_user_operations(&self) -> eyre::Result<Vec<UserOperation>> {
+        let uos = self.sort_user_operations().await?;
+
+        // queued user operations are left out until the nonce gap is filled, they are promoted
+        // once the preceding nonce is consumed on-chain
+        let (uos, queued) = self.split_queued_user_operations(uos).await?;
+        if !queued.is_empty() {
+            debug!(
+                "Skipping {} queued user operations: {:?}",
+                queued.len(),
+                queued.iter().map(|uo| uo.hash).collect::<Vec<_>>()
+            );
+        }
+        Ok(uos)
+    }
+
+    /// Sorts all of the [UserOperations](UserOperation) in the mempool by the configured
+    /// [SortStrategy](SortStrategy), the [UserOperations](UserOperation) of the
+    /// [TrustedSenders](TrustedSenders) are moved to the front
     ///
     /// # Returns
     /// `Result<Vec<UserOperation>, eyre::Error>` - The sorted [UserOperations](UserOperation)
-    pub fn get_sorted_user_operations(&self) -> eyre::Result<Vec<UserOperation>> {
-        self.mempool.get_sorted().map_err(|err| {
+    async fn sort_user_operations(&self) -> eyre::Result<Vec<UserOperation>> {
+        let base_fee = match self.sort_strategy {
+            SortStrategy::PriorityFee | SortStrategy::Fifo => U256::zero(),
+            SortStrategy::GasPrice | SortStrategy::Profitability => self.base_fee_per_gas().await?,
+        };
+        let mut uos = self.mempool.get_sorted_by(self.sort_strategy, base_fee).map_err(|err| {
             format_err!("Getting sorted user operations from mempool failed with error: {err:?}",)
-        })
+        })?;
+        self.trusted_senders.prioritize(&mut uos);
+        Ok(uos)
+    }
+
+    /// Splits the [UserOperations](UserOperation) into the pending ones and the queued ones, i.e.,
+    /// the ones whose nonce is ahead of the on-chain nonce of the sender with a gap that isn't
+    /// filled by other [UserOperations](UserOperation) in the mempool
+    ///
+    /// # Arguments
+    /// `uos` - The [UserOperations](UserOperation) to split
+    ///
+    /// # Returns
+    /// `Result<(Vec<UserOperation>, Vec<UserOperation>), eyre::Error>` - The pending and the queued
+    /// [UserOperations](UserOperation)
+    pub async fn split_queued_user_operations(
+        &self,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<(Vec<UserOperation>, Vec<UserOperation>)> {
+        let keys = uos.iter().map(|uo| (uo.sender, nonce_key(uo.nonce))).collect::<HashSet<_>>();
+
+        let mut nonces = HashMap::new();
+        for (sender, key) in keys {
+            let nonce = self.entry_point.get_nonce(&sender, key).await.map_err(|err| {
+                format_err!("Getting nonce of sender {sender:?} failed with error: {err:?}")
+            })?;
+            nonces.insert((sender, key), nonce);
+        }
+
+        Ok(split_queued(uos, &nonces))
     }
 
     /// Bundles an array of [UserOperations](UserOperation)
@@ -304,18 +938,32 @@ where
     /// [UserOperation](UserOperation) by calling
     /// [UoPool::validate_user_operation](UoPool::validate_user_operation).
     /// If the [UserOperations](UserOperation) passes the validation, push it into the `uos_valid`
-    /// array.
+    /// array. The [UserOperations](UserOperation) that use a signature aggregator are grouped by
+    /// the aggregator.
     ///
     /// # Arguments
     /// `uos` - An array of [UserOperations](UserOperation) to bundle
     ///
     /// # Returns
-    /// `Result<Vec<UserOperation>, eyre::Error>` - The bundled [UserOperations](UserOperation).
+    /// `Result<(Vec<UserOperation>, StorageMap, HashMap<UserOperationHash, StorageAccesses>,
+    /// HashMap<Address, Vec<UserOperationHash>>), eyre::Error>` - The bundled
+    /// [UserOperations](UserOperation), the storage slots read during their validation, the
+    /// storage slots read and written during the validation of each of them and the hashes of the
+    /// aggregated [UserOperations](UserOperation) by aggregator.
+    #[allow(clippy::type_complexity)]
     pub async fn bundle_user_operations(
         &mut self,
         uos: Vec<UserOperation>,
-    ) -> eyre::Result<Vec<UserOperation>> {
+    ) -> eyre::Result<(
+        Vec<UserOperation>,
+        StorageMap,
+        HashMap<UserOperationHash, StorageAccesses>,
+        HashMap<Address, Vec<UserOperationHash>>,
+    )> {
         let mut uos_valid = vec![];
+        let mut uos_by_aggregator: HashMap<Address, Vec<UserOperationHash>> = HashMap::new();
+        let mut storage_map_all = StorageMap::default();
+        let mut storage_accesses = HashMap::new();
         let mut senders = HashSet::new();
         let mut gas_total = U256::zero();
         let mut paymaster_dep = HashMap::new();
@@ -351,6 +999,11 @@ where
                             uo.hash,
                         )
                     })?;
+                    self.publish_event(MempoolEvent::Removed {
+                        uo_hash: uo.hash,
+                        sender: uo.sender,
+                        reason: RemovalReason::Invalidated,
+                    });
                     continue;
                 }
                 (Status::THROTTLED, _) if p_c > THROTTLED_ENTITY_BUNDLE_COUNT => {
@@ -371,16 +1024,18 @@ where
                     UserOperationValidatorMode::Simulation |
                         UserOperationValidatorMode::SimulationTrace,
                 )
+                .instrument(debug_span!("user_operation", uo_hash = %uo.hash))
                 .await;
-            debug!("Second validation for userop {:?} result: {:?}", uo.hash, val_out);
+            debug!(uo_hash = %uo.hash, "Second validation result: {val_out:?}");
 
             match val_out {
                 Ok(val_out) => {
+                    self.grace.reset(&uo.hash);
                     if val_out.valid_after.is_some() {
                         continue;
                     }
 
-                    if let Some(storage_map) = val_out.storage_map {
+                    if let Some(storage_map) = &val_out.storage_map {
                         for addr in storage_map.keys() {
                             if *addr != uo.sender && senders_all.contains(addr) {
                                 continue 'uos;
@@ -421,13 +1076,61 @@ where
                     }
 
                     gas_total = gas_total_new;
+
+                    if let Some(aggregator) = val_out.aggregator {
+                        uos_by_aggregator.entry(aggregator.address).or_default().push(uo.hash);
+                    }
+
+                    // the first read of a slot is the value before the bundle is executed
+                    for (addr, slots) in val_out.storage_map.unwrap_or_default() {
+                        let slots_all = storage_map_all.entry(addr).or_default();
+                        for (slot, value) in slots {
+                            slots_all.entry(slot).or_insert(value);
+                        }
+                    }
+                    if let Some(accesses) = val_out.storage_accesses {
+                        storage_accesses.insert(uo.hash, accesses);
+                    }
                 }
-                Err(_) => {
+                Err(err) if !self.grace.on_failure(&uo.hash, is_transient_failure(&err)) => {
+                    if self.bundle_attempts.on_failure(&uo.hash) {
+                        let reason = match &err {
+                            InvalidMempoolUserOperationError::Simulation(
+                                SimulationError::Validation { inner },
+                            ) => Some(inner.as_str()),
+                            _ => None,
+                        };
+                        self.evict_exhausted_user_operation(&uo, reason)?;
+                        continue;
+                    }
+                    debug!(uo_hash = %uo.hash, "Keeping user operation that failed the 2nd simulation until the next bundle, transient error: {err:?}");
+                    continue;
+                }
+                Err(err) => {
+                    // the entity that caused the failure is treated as if it crashed `handleOps`
+                    if let InvalidMempoolUserOperationError::Simulation(
+                        SimulationError::Validation { inner },
+                    ) = &err
+                    {
+                        if let Some(addr) = failed_validation_entity(&uo.user_operation, inner) {
+                            self.reputation.update_handle_ops_reverted(&addr).map_err(|err| {
+                                format_err!(
+                                    "Updating reputation of {addr:?} with 2nd failed simulation failed with error: {err:?}",
+                                )
+                            })?;
+                        }
+                    }
+
                     self.mempool.remove(&uo.hash).map_err(|err| {
                         format_err!(
                             "Removing a user operation {:?} with 2nd failed simulation failed with error: {err:?}", uo.hash,
                         )
                     })?;
+                    self.publish_event(MempoolEvent::Removed {
+                        uo_hash: uo.hash,
+                        sender: uo.sender,
+                        reason: RemovalReason::Invalidated,
+                    });
                     continue;
                 }
             }
@@ -436,7 +1139,51 @@ where
             senders.insert(uo.sender);
         }
 
-        Ok(uos_valid)
+        Ok((uos_valid, storage_map_all, storage_accesses, uos_by_aggregator))
+    }
+
+    /// Removes the [UserOperations](UserOperation) of the entry point that were not bundled within
+    /// the TTL, counted from the time they were added to the mempool
+    ///
+    /// # Returns
+    /// `Result<Vec<UserOperationHash>, eyre::Error>` - The hashes of the expired
+    /// [UserOperations](UserOperation)
+    pub async fn remove_expired_user_operations(&mut self) -> eyre::Result<Vec<UserOperationHash>> {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => return Ok(vec![]),
+        };
+
+        let uos = self
+            .mempool
+            .get_expired(unix_time(), ttl)
+            .map_err(|err| {
+                format_err!("Getting expired user operations failed with error: {err:?}")
+            })?
+            .into_iter()
+            .filter(|uo| self.user_operation_hash(uo) == uo.hash);
+
+        let mut uo_hashes = vec![];
+        for uo in uos {
+            self.mempool.remove(&uo.hash).map_err(|err| {
+                format_err!(
+                    "Removing an expired user operation {:?} failed with error: {err:?}",
+                    uo.hash,
+                )
+            })?;
+            self.publish_event(MempoolEvent::Removed {
+                uo_hash: uo.hash,
+                sender: uo.sender,
+                reason: RemovalReason::Expired,
+            });
+            uo_hashes.push(uo.hash);
+        }
+
+        if !uo_hashes.is_empty() {
+            info!("Removed {} expired user operations: {uo_hashes:?}", uo_hashes.len());
+        }
+
+        Ok(uo_hashes)
     }
 
     /// Gets the block base fee per gas
@@ -453,9 +1200,44 @@ where
         block.base_fee_per_gas.ok_or(format_err!("No base fee found"))
     }
 
+    /// Simulates the user operation to estimate the verification gas limit, the call gas limit and
+    /// the paymaster verification gas limit
+    async fn simulate_gas_limits(
+        &self,
+        uo: &UserOperation,
+    ) -> Result<(U256, U256, Option<U256>), MempoolError> {
+        estimate_user_op_gas(&uo.user_operation, &self.entry_point).await.map_err(|e| match e {
+            EntryPointError::FailedOp(f) => MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::InvalidUserOperation(
+                    InvalidMempoolUserOperationError::Simulation(SimulationError::Validation {
+                        inner: format!("{f:?}"),
+                    }),
+                ),
+            },
+            EntryPointError::ExecutionReverted(e) => MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::InvalidUserOperation(
+                    InvalidMempoolUserOperationError::Simulation(SimulationError::Execution {
+                        inner: e,
+                    }),
+                ),
+            },
+            EntryPointError::Provider { inner } => {
+                MempoolError { hash: uo.hash, kind: MempoolErrorKind::Provider { inner } }
+            }
+            _ => MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::Other { inner: format!("{e:?}") },
+            },
+        })
+    }
+
     /// Estimates the `verification_gas_limit`, `call_gas_limit` and `pre_verification_gas` for a
     /// user operation. The function is indirectly invoked by the `estimate_user_operation_gas`
-    /// JSON RPC method.
+    /// JSON RPC method. The simulated gas limits are served from the
+    /// [EstimationCache](EstimationCache) if the same user operation was estimated in the
+    /// current block.
     ///
     /// # Arguments
     /// * `uo` - The [UserOperation](UserOperation) to estimate the gas for.
@@ -467,44 +1249,77 @@ where
         &self,
         uo: &UserOperation,
     ) -> Result<UserOperationGasEstimation, MempoolError> {
-        let (verification_gas_limit, call_gas_limit) =
-            estimate_user_op_gas(&uo.user_operation, &self.entry_point).await.map_err(
-                |e| match e {
-                    EntryPointError::FailedOp(f) => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::InvalidUserOperation(
-                            InvalidMempoolUserOperationError::Simulation(
-                                SimulationError::Validation { inner: format!("{f:?}") },
-                            ),
-                        ),
-                    },
-                    EntryPointError::ExecutionReverted(e) => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::InvalidUserOperation(
-                            InvalidMempoolUserOperationError::Simulation(
-                                SimulationError::Execution { inner: e },
-                            ),
-                        ),
-                    },
-                    EntryPointError::Provider { inner } => {
-                        MempoolError { hash: uo.hash, kind: MempoolErrorKind::Provider { inner } }
+        if self.entry_point.version() != EntryPointVersion::V0_6 {
+            return Err(MempoolError {
+                hash: uo.hash,
+                kind: SanityError::UnsupportedEntryPointVersion {
+                    version: self.entry_point.version().as_str().into(),
+                }
+                .into(),
+            });
+        }
+
+        let key = EstimationCache::key(&uo.user_operation);
+        let cached = self.estimation_cache.as_ref().and_then(|cache| cache.get(&key));
+        let (verification_gas_limit, call_gas_limit, paymaster_verification_gas_limit) =
+            match cached {
+                Some(gas) => gas,
+                None => {
+                    let gas = self.simulate_gas_limits(uo).await?;
+                    if let Some(ref cache) = self.estimation_cache {
+                        cache.insert(key, gas);
                     }
-                    _ => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::Other { inner: format!("{e:?}") },
-                    },
-                },
-            )?;
+                    gas
+                }
+            };
+
+        let pre_verification_gas = calculate_pre_verification_gas(
+            uo,
+            &self.entry_point,
+            self.chain,
+            self.pre_verification_gas_overhead,
+        )
+        .await
+        .map_err(|e| match e {
+            EntryPointError::Provider { inner } => {
+                MempoolError { hash: uo.hash, kind: MempoolErrorKind::Provider { inner } }
+            }
+            _ => MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::Other { inner: format!("{e:?}") },
+            },
+        })?;
 
         Ok(UserOperationGasEstimation {
-            pre_verification_gas: Overhead::default()
-                .calculate_pre_verification_gas(uo)
+            pre_verification_gas: pre_verification_gas
                 .saturating_add(PRE_VERIFICATION_SAFE_RESERVE.into()),
             verification_gas_limit,
             call_gas_limit,
+            paymaster_verification_gas_limit,
         })
     }
 
+    /// Validates the [UserOperation](UserOperation) the same way as when it's added into the
+    /// mempool (sanity, simulation and simulation trace checks) and estimates its gas if it's
+    /// valid, so that the validity and the gas are known in one round trip.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to validate and estimate the gas for
+    ///
+    /// # Returns
+    /// `Result<UserOperationGasEstimation, MempoolError>` - The gas estimation result, or the
+    /// reason why the [UserOperation](UserOperation) is rejected.
+    pub async fn validate_and_estimate_user_operation_gas(
+        &self,
+        uo: &UserOperation,
+    ) -> Result<UserOperationGasEstimation, MempoolError> {
+        self.validate_user_operation(uo)
+            .await
+            .map_err(|err| MempoolError { hash: uo.hash, kind: err.into() })?;
+
+        self.estimate_user_operation_gas(uo).await
+    }
+
     /// Filters the events logged from the [EntryPoint](EntryPoint) contract for a given user
     /// operation hash.
     ///
@@ -535,82 +1350,205 @@ where
         Ok(event)
     }
 
+    /// Gets the user operations the [EntryPoint](EntryPoint) executed in the transaction from the
+    /// call trace of the transaction, so that the bundles sent through any contract (e.g., the
+    /// multicall contract) are found. If the transaction can't be traced (e.g., the Ethereum
+    /// execution client doesn't expose the debug API), the input of the transaction is decoded
+    /// instead (the bundles sent to the entry point directly or through the multicall contract).
+    ///
+    /// # Arguments
+    /// * `tx_hash` - The hash of the transaction.
+    ///
+    /// # Returns
+    /// `Result<Vec<UserOperationSigned>, eyre::Error>` - The user operations.
+    pub async fn get_user_operations_in_transaction(
+        &self,
+        tx_hash: H256,
+    ) -> eyre::Result<Vec<UserOperationSigned>> {
+        let eth_client = self.entry_point.eth_client();
+        let trace = eth_client
+            .debug_trace_transaction(
+                tx_hash,
+                GethDebugTracingOptions {
+                    tracer: Some(GethDebugTracerType::BuiltInTracer(
+                        GethDebugBuiltInTracerType::CallTracer,
+                    )),
+                    ..Default::default()
+                },
+            )
+            .await;
+        match trace {
+            Ok(GethTrace::Known(GethTraceFrame::CallTracer(frame))) => {
+                return Ok(parse_from_call_frame(&frame, &self.entry_point.address()));
+            }
+            Ok(_) => debug!("Invalid call trace of transaction {tx_hash:?}, decoding its input"),
+            Err(err) => {
+                debug!("Tracing transaction {tx_hash:?} failed with error: {err:?}, decoding its input")
+            }
+        }
+
+        Ok(eth_client
+            .get_transaction(tx_hash)
+            .await?
+            .and_then(|tx| parse_from_transaction(tx.to, tx.input, &self.entry_point.address()))
+            .unwrap_or_default())
+    }
+
+    /// Finds the user operation in the mined transaction by the call trace of the transaction, so
+    /// that the bundles sent through any contract (e.g., the multicall contract) are found.
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The [UserOperationHash](UserOperationHash) of the user operation.
+    /// * `tx_hash` - The hash of the transaction.
+    ///
+    /// # Returns
+    /// `Result<Option<UserOperationByHash>, eyre::Error>` - The user operation (`None` if the
+    /// transaction is unknown, still pending or doesn't include the user operation).
+    async fn find_user_operation_in_transaction(
+        &self,
+        uo_hash: &UserOperationHash,
+        tx_hash: H256,
+    ) -> eyre::Result<Option<UserOperationByHash>> {
+        let eth_client = self.entry_point.eth_client();
+        let (block_hash, block_number) = match eth_client
+            .get_transaction(tx_hash)
+            .await?
+            .map(|tx| (tx.block_hash, tx.block_number))
+        {
+            Some((Some(block_hash), Some(block_number))) => (block_hash, block_number),
+            _ => return Ok(None),
+        };
+
+        Ok(self
+            .get_user_operations_in_transaction(tx_hash)
+            .await?
+            .into_iter()
+            .find(|uo| self.user_operation_hash(uo) == *uo_hash)
+            .map(|uo| UserOperationByHash {
+                user_operation: uo,
+                entry_point: self.entry_point.address(),
+                transaction_hash: Some(tx_hash),
+                block_hash: Some(block_hash),
+                block_number: Some(block_number),
+            }))
+    }
+
     /// Gets the user operation by hash.
     /// The function is indirectly invoked by the `get_user_operation_by_hash` JSON RPC method.
     ///
     /// # Arguments
     /// * `uo_hash` - The [UserOperationHash](UserOperationHash) to get the user operation for.
+    /// * `tx_hash` - The hash of the transaction that included the user operation, if known (e.g.,
+    ///   the bundler submitted the user operation itself). The [EntryPoint](EntryPoint) logs are
+    ///   scanned if it's not known or the transaction doesn't include the user operation (e.g., the
+    ///   transaction was replaced).
     ///
     /// # Returns
-    /// `Result<UserOperationByHash, eyre::Error>` - The user operation, if any.
+    /// `Result<UserOperationByHash, eyre::Error>` - The user operation, if any. The transaction
+    /// and block fields are `None` if the user operation is still in the mempool.
     pub async fn get_user_operation_by_hash(
         &self,
         uo_hash: &UserOperationHash,
+        tx_hash: Option<H256>,
     ) -> eyre::Result<UserOperationByHash> {
-        let event = self.get_user_operation_event_meta(uo_hash).await?;
-
-        if let Some((event, log_meta)) = event {
-            if let Some((uo, ep)) = self
-                .entry_point
-                .eth_client()
-                .get_transaction(log_meta.transaction_hash)
-                .await?
-                .and_then(|tx| {
-                    let uos = parse_from_input_data(tx.input)?;
-                    let ep = tx.to?;
-                    uos.iter()
-                        .find(|uo| uo.sender == event.sender && uo.nonce == event.nonce)
-                        .map(|uo| (uo.clone(), ep))
-                })
-            {
-                return Ok(UserOperationByHash {
-                    user_operation: uo,
-                    entry_point: ep,
-                    transaction_hash: log_meta.transaction_hash,
-                    block_hash: log_meta.block_hash,
-                    block_number: log_meta.block_number,
-                });
+        if let Some(uo) = self.mempool.get(uo_hash)? {
+            return Ok(UserOperationByHash {
+                user_operation: uo.user_operation,
+                entry_point: self.entry_point.address(),
+                transaction_hash: None,
+                block_hash: None,
+                block_number: None,
+            });
+        }
+
+        if let Some(tx_hash) = tx_hash {
+            if let Some(uo) = self.find_user_operation_in_transaction(uo_hash, tx_hash).await? {
+                return Ok(uo);
+            }
+        }
+
+        if let Some((_, log_meta)) = self.get_user_operation_event_meta(uo_hash).await? {
+            if Some(log_meta.transaction_hash) != tx_hash {
+                if let Some(uo) = self
+                    .find_user_operation_in_transaction(uo_hash, log_meta.transaction_hash)
+                    .await?
+                {
+                    return Ok(uo);
+                }
             }
         }
 
         Err(format_err!("No user operation found"))
     }
 
+    /// Finds the [UserOperationReceipt](UserOperationReceipt) in the receipt of the mined
+    /// transaction.
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The [UserOperationHash](UserOperationHash) of the user operation.
+    /// * `tx_hash` - The hash of the transaction.
+    ///
+    /// # Returns
+    /// `Result<Option<UserOperationReceipt>, eyre::Error>` - The user operation receipt (`None` if
+    /// the transaction is unknown, still pending or doesn't include the user operation).
+    async fn find_user_operation_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+        tx_hash: H256,
+    ) -> eyre::Result<Option<UserOperationReceipt>> {
+        let tx_receipt =
+            match self.entry_point.eth_client().get_transaction_receipt(tx_hash).await? {
+                Some(tx_receipt) if tx_receipt.block_hash.is_some() => tx_receipt,
+                _ => return Ok(None),
+            };
+
+        Ok(find_user_operation_logs(&tx_receipt.logs, &self.entry_point.address(), uo_hash).map(
+            |(event, logs)| UserOperationReceipt {
+                user_operation_hash: *uo_hash,
+                sender: event.sender,
+                nonce: event.nonce,
+                actual_gas_cost: event.actual_gas_cost,
+                actual_gas_used: event.actual_gas_used,
+                success: event.success,
+                tx_receipt,
+                logs,
+                paymaster: if event.paymaster.is_zero() { None } else { Some(event.paymaster) },
+                reason: String::new(), // TODO: this must be set to revert reason
+            },
+        ))
+    }
+
     /// Gets the [UserOperationReceipt](UserOperationReceipt) by hash.
     /// The function is indirectly invoked by the `get_user_operation_receipt` JSON RPC method.
     ///
     /// # Arguments
     /// * `uo_hash` - The [UserOperationHash](UserOperationHash) to get the user operation receipt
     ///   for.
+    /// * `tx_hash` - The hash of the transaction that included the user operation, if known (e.g.,
+    ///   the bundler submitted the user operation itself). The [EntryPoint](EntryPoint) logs are
+    ///   scanned if it's not known or the transaction doesn't include the user operation (e.g., the
+    ///   transaction was replaced).
     ///
     /// # Returns
     /// `Result<UserOperationReceipt, eyre::Error>` - The user operation receipt, if any.
     pub async fn get_user_operation_receipt(
         &self,
         uo_hash: &UserOperationHash,
+        tx_hash: Option<H256>,
     ) -> eyre::Result<UserOperationReceipt> {
-        let event = self.get_user_operation_event_meta(uo_hash).await?;
-
-        if let Some((event, log_meta)) = event {
-            if let Some(tx_receipt) = self
-                .entry_point
-                .eth_client()
-                .get_transaction_receipt(log_meta.transaction_hash)
-                .await?
-            {
-                let uo = self.get_user_operation_by_hash(uo_hash).await?;
-                return Ok(UserOperationReceipt {
-                    user_operation_hash: *uo_hash,
-                    sender: event.sender,
-                    nonce: event.nonce,
-                    actual_gas_cost: event.actual_gas_cost,
-                    actual_gas_used: event.actual_gas_used,
-                    success: event.success,
-                    tx_receipt: tx_receipt.clone(),
-                    logs: tx_receipt.logs.into_iter().collect(),
-                    paymaster: get_address(&uo.user_operation.paymaster_and_data),
-                    reason: String::new(), // TODO: this must be set to revert reason
-                });
+        if let Some(tx_hash) = tx_hash {
+            if let Some(receipt) = self.find_user_operation_receipt(uo_hash, tx_hash).await? {
+                return Ok(receipt);
+            }
+        }
+
+        if let Some((_, log_meta)) = self.get_user_operation_event_meta(uo_hash).await? {
+            if Some(log_meta.transaction_hash) != tx_hash {
+                if let Some(receipt) =
+                    self.find_user_operation_receipt(uo_hash, log_meta.transaction_hash).await?
+                {
+                    return Ok(receipt);
+                }
             }
         }
 
@@ -622,16 +1560,66 @@ where
     ///
     /// # Arguments
     /// * `uo_hash` - The [UserOperationHash](UserOperationHash) to remove the user operation for.
+    /// * `reason` - The [RemovalReason](RemovalReason) published to the subscribers.
     ///
     /// # Returns
     /// `Option<()>` - None if the user operation was successfully removed.
-    pub fn remove_user_operation(&mut self, uo_hash: &UserOperationHash) -> Option<()> {
-        self.mempool.remove(uo_hash).ok();
+    pub fn remove_user_operation(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        reason: RemovalReason,
+    ) -> Option<()> {
+        if let Some(uo) = self.mempool.get(uo_hash).ok().flatten() {
+            if let Ok(true) = self.mempool.remove(uo_hash) {
+                self.publish_event(MempoolEvent::Removed {
+                    uo_hash: *uo_hash,
+                    sender: uo.sender,
+                    reason,
+                });
+            }
+        }
         None
     }
 
-    pub fn remove_user_operation_by_entity(&mut self, entity: &Address) -> Option<()> {
-        self.mempool.remove_by_entity(entity).ok();
+    /// Evicts the [UserOperation](UserOperation) from the mempool on request of the operator
+    /// (e.g., a user operation stuck due to a client bug). The user operation is removed from all
+    /// indexes of the mempool, so it no longer counts towards the limits of its sender.
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The [UserOperationHash](UserOperationHash) of the user operation to evict.
+    ///
+    /// # Returns
+    /// `eyre::Result<bool>` - True if the user operation was in the mempool and was removed.
+    pub fn evict_user_operation(&mut self, uo_hash: &UserOperationHash) -> eyre::Result<bool> {
+        let uo = match self.mempool.get(uo_hash)? {
+            Some(uo) => uo,
+            None => return Ok(false),
+        };
+
+        let removed = self.mempool.remove(uo_hash)?;
+        if removed {
+            self.publish_event(MempoolEvent::Removed {
+                uo_hash: *uo_hash,
+                sender: uo.sender,
+                reason: RemovalReason::Requested,
+            });
+        }
+
+        Ok(removed)
+    }
+
+    pub fn remove_user_operation_by_entity(
+        &mut self,
+        entity: &Address,
+        reason: RemovalReason,
+    ) -> Option<()> {
+        for uo in self.mempool.remove_by_entity(entity).unwrap_or_default() {
+            self.publish_event(MempoolEvent::Removed {
+                uo_hash: uo.hash,
+                sender: uo.sender,
+                reason,
+            });
+        }
         None
     }
 
@@ -642,13 +1630,121 @@ where
     /// # Arguments
     /// * `uo_hashes` - The array of [UserOperationHash](UserOperationHash) to remove the user
     ///   operations for.
+    /// * `reason` - The [RemovalReason](RemovalReason) published to the subscribers.
     ///
     /// # Returns
     /// `Option<()>` - None
-    pub fn remove_user_operations(&mut self, uo_hashes: Vec<UserOperationHash>) {
+    pub fn remove_user_operations(
+        &mut self,
+        uo_hashes: Vec<UserOperationHash>,
+        reason: RemovalReason,
+    ) {
         for uo_hash in uo_hashes {
-            self.remove_user_operation(&uo_hash);
+            self.remove_user_operation(&uo_hash, reason);
+        }
+    }
+
+    /// Removes the pending [UserOperations](UserOperation) invalidated by the included ones, i.e.,
+    /// the user operations of the same sender with the same nonce (e.g., included by another
+    /// bundler in a different version), since the nonce is consumed.
+    ///
+    /// # Arguments
+    /// * `uos_included` - The included [UserOperations](UserOperation)
+    ///
+    /// # Returns
+    /// `Vec<UserOperationHash>` - The hashes of the removed user operations
+    pub fn remove_stale_user_operations(
+        &mut self,
+        uos_included: &[UserOperation],
+    ) -> Vec<UserOperationHash> {
+        let mut removed = vec![];
+        for uo in uos_included {
+            for uo_stale in self.mempool.remove_by_nonce(uo).unwrap_or_default() {
+                debug!(uo_hash = %uo_stale.hash, "Removing user operation with consumed nonce, included: {:?}", uo.hash);
+                self.publish_event(MempoolEvent::Removed {
+                    uo_hash: uo_stale.hash,
+                    sender: uo_stale.sender,
+                    reason: RemovalReason::Invalidated,
+                });
+                removed.push(uo_stale.hash);
+            }
+        }
+        removed
+    }
+
+    /// Removes the [UserOperations](UserOperation) that failed the re-simulation right before
+    /// being bundled or failed in the submitted bundle. The entity that caused the failure is
+    /// treated as if it crashed `handleOps`. The user operations that failed because of a
+    /// possibly transient condition are kept within the [ValidationGrace](ValidationGrace).
+    ///
+    /// # Arguments
+    /// * `uos_failed` - The [UserOperationHash](UserOperationHash) of each failed user operation
+    ///   paired with the revert reason.
+    ///
+    /// # Returns
+    /// `Result<(), eyre::Error>` - Error if updating the reputation fails.
+    pub fn remove_failed_user_operations(
+        &mut self,
+        uos_failed: Vec<(UserOperationHash, String)>,
+    ) -> eyre::Result<()> {
+        for (uo_hash, reason) in uos_failed {
+            if !self.grace.on_failure(&uo_hash, is_transient_reason(&reason)) {
+                if self.bundle_attempts.on_failure(&uo_hash) {
+                    if let Some(uo) = self.mempool.get(&uo_hash)? {
+                        self.evict_exhausted_user_operation(&uo, Some(&reason))?;
+                    }
+                    continue;
+                }
+                debug!(uo_hash = %uo_hash, "Keeping failed user operation until the next bundle, transient error: {reason}");
+                continue;
+            }
+
+            if let Some(uo) = self.mempool.get(&uo_hash)? {
+                if let Some(addr) = failed_validation_entity(&uo.user_operation, &reason) {
+                    self.reputation.update_handle_ops_reverted(&addr).map_err(|err| {
+                        format_err!(
+                            "Updating reputation of {addr:?} with failed re-simulation failed with error: {err:?}",
+                        )
+                    })?;
+                }
+            }
+
+            self.remove_user_operation(&uo_hash, RemovalReason::Invalidated);
         }
+
+        Ok(())
+    }
+
+    /// Evicts the [UserOperation](UserOperation) that exhausted its bundle attempts and penalizes
+    /// the entity that caused the last failure as if it crashed `handleOps`. No entity is
+    /// penalized if the `FailedOp` reason doesn't name it.
+    ///
+    /// # Arguments
+    /// * `uo` - The [UserOperation](UserOperation) to evict
+    /// * `reason` - The `FailedOp` reason of the last failure (if any)
+    ///
+    /// # Returns
+    /// `eyre::Result<()>` - Error if the reputation couldn't be updated
+    fn evict_exhausted_user_operation(
+        &mut self,
+        uo: &UserOperation,
+        reason: Option<&str>,
+    ) -> eyre::Result<()> {
+        match reason.and_then(|reason| failed_validation_entity(&uo.user_operation, reason)) {
+            Some(addr) => {
+                info!(uo_hash = %uo.hash, "Evicting user operation that exhausted the bundle attempts, penalizing {addr:?}");
+                self.reputation.update_handle_ops_reverted(&addr).map_err(|err| {
+                    format_err!(
+                        "Updating reputation of {addr:?} with exhausted bundle attempts failed with error: {err:?}",
+                    )
+                })?;
+            }
+            None => {
+                info!(uo_hash = %uo.hash, "Evicting user operation that exhausted the bundle attempts");
+            }
+        }
+        self.remove_user_operation(&uo.hash, RemovalReason::Invalidated);
+        Ok(())
     }
 
     /// Gets the [StakeInfoResponse](StakeInfoResponse) for entity
@@ -1,6 +1,19 @@
-use ethers::types::{Address, H256, U256};
-use silius_primitives::{simulation::CodeHash, UserOperationSigned};
-use std::{collections::HashMap, ops::Deref};
+use crate::sort::effective_gas_price;
+use alloy_chains::Chain;
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, H256, U256},
+};
+use silius_contracts::{EntryPoint, EntryPointError, GasPriceOracleAPI};
+use silius_primitives::{
+    chain::ChainExt, constants::op_stack::GAS_PRICE_ORACLE, simulation::CodeHash, UserOperation,
+    UserOperationSigned,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ops::Deref,
+    str::FromStr,
+};
 
 pub fn equal_code_hashes(hashes: &[CodeHash], hashes_prev: &Vec<CodeHash>) -> bool {
     if hashes_prev.len() != hashes.len() {
@@ -86,6 +99,59 @@ impl Overhead {
     }
 }
 
+/// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) on the given
+/// chain. On OP-stack chains, the L1 data fee of the packed user operation is queried from the gas
+/// price oracle and converted to L2 gas at the gas price the user operation pays (the base fee if
+/// the user operation has no fees set, e.g., during gas estimation).
+///
+/// # Arguments
+/// `uo` - The [UserOperation](UserOperationSigned) to calculate the pre-verification gas for
+/// `entry_point` - The [EntryPoint](EntryPoint) the user operation is sent to
+/// `chain` - The chain the bundler is running on
+/// `overhead` - Gas added to the calculated pre-verification gas (e.g., the overhead of the
+/// [ChainProfile](silius_primitives::chain::ChainProfile))
+///
+/// # Returns
+/// The pre-verification gas of the [UserOperation](UserOperationSigned), including the L1 data fee
+pub async fn calculate_pre_verification_gas<M: Middleware + 'static>(
+    uo: &UserOperationSigned,
+    entry_point: &EntryPoint<M>,
+    chain: Chain,
+    overhead: U256,
+) -> Result<U256, EntryPointError> {
+    let pre_gas = Overhead::default().calculate_pre_verification_gas(uo).saturating_add(overhead);
+    if !chain.is_op_stack() {
+        return Ok(pre_gas);
+    }
+
+    let eth_client = entry_point.eth_client();
+    let base_fee = eth_client
+        .get_block(BlockNumber::Latest)
+        .await
+        .map_err(|err| EntryPointError::Provider { inner: err.to_string() })?
+        .and_then(|block| block.base_fee_per_gas)
+        .ok_or(EntryPointError::Provider { inner: "No base fee found".into() })?;
+
+    let oracle = GasPriceOracleAPI::new(
+        Address::from_str(GAS_PRICE_ORACLE).expect("Gas price oracle address is valid"),
+        eth_client,
+    );
+    let version = entry_point.version();
+    let l1_fee = oracle
+        .get_l1_fee(uo.pack_with_version(version))
+        .call()
+        .await
+        .map_err(|err| EntryPointError::Provider { inner: err.to_string() })?;
+
+    let gas_price = if uo.max_fee_per_gas.is_zero() {
+        base_fee
+    } else {
+        uo.max_fee_per_gas.min(base_fee.saturating_add(uo.max_priority_fee_per_gas))
+    };
+
+    Ok(pre_gas.saturating_add(div_ceil(l1_fee, gas_price)))
+}
+
 /// Helper function to calculate the valid gas of a [UserOperation](UserOperation)
 /// The function is invoked by the
 /// [check_valid_gas](crates::uopool::validate::sanity::check_valid_gas) method.
@@ -106,6 +172,74 @@ pub fn calculate_valid_gas(gas_price: U256, gas_incr_perc: U256) -> U256 {
     div_ceil(numerator, denominator)
 }
 
+/// Helper function to check whether a [UserOperation](UserOperationSigned) can replace the previous
+/// [UserOperation](UserOperationSigned) with the same sender and nonce.
+/// Both new fees have to be at least the previous fees increased by the gas increase percentage,
+/// but they are not required to increase by the same amount.
+///
+/// # Arguments
+/// `uo_prev` - The [UserOperation](UserOperationSigned) currently in the mempool
+/// `uo` - The replacement [UserOperation](UserOperationSigned)
+/// `gas_incr_perc` - The gas increase percentage
+///
+/// # Returns
+/// True if the replacement is valid, otherwise false
+pub fn is_valid_replacement(
+    uo_prev: &UserOperationSigned,
+    uo: &UserOperationSigned,
+    gas_incr_perc: U256,
+) -> bool {
+    uo.max_fee_per_gas >= calculate_valid_gas(uo_prev.max_fee_per_gas, gas_incr_perc) &&
+        uo.max_priority_fee_per_gas >=
+            calculate_valid_gas(uo_prev.max_priority_fee_per_gas, gas_incr_perc)
+}
+
+/// Helper function to check whether a [UserOperation](UserOperationSigned) only refreshes the
+/// signature of the previous [UserOperation](UserOperationSigned) with the same sender and nonce,
+/// i.e., all the other fields (including the fees) are equal.
+///
+/// # Arguments
+/// `uo_prev` - The [UserOperation](UserOperationSigned) currently in the mempool
+/// `uo` - The resubmitted [UserOperation](UserOperationSigned)
+///
+/// # Returns
+/// True if only the signature changed, otherwise false
+pub fn is_signature_refresh(uo_prev: &UserOperationSigned, uo: &UserOperationSigned) -> bool {
+    uo_prev.signature != uo.signature &&
+        *uo_prev == UserOperationSigned { signature: uo_prev.signature.clone(), ..uo.clone() }
+}
+
+/// Helper function to calculate by how much the fees of a replacement
+/// [UserOperation](UserOperationSigned) were bumped, i.e., the lower percentage increase of the
+/// max fee per gas and the max priority fee per gas (fees that were zero before are ignored).
+///
+/// # Arguments
+/// `uo_prev` - The [UserOperation](UserOperationSigned) currently in the mempool
+/// `uo` - The replacement [UserOperation](UserOperationSigned)
+///
+/// # Returns
+/// The bump percentage (negative if the fees were lowered)
+pub fn calculate_fee_bump_perc(uo_prev: &UserOperationSigned, uo: &UserOperationSigned) -> f64 {
+    let bump_perc = |prev: U256, new: U256| {
+        if prev.is_zero() {
+            return None;
+        }
+        let perc = |diff: U256| {
+            (diff.saturating_mul(U256::from(100)) / prev).min(U256::from(u64::MAX)).as_u64() as f64
+        };
+        Some(if new >= prev { perc(new - prev) } else { -perc(prev - new) })
+    };
+
+    [
+        bump_perc(uo_prev.max_fee_per_gas, uo.max_fee_per_gas),
+        bump_perc(uo_prev.max_priority_fee_per_gas, uo.max_priority_fee_per_gas),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(f64::min)
+    .unwrap_or_default()
+}
+
 /// Helper function to calculate the call gas limit of a [UserOperation](UserOperation)
 /// The function is invoked by the
 /// [estimate_user_operation_gas](crates::uopool::estimate::estimate_user_operation_gas) method.
@@ -123,6 +257,114 @@ pub fn calculate_call_gas_limit(paid: U256, pre_op_gas: U256, fee_per_gas: U256)
     div_ceil(paid, fee_per_gas).saturating_sub(pre_op_gas).saturating_add(Overhead::default().fixed)
 }
 
+/// Helper function to find the entity responsible for a failed validation of a
+/// [UserOperation](UserOperationSigned) from the `FailedOp` reason of the entry point
+/// (`AA1x` errors are caused by the factory and `AA3x` errors by the paymaster).
+///
+/// # Arguments
+/// `uo` - The [UserOperation](UserOperationSigned) that failed the validation
+/// `reason` - The `FailedOp` reason
+///
+/// # Returns
+/// The address of the factory or the paymaster, if it caused the failure
+pub fn failed_validation_entity(uo: &UserOperationSigned, reason: &str) -> Option<Address> {
+    let (_, factory, paymaster) = uo.get_entities();
+    if reason.starts_with("AA3") {
+        paymaster
+    } else if reason.starts_with("AA1") {
+        factory
+    } else {
+        None
+    }
+}
+
+/// Returns the key of the nonce of a [UserOperation](UserOperationSigned), i.e., the upper 192
+/// bits (the lower 64 bits are the sequence number of the key)
+pub fn nonce_key(nonce: U256) -> U256 {
+    nonce >> 64
+}
+
+/// Helper function to split the [UserOperations](UserOperation) into the pending ones and the
+/// queued ones. A user operation is queued if there is a gap between its nonce and the on-chain
+/// nonce of its sender (for the same nonce key) that isn't filled by other user operations, so it
+/// can't be included in a bundle yet. The order of the user operations is preserved.
+///
+/// # Arguments
+/// `uos` - The [UserOperations](UserOperation) to split
+/// `nonces` - The on-chain nonces by sender and nonce key
+///
+/// # Returns
+/// The pending and the queued [UserOperations](UserOperation)
+pub fn split_queued(
+    uos: Vec<UserOperation>,
+    nonces: &HashMap<(Address, U256), U256>,
+) -> (Vec<UserOperation>, Vec<UserOperation>) {
+    let mut by_key: HashMap<(Address, U256), Vec<U256>> = HashMap::new();
+    for uo in uos.iter() {
+        by_key.entry((uo.sender, nonce_key(uo.nonce))).or_default().push(uo.nonce);
+    }
+
+    // the next nonce of each sender and key that is filled by the user operations in the mempool
+    let next = by_key
+        .into_iter()
+        .map(|(key, mut seq)| {
+            seq.sort();
+            let mut next = nonces.get(&key).cloned().unwrap_or(seq[0]);
+            for nonce in seq {
+                if nonce == next {
+                    next = next.saturating_add(U256::one());
+                }
+            }
+            (key, next)
+        })
+        .collect::<HashMap<_, _>>();
+
+    uos.into_iter().partition(|uo| {
+        next.get(&(uo.sender, nonce_key(uo.nonce))).map_or(true, |next| uo.nonce < *next)
+    })
+}
+
+/// Helper function to find the [UserOperations](UserOperation) a new one can evict from a full
+/// mempool, i.e., the ones with a lower effective gas price, from the lowest effective gas price.
+/// Only the user operation with the highest nonce of each sender (and nonce key) can be evicted and
+/// the ones of the same sender are kept, so that no nonce gap is created.
+///
+/// # Arguments
+/// `uos` - The [UserOperations](UserOperation) in the mempool
+/// `uo` - The new [UserOperation](UserOperation)
+/// `base_fee` - The base fee per gas of the latest block
+///
+/// # Returns
+/// The [UserOperations](UserOperation) that can be evicted, the first one should be evicted first
+pub fn eviction_candidates(
+    uos: Vec<UserOperation>,
+    uo: &UserOperation,
+    base_fee: U256,
+) -> Vec<UserOperation> {
+    let gas_price = effective_gas_price(uo, base_fee);
+
+    let mut highest: HashMap<(Address, U256), UserOperation> = HashMap::new();
+    for uo_prev in uos.into_iter().filter(|uo_prev| uo_prev.sender != uo.sender) {
+        match highest.entry((uo_prev.sender, nonce_key(uo_prev.nonce))) {
+            Entry::Occupied(mut entry) if entry.get().nonce < uo_prev.nonce => {
+                entry.insert(uo_prev);
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(uo_prev);
+            }
+        }
+    }
+
+    let mut uos = highest
+        .into_values()
+        .map(|uo_prev| (effective_gas_price(&uo_prev, base_fee), uo_prev))
+        .filter(|(gas_price_prev, _)| *gas_price_prev < gas_price)
+        .collect::<Vec<_>>();
+    uos.sort_by_key(|(gas_price_prev, _)| *gas_price_prev);
+    uos.into_iter().map(|(_, uo_prev)| uo_prev).collect()
+}
+
 /// Performs division and rounds up to the nearest integer.
 ///
 /// This function takes a numerator and a denominator of type `U256`,
@@ -150,15 +392,23 @@ fn div_ceil(numerator: U256, denominator: U256) -> U256 {
 pub mod tests {
     use super::*;
     use crate::{
-        mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+        mempool::{
+            unix_time, Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct,
+        },
         reputation::{HashSetOp, ReputationEntryOp},
         Reputation,
     };
-    use ethers::types::{Address, Bytes, H256, U256};
+    use alloy_chains::NamedChain;
+    use ethers::{
+        abi::AbiEncode,
+        providers::Provider,
+        types::{Address, Block, Bytes, H256, U256},
+    };
     use silius_primitives::{
         reputation::{ReputationEntry, Status},
         UserOperation, UserOperationHash, UserOperationSigned,
     };
+    use std::sync::Arc;
 
     #[test]
     fn pre_verification_gas_calculation() {
@@ -261,6 +511,60 @@ pub mod tests {
         let _ = gas_oh.calculate_pre_verification_gas(&uo);
     }
 
+    #[tokio::test]
+    async fn pre_verification_gas_calculation_on_op_stack() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(300),
+            max_priority_fee_per_gas: U256::from(50),
+            ..UserOperationSigned::random()
+        };
+        let pre_gas = Overhead::default().calculate_pre_verification_gas(&uo);
+
+        // no L1 data fee outside of OP-stack chains
+        assert_eq!(
+            calculate_pre_verification_gas(
+                &uo,
+                &entry_point,
+                Chain::from(NamedChain::Sepolia),
+                U256::zero()
+            )
+            .await
+            .unwrap(),
+            pre_gas
+        );
+
+        // the overhead is added on top
+        assert_eq!(
+            calculate_pre_verification_gas(
+                &uo,
+                &entry_point,
+                Chain::from(NamedChain::Sepolia),
+                U256::from(1_000)
+            )
+            .await
+            .unwrap(),
+            pre_gas + 1_000
+        );
+
+        // L1 data fee of 100000 wei paid at gas price min(300, 200 + 50)
+        mock.push(Bytes::from(U256::from(100_000).encode())).unwrap();
+        mock.push(Block::<H256> { base_fee_per_gas: Some(200.into()), ..Default::default() })
+            .unwrap();
+        assert_eq!(
+            calculate_pre_verification_gas(
+                &uo,
+                &entry_point,
+                Chain::from(NamedChain::Optimism),
+                U256::zero()
+            )
+            .await
+            .unwrap(),
+            pre_gas + 400
+        );
+    }
+
     #[test]
     fn valid_gas_calculation_when_no_round_up_case() {
         let gas_price = U256::from(100);
@@ -276,6 +580,89 @@ pub mod tests {
         assert_eq!(calculate_valid_gas(gas_price, gas_incr_perc), 12.into());
     }
 
+    #[test]
+    fn replacement_with_exactly_bumped_fees() {
+        let uo_prev = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(10),
+            ..Default::default()
+        };
+        let uo = UserOperationSigned {
+            max_fee_per_gas: calculate_valid_gas(uo_prev.max_fee_per_gas, U256::from(10)),
+            max_priority_fee_per_gas: calculate_valid_gas(
+                uo_prev.max_priority_fee_per_gas,
+                U256::from(10),
+            ),
+            ..Default::default()
+        };
+        assert!(is_valid_replacement(&uo_prev, &uo, U256::from(10)));
+
+        let uo = UserOperationSigned {
+            max_fee_per_gas: uo.max_fee_per_gas - U256::one(),
+            max_priority_fee_per_gas: uo.max_priority_fee_per_gas,
+            ..Default::default()
+        };
+        assert!(!is_valid_replacement(&uo_prev, &uo, U256::from(10)));
+    }
+
+    #[test]
+    fn replacement_with_one_fee_bumped_more() {
+        let uo_prev = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(10),
+            ..Default::default()
+        };
+
+        // max fee stays at the bumped threshold, priority fee is raised substantially
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(110),
+            max_priority_fee_per_gas: U256::from(50),
+            ..Default::default()
+        };
+        assert!(is_valid_replacement(&uo_prev, &uo, U256::from(10)));
+
+        // max fee is the same as before
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(50),
+            ..Default::default()
+        };
+        assert!(!is_valid_replacement(&uo_prev, &uo, U256::from(10)));
+    }
+
+    #[test]
+    fn fee_bump_percentage() {
+        let uo_prev = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(10),
+            ..Default::default()
+        };
+
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(125),
+            max_priority_fee_per_gas: U256::from(50),
+            ..Default::default()
+        };
+        assert_eq!(calculate_fee_bump_perc(&uo_prev, &uo), 25.0);
+
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(90),
+            max_priority_fee_per_gas: U256::from(11),
+            ..Default::default()
+        };
+        assert_eq!(calculate_fee_bump_perc(&uo_prev, &uo), -10.0);
+
+        // the zero priority fee can't be bumped by a percentage
+        let uo_prev =
+            UserOperationSigned { max_fee_per_gas: U256::from(100), ..Default::default() };
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(110),
+            max_priority_fee_per_gas: U256::from(1),
+            ..Default::default()
+        };
+        assert_eq!(calculate_fee_bump_perc(&uo_prev, &uo), 10.0);
+    }
+
     #[test]
     fn call_gas_limit_calculation() {
         let paid = U256::from(100);
@@ -380,6 +767,11 @@ pub mod tests {
 
         assert_eq!(mempool.get_all().unwrap().len(), 0);
         assert_eq!(mempool.get_all_by_sender(&senders[0]).len(), 0);
+        for sender in senders.iter() {
+            assert_eq!(mempool.get_number_by_sender(sender), 0);
+            assert_eq!(mempool.get_number_by_entity(sender), 0);
+        }
+        assert!(!mempool.has_code_hashes(&uo_hash).unwrap());
 
         for i in 0..3 {
             uo = UserOperationSigned {
@@ -405,6 +797,40 @@ pub mod tests {
         assert_eq!(sorted.len(), 3);
         assert_eq!(mempool.clear(), ());
 
+        let paymaster = Address::random();
+        for i in 0..2 {
+            uo = UserOperationSigned {
+                sender: senders[i],
+                paymaster_and_data: Bytes::from(paymaster.as_bytes().to_vec()),
+                ..UserOperationSigned::random()
+            };
+            uo_hash = uo.hash(&ep, chain_id);
+            mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo)).unwrap();
+        }
+
+        let factory = Address::random();
+        uo = UserOperationSigned {
+            sender: senders[0],
+            nonce: U256::from(1),
+            init_code: Bytes::from(factory.as_bytes().to_vec()),
+            ..UserOperationSigned::random()
+        };
+        let uo_factory_hash = uo.hash(&ep, chain_id);
+        mempool.add(UserOperation::from_user_operation_signed(uo_factory_hash, uo)).unwrap();
+
+        let composition = mempool.composition().unwrap();
+        assert_eq!(composition.total, 3);
+        assert_eq!(composition.by_sender, HashMap::from([(senders[0], 2), (senders[1], 1)]));
+        assert_eq!(composition.by_paymaster, HashMap::from([(paymaster, 2)]));
+        assert_eq!(composition.by_factory, HashMap::from([(factory, 1)]));
+        assert!(mempool.remove(&uo_factory_hash).unwrap());
+
+        let removed = mempool.remove_by_entity(&paymaster).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert!(removed.iter().any(|uo| uo.hash == uo_hash));
+        assert_eq!(mempool.get_number_by_entity(&paymaster), 0);
+        assert_eq!(mempool.get_all().unwrap().len(), 0);
+
         uo = UserOperationSigned {
             sender: Address::random(),
             nonce: U256::from(0),
@@ -423,6 +849,72 @@ pub mod tests {
 
         let code_hashes_get = mempool.get_code_hashes(&uo_hash).unwrap();
         assert_eq!(code_hashes, code_hashes_get);
+
+        mempool.clear();
+
+        let uo = UserOperationSigned::random();
+        uo_hash = uo.hash(&ep, chain_id);
+        let now = unix_time();
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo.clone())).unwrap();
+        // the TTL counts from the time the user operation was added
+        assert!(mempool.get_expired(now + 9, 10).unwrap().is_empty());
+        assert_eq!(
+            mempool
+                .get_expired(now + 20, 10)
+                .unwrap()
+                .into_iter()
+                .map(|uo| uo.hash)
+                .collect::<Vec<_>>(),
+            vec![uo_hash]
+        );
+        assert!(mempool.remove(&uo_hash).unwrap());
+        assert_eq!(mempool.get_number_by_sender(&uo.sender), 0);
+        assert!(mempool.get_expired(now + 100, 10).unwrap().is_empty());
+
+        // an identical resubmission is acknowledged with the existing hash
+        let uo = UserOperationSigned {
+            sender: Address::random(),
+            nonce: U256::from(0),
+            max_priority_fee_per_gas: U256::from(1),
+            ..UserOperationSigned::random()
+        };
+        uo_hash = uo.hash(&ep, chain_id);
+        assert!(!mempool.contains(&uo_hash).unwrap());
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo.clone())).unwrap();
+        assert!(mempool.contains(&uo_hash).unwrap());
+        assert_eq!(
+            mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo.clone())).unwrap(),
+            uo_hash
+        );
+        assert_eq!(mempool.get_all().unwrap().len(), 1);
+        assert_eq!(mempool.get_number_by_sender(&uo.sender), 1);
+
+        // a replacement (same sender and nonce, different fields) has a different hash
+        let uo_replacement =
+            UserOperationSigned { max_priority_fee_per_gas: U256::from(2), ..uo.clone() };
+        let uo_replacement_hash = uo_replacement.hash(&ep, chain_id);
+        assert_ne!(uo_replacement_hash, uo_hash);
+        assert!(!mempool.contains(&uo_replacement_hash).unwrap());
+        assert_eq!(
+            mempool
+                .get_prev_by_sender(&UserOperation::from_user_operation_signed(
+                    uo_replacement_hash,
+                    uo_replacement.clone()
+                ))
+                .map(|uo| uo.hash),
+            Some(uo_hash)
+        );
+
+        // the inclusion of the replacement consumes the nonce of the pending user operation
+        let removed = mempool
+            .remove_by_nonce(&UserOperation::from_user_operation_signed(
+                uo_replacement_hash,
+                uo_replacement,
+            ))
+            .unwrap();
+        assert_eq!(removed.into_iter().map(|uo| uo.hash).collect::<Vec<_>>(), vec![uo_hash]);
+        assert_eq!(mempool.get_number_by_sender(&uo.sender), 0);
+        mempool.clear();
     }
 
     pub fn reputation_test_case<H, R>(mut reputation: Reputation<H, R>)
@@ -474,6 +966,10 @@ pub mod tests {
         assert_eq!(reputation.increment_included(&addrs[2]).unwrap(), ());
         assert_eq!(reputation.increment_included(&addrs[2]).unwrap(), ());
         assert_eq!(reputation.increment_included(&addrs[3]).unwrap(), ());
+        assert_eq!(reputation.increment_included(&addrs[4]).unwrap(), ());
+        assert_eq!(reputation.decrement_included(&addrs[4]).unwrap(), ());
+        assert_eq!(reputation.decrement_included(&addrs[4]).unwrap(), ());
+        assert_eq!(reputation.get(&addrs[4]).unwrap().uo_included, 0);
 
         assert_eq!(reputation.update_handle_ops_reverted(&addrs[3]).unwrap(), ());
 
@@ -486,5 +982,106 @@ pub mod tests {
             assert_eq!(reputation.increment_seen(&addrs[3]).unwrap(), ());
         }
         assert_eq!(Status::from(reputation.get_status(&addrs[3]).unwrap()), Status::BANNED);
+
+        let all = reputation.get_all().unwrap();
+        assert!(all.windows(2).all(|w| w[0].address < w[1].address));
+    }
+
+    #[test]
+    fn failed_validation_entity_from_reason() {
+        let factory = Address::random();
+        let paymaster = Address::random();
+        let uo = UserOperationSigned {
+            init_code: Bytes::from(factory.as_bytes().to_vec()),
+            paymaster_and_data: Bytes::from(paymaster.as_bytes().to_vec()),
+            ..UserOperationSigned::random()
+        };
+
+        assert_eq!(failed_validation_entity(&uo, "AA13 initCode failed or OOG"), Some(factory));
+        assert_eq!(failed_validation_entity(&uo, "AA33 reverted (or OOG)"), Some(paymaster));
+        assert_eq!(failed_validation_entity(&uo, "AA23 reverted (or OOG)"), None);
+        assert_eq!(
+            failed_validation_entity(&UserOperationSigned::random(), "AA33 reverted (or OOG)"),
+            None
+        );
+    }
+
+    #[test]
+    fn split_queued_user_operations() {
+        let sender = Address::random();
+        let key = U256::from(1) << 64;
+        let uo = |sender: Address, nonce: U256| {
+            UserOperation::from_user_operation_signed(
+                UserOperationHash(H256::random()),
+                UserOperationSigned { sender, nonce, ..UserOperationSigned::random() },
+            )
+        };
+        // nonces 3 and 4 directly follow the on-chain nonce, 6 waits for 5
+        let uos = vec![
+            uo(sender, 6.into()),
+            uo(sender, 3.into()),
+            uo(sender, 4.into()),
+            uo(sender, key + 1),
+            uo(sender, key),
+        ];
+        let nonces =
+            HashMap::from([((sender, U256::zero()), U256::from(3)), ((sender, U256::one()), key)]);
+        let hashes = |uos: Vec<UserOperation>| uos.iter().map(|uo| uo.hash).collect::<Vec<_>>();
+
+        let (pending, queued) = split_queued(uos.clone(), &nonces);
+        assert_eq!(hashes(pending), hashes(uos[1..].to_vec()));
+        assert_eq!(hashes(queued), vec![uos[0].hash]);
+
+        // promoted once the preceding nonce is consumed on-chain
+        let nonces = HashMap::from([((sender, U256::zero()), U256::from(6))]);
+        let (pending, queued) = split_queued(vec![uos[0].clone()], &nonces);
+        assert_eq!(hashes(pending), vec![uos[0].hash]);
+        assert!(queued.is_empty());
+    }
+
+    #[test]
+    fn eviction_candidates_by_effective_gas_price() {
+        let sender = Address::random();
+        let uo = |sender: Address, max_priority_fee: u64| {
+            UserOperation::from_user_operation_signed(
+                UserOperationHash(H256::random()),
+                UserOperationSigned {
+                    sender,
+                    max_fee_per_gas: U256::from(100),
+                    max_priority_fee_per_gas: U256::from(max_priority_fee),
+                    ..UserOperationSigned::random()
+                },
+            )
+        };
+        let uos = vec![
+            uo(Address::random(), 30),
+            uo(Address::random(), 10),
+            uo(Address::random(), 50),
+            uo(sender, 5),
+            uo(Address::random(), 20),
+        ];
+
+        // the effective gas price of the new one is 40 (max fee minus the base fee)
+        let candidates = eviction_candidates(uos.clone(), &uo(sender, 50), U256::from(60));
+        assert_eq!(
+            candidates.iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            vec![uos[1].hash, uos[4].hash, uos[0].hash]
+        );
+        assert!(eviction_candidates(uos, &uo(sender, 10), U256::from(60)).is_empty());
+
+        // only the user operation with the highest nonce of a sender is evicted
+        let sender_other = Address::random();
+        let uos = vec![
+            UserOperation::from_user_operation_signed(
+                UserOperationHash(H256::random()),
+                UserOperationSigned { nonce: U256::from(1), ..uo(sender_other, 10).user_operation },
+            ),
+            UserOperation::from_user_operation_signed(
+                UserOperationHash(H256::random()),
+                UserOperationSigned { nonce: U256::from(2), ..uo(sender_other, 20).user_operation },
+            ),
+        ];
+        let candidates = eviction_candidates(uos.clone(), &uo(sender, 50), U256::from(60));
+        assert_eq!(candidates.iter().map(|uo| uo.hash).collect::<Vec<_>>(), vec![uos[1].hash]);
     }
 }
@@ -0,0 +1,73 @@
+use ethers::types::{Address, Bytes};
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc};
+
+/// Cache of the contract code fetched during the validation of user operations. The code is only
+/// valid until the next block, so the cache is cleared on every new block. The clones share the
+/// same cache.
+#[derive(Clone, Debug)]
+pub struct CodeCache {
+    codes: Arc<Mutex<HashMap<Address, Bytes>>>,
+    /// Max number of cached addresses (the code of other addresses is not cached once full)
+    capacity: usize,
+}
+
+impl CodeCache {
+    /// Creates a new [CodeCache](CodeCache)
+    ///
+    /// # Arguments
+    /// * `capacity` - Max number of cached addresses
+    ///
+    /// # Returns
+    /// * `CodeCache` - The [CodeCache](CodeCache)
+    pub fn new(capacity: usize) -> Self {
+        Self { codes: Default::default(), capacity }
+    }
+
+    /// Gets the cached code of the address
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the contract
+    ///
+    /// # Returns
+    /// * `Option<Bytes>` - The code (`None` if it's not cached)
+    pub fn get(&self, addr: &Address) -> Option<Bytes> {
+        self.codes.lock().get(addr).cloned()
+    }
+
+    /// Caches the code of the address (unless the cache is full)
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the contract
+    /// * `code` - The code of the contract
+    pub fn insert(&self, addr: Address, code: Bytes) {
+        let mut codes = self.codes.lock();
+        if codes.len() < self.capacity {
+            codes.insert(addr, code);
+        }
+    }
+
+    /// Clears the cache (called on every new block)
+    pub fn clear(&self) {
+        self.codes.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn code_cache_is_bounded() {
+        let cache = CodeCache::new(1);
+        let (first, second) = (Address::random(), Address::random());
+
+        cache.insert(first, Bytes::from(vec![1]));
+        cache.insert(second, Bytes::from(vec![2]));
+        assert_eq!(cache.get(&first), Some(Bytes::from(vec![1])));
+        assert_eq!(cache.get(&second), None);
+
+        cache.clone().clear();
+        assert_eq!(cache.get(&first), None);
+    }
+}
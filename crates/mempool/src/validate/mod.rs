@@ -6,21 +6,31 @@ use crate::{
 };
 use alloy_chains::Chain;
 use enumset::{EnumSet, EnumSetType};
-use ethers::{providers::Middleware, types::U256};
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, Bytes, U256},
+};
+use parking_lot::Mutex;
 use silius_contracts::{entry_point::SimulateValidationResult, tracer::JsTracerFrame, EntryPoint};
 use silius_primitives::{
     constants::validation::entities::NUMBER_OF_LEVELS,
     reputation::StakeInfo,
-    simulation::{CodeHash, StorageMap},
+    simulation::{CodeHash, StorageAccesses, StorageMap},
     UserOperation, UserOperationHash,
 };
+use std::{collections::HashMap, future::Future, time::Duration};
 
+mod code_cache;
+mod paymaster_allowlist;
 pub mod sanity;
 pub mod simulation;
 pub mod simulation_trace;
 mod utils;
 pub mod validator;
 
+pub use code_cache::CodeCache;
+pub use paymaster_allowlist::PaymasterAllowlist;
+
 /// The outcome of a user operation validation.
 #[derive(Debug, Clone, Default)]
 pub struct UserOperationValidationOutcome {
@@ -29,9 +39,14 @@ pub struct UserOperationValidationOutcome {
     pub verification_gas_limit: U256,
     // Simulation
     pub valid_after: Option<U256>,
+    // the aggregator of the user operation (if any)
+    pub aggregator: Option<StakeInfo>,
+    // the stake infos of the factory, the sender and the paymaster
+    pub stake_info: Option<[StakeInfo; NUMBER_OF_LEVELS]>,
     // Simulation trace
     pub code_hashes: Option<Vec<CodeHash>>,
     pub storage_map: Option<StorageMap>,
+    pub storage_accesses: Option<StorageAccesses>,
     // the block which the user operation is verified on
     pub verified_block: U256,
 }
@@ -64,12 +79,117 @@ pub trait UserOperationValidator: Send + Sync {
         Z: UserOperationCodeHashAct,
         H: HashSetOp,
         R: ReputationEntryOp;
+
+    /// Called on every new block, e.g., to invalidate the chain state cached by the validator
+    fn on_new_block(&self) {}
 }
 
 /// The [UserOperation](UserOperation) sanity check helper trait.
 pub struct SanityHelper<'a, M: Middleware + 'static> {
     entry_point: &'a EntryPoint<M>,
     chain: Chain,
+    code_cache: Option<&'a CodeCache>,
+    /// Deposits and stake infos fetched during the validation (shared by the sanity checks)
+    deposits: Mutex<HashMap<Address, (U256, StakeInfo)>>,
+    /// Block the chain state is read at (the latest block if `None`)
+    block: Option<BlockNumber>,
+    /// Time to wait for each call to the Ethereum execution client
+    timeout: Duration,
+    /// Paymasters allowed to sponsor the user operations
+    paymaster_allowlist: &'a PaymasterAllowlist,
+}
+
+impl<'a, M: Middleware + 'static> SanityHelper<'a, M> {
+    /// Awaits the call to the Ethereum execution client, so that a degraded node doesn't stall the
+    /// validation
+    ///
+    /// # Arguments
+    /// * `call` - The call to the Ethereum execution client
+    ///
+    /// # Returns
+    /// * `T` - The result of the call, or a [Provider](SanityError::Provider) error if the call
+    ///   didn't complete within the timeout
+    pub async fn with_timeout<T>(
+        &self,
+        call: impl Future<Output = Result<T, SanityError>>,
+    ) -> Result<T, SanityError> {
+        tokio::time::timeout(self.timeout, call).await.map_err(|_| SanityError::Provider {
+            inner: format!("call timed out after {}ms", self.timeout.as_millis()),
+        })?
+    }
+
+    /// Gets the code of the address at the pinned block, or in the latest block if no block is
+    /// pinned (from the [CodeCache](CodeCache) if it's enabled)
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the contract
+    ///
+    /// # Returns
+    /// * `Bytes` - The code of the contract (empty if there is no contract)
+    pub async fn get_code(&self, addr: Address) -> Result<Bytes, SanityError> {
+        if let Some(code) = self.code_cache.and_then(|cache| cache.get(&addr)) {
+            return Ok(code);
+        }
+
+        let code = self
+            .with_timeout(async {
+                self.entry_point
+                    .eth_client()
+                    .get_code(addr, self.block.map(Into::into))
+                    .await
+                    .map_err(|e| SanityError::Provider { inner: e.to_string() })
+            })
+            .await?;
+
+        if let Some(cache) = self.code_cache {
+            cache.insert(addr, code.clone());
+        }
+
+        Ok(code)
+    }
+
+    /// Gets the deposit and the stake info of the entity from the entry point. They are fetched
+    /// once per validation, so the sanity checks don't query the same entity again
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the entity
+    ///
+    /// # Returns
+    /// * `(U256, StakeInfo)` - The deposit and the [StakeInfo](StakeInfo) of the entity
+    pub async fn get_deposit_info(&self, addr: &Address) -> Result<(U256, StakeInfo), SanityError> {
+        let cached = self.deposits.lock().get(addr).copied();
+        if let Some(deposit_info) = cached {
+            return Ok(deposit_info);
+        }
+
+        let info = self
+            .with_timeout(async {
+                self.entry_point.get_deposit_info_at(addr, self.block).await.map_err(Into::into)
+            })
+            .await?;
+        let deposit_info = (
+            U256::from(info.deposit),
+            StakeInfo {
+                address: *addr,
+                stake: U256::from(info.stake),
+                unstake_delay: U256::from(info.unstake_delay_sec),
+            },
+        );
+        self.deposits.lock().insert(*addr, deposit_info);
+
+        Ok(deposit_info)
+    }
+
+    /// Gets the stake info of the entity (see [get_deposit_info](SanityHelper::get_deposit_info))
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the entity
+    ///
+    /// # Returns
+    /// * `StakeInfo` - The [StakeInfo](StakeInfo) of the entity
+    pub async fn get_stake_info(&self, addr: &Address) -> Result<StakeInfo, SanityError> {
+        Ok(self.get_deposit_info(addr).await?.1)
+    }
 }
 
 #[async_trait::async_trait]
@@ -178,6 +298,10 @@ sanity_check_impls! { A B C D F G I }
 sanity_check_impls! { A B C D F G I J }
 sanity_check_impls! { A B C D F G I J K }
 sanity_check_impls! { A B C D F G I J K L }
+sanity_check_impls! { A B C D F G I J K L N }
+sanity_check_impls! { A B C D F G I J K L N O }
+sanity_check_impls! { A B C D F G I J K L N O P }
+sanity_check_impls! { A B C D F G I J K L N O P Q }
 
 /// The [UserOperation](UserOperation) simulation check helper trait.
 pub struct SimulationHelper<'a> {
@@ -288,6 +412,16 @@ pub trait SimulationTraceCheck<M: Middleware>: Send + Sync {
         Z: UserOperationCodeHashAct,
         H: HashSetOp,
         R: ReputationEntryOp;
+
+    /// Whether the check needs the validation trace (the validation is not traced with
+    /// `debug_traceCall` if none of the checks needs it, e.g., in the unsafe mode)
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the validation has to be traced for the check.
+    fn requires_trace(&self) -> bool {
+        true
+    }
 }
 
 macro_rules! simulation_trace_check_impls {
@@ -315,6 +449,11 @@ macro_rules! simulation_trace_check_impls {
                     ($($name.check_user_operation(uo, mempool, reputation, helper).await?,)+);
                     Ok(())
                 }
+
+            fn requires_trace(&self) -> bool {
+                let ($($name,)+) = self;
+                false $(|| $name.requires_trace())+
+            }
         }
     };
 }
@@ -338,6 +477,10 @@ impl<M: Middleware> SimulationTraceCheck<M> for () {
     {
         Ok(())
     }
+
+    fn requires_trace(&self) -> bool {
+        false
+    }
 }
 
 // These macro enable people to chain simulation check implementations:
@@ -354,3 +497,29 @@ simulation_trace_check_impls! { A B C D F G I }
 simulation_trace_check_impls! { A B C D F G I J }
 simulation_trace_check_impls! { A B C D F G I J K }
 simulation_trace_check_impls! { A B C D F G I J K L }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::Provider;
+    use std::{future::pending, sync::Arc};
+
+    #[tokio::test]
+    async fn sanity_helper_call_timeout() {
+        let (eth_client, _mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(10),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let res = helper.with_timeout(pending::<Result<(), SanityError>>()).await;
+        assert!(matches!(res, Err(SanityError::Provider { .. })));
+        assert_eq!(helper.with_timeout(async { Ok(1) }).await.unwrap(), 1);
+    }
+}
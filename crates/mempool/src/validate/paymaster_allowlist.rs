@@ -0,0 +1,78 @@
+use crate::Allowlist;
+use ethers::types::Address;
+
+/// Paymasters allowed to sponsor the user operations (sponsored-only mode), e.g., for a private
+/// bundler that only relays the user operations sponsored by its own paymasters. The allowlist can
+/// be updated at runtime and the clones share the same allowlist.
+#[derive(Clone, Debug, Default)]
+pub struct PaymasterAllowlist {
+    /// Allowed paymasters (any paymaster is allowed if not set)
+    paymasters: Allowlist,
+    /// Whether the user operations without a paymaster are refused
+    require_paymaster: bool,
+}
+
+impl PaymasterAllowlist {
+    /// Creates a new [PaymasterAllowlist](PaymasterAllowlist)
+    ///
+    /// # Arguments
+    /// * `paymasters` - Allowed paymasters (any paymaster is allowed if empty)
+    /// * `require_paymaster` - Whether the user operations without a paymaster are refused
+    ///
+    /// # Returns
+    /// * `PaymasterAllowlist` - The [PaymasterAllowlist](PaymasterAllowlist)
+    pub fn new(paymasters: Vec<Address>, require_paymaster: bool) -> Self {
+        Self { paymasters: Allowlist::new(paymasters), require_paymaster }
+    }
+
+    /// Checks whether the paymaster is allowed to sponsor user operations
+    ///
+    /// # Arguments
+    /// * `paymaster` - The paymaster of the user operation (`None` if there is no paymaster)
+    ///
+    /// # Returns
+    /// * `bool` - Whether the user operation is allowed
+    pub fn is_allowed(&self, paymaster: Option<&Address>) -> bool {
+        match paymaster {
+            Some(paymaster) => self.paymasters.allows(paymaster),
+            None => !self.require_paymaster,
+        }
+    }
+
+    /// Gets the [Allowlist](Allowlist) of the paymasters, which is updated by the admin methods.
+    /// Once updated, only the allowed paymasters are accepted (even if all of them are removed).
+    ///
+    /// # Returns
+    /// * `&Allowlist` - The [Allowlist](Allowlist) of the paymasters
+    pub fn paymasters(&self) -> &Allowlist {
+        &self.paymasters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paymaster_allowlist_updates() {
+        let (first, second) = (Address::random(), Address::random());
+
+        let allowlist = PaymasterAllowlist::default();
+        assert!(allowlist.is_allowed(Some(&first)));
+        assert!(allowlist.is_allowed(None));
+
+        let allowlist = PaymasterAllowlist::new(vec![first], true);
+        assert!(allowlist.is_allowed(Some(&first)));
+        assert!(!allowlist.is_allowed(Some(&second)));
+        assert!(!allowlist.is_allowed(None));
+
+        // the clones share the allowlist
+        allowlist.clone().paymasters().update(&[second], &[first]);
+        assert!(!allowlist.is_allowed(Some(&first)));
+        assert!(allowlist.is_allowed(Some(&second)));
+
+        let allowlist = PaymasterAllowlist::default();
+        allowlist.paymasters().update(&[], &[]);
+        assert!(!allowlist.is_allowed(Some(&first)));
+    }
+}
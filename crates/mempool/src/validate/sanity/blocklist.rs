@@ -0,0 +1,55 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, Reputation, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    UserOperation,
+};
+
+#[derive(Clone)]
+pub struct Blocklist;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for Blocklist {
+    /// The [check_user_operation] method implementation that refuses the user operations whose
+    /// sender, factory or paymaster is blocklisted by the operator (an address that is also
+    /// allowlisted is not refused).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `reputation` - The [Reputation](Reputation) that holds the allow and block lists.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        reputation: &Reputation<H, R>,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        let (sender, factory, paymaster) = uo.get_entities();
+
+        for (entity, addr) in [(SENDER, Some(sender)), (FACTORY, factory), (PAYMASTER, paymaster)] {
+            if let Some(addr) = addr {
+                if reputation.is_blacklist(&addr) && !reputation.is_whitelist(&addr) {
+                    return Err(SanityError::Blocklisted { entity: entity.into(), address: addr });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -52,6 +52,7 @@ impl Entities {
     /// mempool
     fn check_throttled<M: Middleware, T, Y, X, Z, H, R>(
         &self,
+        uo: &UserOperation,
         entity: &str,
         addr: &Address,
         status: &Status,
@@ -67,8 +68,11 @@ impl Entities {
         H: HashSetOp,
         R: ReputationEntryOp,
     {
+        // the user operation validated in place isn't counted against its own limit
+        let own = mempool.contains_identical(uo).unwrap_or_default() as usize;
         if *status == Status::THROTTLED &&
-            (mempool.get_number_by_sender(addr) + mempool.get_number_by_entity(addr)) >=
+            (mempool.get_number_by_sender(addr) + mempool.get_number_by_entity(addr))
+                .saturating_sub(own) >=
                 THROTTLED_ENTITY_MEMPOOL_COUNT
         {
             return Err(
@@ -114,20 +118,20 @@ impl<M: Middleware> SanityCheck<M> for Entities {
         // sender
         let status = self.get_status(&sender, helper, reputation)?;
         self.check_banned(SENDER, &sender, &status)?;
-        self.check_throttled(SENDER, &sender, &status, helper, mempool, reputation)?;
+        self.check_throttled(uo, SENDER, &sender, &status, helper, mempool, reputation)?;
 
         // factory
         if let Some(factory) = factory {
             let status = self.get_status(&factory, helper, reputation)?;
             self.check_banned(FACTORY, &factory, &status)?;
-            self.check_throttled(FACTORY, &factory, &status, helper, mempool, reputation)?;
+            self.check_throttled(uo, FACTORY, &factory, &status, helper, mempool, reputation)?;
         }
 
         // paymaster
         if let Some(paymaster) = paymaster {
             let status = self.get_status(&paymaster, helper, reputation)?;
             self.check_banned(PAYMASTER, &paymaster, &status)?;
-            self.check_throttled(PAYMASTER, &paymaster, &status, helper, mempool, reputation)?;
+            self.check_throttled(uo, PAYMASTER, &paymaster, &status, helper, mempool, reputation)?;
         }
 
         Ok(())
@@ -0,0 +1,171 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, Reputation, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::UserOperation;
+
+#[derive(Clone)]
+pub struct Factory;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for Factory {
+    /// The [check_user_operation] method implementation that validates the factory of the user
+    /// operations that deploy the sender. The factory (the first 20 bytes of the initCode) has to
+    /// be deployed. An unstaked factory is accepted, a THROTTLED one included (its number of user
+    /// operations in the mempool is limited by the
+    /// [Entities](crate::validate::sanity::entities::Entities) check), as its stake is only
+    /// required if the account accesses its associated storage during the deployment (STO-022,
+    /// enforced by the
+    /// [StorageAccess](crate::validate::simulation_trace::storage_access::StorageAccess) check).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        if uo.init_code.is_empty() {
+            return Ok(());
+        }
+
+        let (_, factory, _) = uo.get_entities();
+        let factory = match factory {
+            Some(factory) => factory,
+            None => {
+                return Err(SanityError::Factory {
+                    inner: format!(
+                        "initCode {:?} is too short to contain the factory address",
+                        uo.init_code
+                    ),
+                })
+            }
+        };
+
+        if helper.get_code(factory).await?.is_empty() {
+            return Err(SanityError::Factory { inner: format!("factory {factory:?} has no code") });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, Bytes, U256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::{ReputationEntry, Status},
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn factory_deploying_sender() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let mut reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        // the unstaked factory is throttled
+        let factory = Address::random();
+        reputation
+            .set_entities(vec![ReputationEntry {
+                uo_seen: 200,
+                ..ReputationEntry::default_with_addr(factory)
+            }])
+            .unwrap();
+        assert_eq!(Status::from(reputation.get_status(&factory).unwrap()), Status::THROTTLED);
+
+        let uo = |init_code: Vec<u8>| {
+            UserOperation::from_user_operation_signed(
+                Default::default(),
+                UserOperationSigned {
+                    init_code: init_code.into(),
+                    ..UserOperationSigned::random()
+                },
+            )
+        };
+
+        // no deployment, nothing to check
+        assert!(Factory
+            .check_user_operation(&uo(vec![]), &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        // the throttled unstaked factory is accepted
+        let deploying = uo([factory.as_bytes(), &[1, 2, 3]].concat());
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(Factory
+            .check_user_operation(&deploying, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        // the factory isn't deployed
+        mock.push(Bytes::default()).unwrap();
+        assert!(matches!(
+            Factory.check_user_operation(&deploying, &mempool, &reputation, &helper).await,
+            Err(SanityError::Factory { .. })
+        ));
+
+        // the initCode doesn't contain the factory address
+        assert!(matches!(
+            Factory.check_user_operation(&uo(vec![1, 2, 3]), &mempool, &reputation, &helper).await,
+            Err(SanityError::Factory { .. })
+        ));
+    }
+}
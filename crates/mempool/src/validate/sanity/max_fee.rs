@@ -8,11 +8,92 @@ use ethers::{
     providers::Middleware,
     types::{BlockNumber, U256},
 };
-use silius_primitives::UserOperation;
+use parking_lot::Mutex;
+use silius_primitives::{
+    constants::validation::{entities::SENDER, sanity::BASE_FEE_CACHE_TTL},
+    UserOperation,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 #[derive(Clone)]
 pub struct MaxFee {
+    /// Min priority fee per gas that the bundler would accept
     pub min_priority_fee_per_gas: U256,
+    /// Min max fee per gas as a percentage of the current base fee per gas
+    pub min_base_fee_perc: u64,
+    /// Percentage by which the base fee floor is lowered for staked senders
+    pub staked_grace_perc: u64,
+    /// Base fee per gas of the latest block and when it was fetched
+    base_fee: Arc<Mutex<Option<(Instant, U256)>>>,
+}
+
+impl MaxFee {
+    /// Creates a new [MaxFee](MaxFee) sanity check
+    ///
+    /// # Arguments
+    /// `min_priority_fee_per_gas` - Min priority fee per gas that the bundler would accept
+    /// `min_base_fee_perc` - Min max fee per gas as a percentage of the current base fee per gas
+    /// `staked_grace_perc` - Percentage by which the base fee floor is lowered for staked senders
+    ///
+    /// # Returns
+    /// A new [MaxFee](MaxFee) sanity check
+    pub fn new(
+        min_priority_fee_per_gas: U256,
+        min_base_fee_perc: u64,
+        staked_grace_perc: u64,
+    ) -> Self {
+        Self {
+            min_priority_fee_per_gas,
+            min_base_fee_perc,
+            staked_grace_perc,
+            base_fee: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Gets the base fee per gas of the latest block, cached for a short time so that not every
+    /// user operation triggers an RPC call (the base fee of the pinned block isn't cached)
+    async fn get_base_fee<M: Middleware>(
+        &self,
+        helper: &SanityHelper<'_, M>,
+    ) -> Result<U256, SanityError> {
+        if let Some(block) = helper.block {
+            return Self::fetch_base_fee(helper, block).await;
+        }
+
+        let cached = *self.base_fee.lock();
+        if let Some((fetched_at, base_fee)) = cached {
+            if fetched_at.elapsed() < Duration::from_millis(BASE_FEE_CACHE_TTL) {
+                return Ok(base_fee);
+            }
+        }
+
+        let base_fee = Self::fetch_base_fee(helper, BlockNumber::Latest).await?;
+
+        *self.base_fee.lock() = Some((Instant::now(), base_fee));
+        Ok(base_fee)
+    }
+
+    /// Fetches the base fee per gas of the block
+    async fn fetch_base_fee<M: Middleware>(
+        helper: &SanityHelper<'_, M>,
+        block: BlockNumber,
+    ) -> Result<U256, SanityError> {
+        let block = helper
+            .with_timeout(async {
+                helper
+                    .entry_point
+                    .eth_client()
+                    .get_block(block)
+                    .await
+                    .map_err(|err| SanityError::Provider { inner: err.to_string() })
+            })
+            .await?
+            .ok_or(SanityError::Other { inner: "No block found".into() })?;
+        block.base_fee_per_gas.ok_or(SanityError::Other { inner: "No base fee".into() })
+    }
 }
 
 #[async_trait::async_trait]
@@ -29,7 +110,7 @@ impl<M: Middleware> SanityCheck<M> for MaxFee {
         &self,
         uo: &UserOperation,
         _mempool: &Mempool<T, Y, X, Z>,
-        _reputation: &Reputation<H, R>,
+        reputation: &Reputation<H, R>,
         helper: &SanityHelper<M>,
     ) -> Result<(), SanityError>
     where
@@ -47,20 +128,24 @@ impl<M: Middleware> SanityCheck<M> for MaxFee {
             });
         }
 
-        let block = helper
-            .entry_point
-            .eth_client()
-            .get_block(BlockNumber::Latest)
-            .await
-            .map_err(|err| SanityError::Provider { inner: err.to_string() })?
-            .ok_or(SanityError::Other { inner: "No block found".into() })?;
-        let base_fee_per_gas =
-            block.base_fee_per_gas.ok_or(SanityError::Other { inner: "No base fee".into() })?;
+        let base_fee_per_gas = self.get_base_fee(helper).await?;
+        let mut max_fee_per_gas_expected =
+            base_fee_per_gas.saturating_mul(self.min_base_fee_perc.into()) / 100;
+
+        if max_fee_per_gas_expected > uo.max_fee_per_gas && self.staked_grace_perc > 0 {
+            // staked senders are given a grace margin
+            let stake_info = helper.get_stake_info(&uo.sender).await?;
+            if reputation.verify_stake(SENDER, Some(stake_info)).is_ok() {
+                max_fee_per_gas_expected = max_fee_per_gas_expected
+                    .saturating_mul(100_u64.saturating_sub(self.staked_grace_perc).into()) /
+                    100;
+            }
+        }
 
-        if base_fee_per_gas > uo.max_fee_per_gas {
+        if max_fee_per_gas_expected > uo.max_fee_per_gas {
             return Err(SanityError::MaxFeePerGasTooLow {
                 max_fee_per_gas: uo.max_fee_per_gas,
-                base_fee_per_gas,
+                max_fee_per_gas_expected,
             });
         }
 
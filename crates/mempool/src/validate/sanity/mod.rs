@@ -1,9 +1,18 @@
-//! Sanity module performs call gas limit, verification gas limit, max priority fee, paymaster
-//! verification, sender vericiation, and UserOperation type checks
+//! Sanity module performs blocklist, call gas limit, verification gas limit, max priority fee,
+//! paymaster data, paymaster verification, paymaster allowlist, factory verification, sender
+//! vericiation, sender signature pre-check, sender mempool limit, UserOperation size, and
+//! UserOperation type checks
+pub mod blocklist;
 pub mod call_gas;
 pub mod entities;
+pub mod factory;
 pub mod max_fee;
 pub mod paymaster;
+pub mod paymaster_data;
 pub mod sender;
+pub mod sender_limit;
+pub mod signature;
+pub mod size;
+pub mod sponsorship;
 pub mod unstaked_entities;
 pub mod verification_gas;
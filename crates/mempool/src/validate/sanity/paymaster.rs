@@ -4,8 +4,11 @@ use crate::{
     validate::{SanityCheck, SanityHelper},
     Reputation, SanityError,
 };
-use ethers::{providers::Middleware, types::U256};
-use silius_primitives::{get_address, UserOperation};
+use ethers::providers::Middleware;
+use silius_primitives::{
+    constants::validation::entities::PAYMASTER, get_address, EntryPointVersion,
+    PackedUserOperation, UserOperation,
+};
 
 #[derive(Clone)]
 pub struct Paymaster;
@@ -13,7 +16,7 @@ pub struct Paymaster;
 #[async_trait::async_trait]
 impl<M: Middleware> SanityCheck<M> for Paymaster {
     /// The [check_user_operation] method implementation that performs the sanity check on the
-    /// paymaster.
+    /// paymaster (code, deposit, and stake).
     ///
     /// # Arguments
     /// `uo` - The user operation to be checked.
@@ -26,7 +29,7 @@ impl<M: Middleware> SanityCheck<M> for Paymaster {
         &self,
         uo: &UserOperation,
         _mempool: &Mempool<T, Y, X, Z>,
-        _reputation: &Reputation<H, R>,
+        reputation: &Reputation<H, R>,
         helper: &SanityHelper<M>,
     ) -> Result<(), SanityError>
     where
@@ -38,26 +41,178 @@ impl<M: Middleware> SanityCheck<M> for Paymaster {
         R: ReputationEntryOp,
     {
         if !uo.paymaster_and_data.is_empty() {
-            if let Some(addr) = get_address(&uo.paymaster_and_data) {
-                let code = helper
-                    .entry_point
-                    .eth_client()
-                    .get_code(addr, None)
-                    .await
-                    .map_err(|e| SanityError::Provider { inner: e.to_string() })?;
-
-                if !code.is_empty() {
-                    let deposit_info = helper.entry_point.get_deposit_info(&addr).await?;
-
-                    if U256::from(deposit_info.deposit) >= uo.max_fee_per_gas {
-                        return Ok(());
-                    }
+            let addr = get_address(&uo.paymaster_and_data).ok_or(SanityError::Paymaster {
+                inner: format!(
+                    "paymasterAndData {:?} does not contain a paymaster address",
+                    uo.paymaster_and_data
+                ),
+            })?;
+
+            let code = helper.get_code(addr).await?;
+
+            if code.is_empty() {
+                return Err(SanityError::Paymaster {
+                    inner: format!("paymaster {addr:?} has no code"),
+                });
+            }
+
+            let (deposit, stake_info) = helper.get_deposit_info(&addr).await?;
+
+            let required_gas = match helper.entry_point.version() {
+                // verification gas limit is used three times because of the postOp call
+                EntryPointVersion::V0_6 => uo
+                    .call_gas_limit
+                    .saturating_add(uo.verification_gas_limit.saturating_mul(3.into())),
+                // paymaster gas limits are set separately
+                EntryPointVersion::V0_7 => {
+                    let (verification_gas_limit, post_op_gas_limit) =
+                        PackedUserOperation::from(uo.user_operation.clone()).paymaster_gas_limits();
+                    uo.call_gas_limit
+                        .saturating_add(uo.verification_gas_limit)
+                        .saturating_add(verification_gas_limit)
+                        .saturating_add(post_op_gas_limit)
                 }
+            };
+
+            // required prefund of the paymaster
+            let required_prefund = required_gas
+                .saturating_add(uo.pre_verification_gas)
+                .saturating_mul(uo.max_fee_per_gas);
+            if deposit < required_prefund {
+                return Err(SanityError::PaymasterDeposit {
+                    paymaster: addr,
+                    deposit,
+                    required_prefund,
+                });
             }
 
-            return Err(SanityError::Paymaster { inner: "Problem with paymaster".into() });
+            reputation
+                .verify_stake(PAYMASTER, Some(stake_info))
+                .map_err(|e| SanityError::Paymaster { inner: e.to_string() })?;
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, Bytes, U256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::{ReputationEntry, StakeInfo},
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn paymaster_code_deposit_and_stake() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random())
+            .with_version(EntryPointVersion::V0_6);
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let paymaster = Address::random();
+        let uo = |paymaster_and_data: Vec<u8>| {
+            UserOperation::from_user_operation_signed(
+                Default::default(),
+                UserOperationSigned {
+                    call_gas_limit: U256::from(100),
+                    verification_gas_limit: U256::from(100),
+                    pre_verification_gas: U256::from(100),
+                    max_fee_per_gas: U256::from(1),
+                    paymaster_and_data: paymaster_and_data.into(),
+                    ..UserOperationSigned::random()
+                },
+            )
+        };
+        let sponsored = uo([paymaster.as_bytes(), &[1, 2, 3]].concat());
+        let stake_info = |stake: u64| StakeInfo {
+            address: paymaster,
+            stake: U256::from(stake),
+            unstake_delay: U256::from(2),
+        };
+
+        // no paymaster, nothing to check
+        assert!(Paymaster
+            .check_user_operation(&uo(vec![]), &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        // the paymasterAndData doesn't contain the paymaster address
+        assert!(matches!(
+            Paymaster
+                .check_user_operation(&uo(vec![1, 2, 3]), &mempool, &reputation, &helper)
+                .await,
+            Err(SanityError::Paymaster { .. })
+        ));
+
+        // the paymaster isn't deployed
+        mock.push(Bytes::default()).unwrap();
+        assert!(matches!(
+            Paymaster.check_user_operation(&sponsored, &mempool, &reputation, &helper).await,
+            Err(SanityError::Paymaster { .. })
+        ));
+
+        // the deposit doesn't cover the prefund of (100 + 3 * 100 + 100) * 1
+        helper.deposits.lock().insert(paymaster, (U256::from(499), stake_info(1)));
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(matches!(
+            Paymaster.check_user_operation(&sponsored, &mempool, &reputation, &helper).await,
+            Err(SanityError::PaymasterDeposit { deposit, required_prefund, .. })
+                if deposit == U256::from(499) && required_prefund == U256::from(500)
+        ));
+
+        helper.deposits.lock().insert(paymaster, (U256::from(500), stake_info(1)));
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(Paymaster
+            .check_user_operation(&sponsored, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        // the paymaster isn't staked
+        helper.deposits.lock().insert(paymaster, (U256::from(500), stake_info(0)));
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(matches!(
+            Paymaster.check_user_operation(&sponsored, &mempool, &reputation, &helper).await,
+            Err(SanityError::Paymaster { .. })
+        ));
+    }
+}
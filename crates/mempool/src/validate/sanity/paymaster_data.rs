@@ -0,0 +1,124 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{
+    providers::Middleware,
+    types::{Address, Bytes, H256},
+};
+use silius_primitives::{
+    get_address, unpack_uints, EntryPointVersion, UserOperation, PAYMASTER_DATA_OFFSET,
+};
+
+#[derive(Clone)]
+pub struct PaymasterData;
+
+/// Checks that the `paymasterAndData` is well-formed for the entry point version, i.e., it starts
+/// with a non-zero paymaster address and, for EntryPoint v0.7, it's followed by the paymaster
+/// verification gas limit (non-zero) and the paymaster post-op gas limit
+///
+/// # Arguments
+/// `paymaster_and_data` - The `paymasterAndData` of the user operation (not checked if empty)
+/// `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+///
+/// # Returns
+/// None if the `paymasterAndData` is well-formed, otherwise a [SanityError] is returned.
+pub fn check_paymaster_and_data(
+    paymaster_and_data: &Bytes,
+    version: EntryPointVersion,
+) -> Result<(), SanityError> {
+    if paymaster_and_data.is_empty() {
+        return Ok(());
+    }
+
+    let paymaster = get_address(paymaster_and_data).ok_or(SanityError::Paymaster {
+        inner: format!(
+            "paymasterAndData of {} bytes is too short to contain the paymaster address",
+            paymaster_and_data.len()
+        ),
+    })?;
+    if paymaster == Address::zero() {
+        return Err(SanityError::Paymaster {
+            inner: "paymasterAndData contains the zero paymaster address".into(),
+        });
+    }
+
+    if version == EntryPointVersion::V0_7 {
+        let limits = paymaster_and_data.get(20..PAYMASTER_DATA_OFFSET).ok_or(
+            SanityError::Paymaster {
+                inner: format!(
+                    "paymasterAndData of {} bytes is too short to contain the paymaster gas limits (at least {PAYMASTER_DATA_OFFSET} bytes)",
+                    paymaster_and_data.len()
+                ),
+            },
+        )?;
+        let (verification_gas_limit, _) = unpack_uints(&H256::from_slice(limits));
+        if verification_gas_limit.is_zero() {
+            return Err(SanityError::Paymaster {
+                inner: format!("paymaster {paymaster:?} verification gas limit is zero"),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for PaymasterData {
+    /// The [check_user_operation] method implementation that rejects the user operations with a
+    /// truncated or malformed `paymasterAndData` before they are simulated.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        check_paymaster_and_data(&uo.paymaster_and_data, helper.entry_point.version())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U256;
+    use silius_primitives::pack_paymaster_and_data;
+
+    #[test]
+    fn malformed_paymaster_and_data() {
+        let paymaster = Address::random();
+        let check = |data: Vec<u8>, version| check_paymaster_and_data(&data.into(), version);
+
+        assert!(check(vec![], EntryPointVersion::V0_7).is_ok());
+        assert!(check(vec![1; 19], EntryPointVersion::V0_6).is_err());
+        assert!(check(vec![0; 20], EntryPointVersion::V0_6).is_err());
+        assert!(check(paymaster.as_bytes().to_vec(), EntryPointVersion::V0_6).is_ok());
+
+        // the gas limits are required by EntryPoint v0.7
+        assert!(check(paymaster.as_bytes().to_vec(), EntryPointVersion::V0_7).is_err());
+        let data = pack_paymaster_and_data(Some(paymaster), U256::zero(), 10.into(), &Bytes::new());
+        assert!(check(data.to_vec(), EntryPointVersion::V0_7).is_err());
+        let data =
+            pack_paymaster_and_data(Some(paymaster), 100_000.into(), U256::zero(), &vec![1].into());
+        assert!(check(data.to_vec(), EntryPointVersion::V0_7).is_ok());
+        assert!(check(data[..40].to_vec(), EntryPointVersion::V0_7).is_err());
+    }
+}
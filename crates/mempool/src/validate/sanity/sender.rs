@@ -1,15 +1,37 @@
 use crate::{
     mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    metrics::record_replacement,
     reputation::{HashSetOp, ReputationEntryOp},
-    utils::calculate_valid_gas,
+    utils::{
+        calculate_fee_bump_perc, calculate_valid_gas, is_signature_refresh, is_valid_replacement,
+    },
     validate::{SanityCheck, SanityHelper},
     Reputation, SanityError,
 };
-use ethers::providers::Middleware;
-use silius_primitives::{constants::mempool::GAS_INCREASE_PERC, UserOperation};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{
+    constants::mempool::GAS_INCREASE_PERC, get_delegation, mempool::EqualFeeReplacement,
+    EntryPointVersion, PackedUserOperation, UserOperation,
+};
+use tracing::{debug, info};
 
 #[derive(Clone)]
-pub struct Sender;
+pub struct Sender {
+    /// Percentage increase of fees required to replace a user operation in the mempool
+    pub gas_increase_perc: U256,
+    /// How a user operation with the same sender, nonce and fees as a user operation in the
+    /// mempool is handled
+    pub equal_fee_replacement: EqualFeeReplacement,
+}
+
+impl Default for Sender {
+    fn default() -> Self {
+        Self {
+            gas_increase_perc: GAS_INCREASE_PERC.into(),
+            equal_fee_replacement: Default::default(),
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl<M: Middleware> SanityCheck<M> for Sender {
@@ -39,19 +61,42 @@ impl<M: Middleware> SanityCheck<M> for Sender {
         H: HashSetOp,
         R: ReputationEntryOp,
     {
-        let code = helper
-            .entry_point
-            .eth_client()
-            .get_code(uo.sender, None)
-            .await
-            .map_err(|e| SanityError::Provider { inner: e.to_string() })?;
+        let code = helper.get_code(uo.sender).await?;
 
-        // check if sender or init code
-        if (code.is_empty() && uo.init_code.is_empty()) ||
-            (!code.is_empty() && !uo.init_code.is_empty())
-        {
+        // check if sender or init code (factory and factory data for EntryPoint v0.7)
+        let has_init_code = match helper.entry_point.version() {
+            EntryPointVersion::V0_6 => !uo.init_code.is_empty(),
+            EntryPointVersion::V0_7 => {
+                let packed = PackedUserOperation::from(uo.user_operation.clone());
+                if !packed.init_code.is_empty() && packed.factory().is_none() {
+                    return Err(SanityError::Sender {
+                        inner: format!(
+                            "initCode {:?} is too short to contain the factory address",
+                            packed.init_code
+                        ),
+                    });
+                }
+                packed.factory().is_some()
+            }
+        };
+
+        // an EIP-7702 delegated EOA has the delegation designator as code, but it's an existing
+        // account that can't be deployed by a factory
+        if let Some(delegation) = get_delegation(&code) {
+            if has_init_code {
+                return Err(SanityError::Sender {
+                    inner: format!(
+                        "sender {:?} is an EOA delegated to {delegation:?}, the initCode {:?} should be empty",
+                        uo.sender, uo.init_code
+                    ),
+                });
+            }
+        } else if (code.is_empty() && !has_init_code) || (!code.is_empty() && has_init_code) {
             return Err(SanityError::Sender {
-                inner: "sender {uo.sender} is an existing contract, or the initCode {uo.init_code.clone()} is not empty (but not both)".into(),
+                inner: format!(
+                    "sender {:?} is an existing contract, or the initCode {:?} is not empty (but not both)",
+                    uo.sender, uo.init_code
+                ),
             });
         }
 
@@ -60,28 +105,380 @@ impl<M: Middleware> SanityCheck<M> for Sender {
             return Ok(());
         }
 
+        // the user operation itself (validated in place) doesn't replace anything
         let uo_prev = mempool
             .get_all_by_sender(&uo.sender)
             .iter()
-            .find(|uo_prev| uo_prev.nonce == uo.nonce)
+            .find(|uo_prev| {
+                uo_prev.nonce == uo.nonce && uo_prev.user_operation != uo.user_operation
+            })
             .cloned();
 
         if let Some(uo_prev) = uo_prev {
-            if uo.max_fee_per_gas <
-                calculate_valid_gas(uo_prev.max_fee_per_gas, GAS_INCREASE_PERC.into()) ||
-                uo.max_priority_fee_per_gas <
-                    calculate_valid_gas(
-                        uo_prev.max_priority_fee_per_gas,
-                        GAS_INCREASE_PERC.into(),
-                    )
+            let bump_perc = calculate_fee_bump_perc(&uo_prev.user_operation, &uo.user_operation);
+            if self.equal_fee_replacement == EqualFeeReplacement::SignatureRefresh &&
+                is_signature_refresh(&uo_prev.user_operation, &uo.user_operation)
             {
+                debug!(
+                    "Accepted signature refresh of {:?} (sender {:?}, nonce {})",
+                    uo_prev.hash, uo.sender, uo.nonce
+                );
+                return Ok(());
+            }
+
+            if !is_valid_replacement(
+                &uo_prev.user_operation,
+                &uo.user_operation,
+                self.gas_increase_perc,
+            ) {
+                record_replacement(&uo.sender, false, bump_perc);
+                info!(
+                    "Rejected replacement of {:?} (sender {:?}, nonce {}) with fees bumped by {bump_perc}%",
+                    uo_prev.hash, uo.sender, uo.nonce
+                );
+                let equal_fees = uo.max_fee_per_gas == uo_prev.max_fee_per_gas &&
+                    uo.max_priority_fee_per_gas == uo_prev.max_priority_fee_per_gas;
+                let policy = match (equal_fees, self.equal_fee_replacement) {
+                    (false, _) => "",
+                    (true, EqualFeeReplacement::Reject) => {
+                        "; user operations with equal fees are rejected even if only the signature changed (equal fee replacement policy: reject)"
+                    }
+                    (true, EqualFeeReplacement::SignatureRefresh) => {
+                        "; user operations with equal fees are only accepted if just the signature changed (equal fee replacement policy: signature-refresh)"
+                    }
+                };
                 return Err(SanityError::Sender {
-                    inner: "{uo.sender} couldn't replace user operation (gas increase too low)"
-                        .into(),
+                    inner: format!(
+                        "sender {:?} couldn't replace user operation with nonce {} (gas increase too low): maxFeePerGas {} (previous {}, required at least {}), maxPriorityFeePerGas {} (previous {}, required at least {}){policy}",
+                        uo.sender,
+                        uo.nonce,
+                        uo.max_fee_per_gas,
+                        uo_prev.max_fee_per_gas,
+                        calculate_valid_gas(uo_prev.max_fee_per_gas, self.gas_increase_perc),
+                        uo.max_priority_fee_per_gas,
+                        uo_prev.max_priority_fee_per_gas,
+                        calculate_valid_gas(
+                            uo_prev.max_priority_fee_per_gas,
+                            self.gas_increase_perc
+                        ),
+                    ),
                 });
             }
+
+            debug!(
+                "Accepted replacement of {:?} (sender {:?}, nonce {}) with fees bumped by {bump_perc}%",
+                uo_prev.hash, uo.sender, uo.nonce
+            );
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Mempool;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, Bytes, H256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::{
+            eip7702::DELEGATION_PREFIX,
+            validation::{
+                reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+                sanity::RPC_TIMEOUT,
+            },
+        },
+        reputation::ReputationEntry,
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn replacement_with_custom_gas_increase() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mut mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let uo_prev = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(100),
+            ..UserOperationSigned::random()
+        };
+        mempool
+            .add(UserOperation::from_user_operation_signed(H256::random().into(), uo_prev.clone()))
+            .unwrap();
+
+        // replacement bumps both fees by 6%
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned {
+                max_fee_per_gas: U256::from(106),
+                max_priority_fee_per_gas: U256::from(106),
+                ..uo_prev
+            },
+        );
+
+        // sender is an existing contract
+        mock.push(Bytes::from(vec![1])).unwrap();
+        let sender = Sender { gas_increase_perc: U256::from(5), ..Default::default() };
+        assert!(sender.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+
+        mock.push(Bytes::from(vec![1])).unwrap();
+        let sender = Sender::default();
+        let err = sender.check_user_operation(&uo, &mempool, &reputation, &helper).await;
+        match err {
+            Err(SanityError::Sender { inner }) => {
+                assert!(inner.contains(&format!("{:?}", uo.sender)));
+                assert!(inner.contains(&format!("nonce {}", uo.nonce)));
+                assert!(inner.contains("maxFeePerGas 106 (previous 100, required at least 110)"));
+            }
+            _ => panic!("expected sender sanity error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn replacement_with_nonce_keys() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mut mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let key = U256::from(1) << 64;
+        let uo_prev = UserOperationSigned {
+            nonce: key,
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(100),
+            ..UserOperationSigned::random()
+        };
+        mempool
+            .add(UserOperation::from_user_operation_signed(H256::random().into(), uo_prev.clone()))
+            .unwrap();
+
+        let sender = Sender::default();
+        for (nonce, replacement) in [
+            // same sequence with another key
+            (U256::from(2) << 64, false),
+            // next sequence of the same key
+            (key + 1, false),
+            // same key and sequence
+            (key, true),
+        ] {
+            // the fees are not bumped, so only the replacement is refused
+            let uo = UserOperation::from_user_operation_signed(
+                H256::random().into(),
+                UserOperationSigned { nonce, ..uo_prev.clone() },
+            );
+            mock.push(Bytes::from(vec![1])).unwrap();
+            let res = sender.check_user_operation(&uo, &mempool, &reputation, &helper).await;
+            assert_eq!(res.is_err(), replacement, "nonce {nonce:#x}");
+        }
+    }
+
+    #[tokio::test]
+    async fn replacement_with_equal_fees() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mut mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let uo_prev = UserOperationSigned {
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(100),
+            ..UserOperationSigned::random()
+        };
+        let uo_hash: UserOperationHash = H256::random().into();
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo_prev.clone())).unwrap();
+
+        // the signature isn't part of the hash
+        let uo_signature = UserOperation::from_user_operation_signed(
+            uo_hash,
+            UserOperationSigned { signature: Bytes::from(vec![1; 65]), ..uo_prev.clone() },
+        );
+        let uo_call_data = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned { call_data: Bytes::from(vec![1]), ..uo_prev.clone() },
+        );
+
+        let sender = Sender::default();
+        for uo in [&uo_signature, &uo_call_data] {
+            mock.push(Bytes::from(vec![1])).unwrap();
+            match sender.check_user_operation(uo, &mempool, &reputation, &helper).await {
+                Err(SanityError::Sender { inner }) => {
+                    assert!(inner.contains("equal fee replacement policy: reject"))
+                }
+                _ => panic!("expected sender sanity error"),
+            }
+        }
+
+        let sender = Sender {
+            equal_fee_replacement: EqualFeeReplacement::SignatureRefresh,
+            ..Default::default()
+        };
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(sender
+            .check_user_operation(&uo_signature, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        mock.push(Bytes::from(vec![1])).unwrap();
+        match sender.check_user_operation(&uo_call_data, &mempool, &reputation, &helper).await {
+            Err(SanityError::Sender { inner }) => {
+                assert!(inner.contains("equal fee replacement policy: signature-refresh"))
+            }
+            _ => panic!("expected sender sanity error"),
+        }
+
+        // the user operation in the mempool is validated in place (e.g., on reload)
+        let uo = UserOperation::from_user_operation_signed(uo_hash, uo_prev);
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(sender.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn delegated_sender() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let delegation =
+            Bytes::from([DELEGATION_PREFIX.as_slice(), Address::random().as_bytes()].concat());
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::random(),
+        );
+        let uo_init_code = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned {
+                init_code: Bytes::from(Address::random().as_bytes().to_vec()),
+                ..UserOperationSigned::random()
+            },
+        );
+        let sender = Sender::default();
+
+        // the delegated EOA needs no initCode
+        mock.push(delegation.clone()).unwrap();
+        assert!(sender.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+
+        mock.push(delegation).unwrap();
+        assert!(matches!(
+            sender.check_user_operation(&uo_init_code, &mempool, &reputation, &helper).await,
+            Err(SanityError::Sender { .. })
+        ));
+
+        // existing contracts still follow the rule (code or initCode, but not both)
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(sender.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(matches!(
+            sender.check_user_operation(&uo_init_code, &mempool, &reputation, &helper).await,
+            Err(SanityError::Sender { .. })
+        ));
+
+        mock.push(Bytes::default()).unwrap();
+        assert!(matches!(
+            sender.check_user_operation(&uo, &mempool, &reputation, &helper).await,
+            Err(SanityError::Sender { .. })
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{constants::validation::entities::SENDER, UserOperation};
+
+#[derive(Clone)]
+pub struct SenderLimit {
+    /// Max number of user operations an unstaked sender can have in the mempool
+    pub max_uos_per_sender: usize,
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for SenderLimit {
+    /// The [check_user_operation] method implementation that limits the number of user operations
+    /// an unstaked sender can have in the mempool.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// Nothing if the sanity check is successful, otherwise a [SanityError](SanityError)
+    /// is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool<T, Y, X, Z>,
+        reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        let count = mempool.get_number_by_sender(&uo.sender);
+        if count < self.max_uos_per_sender {
+            return Ok(());
+        }
+
+        // replacements don't increase the number of user operations in the mempool
+        if mempool.get_all_by_sender(&uo.sender).iter().any(|uo_prev| uo_prev.nonce == uo.nonce) {
+            return Ok(());
+        }
+
+        // [UREP-010] - UserOperation with unstaked sender are only allowed up to
+        // SAME_SENDER_MEMPOOL_COUNT times in the mempool
+        let stake = helper.get_stake_info(&uo.sender).await?;
+        if reputation.verify_stake(SENDER, Some(stake)).is_ok() {
+            return Ok(());
+        }
+
+        Err(SanityError::SenderLimit {
+            sender: uo.sender,
+            count,
+            max_uos_per_sender: self.max_uos_per_sender,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, H256, U256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::{ReputationEntry, StakeInfo},
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn unstaked_sender_limit() {
+        let (eth_client, _) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mut mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let sender = Address::random();
+        let uo = |nonce: u64| {
+            UserOperation::from_user_operation_signed(
+                H256::random().into(),
+                UserOperationSigned {
+                    sender,
+                    nonce: nonce.into(),
+                    ..UserOperationSigned::random()
+                },
+            )
+        };
+        let check = SenderLimit { max_uos_per_sender: 1 };
+
+        // the sender has no user operations in the mempool yet
+        assert!(check.check_user_operation(&uo(0), &mempool, &reputation, &helper).await.is_ok());
+        mempool.add(uo(0)).unwrap();
+
+        // the unstaked sender reached the limit
+        helper.deposits.lock().insert(
+            sender,
+            (
+                U256::zero(),
+                StakeInfo { address: sender, stake: U256::zero(), unstake_delay: U256::from(2) },
+            ),
+        );
+        assert!(matches!(
+            check.check_user_operation(&uo(1), &mempool, &reputation, &helper).await,
+            Err(SanityError::SenderLimit { count: 1, max_uos_per_sender: 1, .. })
+        ));
+
+        // the replacement doesn't add a user operation
+        assert!(check.check_user_operation(&uo(0), &mempool, &reputation, &helper).await.is_ok());
+
+        // the staked sender isn't limited
+        helper.deposits.lock().insert(
+            sender,
+            (
+                U256::zero(),
+                StakeInfo { address: sender, stake: U256::from(1), unstake_delay: U256::from(2) },
+            ),
+        );
+        assert!(check.check_user_operation(&uo(1), &mempool, &reputation, &helper).await.is_ok());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_contracts::EntryPointError;
+use silius_primitives::{constants::validation::sanity::SIG_VALIDATION_FAILED, UserOperation};
+
+#[derive(Clone)]
+pub struct SenderSignature;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for SenderSignature {
+    /// The [check_user_operation] method implementation that calls `validateUserOp` of the sender
+    /// (on behalf of the entry point) to reject the user operations with an invalid signature
+    /// before the full simulation. The senders that are not deployed yet are skipped (the code of
+    /// the sender is served by the [CodeCache](crate::validate::CodeCache) if it's enabled). The
+    /// senders that use a signature aggregator pass the check, their signature is validated by the
+    /// aggregator during the simulation.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        // the sender is deployed by the factory during the simulation
+        if helper.get_code(uo.sender).await?.is_empty() {
+            return Ok(());
+        }
+
+        let version = helper.entry_point.version();
+        let validation_data = helper
+            .with_timeout(async {
+                helper
+                    .entry_point
+                    .validate_user_op(uo.user_operation.clone(), uo.hash.0, version, helper.block)
+                    .await
+                    .map_err(|err| match err {
+                        EntryPointError::ExecutionReverted(reason) => SanityError::Signature {
+                            inner: format!(
+                                "validateUserOp of sender {:?} reverted: {reason}",
+                                uo.sender
+                            ),
+                        },
+                        err => err.into(),
+                    })
+            })
+            .await?;
+
+        // the lower 20 bytes of the validation data are the aggregator (1 if the signature is
+        // invalid), any other aggregator validates the signature itself
+        let aggregator = validation_data & ((U256::one() << 160) - 1);
+        if aggregator == U256::from(SIG_VALIDATION_FAILED) {
+            return Err(SanityError::Signature {
+                inner: format!("validateUserOp of sender {:?} failed", uo.sender),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{
+        abi::AbiEncode,
+        providers::Provider,
+        types::{Address, Bytes, H256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::ReputationEntry,
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn sender_signature_precheck() {
+        let (eth_client, mock) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::random(),
+        );
+        let check = SenderSignature;
+
+        // the responses are returned in the reverse order (the code of the sender first)
+        mock.push(Bytes::from(U256::from(SIG_VALIDATION_FAILED).encode())).unwrap();
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(matches!(
+            check.check_user_operation(&uo, &mempool, &reputation, &helper).await,
+            Err(SanityError::Signature { .. })
+        ));
+
+        // valid signature with a time range
+        let validation_data = (U256::from(2_000) << 160) | (U256::from(1_000) << 208);
+        mock.push(Bytes::from(validation_data.encode())).unwrap();
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(check.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+
+        // the signature is validated by the aggregator
+        let validation_data = U256::from_big_endian(Address::random().as_bytes());
+        mock.push(Bytes::from(validation_data.encode())).unwrap();
+        mock.push(Bytes::from(vec![1])).unwrap();
+        assert!(check.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+
+        // undeployed sender is left to the simulation
+        mock.push(Bytes::default()).unwrap();
+        assert!(check.check_user_operation(&uo, &mempool, &reputation, &helper).await.is_ok());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::UserOperation;
+
+#[derive(Clone)]
+pub struct Size {
+    pub max_uo_size: usize,
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for Size {
+    /// The [check_user_operation] method implementation that performs the check on the encoded
+    /// size of the [UserOperation](UserOperation).
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// Nothing if the sanity check is successful, otherwise a [SanityError](SanityError)
+    /// is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        let version = helper.entry_point.version();
+        let size = uo.user_operation.pack_with_version(version).len();
+
+        if size > self.max_uo_size {
+            return Err(SanityError::UserOperationTooLarge { size, max_size: self.max_uo_size });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, U256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::ReputationEntry,
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn user_operation_size() {
+        let (eth_client, _) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let uo = UserOperation::from_user_operation_signed(
+            Default::default(),
+            UserOperationSigned {
+                call_data: vec![1; 1024].into(),
+                ..UserOperationSigned::random()
+            },
+        );
+        let size = uo.user_operation.pack_with_version(entry_point.version()).len();
+
+        assert!(Size { max_uo_size: size }
+            .check_user_operation(&uo, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        // the call data makes the user operation exceed the limit by one byte
+        assert!(matches!(
+            Size { max_uo_size: size - 1 }
+                .check_user_operation(&uo, &mempool, &reputation, &helper)
+                .await,
+            Err(SanityError::UserOperationTooLarge { size: s, max_size }) if s == size && max_size == size - 1
+        ));
+    }
+}
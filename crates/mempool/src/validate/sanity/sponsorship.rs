@@ -0,0 +1,162 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, Reputation, ReputationEntryOp},
+    validate::{SanityCheck, SanityHelper},
+    SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::UserOperation;
+
+#[derive(Clone)]
+pub struct Sponsorship;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for Sponsorship {
+    /// The [check_user_operation] method implementation that refuses the user operations whose
+    /// paymaster is not in the [PaymasterAllowlist](crate::validate::PaymasterAllowlist) of the
+    /// operator (and the user operations without a paymaster if a paymaster is required).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that holds the paymaster allowlist.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        let (_, _, paymaster) = uo.get_entities();
+
+        if helper.paymaster_allowlist.is_allowed(paymaster.as_ref()) {
+            return Ok(());
+        }
+
+        Err(match paymaster {
+            Some(address) => SanityError::PaymasterNotAllowed { address },
+            None => SanityError::PaymasterRequired,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::PaymasterAllowlist;
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::Provider,
+        types::{Address, U256},
+    };
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::ReputationEntry,
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn sponsored_only_user_operations() {
+        let (eth_client, _) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let (allowed, other) = (Address::random(), Address::random());
+        let uo = |paymaster: Option<Address>| {
+            UserOperation::from_user_operation_signed(
+                Default::default(),
+                UserOperationSigned {
+                    paymaster_and_data: paymaster
+                        .map(|paymaster| paymaster.as_bytes().to_vec())
+                        .unwrap_or_default()
+                        .into(),
+                    ..UserOperationSigned::random()
+                },
+            )
+        };
+
+        let allowlists = [
+            PaymasterAllowlist::default(),
+            PaymasterAllowlist::new(vec![allowed], false),
+            PaymasterAllowlist::new(vec![allowed], true),
+        ];
+        let helpers: Vec<_> = allowlists
+            .iter()
+            .map(|paymaster_allowlist| SanityHelper {
+                entry_point: &entry_point,
+                chain: Chain::from(5),
+                code_cache: None,
+                deposits: Default::default(),
+                block: None,
+                timeout: Duration::from_millis(RPC_TIMEOUT),
+                paymaster_allowlist,
+            })
+            .collect();
+
+        // any user operation is allowed by default
+        for uo in [uo(None), uo(Some(other))] {
+            assert!(Sponsorship
+                .check_user_operation(&uo, &mempool, &reputation, &helpers[0])
+                .await
+                .is_ok());
+        }
+
+        // only the allowed paymasters sponsor the user operations
+        for uo in [uo(Some(allowed)), uo(None)] {
+            assert!(Sponsorship
+                .check_user_operation(&uo, &mempool, &reputation, &helpers[1])
+                .await
+                .is_ok());
+        }
+        assert!(matches!(
+            Sponsorship
+                .check_user_operation(&uo(Some(other)), &mempool, &reputation, &helpers[1])
+                .await,
+            Err(SanityError::PaymasterNotAllowed { address }) if address == other
+        ));
+
+        // the user operations without a paymaster are refused if a paymaster is required
+        assert!(Sponsorship
+            .check_user_operation(&uo(Some(allowed)), &mempool, &reputation, &helpers[2])
+            .await
+            .is_ok());
+        assert!(matches!(
+            Sponsorship.check_user_operation(&uo(None), &mempool, &reputation, &helpers[2]).await,
+            Err(SanityError::PaymasterRequired)
+        ));
+    }
+}
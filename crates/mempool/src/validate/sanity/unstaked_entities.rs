@@ -4,18 +4,13 @@ use crate::{
     validate::{SanityCheck, SanityHelper},
     ReputationError, SanityError,
 };
-use ethers::{
-    providers::Middleware,
-    types::{Address, U256},
-};
+use ethers::{providers::Middleware, types::Address};
 use silius_primitives::{
     constants::validation::{
         entities::{FACTORY, PAYMASTER, SENDER},
-        reputation::{
-            INCLUSION_RATE_FACTOR, SAME_SENDER_MEMPOOL_COUNT, SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT,
-        },
+        reputation::{INCLUSION_RATE_FACTOR, SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT},
     },
-    reputation::{ReputationEntry, StakeInfo},
+    reputation::ReputationEntry,
     UserOperation,
 };
 use std::cmp;
@@ -24,21 +19,6 @@ use std::cmp;
 pub struct UnstakedEntities;
 
 impl UnstakedEntities {
-    /// Gets the deposit info for entity.
-    async fn get_stake<'a, M: Middleware>(
-        &self,
-        addr: &Address,
-        helper: &SanityHelper<'a, M>,
-    ) -> Result<StakeInfo, SanityError> {
-        let info = helper.entry_point.get_deposit_info(addr).await?;
-
-        Ok(StakeInfo {
-            address: *addr,
-            stake: U256::from(info.stake),
-            unstake_delay: U256::from(info.unstake_delay_sec),
-        })
-    }
-
     /// Gets the reputation entry for entity.
     fn get_entity<M: Middleware, H, R>(
         &self,
@@ -54,7 +34,7 @@ impl UnstakedEntities {
     }
 
     /// Calculates allowed number of user operations
-    fn calculate_allowed_user_operations(entity: ReputationEntry) -> u64 {
+    pub(crate) fn calculate_allowed_user_operations(entity: ReputationEntry) -> u64 {
         if entity.uo_seen == 0 {
             SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT as u64
         } else {
@@ -94,6 +74,8 @@ impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
         R: ReputationEntryOp,
     {
         let (sender, factory, paymaster) = uo.get_entities();
+        // the user operation validated in place isn't counted against its own limit
+        let own = mempool.contains_identical(uo).unwrap_or_default() as usize;
 
         // [SREP-010] - the "canonical mempool" defines a staked entity if it has MIN_STAKE_VALUE
         // and unstake delay of MIN_UNSTAKE_DELAY
@@ -109,18 +91,7 @@ impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
             });
         }
 
-        // [UREP-010] - UserOperation with unstaked sender are only allowed up to
-        // SAME_SENDER_MEMPOOL_COUNT times in the mempool
-        let sender_stake = self.get_stake(&sender, helper).await?;
-        if reputation.verify_stake(SENDER, Some(sender_stake)).is_err() &&
-            mempool.get_number_by_sender(&uo.sender) >= SAME_SENDER_MEMPOOL_COUNT
-        {
-            return Err(ReputationError::UnstakedEntity {
-                entity: SENDER.into(),
-                address: uo.sender,
-            }
-            .into());
-        }
+        // [UREP-010] is enforced by the SenderLimit sanity check
 
         // factory
         if let Some(factory) = factory {
@@ -135,12 +106,13 @@ impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
                 });
             }
 
-            let factory_stake = self.get_stake(&factory, helper).await?;
+            let factory_stake = helper.get_stake_info(&factory).await?;
             if reputation.verify_stake(FACTORY, Some(factory_stake)).is_err() {
                 // [UREP-020] - for other entities
                 let entity = self.get_entity(&factory, helper, reputation)?;
                 let uos_allowed = Self::calculate_allowed_user_operations(entity);
-                if mempool.get_number_by_entity(&factory) as u64 >= uos_allowed {
+                if mempool.get_number_by_entity(&factory).saturating_sub(own) as u64 >= uos_allowed
+                {
                     return Err(ReputationError::UnstakedEntity {
                         entity: FACTORY.into(),
                         address: factory,
@@ -163,12 +135,14 @@ impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
                 });
             }
 
-            let paymaster_stake = self.get_stake(&paymaster, helper).await?;
+            let paymaster_stake = helper.get_stake_info(&paymaster).await?;
             if reputation.verify_stake(PAYMASTER, Some(paymaster_stake)).is_err() {
                 // [UREP-020] - for other entities
                 let entity = self.get_entity(&paymaster, helper, reputation)?;
                 let uos_allowed = Self::calculate_allowed_user_operations(entity);
-                if mempool.get_number_by_entity(&paymaster) as u64 >= uos_allowed {
+                if mempool.get_number_by_entity(&paymaster).saturating_sub(own) as u64 >=
+                    uos_allowed
+                {
                     return Err(ReputationError::UnstakedEntity {
                         entity: PAYMASTER.into(),
                         address: paymaster,
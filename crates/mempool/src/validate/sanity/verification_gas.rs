@@ -1,15 +1,22 @@
 use crate::{
     mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
     reputation::{HashSetOp, ReputationEntryOp},
+    utils::calculate_pre_verification_gas,
     validate::{SanityCheck, SanityHelper},
-    Overhead, Reputation, SanityError,
+    Reputation, SanityError,
 };
 use ethers::{providers::Middleware, types::U256};
 use silius_primitives::UserOperation;
 
 #[derive(Clone)]
 pub struct VerificationGas {
+    /// Maximum verification gas limit of the user operation
     pub max_verification_gas: U256,
+    /// Maximum pre-verification gas as a percentage of the calculated pre-verification gas (not
+    /// enforced if not set)
+    pub max_pre_verification_gas_perc: Option<u64>,
+    /// Gas added to the calculated pre-verification gas
+    pub pre_verification_gas_overhead: U256,
 }
 
 #[async_trait::async_trait]
@@ -30,7 +37,7 @@ impl<M: Middleware> SanityCheck<M> for VerificationGas {
         uo: &UserOperation,
         _mempool: &Mempool<T, Y, X, Z>,
         _reputation: &Reputation<H, R>,
-        _helper: &SanityHelper<M>,
+        helper: &SanityHelper<M>,
     ) -> Result<(), SanityError>
     where
         T: UserOperationAct,
@@ -47,7 +54,18 @@ impl<M: Middleware> SanityCheck<M> for VerificationGas {
             });
         }
 
-        let pre_gas = Overhead::default().calculate_pre_verification_gas(uo);
+        let pre_gas = helper
+            .with_timeout(async {
+                calculate_pre_verification_gas(
+                    uo,
+                    helper.entry_point,
+                    helper.chain,
+                    self.pre_verification_gas_overhead,
+                )
+                .await
+                .map_err(Into::into)
+            })
+            .await?;
         if uo.pre_verification_gas < pre_gas {
             return Err(SanityError::PreVerificationGasTooLow {
                 pre_verification_gas: uo.pre_verification_gas,
@@ -55,6 +73,123 @@ impl<M: Middleware> SanityCheck<M> for VerificationGas {
             });
         }
 
+        if let Some(perc) = self.max_pre_verification_gas_perc {
+            let pre_gas_max = pre_gas.saturating_mul(perc.into()) / 100;
+            if uo.pre_verification_gas > pre_gas_max {
+                return Err(SanityError::PreVerificationGasTooHigh {
+                    pre_verification_gas: uo.pre_verification_gas,
+                    pre_verification_gas_expected: pre_gas_max,
+                });
+            }
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+    use ethers::{providers::Provider, types::Address};
+    use silius_contracts::EntryPoint;
+    use silius_primitives::{
+        constants::validation::{
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
+            sanity::RPC_TIMEOUT,
+        },
+        reputation::ReputationEntry,
+        simulation::CodeHash,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+        time::Duration,
+    };
+
+    #[tokio::test]
+    async fn verification_gas_bounds() {
+        let (eth_client, _) = Provider::mocked();
+        let entry_point = EntryPoint::new(Arc::new(eth_client), Address::random());
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            chain: Chain::from(5),
+            code_cache: None,
+            deposits: Default::default(),
+            block: None,
+            timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: &Default::default(),
+        };
+
+        let mempool = Mempool::new(
+            HashMap::<UserOperationHash, UserOperationSigned>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
+        );
+        let reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let base = UserOperationSigned { sender: Address::random(), ..Default::default() };
+        let uo = |verification_gas_limit: u64, pre_verification_gas: u64| {
+            UserOperation::from_user_operation_signed(
+                Default::default(),
+                UserOperationSigned {
+                    verification_gas_limit: verification_gas_limit.into(),
+                    pre_verification_gas: pre_verification_gas.into(),
+                    ..base.clone()
+                },
+            )
+        };
+        let check = VerificationGas {
+            max_verification_gas: U256::from(1_000_000),
+            max_pre_verification_gas_perc: None,
+            pre_verification_gas_overhead: U256::zero(),
+        };
+
+        // the user operation pays between 2 and 3 times the calculated pre-verification gas
+        let uo_priced = uo(1_000_000, 100_000);
+        let pre_gas =
+            calculate_pre_verification_gas(&uo_priced, &entry_point, helper.chain, U256::zero())
+                .await
+                .unwrap();
+        assert!(pre_gas * 2 < U256::from(100_000) && pre_gas * 3 >= U256::from(100_000));
+
+        assert!(check
+            .check_user_operation(&uo_priced, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        assert!(matches!(
+            check
+                .check_user_operation(&uo(1_000_001, 100_000), &mempool, &reputation, &helper)
+                .await,
+            Err(SanityError::VerificationGasLimitTooHigh { .. })
+        ));
+
+        assert!(matches!(
+            check.check_user_operation(&uo(1_000_000, 1), &mempool, &reputation, &helper).await,
+            Err(SanityError::PreVerificationGasTooLow { .. })
+        ));
+
+        let check = VerificationGas { max_pre_verification_gas_perc: Some(300), ..check };
+        assert!(check
+            .check_user_operation(&uo_priced, &mempool, &reputation, &helper)
+            .await
+            .is_ok());
+
+        let check = VerificationGas { max_pre_verification_gas_perc: Some(200), ..check };
+        assert!(matches!(
+            check.check_user_operation(&uo_priced, &mempool, &reputation, &helper).await,
+            Err(SanityError::PreVerificationGasTooHigh { pre_verification_gas_expected, .. })
+                if pre_verification_gas_expected == pre_gas * 2
+        ));
+    }
+}
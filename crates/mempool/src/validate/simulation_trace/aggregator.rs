@@ -0,0 +1,167 @@
+use crate::{
+    mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{utils::extract_aggregator, SimulationTraceCheck, SimulationTraceHelper},
+    Reputation, ReputationError, SimulationError,
+};
+use ethers::providers::Middleware;
+use silius_contracts::AggregatorAPI;
+use silius_primitives::{
+    constants::validation::entities::AGGREGATOR,
+    reputation::{StakeInfo, Status},
+    UserOperation,
+};
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct Aggregator;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for Aggregator {
+    /// The [check_user_operation] method implementation that checks the aggregator of the user
+    /// operation (if any) is staked and not banned, and that it accepts the signature of the user
+    /// operation
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to check
+    /// `reputation` - The [reputation](crate::Reputation)
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        reputation: &Reputation<H, R>,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        match extract_aggregator(helper.simulate_validation_result) {
+            Some(info) => {
+                self.check_aggregator(uo, info, reputation, helper.entry_point.eth_client()).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The aggregator check doesn't inspect the trace, so it also runs in the unsafe mode
+    fn requires_trace(&self) -> bool {
+        false
+    }
+}
+
+impl Aggregator {
+    /// Checks the aggregator is staked and not banned, and that it accepts the signature of the
+    /// user operation (the bundler gets the signature to bundle the user operation with from the
+    /// aggregator when aggregating the signatures)
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to check
+    /// `info` - The [StakeInfo](StakeInfo) of the aggregator
+    /// `reputation` - The [reputation](crate::Reputation)
+    /// `eth_client` - The Ethereum execution client
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_aggregator<M: Middleware, H: HashSetOp, R: ReputationEntryOp>(
+        &self,
+        uo: &UserOperation,
+        info: StakeInfo,
+        reputation: &Reputation<H, R>,
+        eth_client: Arc<M>,
+    ) -> Result<(), SimulationError> {
+        if Status::from(reputation.get_status(&info.address)?) == Status::BANNED {
+            return Err(ReputationError::BannedEntity {
+                entity: AGGREGATOR.into(),
+                address: info.address,
+            }
+            .into());
+        }
+
+        reputation.verify_stake(AGGREGATOR, Some(info))?;
+
+        // the aggregator validates the signature of the single user operation off-chain
+        AggregatorAPI::new(info.address, eth_client)
+            .validate_user_op_signature(uo.user_operation.clone().into())
+            .call()
+            .await
+            .map_err(|_| SimulationError::Signature)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::{encode, Token},
+        providers::Provider,
+        types::{Address, Bytes, U256},
+    };
+    use silius_primitives::{
+        constants::validation::reputation::{
+            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        },
+        reputation::ReputationEntry,
+        UserOperationSigned,
+    };
+    use std::collections::{HashMap, HashSet};
+
+    #[tokio::test]
+    async fn aggregator_stake_and_signature() {
+        let (eth_client, mock) = Provider::mocked();
+        let eth_client = Arc::new(eth_client);
+        let mut reputation =
+            Reputation::<HashSet<Address>, HashMap<Address, ReputationEntry>>::new_default(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+            );
+
+        let uo = UserOperation::from_user_operation_signed(
+            Default::default(),
+            UserOperationSigned::random(),
+        );
+        let info = |stake: u64| StakeInfo {
+            address: Address::random(),
+            stake: U256::from(stake),
+            unstake_delay: U256::from(2),
+        };
+
+        // the aggregator accepts the signature (sigForUserOp is empty)
+        mock.push(Bytes::from(encode(&[Token::Bytes(vec![])]))).unwrap();
+        assert!(Aggregator
+            .check_aggregator(&uo, info(1), &reputation, eth_client.clone())
+            .await
+            .is_ok());
+
+        // the call to the aggregator fails (e.g., it reverts on an invalid signature)
+        assert!(matches!(
+            Aggregator.check_aggregator(&uo, info(1), &reputation, eth_client.clone()).await,
+            Err(SimulationError::Signature)
+        ));
+
+        assert!(matches!(
+            Aggregator.check_aggregator(&uo, info(0), &reputation, eth_client.clone()).await,
+            Err(SimulationError::Reputation(ReputationError::StakeTooLow { .. }))
+        ));
+
+        let banned = info(1);
+        reputation.add_blacklist(&banned.address);
+        assert!(matches!(
+            Aggregator.check_aggregator(&uo, banned, &reputation, eth_client).await,
+            Err(SimulationError::Reputation(ReputationError::BannedEntity { .. }))
+        ));
+    }
+}
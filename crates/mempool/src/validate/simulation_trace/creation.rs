@@ -0,0 +1,173 @@
+use crate::{
+    mempool::{UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
+    reputation::{HashSetOp, ReputationEntryOp},
+    validate::{SimulationTraceCheck, SimulationTraceHelper},
+    Mempool, Reputation, SimulationError,
+};
+use ethers::{providers::Middleware, types::Address};
+use silius_contracts::{entry_point::SELECTORS_INDICES, tracer::JsTracerFrame};
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, LEVEL_TO_ENTITY},
+    simulation::{CREATE2_OPCODE, CREATE_OPCODE, SELFDESTRUCT_OPCODE},
+    UserOperation,
+};
+
+/// Checks that the validation doesn't create contracts (other than the sender deployed by the
+/// factory) and doesn't self-destruct, regardless of the configured forbidden opcodes
+#[derive(Clone)]
+pub struct Creation;
+
+impl Creation {
+    /// The helper method that checks the contract creations and self-destructs in the trace.
+    ///
+    /// # Arguments
+    /// `sender` - The sender of the user operation
+    /// `trace` - The [JsTracerFrame] of the validation
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_trace(&self, sender: &Address, trace: &JsTracerFrame) -> Result<(), SimulationError> {
+        for call_info in trace.calls_from_entry_point.iter() {
+            let Some(l) = SELECTORS_INDICES.get(call_info.top_level_method_sig.as_ref()).cloned()
+            else {
+                continue;
+            };
+            let entity = LEVEL_TO_ENTITY[l];
+
+            // [OP-011] - SELFDESTRUCT is not allowed
+            if call_info.opcodes.contains_key(&*SELFDESTRUCT_OPCODE) {
+                return Err(SimulationError::SelfDestruct { entity: entity.to_string() });
+            }
+
+            // [OP-011] - CREATE is not allowed
+            if call_info.opcodes.contains_key(&*CREATE_OPCODE) {
+                return Err(SimulationError::ContractCreation {
+                    entity: entity.to_string(),
+                    opcode: CREATE_OPCODE.to_string(),
+                });
+            }
+
+            // [OP-031] - CREATE2 is allowed exactly once in the deployment phase
+            if let Some(c) = call_info.opcodes.get(&*CREATE2_OPCODE) {
+                if entity != FACTORY || *c != 1 {
+                    return Err(SimulationError::ContractCreation {
+                        entity: entity.to_string(),
+                        opcode: CREATE2_OPCODE.to_string(),
+                    });
+                }
+            }
+        }
+
+        // [OP-031] - the only contract created must be the sender
+        for call in trace.calls.iter() {
+            if call.typ == *CREATE2_OPCODE && call.to.is_some_and(|to| to != *sender) {
+                return Err(SimulationError::ContractCreation {
+                    entity: FACTORY.to_string(),
+                    opcode: CREATE2_OPCODE.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for Creation {
+    /// The [check_user_operation] method implementation that checks the contract creations and
+    /// self-destructs during the validation
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper]
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation<T, Y, X, Z, H, R>(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool<T, Y, X, Z>,
+        _reputation: &Reputation<H, R>,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError>
+    where
+        T: UserOperationAct,
+        Y: UserOperationAddrAct,
+        X: UserOperationAddrAct,
+        Z: UserOperationCodeHashAct,
+        H: HashSetOp,
+        R: ReputationEntryOp,
+    {
+        self.check_trace(&uo.sender, helper.js_trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_contracts::tracer::{Call, TopLevelCallInfo};
+    use silius_primitives::constants::validation::entities::SENDER;
+
+    /// Creates the trace of the validation of the entity at the level
+    fn trace(level: usize, opcodes: &[(&str, u64)], created: Option<Address>) -> JsTracerFrame {
+        let sig = SELECTORS_INDICES
+            .iter()
+            .find(|(_, l)| **l == level)
+            .map(|(sig, _)| sig.to_vec().into())
+            .expect("level should have a selector");
+
+        JsTracerFrame {
+            calls_from_entry_point: vec![TopLevelCallInfo {
+                top_level_method_sig: sig,
+                opcodes: opcodes.iter().map(|(op, c)| (op.to_string(), *c)).collect(),
+                ..Default::default()
+            }],
+            calls: created
+                .map(|to| Call {
+                    typ: CREATE2_OPCODE.to_string(),
+                    to: Some(to),
+                    ..Default::default()
+                })
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn contract_creation_during_validation() {
+        let sender = Address::random();
+        let (factory, account) = (0, 1);
+
+        // the factory deploys the sender
+        assert!(Creation
+            .check_trace(&sender, &trace(factory, &[("CREATE2", 1)], Some(sender)))
+            .is_ok());
+        assert!(Creation.check_trace(&sender, &trace(account, &[], None)).is_ok());
+
+        // the factory deploys another contract
+        let other = Some(Address::random());
+        assert!(matches!(
+            Creation.check_trace(&sender, &trace(factory, &[("CREATE2", 1)], other)),
+            Err(SimulationError::ContractCreation { ref entity, ref opcode })
+                if entity == FACTORY && opcode == "CREATE2"
+        ));
+
+        // the account creates contracts
+        assert!(matches!(
+            Creation.check_trace(&sender, &trace(account, &[("CREATE", 1)], None)),
+            Err(SimulationError::ContractCreation { ref entity, ref opcode })
+                if entity == SENDER && opcode == "CREATE"
+        ));
+        assert!(matches!(
+            Creation.check_trace(&sender, &trace(account, &[("CREATE2", 1)], None)),
+            Err(SimulationError::ContractCreation { ref entity, ref opcode })
+                if entity == SENDER && opcode == "CREATE2"
+        ));
+
+        assert!(matches!(
+            Creation.check_trace(&sender, &trace(account, &[("SELFDESTRUCT", 1)], None)),
+            Err(SimulationError::SelfDestruct { ref entity }) if entity == SENDER
+        ));
+    }
+}
@@ -7,13 +7,41 @@ use crate::{
 use ethers::providers::Middleware;
 use silius_contracts::entry_point::SELECTORS_INDICES;
 use silius_primitives::{
-    constants::validation::entities::{FACTORY, LEVEL_TO_ENTITY},
-    simulation::{CREATE2_OPCODE, FORBIDDEN_OPCODES},
-    UserOperation,
+    constants::validation::entities::LEVEL_TO_ENTITY, simulation::forbidden_opcodes,
+    EntryPointVersion, UserOperation,
 };
+use std::collections::{HashMap, HashSet};
 
-#[derive(Clone)]
-pub struct Opcodes;
+/// Checks the use of the forbidden opcodes, which are configurable per entry point version (the
+/// versions without configured opcodes use the [default](forbidden_opcodes) ones)
+#[derive(Clone, Debug, Default)]
+pub struct Opcodes {
+    forbidden_opcodes: HashMap<EntryPointVersion, HashSet<String>>,
+}
+
+impl Opcodes {
+    /// Creates a new [Opcodes](Opcodes) check
+    ///
+    /// # Arguments
+    /// * `forbidden_opcodes` - The forbidden opcodes by the entry point version
+    ///
+    /// # Returns
+    /// * `Opcodes` - The [Opcodes](Opcodes) check
+    pub fn new(forbidden_opcodes: HashMap<EntryPointVersion, HashSet<String>>) -> Self {
+        Self { forbidden_opcodes }
+    }
+
+    /// Gets the forbidden opcodes of the entry point version
+    ///
+    /// # Arguments
+    /// * `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+    ///
+    /// # Returns
+    /// * `HashSet<String>` - The forbidden opcodes
+    pub fn forbidden_opcodes(&self, version: EntryPointVersion) -> HashSet<String> {
+        self.forbidden_opcodes.get(&version).cloned().unwrap_or_else(|| forbidden_opcodes(version))
+    }
+}
 
 #[async_trait::async_trait]
 impl<M: Middleware> SimulationTraceCheck<M> for Opcodes {
@@ -40,34 +68,46 @@ impl<M: Middleware> SimulationTraceCheck<M> for Opcodes {
         H: HashSetOp,
         R: ReputationEntryOp,
     {
+        let forbidden_opcodes = self.forbidden_opcodes(helper.entry_point.version());
+
         for call_info in helper.js_trace.calls_from_entry_point.iter() {
             let level = SELECTORS_INDICES.get(call_info.top_level_method_sig.as_ref()).cloned();
 
             if let Some(l) = level {
                 // [OP-011] - block opcodes
                 for op in call_info.opcodes.keys() {
-                    if FORBIDDEN_OPCODES.contains(op) {
+                    if forbidden_opcodes.contains(op) {
                         return Err(SimulationError::Opcode {
                             entity: LEVEL_TO_ENTITY[l].to_string(),
                             opcode: op.clone(),
                         });
                     }
                 }
-
-                // [OP-031] - CREATE2 is allowed exactly once in the deployment phase and must
-                // deploy code for the "sender" address
-                if let Some(c) = call_info.opcodes.get(&*CREATE2_OPCODE) {
-                    if LEVEL_TO_ENTITY[l] == FACTORY && *c == 1 {
-                        continue;
-                    }
-                    return Err(SimulationError::Opcode {
-                        entity: LEVEL_TO_ENTITY[l].to_string(),
-                        opcode: CREATE2_OPCODE.to_string(),
-                    });
-                }
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forbidden_opcodes_per_version() {
+        let opcodes = Opcodes::new(HashMap::from([(
+            EntryPointVersion::V0_7,
+            HashSet::from(["GAS".to_string(), "NUMBER".to_string()]),
+        )]));
+
+        assert_eq!(
+            opcodes.forbidden_opcodes(EntryPointVersion::V0_7),
+            HashSet::from(["GAS".to_string(), "NUMBER".to_string()])
+        );
+        assert_eq!(
+            opcodes.forbidden_opcodes(EntryPointVersion::V0_6),
+            forbidden_opcodes(EntryPointVersion::V0_6)
+        );
+    }
+}
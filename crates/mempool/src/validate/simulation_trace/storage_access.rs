@@ -11,7 +11,7 @@ use ethers::{
 };
 use silius_contracts::entry_point::SELECTORS_INDICES;
 use silius_primitives::{
-    constants::validation::entities::{FACTORY_LEVEL, LEVEL_TO_ENTITY, NUMBER_OF_LEVELS},
+    constants::validation::entities::{FACTORY, FACTORY_LEVEL, LEVEL_TO_ENTITY, NUMBER_OF_LEVELS},
     reputation::StakeInfo,
     UserOperation,
 };
@@ -91,6 +91,46 @@ impl StorageAccess {
 
         Ok(false)
     }
+
+    /// The helper method that checks the access to the associated storage of the account in an
+    /// external (non-entity) contract.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `entity` - The stake info of the entity that accessed the slot
+    /// `factory` - The stake info of the factory
+    /// `slot` - The accessed slot
+    ///
+    /// # Returns
+    /// false if the access is allowed, true if it's only allowed if the entity is staked, otherwise
+    /// a [SimulationError] error.
+    fn check_associated_storage(
+        uo: &UserOperation,
+        entity: &StakeInfo,
+        factory: &StakeInfo,
+        slot: &str,
+    ) -> Result<bool, SimulationError> {
+        // [STO-021] - allowed if the account already exists
+        if uo.init_code.is_empty() {
+            return Ok(false);
+        }
+
+        if uo.sender != entity.address {
+            return Ok(true);
+        }
+
+        // [STO-022] - the account accesses its associated storage during the deployment only if
+        // the factory is staked
+        if !factory.is_staked() {
+            return Err(SimulationError::Unstaked {
+                entity: FACTORY.into(),
+                address: factory.address,
+                inner: format!("account accessed its associated slot {slot} during the deployment"),
+            });
+        }
+
+        Ok(false)
+    }
 }
 
 #[async_trait::async_trait]
@@ -158,10 +198,12 @@ impl<M: Middleware> SimulationTraceCheck<M> for StorageAccess {
                             // an external (non-entity contract) is allowed if either The account
                             // already exists or There is an initCode and the factory contract is
                             // staked
-                            if !(uo.init_code.is_empty() ||
-                                uo.sender == stake_info_l.address &&
-                                    stake_info[FACTORY_LEVEL].is_staked())
-                            {
+                            if Self::check_associated_storage(
+                                uo,
+                                &stake_info_l,
+                                &stake_info[FACTORY_LEVEL],
+                                &slot,
+                            )? {
                                 slot_staked = slot.clone();
                             }
                         } else if *addr == stake_info_l.address // [STO-031] - access the entity's own storage (if entity staked)
@@ -190,3 +232,66 @@ impl<M: Middleware> SimulationTraceCheck<M> for StorageAccess {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::UserOperationSigned;
+
+    #[test]
+    fn associated_storage_during_deployment() {
+        let staked = |address: Address| StakeInfo {
+            address,
+            stake: U256::from(1),
+            unstake_delay: U256::from(1),
+        };
+        let unstaked = |address: Address| StakeInfo { address, ..Default::default() };
+        let (sender, factory, paymaster) =
+            (Address::random(), Address::random(), Address::random());
+        let uo = |init_code: Vec<u8>| {
+            UserOperation::from_user_operation_signed(
+                Default::default(),
+                UserOperationSigned { sender, init_code: init_code.into(), ..Default::default() },
+            )
+        };
+        let deployed = uo(vec![]);
+        let deploying = uo(factory.as_bytes().to_vec());
+
+        // [STO-021] - the account already exists
+        assert!(!StorageAccess::check_associated_storage(
+            &deployed,
+            &unstaked(paymaster),
+            &unstaked(Address::zero()),
+            "0"
+        )
+        .unwrap());
+
+        // [STO-022] - the account's own validation needs a staked factory
+        assert!(!StorageAccess::check_associated_storage(
+            &deploying,
+            &unstaked(sender),
+            &staked(factory),
+            "0"
+        )
+        .unwrap());
+        assert!(matches!(
+            StorageAccess::check_associated_storage(
+                &deploying,
+                &unstaked(sender),
+                &unstaked(factory),
+                "0"
+            ),
+            Err(SimulationError::Unstaked { entity, address, .. })
+                if entity == FACTORY && address == factory
+        ));
+
+        // the other entities need to be staked themselves
+        assert!(StorageAccess::check_associated_storage(
+            &deploying,
+            &unstaked(paymaster),
+            &staked(factory),
+            "0"
+        )
+        .unwrap());
+    }
+}
@@ -1,8 +1,11 @@
 use ethers::types::{Address, U256};
 use silius_contracts::{entry_point::SimulateValidationResult, tracer::JsTracerFrame};
 use silius_primitives::{
-    constants::validation::entities::NUMBER_OF_LEVELS, get_address, reputation::StakeInfo,
-    simulation::StorageMap, UserOperation,
+    constants::validation::entities::NUMBER_OF_LEVELS,
+    get_address,
+    reputation::StakeInfo,
+    simulation::{StorageAccesses, StorageMap},
+    UserOperation,
 };
 
 /// Helper function to extract the gas limit for verification from the simulation result
@@ -90,6 +93,23 @@ pub fn extract_stake_info(
     ]
 }
 
+/// Helper function to extract the aggregator stake info from the simulation result
+///
+/// # Arguments
+/// `sim_res` - The [simulation result](SimulateValidationResult) from the simulation
+///
+/// # Returns
+/// The stake info of the aggregator if the user operation uses one
+pub fn extract_aggregator(sim_res: &SimulateValidationResult) -> Option<StakeInfo> {
+    match sim_res {
+        SimulateValidationResult::ValidationResult(_) => None,
+        SimulateValidationResult::ValidationResultWithAggregation(res) => {
+            let (address, (stake, unstake_delay)) = res.aggregator_info;
+            Some(StakeInfo { address, stake, unstake_delay })
+        }
+    }
+}
+
 /// Helper function to extract the storage map from the simulation result
 ///
 /// # Arguments
@@ -108,3 +128,33 @@ pub fn extract_storage_map(js_trace: &JsTracerFrame) -> StorageMap {
 
     storage_map
 }
+
+/// Helper function to extract the storage slots read and written during the validation from the
+/// simulation result
+///
+/// # Arguments
+/// `js_trace` - The [js tracer frame](JsTracerFrame) to extract the storage accesses from
+///
+/// # Returns
+/// The [storage accesses](StorageAccesses)
+pub fn extract_storage_accesses(js_trace: &JsTracerFrame) -> StorageAccesses {
+    let mut accesses = StorageAccesses::default();
+
+    for l in js_trace.calls_from_entry_point.iter() {
+        for (addr, acc) in l.access.iter() {
+            accesses.writes.entry(*addr).or_default().extend(acc.writes.keys().cloned());
+            accesses.reads.entry(*addr).or_default().extend(acc.reads.keys().cloned());
+        }
+    }
+
+    // a slot that is written is not counted as read
+    for (addr, reads) in accesses.reads.iter_mut() {
+        if let Some(writes) = accesses.writes.get(addr) {
+            reads.retain(|slot| !writes.contains(slot));
+        }
+    }
+    accesses.reads.retain(|_, reads| !reads.is_empty());
+    accesses.writes.retain(|_, writes| !writes.is_empty());
+
+    accesses
+}
@@ -1,19 +1,26 @@
 use super::{
     sanity::{
-        call_gas::CallGas, entities::Entities, max_fee::MaxFee, paymaster::Paymaster,
-        sender::Sender, unstaked_entities::UnstakedEntities, verification_gas::VerificationGas,
+        blocklist::Blocklist, call_gas::CallGas, entities::Entities, factory::Factory,
+        max_fee::MaxFee, paymaster::Paymaster, paymaster_data::PaymasterData, sender::Sender,
+        sender_limit::SenderLimit, signature::SenderSignature, size::Size,
+        sponsorship::Sponsorship, unstaked_entities::UnstakedEntities,
+        verification_gas::VerificationGas,
     },
     simulation::{
         signature::Signature, timestamp::Timestamp, verification_extra_gas::VerificationExtraGas,
     },
     simulation_trace::{
-        call_stack::CallStack, code_hashes::CodeHashes, external_contracts::ExternalContracts,
-        gas::Gas, opcodes::Opcodes, storage_access::StorageAccess,
+        aggregator::Aggregator, call_stack::CallStack, code_hashes::CodeHashes, creation::Creation,
+        external_contracts::ExternalContracts, gas::Gas, opcodes::Opcodes,
+        storage_access::StorageAccess,
     },
-    utils::{extract_pre_fund, extract_storage_map, extract_verification_gas_limit},
-    SanityCheck, SanityHelper, SimulationCheck, SimulationHelper, SimulationTraceCheck,
-    SimulationTraceHelper, UserOperationValidationOutcome, UserOperationValidator,
-    UserOperationValidatorMode,
+    utils::{
+        extract_aggregator, extract_pre_fund, extract_stake_info, extract_storage_accesses,
+        extract_storage_map, extract_verification_gas_limit,
+    },
+    CodeCache, PaymasterAllowlist, SanityCheck, SanityHelper, SimulationCheck, SimulationHelper,
+    SimulationTraceCheck, SimulationTraceHelper, UserOperationValidationOutcome,
+    UserOperationValidator, UserOperationValidatorMode,
 };
 use crate::{
     mempool::{Mempool, UserOperationAct, UserOperationAddrAct, UserOperationCodeHashAct},
@@ -24,28 +31,65 @@ use alloy_chains::Chain;
 use enumset::EnumSet;
 use ethers::{
     providers::Middleware,
-    types::{BlockNumber, GethTrace, U256},
+    types::{Address, BlockNumber, GethTrace, U256},
 };
 use silius_contracts::{
     entry_point::{EntryPointError, SimulateValidationResult},
     tracer::JsTracerFrame,
     EntryPoint,
 };
-use silius_primitives::UserOperation;
+use silius_primitives::{
+    constants::validation::sanity::RPC_TIMEOUT, mempool::EqualFeeReplacement, EntryPointVersion,
+    UserOperation,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 use tracing::debug;
 
 pub type StandardValidator<M> = StandardUserOperationValidator<
     M,
-    (Sender, VerificationGas, CallGas, MaxFee, Paymaster, Entities, UnstakedEntities),
+    (
+        Blocklist,
+        Sponsorship,
+        Sender,
+        SenderSignature,
+        Factory,
+        SenderLimit,
+        Size,
+        VerificationGas,
+        CallGas,
+        MaxFee,
+        PaymasterData,
+        Paymaster,
+        Entities,
+        UnstakedEntities,
+    ),
     (Signature, Timestamp, VerificationExtraGas),
-    (Gas, Opcodes, ExternalContracts, StorageAccess, CallStack, CodeHashes),
+    (Gas, Creation, Opcodes, ExternalContracts, StorageAccess, CallStack, CodeHashes, Aggregator),
 >;
 
 type UnsafeValidator<M> = StandardUserOperationValidator<
     M,
-    (Sender, VerificationGas, CallGas, MaxFee, Paymaster, Entities, UnstakedEntities),
+    (
+        Blocklist,
+        Sponsorship,
+        Sender,
+        SenderSignature,
+        Factory,
+        SenderLimit,
+        Size,
+        VerificationGas,
+        CallGas,
+        MaxFee,
+        PaymasterData,
+        Paymaster,
+        Entities,
+        UnstakedEntities,
+    ),
     (Signature, Timestamp, VerificationExtraGas),
-    (),
+    (Aggregator,),
 >;
 
 /// Standard implementation of [UserOperationValidator](UserOperationValidator).
@@ -65,6 +109,14 @@ where
     simulation_checks: SimCk,
     /// An array of [SimulationTraceChecks](SimulationTraceCheck).
     simulation_trace_checks: SimTrCk,
+    /// The [CodeCache](CodeCache) used by the sanity checks (disabled if `None`)
+    code_cache: Option<CodeCache>,
+    /// Block the sanity checks read the chain state at (the latest block if `None`)
+    block: Option<BlockNumber>,
+    /// Time the sanity checks wait for each call to the Ethereum execution client
+    rpc_timeout: Duration,
+    /// Paymasters allowed to sponsor the user operations (any paymaster is allowed by default)
+    paymaster_allowlist: PaymasterAllowlist,
 }
 
 impl<M: Middleware + Clone + 'static, SanCk, SimCk, SimTrCk> Clone
@@ -81,6 +133,10 @@ where
             sanity_checks: self.sanity_checks.clone(),
             simulation_checks: self.simulation_checks.clone(),
             simulation_trace_checks: self.simulation_trace_checks.clone(),
+            code_cache: self.code_cache.clone(),
+            block: self.block,
+            rpc_timeout: self.rpc_timeout,
+            paymaster_allowlist: self.paymaster_allowlist.clone(),
         }
     }
 }
@@ -93,9 +149,20 @@ where
 /// `chain` - A [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID.
 /// `max_verification_gas` - max verification gas that bundler would accept for one user operation
 /// `min_priority_fee_per_gas` - min priority fee per gas that bundler would accept for one user
-/// operation `max_uos_per_sender` - max user operations that bundler would accept from one sender
+/// operation
+/// `max_uo_size` - max encoded size (in bytes) of one user operation that bundler would accept
 /// `gas_increase_perc` - gas increase percentage that bundler would accept for overwriting one user
 /// operation
+/// `equal_fee_replacement` - how a user operation with the same sender, nonce and fees as a user
+/// operation in the mempool is handled
+/// `max_uos_per_sender` - max number of user operations an unstaked sender can have in the mempool
+/// `max_pre_verification_gas_perc` - max pre-verification gas as a percentage of the calculated
+/// pre-verification gas (not enforced if `None`)
+/// `pre_verification_gas_overhead` - gas added to the calculated pre-verification gas
+/// `min_base_fee_perc` - min max fee per gas as a percentage of the current base fee per gas
+/// `staked_grace_perc` - percentage by which the base fee floor is lowered for staked senders
+/// `forbidden_opcodes` - forbidden opcodes by the entry point version (the versions without
+/// configured opcodes use the default ones)
 ///
 /// # Returns
 /// A new [StandardUserOperationValidator](StandardUserOperationValidator).
@@ -104,21 +171,50 @@ pub fn new_canonical<M: Middleware + 'static>(
     chain: Chain,
     max_verification_gas: U256,
     min_priority_fee_per_gas: U256,
+    max_uo_size: usize,
+    gas_increase_perc: U256,
+    equal_fee_replacement: EqualFeeReplacement,
+    max_uos_per_sender: usize,
+    max_pre_verification_gas_perc: Option<u64>,
+    pre_verification_gas_overhead: U256,
+    min_base_fee_perc: u64,
+    staked_grace_perc: u64,
+    forbidden_opcodes: HashMap<EntryPointVersion, HashSet<String>>,
 ) -> StandardValidator<M> {
     StandardUserOperationValidator::new(
         entry_point,
         chain,
         (
-            Sender,
-            VerificationGas { max_verification_gas },
+            Blocklist,
+            Sponsorship,
+            Sender { gas_increase_perc, equal_fee_replacement },
+            SenderSignature,
+            Factory,
+            SenderLimit { max_uos_per_sender },
+            Size { max_uo_size },
+            VerificationGas {
+                max_verification_gas,
+                max_pre_verification_gas_perc,
+                pre_verification_gas_overhead,
+            },
             CallGas,
-            MaxFee { min_priority_fee_per_gas },
+            MaxFee::new(min_priority_fee_per_gas, min_base_fee_perc, staked_grace_perc),
+            PaymasterData,
             Paymaster,
             Entities,
             UnstakedEntities,
         ),
         (Signature, Timestamp, VerificationExtraGas),
-        (Gas, Opcodes, ExternalContracts, StorageAccess, CallStack, CodeHashes),
+        (
+            Gas,
+            Creation,
+            Opcodes::new(forbidden_opcodes),
+            ExternalContracts,
+            StorageAccess,
+            CallStack,
+            CodeHashes,
+            Aggregator,
+        ),
     )
 }
 
@@ -127,21 +223,40 @@ pub fn new_canonical_unsafe<M: Middleware + Clone + 'static>(
     chain: Chain,
     max_verification_gas: U256,
     min_priority_fee_per_gas: U256,
+    max_uo_size: usize,
+    gas_increase_perc: U256,
+    equal_fee_replacement: EqualFeeReplacement,
+    max_uos_per_sender: usize,
+    max_pre_verification_gas_perc: Option<u64>,
+    pre_verification_gas_overhead: U256,
+    min_base_fee_perc: u64,
+    staked_grace_perc: u64,
 ) -> UnsafeValidator<M> {
     StandardUserOperationValidator::new(
         entry_point.clone(),
         chain,
         (
-            Sender,
-            VerificationGas { max_verification_gas },
+            Blocklist,
+            Sponsorship,
+            Sender { gas_increase_perc, equal_fee_replacement },
+            SenderSignature,
+            Factory,
+            SenderLimit { max_uos_per_sender },
+            Size { max_uo_size },
+            VerificationGas {
+                max_verification_gas,
+                max_pre_verification_gas_perc,
+                pre_verification_gas_overhead,
+            },
             CallGas,
-            MaxFee { min_priority_fee_per_gas },
+            MaxFee::new(min_priority_fee_per_gas, min_base_fee_perc, staked_grace_perc),
+            PaymasterData,
             Paymaster,
             Entities,
             UnstakedEntities,
         ),
         (Signature, Timestamp, VerificationExtraGas),
-        (),
+        (Aggregator,),
     )
 }
 
@@ -159,7 +274,91 @@ where
         simulation_checks: SimCk,
         simulation_trace_checks: SimTrCk,
     ) -> Self {
-        Self { entry_point, chain, sanity_checks, simulation_checks, simulation_trace_checks }
+        Self {
+            entry_point,
+            chain,
+            sanity_checks,
+            simulation_checks,
+            simulation_trace_checks,
+            code_cache: None,
+            block: None,
+            rpc_timeout: Duration::from_millis(RPC_TIMEOUT),
+            paymaster_allowlist: Default::default(),
+        }
+    }
+
+    /// Binds the validator to the entry point the user operations are validated against (the
+    /// validators of several entry points share the checks and the caches)
+    ///
+    /// # Arguments
+    /// `address` - The address of the entry point smart contract
+    /// `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+    ///
+    /// # Returns
+    /// The validator bound to the entry point
+    pub fn with_entry_point(mut self, address: Address, version: EntryPointVersion) -> Self {
+        self.entry_point = self.entry_point.at(address, version);
+        self
+    }
+
+    /// Sets the size of the [CodeCache](CodeCache) that caches the code fetched by the sanity
+    /// checks until the next block
+    ///
+    /// # Arguments
+    /// `capacity` - Max number of cached addresses (the cache is disabled if `None`)
+    ///
+    /// # Returns
+    /// The validator with the [CodeCache](CodeCache)
+    pub fn with_code_cache(mut self, capacity: Option<usize>) -> Self {
+        self.code_cache = capacity.map(CodeCache::new);
+        self
+    }
+
+    /// Pins the block the sanity checks read the chain state at (e.g., the code and the stake of
+    /// the entities), so that the validation is reproducible
+    ///
+    /// # Arguments
+    /// `block` - The block number (the latest block if `None`)
+    ///
+    /// # Returns
+    /// The validator with the pinned block
+    pub fn with_block(mut self, block: Option<u64>) -> Self {
+        self.block = block.map(BlockNumber::from);
+        self
+    }
+
+    /// Sets the time the sanity checks wait for each call to the Ethereum execution client, so
+    /// that a slow or hanging node fails the validation instead of stalling it
+    ///
+    /// # Arguments
+    /// `rpc_timeout` - The timeout of each call
+    ///
+    /// # Returns
+    /// The validator with the timeout
+    pub fn with_rpc_timeout(mut self, rpc_timeout: Duration) -> Self {
+        self.rpc_timeout = rpc_timeout;
+        self
+    }
+
+    /// Sets the [PaymasterAllowlist](PaymasterAllowlist) of the paymasters allowed to sponsor the
+    /// user operations (sponsored-only mode)
+    ///
+    /// # Arguments
+    /// `paymaster_allowlist` - The [PaymasterAllowlist](PaymasterAllowlist)
+    ///
+    /// # Returns
+    /// The validator with the paymaster allowlist
+    pub fn with_paymaster_allowlist(mut self, paymaster_allowlist: PaymasterAllowlist) -> Self {
+        self.paymaster_allowlist = paymaster_allowlist;
+        self
+    }
+
+    /// Gets the [PaymasterAllowlist](PaymasterAllowlist), which can be updated at runtime
+    ///
+    /// # Returns
+    /// The [PaymasterAllowlist](PaymasterAllowlist) shared with the validator
+    pub fn paymaster_allowlist(&self) -> &PaymasterAllowlist {
+        &self.paymaster_allowlist
     }
 
     /// Simulates validation of a [UserOperation](UserOperation) via the
@@ -251,10 +450,27 @@ where
         H: HashSetOp,
         R: ReputationEntryOp,
     {
+        // the simulation and bundling go through the v0.6 bindings, v0.7 user operations would
+        // be validated against the wrong ABI
+        if self.entry_point.version() != EntryPointVersion::V0_6 {
+            return Err(SanityError::UnsupportedEntryPointVersion {
+                version: self.entry_point.version().as_str().into(),
+            }
+            .into());
+        }
+
         let mut out: UserOperationValidationOutcome = Default::default();
 
         if mode.contains(UserOperationValidatorMode::Sanity) {
-            let sanity_helper = SanityHelper { entry_point: &self.entry_point, chain: self.chain };
+            let sanity_helper = SanityHelper {
+                entry_point: &self.entry_point,
+                chain: self.chain,
+                code_cache: self.code_cache.as_ref(),
+                deposits: Default::default(),
+                block: self.block,
+                timeout: self.rpc_timeout,
+                paymaster_allowlist: &self.paymaster_allowlist,
+            };
 
             self.sanity_checks
                 .check_user_operation(uo, mempool, reputation, &sanity_helper)
@@ -278,6 +494,8 @@ where
 
         out.pre_fund = extract_pre_fund(&sim_res);
         out.verification_gas_limit = extract_verification_gas_limit(&sim_res);
+        out.aggregator = extract_aggregator(&sim_res);
+        out.stake_info = Some(extract_stake_info(uo, &sim_res));
 
         let block_number = self
             .entry_point
@@ -289,16 +507,25 @@ where
         out.verified_block = U256::from(block_number.hash.expect("block hash should exist").0);
 
         if mode.contains(UserOperationValidatorMode::SimulationTrace) {
-            debug!("Simulate user operation with trace from {:?}", uo.sender);
-            let geth_trace = self.simulate_validation_trace(uo).await?;
-            let js_trace: JsTracerFrame = JsTracerFrame::try_from(geth_trace)
-                .map_err(|error| SimulationError::Validation { inner: error.to_string() })?;
+            // the checks that don't inspect the trace (e.g., the aggregator check) run without
+            // tracing the validation, e.g., in the unsafe mode
+            let js_trace = if self.simulation_trace_checks.requires_trace() {
+                debug!("Simulate user operation with trace from {:?}", uo.sender);
+                let geth_trace = self.simulate_validation_trace(uo).await?;
+                Some(
+                    JsTracerFrame::try_from(geth_trace)
+                        .map_err(|error| SimulationError::Tracer { inner: error.to_string() })?,
+                )
+            } else {
+                None
+            };
+            let js_trace_empty = JsTracerFrame::default();
 
             let mut sim_helper = SimulationTraceHelper {
                 entry_point: &self.entry_point,
                 chain: self.chain,
                 simulate_validation_result: &sim_res,
-                js_trace: &js_trace,
+                js_trace: js_trace.as_ref().unwrap_or(&js_trace_empty),
                 stake_info: None,
                 code_hashes: None,
             };
@@ -308,9 +535,19 @@ where
                 .await?;
 
             out.code_hashes = sim_helper.code_hashes;
-            out.storage_map = Some(extract_storage_map(&js_trace));
+            if let Some(js_trace) = js_trace {
+                out.storage_map = Some(extract_storage_map(&js_trace));
+                out.storage_accesses = Some(extract_storage_accesses(&js_trace));
+            }
         }
 
         Ok(out)
     }
+
+    /// Clears the [CodeCache](CodeCache) (if enabled) since the code may change in the new block
+    fn on_new_block(&self) {
+        if let Some(cache) = &self.code_cache {
+            cache.clear();
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use ethers::types::{Address, I256, U256};
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use std::time::Duration;
+
+const BUNDLER_USER_OPERATIONS_PULLED: &str = "silius_bundler_user_operations_pulled";
+const BUNDLER_SEND_BUNDLE_DURATION: &str = "silius_bundler_send_bundle_duration_seconds";
+const BUNDLER_BUNDLE_USER_OPERATIONS: &str = "silius_bundler_bundle_user_operations";
+const BUNDLER_SEND_BUNDLE_ERROR: &str = "silius_bundler_send_bundle_error";
+const BUNDLER_SIGNER_BALANCE: &str = "silius_bundler_signer_balance";
+const BUNDLER_LOW_BALANCE: &str = "silius_bundler_low_balance";
+const BUNDLER_UOPOOL_UNAVAILABLE: &str = "silius_bundler_uopool_unavailable";
+const BUNDLER_BUNDLE_PROFIT: &str = "silius_bundler_bundle_profit";
+const BUNDLER_AGGREGATION_CACHE_HIT: &str = "silius_bundler_aggregation_cache_hit";
+const BUNDLER_AGGREGATION_CACHE_MISS: &str = "silius_bundler_aggregation_cache_miss";
+
+/// Records the number of user operations pulled from the mempool for a new bundle
+pub fn record_user_operations_pulled(ep: &Address, count: usize) {
+    histogram!(BUNDLER_USER_OPERATIONS_PULLED, "entry_point" => format!("{ep:?}"))
+        .record(count as f64);
+}
+
+/// Records the time spent sending one bundle (including retries)
+pub fn record_send_bundle_duration(ep: &Address, duration: Duration) {
+    histogram!(BUNDLER_SEND_BUNDLE_DURATION, "entry_point" => format!("{ep:?}"))
+        .record(duration.as_secs_f64());
+}
+
+/// Records the number of user operations included in the sent bundle
+pub fn record_bundle_user_operations(ep: &Address, count: usize) {
+    histogram!(BUNDLER_BUNDLE_USER_OPERATIONS, "entry_point" => format!("{ep:?}"))
+        .record(count as f64);
+}
+
+/// Records the failed bundle submission by the error category
+pub fn record_send_bundle_error(ep: &Address, category: &'static str) {
+    counter!(BUNDLER_SEND_BUNDLE_ERROR, "entry_point" => format!("{ep:?}"), "error" => category)
+        .increment(1);
+}
+
+/// Records the balance of the bundler's signer (in wei) and whether it is below the minimum
+/// balance
+pub fn record_signer_balance(address: &Address, balance: U256, low: bool) {
+    gauge!(BUNDLER_SIGNER_BALANCE, "address" => format!("{address:?}"))
+        .set(u128::try_from(balance).unwrap_or(u128::MAX) as f64);
+    gauge!(BUNDLER_LOW_BALANCE, "address" => format!("{address:?}")).set(f64::from(u8::from(low)));
+}
+
+/// Records a call to the uopool gRPC service that failed because the service was unreachable
+pub fn record_uopool_unavailable(ep: &Address) {
+    counter!(BUNDLER_UOPOOL_UNAVAILABLE, "entry_point" => format!("{ep:?}")).increment(1);
+}
+
+/// Records the estimated profit (in wei) of a bundle of the bundler's signer, including the
+/// unprofitable bundles that were skipped
+pub fn record_bundle_profit(address: &Address, profit: I256) {
+    let profit =
+        i128::try_from(profit).unwrap_or(if profit.is_negative() { i128::MIN } else { i128::MAX });
+    histogram!(BUNDLER_BUNDLE_PROFIT, "address" => format!("{address:?}")).record(profit as f64);
+}
+
+/// Records the total number of hits and misses of the aggregated signature cache of the bundler
+pub fn record_aggregation_cache(ep: &Address, hits: u64, misses: u64) {
+    counter!(BUNDLER_AGGREGATION_CACHE_HIT, "entry_point" => format!("{ep:?}")).absolute(hits);
+    counter!(BUNDLER_AGGREGATION_CACHE_MISS, "entry_point" => format!("{ep:?}")).absolute(misses);
+}
+
+pub fn describe_bundler_metrics() {
+    describe_histogram!(
+        BUNDLER_USER_OPERATIONS_PULLED,
+        "The number of user operations pulled from the mempool for each bundle"
+    );
+    describe_histogram!(BUNDLER_SEND_BUNDLE_DURATION, "The time spent sending a bundle");
+    describe_histogram!(
+        BUNDLER_BUNDLE_USER_OPERATIONS,
+        "The number of user operations included in each sent bundle"
+    );
+    describe_counter!(BUNDLER_SEND_BUNDLE_ERROR, "The number of failed bundle submissions");
+    counter!(BUNDLER_SEND_BUNDLE_ERROR).absolute(0);
+    describe_counter!(
+        BUNDLER_UOPOOL_UNAVAILABLE,
+        "The number of calls to the uopool gRPC service that failed because it was unreachable"
+    );
+    counter!(BUNDLER_UOPOOL_UNAVAILABLE).absolute(0);
+    describe_histogram!(BUNDLER_BUNDLE_PROFIT, "The estimated profit of each bundle (in wei)");
+    describe_counter!(
+        BUNDLER_AGGREGATION_CACHE_HIT,
+        "The number of signature aggregations served from the cache"
+    );
+    describe_counter!(
+        BUNDLER_AGGREGATION_CACHE_MISS,
+        "The number of signature aggregations that called the aggregator"
+    );
+    describe_gauge!(BUNDLER_SIGNER_BALANCE, "The balance of the bundler's signer (in wei)");
+    describe_gauge!(
+        BUNDLER_LOW_BALANCE,
+        "Whether auto bundling is paused because the signer's balance is below the minimum balance"
+    );
+}
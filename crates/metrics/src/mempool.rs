@@ -1,8 +1,16 @@
-use metrics::{counter, describe_counter, describe_gauge, gauge};
+use ethers::types::Address;
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
 use silius_mempool::{
-    AddRemoveUserOp, ClearOp, MempoolErrorKind, ReputationEntryOp, ReputationError, UserOperationOp,
+    metrics::{
+        MEMPOOL_ESTIMATION_CACHE_HIT, MEMPOOL_ESTIMATION_CACHE_MISS,
+        MEMPOOL_MAX_ENTITY_USER_OPERATIONS, MEMPOOL_REPLACEMENT_ACCEPTED,
+        MEMPOOL_REPLACEMENT_BUMP_PERC, MEMPOOL_REPLACEMENT_REJECTED, MEMPOOL_USER_OPERATIONS,
+    },
+    AddRemoveUserOp, ClearOp, MempoolErrorKind, ReputationEntryOp, ReputationError,
+    UserOperationOp,
 };
 use silius_primitives::{UserOperation, UserOperationHash};
+use std::time::Duration;
 
 const MEMPOOL_SIZE: &str = "silius_mempool_size";
 const MEMPOOL_ADD_ERROR: &str = "silius_mempool_add_error";
@@ -11,6 +19,7 @@ const REPUTATION_UO_SEEN: &str = "silius_reputation_uo_seen";
 const REPUTATION_UO_INCLUDED: &str = "silius_reputation_uo_included";
 const REPUTATION_STATUS: &str = "silius_reputation_status";
 const REPUTATION_SET_ENTRY_ERROR: &str = "silius_reputation_set_entry.error";
+const MEMPOOL_VALIDATION_DURATION: &str = "silius_mempool_validation_duration_seconds";
 
 #[derive(Clone)]
 pub struct MetricsHandler<S> {
@@ -116,6 +125,13 @@ impl<S: ReputationEntryOp> ReputationEntryOp for MetricsHandler<S> {
     }
 }
 
+/// Records the time spent validating one user operation (the number of samples gives the
+/// validation throughput)
+pub fn record_validation_duration(ep: &Address, duration: Duration) {
+    histogram!(MEMPOOL_VALIDATION_DURATION, "entry_point" => format!("{ep:?}"))
+        .record(duration.as_secs_f64());
+}
+
 pub fn describe_mempool_metrics() {
     describe_gauge!(MEMPOOL_SIZE, "The number of user operations in the mempool");
     describe_counter!(MEMPOOL_ADD_ERROR, "The number of errors when adding to the mempool");
@@ -130,9 +146,40 @@ pub fn describe_mempool_metrics() {
         REPUTATION_SET_ENTRY_ERROR,
         "The number of errors when setting a reputation entry"
     );
+    describe_histogram!(MEMPOOL_VALIDATION_DURATION, "The time spent validating a user operation");
+    describe_counter!(
+        MEMPOOL_REPLACEMENT_ACCEPTED,
+        "The number of user operations that replaced the previous one of the sender"
+    );
+    describe_counter!(
+        MEMPOOL_REPLACEMENT_REJECTED,
+        "The number of replacements rejected because the gas increase was too low"
+    );
+    describe_histogram!(
+        MEMPOOL_REPLACEMENT_BUMP_PERC,
+        "The fee increase (in percent) of the replacement user operations"
+    );
+    describe_counter!(
+        MEMPOOL_ESTIMATION_CACHE_HIT,
+        "The number of gas estimations served from the estimation cache"
+    );
+    describe_counter!(
+        MEMPOOL_ESTIMATION_CACHE_MISS,
+        "The number of gas estimations that were not cached and were simulated"
+    );
+    describe_gauge!(
+        MEMPOOL_USER_OPERATIONS,
+        "The number of user operations in the mempool of an entry point"
+    );
+    describe_gauge!(
+        MEMPOOL_MAX_ENTITY_USER_OPERATIONS,
+        "The max number of user operations of one sender, paymaster or factory in the mempool"
+    );
     counter!(MEMPOOL_ADD_ERROR).absolute(0);
     counter!(MEMPOOL_REMOVE_ERROR).absolute(0);
     counter!(REPUTATION_SET_ENTRY_ERROR).absolute(0);
+    counter!(MEMPOOL_ESTIMATION_CACHE_HIT).absolute(0);
+    counter!(MEMPOOL_ESTIMATION_CACHE_MISS).absolute(0);
     gauge!(MEMPOOL_SIZE).set(0f64);
     gauge!(REPUTATION_UO_SEEN).set(0f64);
     gauge!(REPUTATION_UO_INCLUDED).set(0f64);
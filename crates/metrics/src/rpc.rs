@@ -14,6 +14,7 @@ use tower::Layer;
 const RPC_REQUEST: &str = "silius_rpc_request";
 const RPC_REQUEST_SUCCESS: &str = "silius_rpc_request_success";
 const RPC_REQUEST_FAILED: &str = "silius_rpc_request_failed";
+const RPC_RATE_LIMITED: &str = "silius_rpc_rate_limited";
 
 #[derive(Clone, Debug, Default)]
 pub struct MetricsLayer;
@@ -100,11 +101,17 @@ impl<F: Future<Output = MethodResponse>> Future for MetricsFuture<F> {
     }
 }
 
+pub fn record_rate_limited() {
+    counter!(RPC_RATE_LIMITED).increment(1);
+}
+
 pub fn describe_json_rpc_metrics() {
     describe_counter!(RPC_REQUEST, "The number of json rpc requests so far");
     describe_counter!(RPC_REQUEST_SUCCESS, "The number of successful json rpc requests so far");
     describe_counter!(RPC_REQUEST_FAILED, "The number of failed json rpc requests so far");
+    describe_counter!(RPC_RATE_LIMITED, "The number of rate limited json rpc requests so far");
     counter!(RPC_REQUEST).absolute(0);
     counter!(RPC_REQUEST_SUCCESS).absolute(0);
     counter!(RPC_REQUEST_FAILED).absolute(0);
+    counter!(RPC_RATE_LIMITED).absolute(0);
 }
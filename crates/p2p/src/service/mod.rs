@@ -205,7 +205,7 @@ impl Network {
                         let uo = user_op.clone().user_operation();
                         new_coming_uos_ch
                             .unbounded_send(UserOperation::from_user_operation_signed(
-                                uo.hash(ep, chain.id()),
+                                uo.hash_for_entry_point(ep, chain.id()),
                                 uo,
                             ))
                             .expect("new user operation channel should be open all the time");
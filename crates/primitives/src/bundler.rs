@@ -1,6 +1,9 @@
 //! Bundler-related primitives
 
-use serde::Deserialize;
+use crate::{simulation::StorageMap, UserOperationHash};
+use ethers::types::{Address, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt};
 use strum_macros::{EnumString, EnumVariantNames};
 
 /// Bundler modes
@@ -22,4 +25,182 @@ pub enum SendStrategy {
     EthereumClient,
     /// Sends the bundle to the Flashbots relay
     Flashbots,
+    /// Sends the bundle to the Ethereum execution client with `eth_sendRawTransactionConditional`
+    Conditional,
+}
+
+/// Determines how the beneficiary of each bundle is selected when several are configured
+#[derive(Clone, Copy, Debug, Default, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum BeneficiaryStrategy {
+    /// Rotates through the beneficiaries, one per bundle
+    #[default]
+    RoundRobin,
+    /// Picks the beneficiary with the lowest balance
+    LowestBalance,
+}
+
+/// Determines how the fees of the bundle transactions are set
+#[derive(Clone, Copy, Debug, Default, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum FeeStrategy {
+    /// The fees estimated by the Ethereum execution client
+    #[default]
+    Provider,
+    /// The base fee of the next block plus the estimated priority fee, targeting the inclusion in
+    /// the next block. The fees are capped by the fees of the user operations in the bundle.
+    NextBlock,
+}
+
+/// Whether a user operation of the mempool would be included in the next bundle, and the reason
+/// why not otherwise
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum BundleInclusion {
+    /// The user operation would be included in the next bundle
+    Included,
+    /// The max fee per gas of the user operation doesn't cover the base fee of the next block
+    FeeBelowFloor {
+        #[serde(rename = "maxFeePerGas")]
+        max_fee_per_gas: U256,
+        #[serde(rename = "baseFee")]
+        base_fee: U256,
+    },
+    /// Higher priority user operations of the same sender take all the places of the sender in
+    /// the bundle
+    SenderLimit,
+    /// The user operation accesses the same storage slot as a higher priority user operation
+    StorageConflict {
+        #[serde(rename = "conflictsWith")]
+        conflicts_with: UserOperationHash,
+    },
+    /// Higher priority user operations fill the bundle (max number of user operations or gas)
+    BundleFull,
+    /// The mempool didn't select the user operation for the next bundle (e.g., a throttled
+    /// entity, it's not valid yet or it's queued behind a nonce gap)
+    NotSelected,
+}
+
+impl fmt::Display for BundleInclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Included => write!(f, "included in the next bundle"),
+            Self::FeeBelowFloor { max_fee_per_gas, base_fee } => write!(
+                f,
+                "max fee per gas {max_fee_per_gas} is below the base fee of the next block {base_fee}"
+            ),
+            Self::SenderLimit => write!(f, "per-sender limit of the bundle is reached"),
+            Self::StorageConflict { conflicts_with } => {
+                write!(f, "storage conflict with the user operation {conflicts_with}")
+            }
+            Self::BundleFull => write!(f, "bundle is full"),
+            Self::NotSelected => write!(f, "not selected by the mempool for the next bundle"),
+        }
+    }
+}
+
+/// Expected storage of an account used in [TransactionConditional](TransactionConditional)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AccountStorage {
+    /// Expected storage root of the account
+    RootHash(H256),
+    /// Expected values of the storage slots of the account
+    SlotValues(HashMap<H256, H256>),
+}
+
+/// Conditions of the `eth_sendRawTransactionConditional` method under which the transaction is
+/// included
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionConditional {
+    pub known_accounts: HashMap<Address, AccountStorage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_min: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number_max: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_min: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_max: Option<u64>,
+}
+
+impl TransactionConditional {
+    /// Creates the conditions from the storage slots read during the validation of the user
+    /// operations (slots whose value can't be parsed are skipped)
+    pub fn from_storage_map(storage_map: &StorageMap) -> Self {
+        let known_accounts = storage_map
+            .iter()
+            .map(|(addr, slots)| {
+                let slots = slots
+                    .iter()
+                    .filter_map(|(slot, value)| Some((parse_word(slot)?, parse_word(value)?)))
+                    .collect();
+                (*addr, AccountStorage::SlotValues(slots))
+            })
+            .collect();
+        Self { known_accounts, ..Default::default() }
+    }
+}
+
+/// Parses a hex string (with or without leading zeros) into a 32-byte word
+fn parse_word(s: &str) -> Option<H256> {
+    let hex = s.trim_start_matches("0x");
+    if hex.len() > 64 {
+        return None;
+    }
+    format!("{hex:0>64}").parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transaction_conditional_from_storage_map() {
+        let addr = Address::random();
+        let storage_map = StorageMap::from([(
+            addr,
+            HashMap::from([
+                ("0x01".to_string(), "0x0a".to_string()),
+                ("0xzz".to_string(), "0x00".to_string()),
+            ]),
+        )]);
+
+        let conditional = TransactionConditional::from_storage_map(&storage_map);
+        assert_eq!(
+            conditional.known_accounts.get(&addr),
+            Some(&AccountStorage::SlotValues(HashMap::from([(
+                H256::from_low_u64_be(1),
+                H256::from_low_u64_be(10)
+            )])))
+        );
+        assert_eq!(
+            serde_json::to_value(&conditional).unwrap(),
+            serde_json::json!({
+                "knownAccounts": {
+                    format!("{addr:?}"): {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                        "0x000000000000000000000000000000000000000000000000000000000000000a"
+                    }
+                }
+            })
+        );
+    }
+    #[test]
+    fn bundle_inclusion_serde() {
+        let conflicts_with = UserOperationHash(H256::from_low_u64_be(1));
+        let inclusion = BundleInclusion::StorageConflict { conflicts_with };
+        assert_eq!(
+            serde_json::to_value(inclusion).unwrap(),
+            serde_json::json!({
+                "status": "storageConflict",
+                "conflictsWith": "0x0000000000000000000000000000000000000000000000000000000000000001"
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(BundleInclusion::BundleFull).unwrap(),
+            serde_json::json!({ "status": "bundleFull" })
+        );
+    }
 }
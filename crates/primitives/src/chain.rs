@@ -1,8 +1,13 @@
 //! Chain extensions
+use crate::constants::bundler::{BUNDLE_INTERVAL, MIN_BALANCE};
 use alloy_chains::{Chain, NamedChain};
+use ethers::types::U256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 pub trait ChainExt {
     fn canonical_mempool_id(&self) -> &str;
+    fn is_op_stack(&self) -> bool;
 }
 
 impl ChainExt for Chain {
@@ -15,4 +20,159 @@ impl ChainExt for Chain {
             _ => panic!("Canonical mempool on chain {self:?} is not supported!"),
         }
     }
+
+    /// Whether the chain is an OP-stack chain, i.e., transactions pay an additional L1 data fee
+    fn is_op_stack(&self) -> bool {
+        matches!(
+            self.named(),
+            Some(
+                NamedChain::Optimism |
+                    NamedChain::OptimismGoerli |
+                    NamedChain::OptimismSepolia |
+                    NamedChain::Base |
+                    NamedChain::BaseGoerli |
+                    NamedChain::BaseSepolia
+            )
+        )
+    }
+}
+
+/// Chain-aware defaults of the bundler configuration, the explicitly configured values override
+/// them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChainProfile {
+    /// Minimum balance (in wei) of the bundler's signer
+    pub min_balance: U256,
+    /// Auto bundling interval (in seconds)
+    pub bundle_interval: u64,
+    /// Minimum priority fee per gas of the user operations and the bundle transactions
+    pub min_priority_fee_per_gas: U256,
+    /// Gas added to the calculated pre-verification gas of the user operations (e.g., to cover
+    /// the chain-specific costs of the bundle transaction)
+    pub pre_verification_gas_overhead: U256,
+}
+
+impl Default for ChainProfile {
+    fn default() -> Self {
+        Self {
+            min_balance: MIN_BALANCE.into(),
+            bundle_interval: BUNDLE_INTERVAL,
+            min_priority_fee_per_gas: U256::zero(),
+            pre_verification_gas_overhead: U256::zero(),
+        }
+    }
+}
+
+impl ChainProfile {
+    /// Returns the built-in profile of the chain
+    ///
+    /// # Arguments
+    /// * `chain` - The chain
+    ///
+    /// # Returns
+    /// * `Option<ChainProfile>` - The built-in profile (`None` if there is no built-in profile for
+    ///   the chain)
+    pub fn builtin(chain: &Chain) -> Option<Self> {
+        let gwei = U256::exp10(9);
+        let profile = match chain.named()? {
+            NamedChain::Mainnet => Self {
+                bundle_interval: 12,
+                min_priority_fee_per_gas: gwei / 10,
+                ..Default::default()
+            },
+            NamedChain::Goerli | NamedChain::Sepolia | NamedChain::Holesky => {
+                Self { min_balance: U256::exp10(16), bundle_interval: 12, ..Default::default() }
+            }
+            // the validators of Polygon reject transactions with a priority fee below 30 gwei
+            NamedChain::Polygon | NamedChain::PolygonMumbai | NamedChain::PolygonAmoy => Self {
+                min_balance: U256::exp10(18),
+                bundle_interval: 4,
+                min_priority_fee_per_gas: gwei * 30,
+                ..Default::default()
+            },
+            NamedChain::Optimism |
+            NamedChain::OptimismGoerli |
+            NamedChain::OptimismSepolia |
+            NamedChain::Base |
+            NamedChain::BaseGoerli |
+            NamedChain::BaseSepolia |
+            NamedChain::Arbitrum |
+            NamedChain::ArbitrumGoerli |
+            NamedChain::ArbitrumSepolia => {
+                Self { min_balance: U256::exp10(16), bundle_interval: 2, ..Default::default() }
+            }
+            NamedChain::Dev => Self { min_balance: U256::zero(), ..Default::default() },
+            _ => return None,
+        };
+        Some(profile)
+    }
+}
+
+/// The chain profiles selected by the operator. By default no profile is selected, so the generic
+/// defaults of [ChainProfile](ChainProfile) are used on every chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainProfiles {
+    /// Whether the built-in profiles are used
+    builtin: bool,
+    /// Custom profiles by chain id (take precedence over the built-in profiles)
+    custom: HashMap<u64, ChainProfile>,
+}
+
+impl ChainProfiles {
+    /// Creates a new [ChainProfiles](ChainProfiles) selection
+    ///
+    /// # Arguments
+    /// * `builtin` - Whether the built-in profiles are used
+    /// * `custom` - Custom profiles by chain id
+    ///
+    /// # Returns
+    /// * `ChainProfiles` - The [ChainProfiles](ChainProfiles)
+    pub fn new(builtin: bool, custom: HashMap<u64, ChainProfile>) -> Self {
+        Self { builtin, custom }
+    }
+
+    /// Gets the profile of the chain, i.e., the custom profile, the built-in profile (if the
+    /// built-in profiles are selected) or the generic defaults (in this order)
+    ///
+    /// # Arguments
+    /// * `chain` - The chain
+    ///
+    /// # Returns
+    /// * `ChainProfile` - The [ChainProfile](ChainProfile) of the chain
+    pub fn get(&self, chain: &Chain) -> ChainProfile {
+        if let Some(profile) = self.custom.get(&chain.id()) {
+            return *profile;
+        }
+
+        self.builtin.then(|| ChainProfile::builtin(chain)).flatten().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chain_profiles_get() {
+        // the generic defaults are used unless a profile is selected
+        let profiles = ChainProfiles::default();
+        assert_eq!(profiles.get(&Chain::mainnet()), ChainProfile::default());
+
+        let profiles = ChainProfiles::new(true, HashMap::new());
+        assert_eq!(profiles.get(&Chain::mainnet()).bundle_interval, 12);
+        assert_eq!(profiles.get(&Chain::from_id(123_456_789)), ChainProfile::default());
+
+        // the custom profiles take precedence over the built-in ones
+        let profile = ChainProfile { bundle_interval: 5, ..Default::default() };
+        let profiles = ChainProfiles::new(true, HashMap::from([(1, profile)]));
+        assert_eq!(profiles.get(&Chain::mainnet()), profile);
+        let profiles = ChainProfiles::new(false, HashMap::from([(1, profile)]));
+        assert_eq!(profiles.get(&Chain::mainnet()), profile);
+        assert_eq!(profiles.get(&Chain::from(NamedChain::Polygon)), ChainProfile::default());
+
+        // the missing fields of a custom profile are the generic defaults
+        let profile: ChainProfile = serde_json::from_str(r#"{"bundleInterval": 5}"#).unwrap();
+        assert_eq!(profile, ChainProfile { bundle_interval: 5, ..Default::default() });
+    }
 }
@@ -6,12 +6,68 @@ pub mod entry_point {
     pub const ADDRESS: &str = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
     /// Version of the entry point smart contract
     pub const VERSION: &str = "0.6.0";
+    /// Address of the entry point smart contract (v0.7)
+    pub const ADDRESS_V07: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+    /// Version of the entry point smart contract (v0.7)
+    pub const VERSION_V07: &str = "0.7.0";
 }
 
 /// Bundler
 pub mod bundler {
     /// Default time interval for auto bundling mode (in seconds)
     pub const BUNDLE_INTERVAL: u64 = 10;
+    /// Default minimum balance (in wei) of the bundler's signer
+    pub const MIN_BALANCE: u64 = 100_000_000_000_000_000;
+    /// Default maximum number of attempts to send a bundle on transient errors
+    pub const SEND_BUNDLE_MAX_ATTEMPTS: u64 = 3;
+    /// Default base delay between send bundle attempts (in milliseconds), doubled on each retry
+    pub const SEND_BUNDLE_RETRY_DELAY: u64 = 500;
+    /// Default time to wait for in-flight bundles to finish on shutdown (in seconds)
+    pub const SHUTDOWN_TIMEOUT: u64 = 30;
+    /// Default time to wait for the bundle transaction receipt when sending bundle manually (in
+    /// seconds)
+    pub const RECEIPT_TIMEOUT: u64 = 120;
+    /// Default number of block confirmations after which a mined bundle is considered final
+    pub const BUNDLE_CONFIRMATIONS: u64 = 1;
+    /// Time interval (in seconds) in which the deposit of the bundler's signer in the entry point
+    /// is checked and topped up (if enabled)
+    pub const DEPOSIT_TOP_UP_INTERVAL: u64 = 60;
+    /// Number of recently submitted user operations for which the bundle transaction is cached
+    pub const SUBMITTED_USER_OPERATIONS_CACHE_SIZE: usize = 10_000;
+    /// Number of bundled user operation notifications buffered for each subscriber before the
+    /// oldest are dropped
+    pub const BUNDLED_EVENTS_CHANNEL_SIZE: usize = 1024;
+    /// Default number of consecutive blocks a Flashbots bundle is submitted for
+    pub const FLASHBOTS_TARGET_BLOCKS: u64 = 3;
+    /// Default number of blocks after which a submitted bundle that is not mined is replaced
+    pub const STUCK_BUNDLE_BLOCKS: u64 = 10;
+    /// Percentage increase of gas price to replace a stuck bundle transaction
+    pub const REPLACE_BUNDLE_GAS_INCREASE_PERC: u64 = 15;
+    /// Number of replaced bundle transactions for which the hash of the replacement is kept
+    pub const REPLACED_BUNDLES_CACHE_SIZE: usize = 1024;
+    /// Default maximum gas of a bundle as a percentage of the block gas limit
+    pub const MAX_BUNDLE_GAS_PERC: u64 = 90;
+    /// Default maximum validation gas of a bundle as a percentage of the block gas limit
+    pub const MAX_BUNDLE_VALIDATION_GAS_PERC: u64 = 50;
+    /// Gas the entry point spends on each user operation during the validation phase of
+    /// `handleOps` on top of the verification gas limit (prefund and nonce checks, bookkeeping)
+    pub const VALIDATION_GAS_OVERHEAD: u64 = 10_000;
+    /// Default maximum number of user operations of the same sender in a bundle
+    pub const MAX_USER_OPERATIONS_PER_SENDER: usize = 4;
+    /// Maximum number of times a bundle is resubmitted right away without the user operation
+    /// that failed in it
+    pub const FAILED_OP_MAX_RETRIES: u64 = 3;
+    /// Time to wait for each check of the health endpoint (in seconds)
+    pub const HEALTH_CHECK_TIMEOUT: u64 = 5;
+    /// Maximum number of attempts to call the uopool gRPC service while it's unreachable
+    pub const UOPOOL_RECONNECT_MAX_ATTEMPTS: u64 = 5;
+    /// Base delay between the attempts to reconnect to the uopool gRPC service (in milliseconds),
+    /// doubled on each retry
+    pub const UOPOOL_RECONNECT_DELAY: u64 = 200;
+    /// Bound of the base fee change between two blocks (EIP-1559), i.e., at most 1/8
+    pub const BASE_FEE_MAX_CHANGE_DENOMINATOR: u64 = 8;
+    /// Ratio of the block gas limit to the gas target (EIP-1559)
+    pub const ELASTICITY_MULTIPLIER: u64 = 2;
 }
 
 /// User operation mempool
@@ -20,6 +76,23 @@ pub mod mempool {
     pub const GAS_INCREASE_PERC: u64 = 10;
     /// Depth scan when searching for previous user operations
     pub const LATEST_SCAN_DEPTH: u64 = 1000;
+    /// Number of mempool events buffered for each subscriber before the oldest are dropped
+    pub const EVENTS_CHANNEL_SIZE: usize = 1024;
+    /// Default maximum number of user operations validated concurrently
+    pub const VALIDATION_CONCURRENCY: usize = 16;
+    /// Number of blocks after which an included bundle is considered final and is no longer
+    /// checked for reorgs
+    pub const REORG_DEPTH: u64 = 64;
+    /// Number of gas estimations cached until the next block
+    pub const ESTIMATION_CACHE_SIZE: usize = 1024;
+    /// Number of recent blocks the suggested priority fee is derived from
+    pub const FEE_HISTORY_BLOCKS: u64 = 10;
+    /// Percentile of the priority fees paid in each recent block used for the suggested priority
+    /// fee
+    pub const FEE_HISTORY_PERCENTILE: f64 = 50.0;
+    /// Suggested max fee per gas as a percentage of the current base fee per gas (the headroom
+    /// covers the base fee increase of the next blocks), excluding the priority fee
+    pub const BASE_FEE_HEADROOM_PERC: u64 = 125;
 }
 
 /// User operation validation
@@ -32,6 +105,7 @@ pub mod validation {
         pub const FACTORY: &str = "factory";
         pub const SENDER: &str = "account";
         pub const PAYMASTER: &str = "paymaster";
+        pub const AGGREGATOR: &str = "aggregator";
 
         pub const FACTORY_LEVEL: usize = 0;
         pub const SENDER_LEVEL: usize = 1;
@@ -57,12 +131,44 @@ pub mod validation {
         pub const BAN_SLACK: u64 = 50;
     }
 
+    /// Sanity
+    pub mod sanity {
+        /// Default maximum size of the encoded user operation (in bytes)
+        pub const MAX_UO_SIZE: usize = 128 * 1024;
+        /// Default maximum verification gas limit of the user operation
+        pub const MAX_VERIFICATION_GAS: u64 = 5_000_000;
+        /// Default min max fee per gas as a percentage of the current base fee per gas
+        pub const MIN_BASE_FEE_PERC: u64 = 100;
+        /// Default percentage by which the base fee floor is lowered for staked senders
+        pub const STAKED_GRACE_PERC: u64 = 0;
+        /// Time (in milliseconds) for which the base fee per gas of the latest block is cached
+        pub const BASE_FEE_CACHE_TTL: u64 = 2000;
+        /// Default time (in milliseconds) to wait for each call of the sanity checks to the
+        /// Ethereum execution client
+        pub const RPC_TIMEOUT: u64 = 5000;
+        /// Validation data returned by `validateUserOp` of the sender if the signature is invalid
+        pub const SIG_VALIDATION_FAILED: u64 = 1;
+    }
+
     /// Simulation
     pub mod simulation {
         pub const MIN_EXTRA_GAS: u64 = 2000;
     }
 }
 
+/// EIP-7702 (set code for EOAs)
+pub mod eip7702 {
+    /// Prefix of the delegation designator (`0xef0100 || address`) set as the code of a
+    /// delegated EOA
+    pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+}
+
+/// OP-stack chains
+pub mod op_stack {
+    /// Address of the gas price oracle predeploy that computes the L1 data fee
+    pub const GAS_PRICE_ORACLE: &str = "0x420000000000000000000000000000000000000F";
+}
+
 /// Flashbots relay endpoints
 pub mod flashbots_relay_endpoints {
     // mainnet
@@ -80,6 +186,9 @@ pub mod flashbots_relay_endpoints {
 
     // goerli
     pub const FLASHBOTS_GOERLI: &str = "https://relay-goerli.flashbots.net";
+
+    // sepolia
+    pub const FLASHBOTS_SEPOLIA: &str = "https://relay-sepolia.flashbots.net";
 }
 
 /// Supported chains
@@ -111,6 +220,14 @@ pub mod rpc {
     pub const HTTP_PORT: u16 = 3000;
     /// The default port for WS
     pub const WS_PORT: u16 = 3001;
+    /// Window (in seconds) in which the user operations submitted by one client are rate limited
+    pub const RATE_LIMIT_WINDOW: u64 = 60;
+}
+
+/// Ethereum execution client provider
+pub mod provider {
+    /// Interval between the health checks of the failover RPC endpoints (in seconds)
+    pub const HEALTH_CHECK_INTERVAL: u64 = 15;
 }
 
 /// gRPC
@@ -125,6 +242,8 @@ pub mod grpc {
 pub mod storage {
     /// The default path for database
     pub const DATABASE_FOLDER_NAME: &str = "db";
+    /// The default path for RocksDB database
+    pub const ROCKSDB_FOLDER_NAME: &str = "rocksdb";
 }
 
 /// P2P
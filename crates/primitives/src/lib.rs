@@ -9,6 +9,7 @@ pub mod mempool;
 pub mod p2p;
 pub mod provider;
 pub mod reputation;
+mod signer;
 pub mod simulation;
 mod user_operation;
 mod utils;
@@ -17,9 +18,12 @@ mod wallet;
 pub use bundler::Mode as BundlerMode;
 pub use mempool::Mode as UoPoolMode;
 pub use p2p::VerifiedUserOperation;
+pub use signer::{BundleSigner, SignerError, TransactionSigner};
 pub use user_operation::{
-    UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
-    UserOperationReceipt, UserOperationRequest, UserOperationSigned,
+    pack_init_code, pack_paymaster_and_data, pack_uints, unpack_uints, user_operation_hash,
+    BundledUserOperation, EntryPointVersion, PackedUserOperation, UserOperation,
+    UserOperationByHash, UserOperationGasEstimation, UserOperationGasPrice, UserOperationHash,
+    UserOperationReceipt, UserOperationRequest, UserOperationSigned, PAYMASTER_DATA_OFFSET,
 };
-pub use utils::get_address;
+pub use utils::{get_address, get_delegation};
 pub use wallet::Wallet;
@@ -1,5 +1,9 @@
 //! Mempool/related primitives
 
+use crate::{reputation::ReputationEntry, UserOperation, UserOperationHash, UserOperationRequest};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use strum_macros::{EnumString, EnumVariantNames};
 
 /// Verification modes for user operation mempool
@@ -9,3 +13,112 @@ pub enum Mode {
     Standard,
     Unsafe,
 }
+
+/// Determines the order in which the user operations of the mempool are bundled
+#[derive(Clone, Copy, Debug, Default, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum SortStrategy {
+    /// Highest max priority fee per gas first
+    #[default]
+    PriorityFee,
+    /// Highest effective gas price first, i.e., `min(maxPriorityFeePerGas, maxFeePerGas -
+    /// baseFee)`
+    GasPrice,
+    /// The order in which the user operations were added to the mempool
+    Fifo,
+    /// Highest expected profit first, i.e., the refund to the beneficiary for the total gas limit
+    /// of the user operation minus the cost of that gas at the base fee
+    Profitability,
+}
+
+/// Determines how a user operation with the same sender and nonce as a user operation in the
+/// mempool, but with equal fees, is handled (an identical resubmission is always acknowledged)
+#[derive(Clone, Copy, Debug, Default, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum EqualFeeReplacement {
+    /// Rejected as a replacement whose fees aren't bumped, even if only the signature changed
+    #[default]
+    Reject,
+    /// Accepted if only the signature changed, the user operation in the mempool gets the new
+    /// signature (e.g., a client that resubmits after a timeout with a fresh signature). Any other
+    /// change is rejected as a replacement whose fees aren't bumped.
+    SignatureRefresh,
+}
+
+/// Snapshot of the mempool of one entry point, i.e., the user operations and the reputation of the
+/// entities. Used to migrate the pending user operations to another bundler or to debug offline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolSnapshot {
+    /// Address of the entry point
+    pub entry_point: Address,
+    /// The [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID
+    pub chain_id: u64,
+    /// The user operations of the mempool
+    pub user_operations: Vec<UserOperation>,
+    /// The reputation of the entities
+    pub reputation: Vec<ReputationEntry>,
+}
+
+/// Content of the mempool of one entry point, i.e., the pending user operations that can be
+/// included in a bundle and the queued ones whose nonce is ahead of the on-chain nonce of the
+/// sender (with a gap that isn't filled by other user operations in the mempool)
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolContent {
+    /// The user operations that can be included in a bundle
+    pub pending: Vec<UserOperationRequest>,
+    /// The user operations that wait for the nonce gap to be filled
+    pub queued: Vec<UserOperationRequest>,
+    /// The failed bundle attempts of the user operations that failed at bundle time at least once
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub bundle_attempts: HashMap<UserOperationHash, u64>,
+}
+
+/// Composition of the mempool of one entry point, i.e., the number of user operations by sender,
+/// by paymaster and by factory. Used to spot an entity flooding the mempool.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolComposition {
+    /// Total number of user operations in the mempool
+    pub total: usize,
+    /// Number of user operations by sender
+    pub by_sender: HashMap<Address, usize>,
+    /// Number of user operations by paymaster
+    pub by_paymaster: HashMap<Address, usize>,
+    /// Number of user operations by factory
+    pub by_factory: HashMap<Address, usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reputation::Status, UserOperationHash, UserOperationSigned};
+    use ethers::types::H256;
+
+    #[test]
+    fn mempool_snapshot_json() {
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash(H256::random()),
+            UserOperationSigned::default().sender(Address::random()).nonce(1.into()),
+        );
+        let entry = ReputationEntry {
+            address: Address::random(),
+            uo_seen: 20,
+            uo_included: 3,
+            status: Status::THROTTLED.into(),
+        };
+        let snapshot = MempoolSnapshot {
+            entry_point: Address::random(),
+            chain_id: 1,
+            user_operations: vec![uo.clone()],
+            reputation: vec![entry.clone()],
+        };
+
+        let snapshot: MempoolSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(snapshot.user_operations[0].hash, uo.hash);
+        assert_eq!(snapshot.user_operations[0].user_operation, uo.user_operation);
+        assert_eq!(snapshot.reputation, vec![entry]);
+    }
+}
@@ -1,15 +1,193 @@
 //! Utils for creating ethers providers
 
+use crate::constants::provider::HEALTH_CHECK_INTERVAL;
 use async_stream::stream;
+use async_trait::async_trait;
 use ethers::{
-    providers::{Http, Middleware, Provider, PubsubClient, Ws},
-    types::H256,
+    providers::{
+        Http, HttpClientError, JsonRpcClient, JsonRpcError, Middleware, Provider, ProviderError,
+        PubsubClient, RpcError, Ws,
+    },
+    types::{H256, U64},
 };
 use futures_util::{Stream, StreamExt};
-use std::{pin::Pin, sync::Arc, time::Duration};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt::Debug,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::warn;
 
 pub type BlockStream = Pin<Box<dyn Stream<Item = eyre::Result<H256>> + Send>>;
 
+/// Error of the [FailoverClient](FailoverClient)
+#[derive(Debug, thiserror::Error)]
+pub enum FailoverClientError {
+    /// Error of the last RPC endpoint that was tried
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+    /// The request parameters could not be serialized
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    /// No RPC endpoint is configured
+    #[error("no rpc endpoint configured")]
+    NoEndpoints,
+}
+
+impl RpcError for FailoverClientError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            FailoverClientError::Http(err) => err.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            FailoverClientError::Http(err) => err.as_serde_error(),
+            FailoverClientError::Serde(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FailoverClientError> for ProviderError {
+    fn from(err: FailoverClientError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    client: Http,
+    /// Whether the last request to the endpoint succeeded
+    healthy: AtomicBool,
+    /// Latency of the last successful health check in milliseconds (0 until checked)
+    latency: AtomicU64,
+}
+
+impl Endpoint {
+    fn is_healthy(&self) -> bool {
+        self.healthy.load(Ordering::Relaxed)
+    }
+
+    fn latency(&self) -> u64 {
+        self.latency.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, healthy: bool) {
+        self.healthy.store(healthy, Ordering::Relaxed);
+    }
+
+    fn record_latency(&self, latency: Duration) {
+        self.latency.store(latency.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+/// JSON-RPC client over HTTP that fails over to the next RPC endpoint when a request to an
+/// endpoint fails (e.g., connection error, invalid response). The requests go to the healthy
+/// endpoint with the lowest latency measured by the health check (in the order of preference
+/// until the endpoints are checked or when the latencies are equal); a failed endpoint is used
+/// again once the health check succeeds. JSON-RPC error responses (e.g., reverts) are returned as
+/// they are.
+#[derive(Clone, Debug)]
+pub struct FailoverClient {
+    endpoints: Arc<Vec<Endpoint>>,
+}
+
+impl FailoverClient {
+    /// Creates a new [FailoverClient](FailoverClient)
+    ///
+    /// # Arguments
+    /// * `addrs` - The URLs of the RPC endpoints, in the order of preference
+    ///
+    /// # Returns
+    /// * `Self` - A new `FailoverClient` instance
+    pub fn new(addrs: &[String]) -> eyre::Result<Self> {
+        if addrs.is_empty() {
+            return Err(FailoverClientError::NoEndpoints.into());
+        }
+
+        let endpoints = addrs
+            .iter()
+            .map(|addr| {
+                Ok(Endpoint {
+                    client: Http::from_str(addr)?,
+                    healthy: AtomicBool::new(true),
+                    latency: AtomicU64::new(0),
+                })
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(Self { endpoints: Arc::new(endpoints) })
+    }
+
+    /// Returns the indexes of the endpoints in the order the requests are tried (healthy
+    /// endpoints first, each group by latency and then in the order of preference)
+    fn ordered(&self) -> Vec<usize> {
+        let mut indexes = (0..self.endpoints.len()).collect::<Vec<_>>();
+        indexes.sort_by_key(|i| {
+            let endpoint = &self.endpoints[*i];
+            (!endpoint.is_healthy(), endpoint.latency())
+        });
+        indexes
+    }
+
+    /// Checks the health and measures the latency of every endpoint with `eth_blockNumber`
+    pub async fn health_check(&self) {
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            let start = Instant::now();
+            let res: Result<U64, _> = endpoint.client.request("eth_blockNumber", ()).await;
+            match res {
+                Ok(_) => endpoint.record_latency(start.elapsed()),
+                Err(ref err) => warn!("Health check of RPC endpoint #{i} failed: {err:?}"),
+            }
+            endpoint.record(res.is_ok());
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for FailoverClient {
+    type Error = FailoverClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = serde_json::to_value(params)?;
+
+        let mut res = Err(FailoverClientError::NoEndpoints);
+        for i in self.ordered() {
+            let endpoint = &self.endpoints[i];
+            match endpoint.client.request(method, params.clone()).await {
+                Ok(val) => {
+                    endpoint.record(true);
+                    return Ok(val);
+                }
+                Err(err) if err.is_error_response() => {
+                    endpoint.record(true);
+                    return Err(err.into());
+                }
+                Err(err) => {
+                    warn!("Request {method} to RPC endpoint #{i} failed, failing over: {err:?}");
+                    endpoint.record(false);
+                    res = Err(err.into());
+                }
+            }
+        }
+
+        res
+    }
+}
+
 /// Creates ethers provider with HTTP connection
 pub async fn create_http_provider(
     addr: &str,
@@ -20,6 +198,27 @@ pub async fn create_http_provider(
     Ok(provider.interval(poll_interval))
 }
 
+/// Creates ethers provider with HTTP connection that fails over between several RPC endpoints.
+/// The health of the endpoints is checked periodically in the background.
+pub async fn create_failover_provider(
+    addrs: &[String],
+    poll_interval: Duration,
+) -> eyre::Result<Provider<FailoverClient>> {
+    let client = FailoverClient::new(addrs)?;
+
+    if addrs.len() > 1 {
+        let client = client.clone();
+        tokio::spawn(async move {
+            loop {
+                client.health_check().await;
+                tokio::time::sleep(Duration::from_secs(HEALTH_CHECK_INTERVAL)).await;
+            }
+        });
+    }
+
+    Ok(Provider::new(client).interval(poll_interval))
+}
+
 /// Creates ethers provider with WebSockets connection
 pub async fn create_ws_provider(addr: &str) -> eyre::Result<Provider<Ws>> {
     let provider = Provider::<Ws>::connect_with_reconnects(addr, usize::MAX).await?;
@@ -77,3 +276,42 @@ where
     }
     streams
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failover_endpoints_order() {
+        let client = FailoverClient::new(&[
+            "http://127.0.0.1:8545".to_string(),
+            "http://127.0.0.1:8546".to_string(),
+            "http://127.0.0.1:8547".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(client.ordered(), vec![0, 1, 2]);
+
+        // the requests go to the primary endpoint until it fails
+        client.endpoints[0].record(false);
+        assert_eq!(client.ordered(), vec![1, 2, 0]);
+        client.endpoints[1].record(false);
+        assert_eq!(client.ordered(), vec![2, 0, 1]);
+
+        // the primary endpoint is used again once it's healthy
+        client.endpoints[0].record(true);
+        assert_eq!(client.ordered(), vec![0, 2, 1]);
+
+        // the healthy endpoints are ordered by latency
+        client.endpoints[1].record(true);
+        client.endpoints[0].record_latency(Duration::from_millis(120));
+        client.endpoints[1].record_latency(Duration::from_millis(80));
+        client.endpoints[2].record_latency(Duration::from_millis(80));
+        assert_eq!(client.ordered(), vec![1, 2, 0]);
+        client.endpoints[2].record_latency(Duration::from_millis(40));
+        assert_eq!(client.ordered(), vec![2, 1, 0]);
+        client.endpoints[2].record(false);
+        assert_eq!(client.ordered(), vec![1, 0, 2]);
+
+        assert!(FailoverClient::new(&[]).is_err());
+    }
+}
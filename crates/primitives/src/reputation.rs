@@ -1,6 +1,6 @@
 //! Primitives for reputation
 
-use super::utils::{as_checksum_addr, as_hex_string, as_u64};
+use super::utils::{as_checksum_addr, as_hex_string, as_u64, from_hex_string};
 use ethers::{
     prelude::{EthAbiCodec, EthAbiType},
     types::{Address, U256},
@@ -56,11 +56,19 @@ impl From<ReputationStatus> for Status {
 )]
 pub struct ReputationEntry {
     pub address: Address,
-    #[serde(rename = "opsSeen", serialize_with = "as_hex_string")]
+    #[serde(
+        rename = "opsSeen",
+        serialize_with = "as_hex_string",
+        deserialize_with = "from_hex_string"
+    )]
     pub uo_seen: u64,
-    #[serde(rename = "opsIncluded", serialize_with = "as_hex_string")]
+    #[serde(
+        rename = "opsIncluded",
+        serialize_with = "as_hex_string",
+        deserialize_with = "from_hex_string"
+    )]
     pub uo_included: u64,
-    #[serde(default, serialize_with = "as_hex_string")]
+    #[serde(default, serialize_with = "as_hex_string", deserialize_with = "from_hex_string")]
     pub status: ReputationStatus,
 }
 
@@ -0,0 +1,173 @@
+//! Signing of the bundle transactions, either with the local [Wallet](Wallet) or with a remote
+//! signer (e.g., KMS or a remote signer service)
+
+use crate::Wallet;
+use async_trait::async_trait;
+use ethers::{
+    signers::Signer,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature,
+    },
+};
+use std::{fmt::Debug, sync::Arc};
+use thiserror::Error;
+
+/// Signs the bundle transactions of the bundler
+#[async_trait]
+pub trait TransactionSigner: Debug + Send + Sync {
+    /// The address of the signer (the sender of the bundle transactions)
+    fn address(&self) -> Address;
+
+    /// The chain id the transactions are signed for
+    fn chain_id(&self) -> u64;
+
+    /// Signs the transaction
+    ///
+    /// # Arguments
+    /// * `tx` - The [TypedTransaction](TypedTransaction) to be signed
+    ///
+    /// # Returns
+    /// * `Signature` - The signature of the transaction
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> eyre::Result<Signature>;
+}
+
+#[async_trait]
+impl TransactionSigner for Wallet {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.signer.chain_id()
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> eyre::Result<Signature> {
+        Ok(self.signer.sign_transaction(tx).await?)
+    }
+}
+
+/// Error returned by the [BundleSigner](BundleSigner)
+#[derive(Debug, Error)]
+pub enum SignerError {
+    /// The signer failed to sign the transaction
+    #[error("failed to sign transaction: {inner}")]
+    Signing { inner: String },
+    /// The signer only signs transactions
+    #[error("signer only signs transactions")]
+    Unsupported,
+}
+
+/// Signer of the bundle transactions, backed by any [TransactionSigner](TransactionSigner). It
+/// implements the ethers [Signer](Signer), so it can be used with the signer middleware.
+#[derive(Clone, Debug)]
+pub struct BundleSigner(Arc<dyn TransactionSigner>);
+
+impl BundleSigner {
+    /// Creates a new [BundleSigner](BundleSigner)
+    ///
+    /// # Arguments
+    /// * `signer` - The [TransactionSigner](TransactionSigner) (e.g., a remote signer)
+    ///
+    /// # Returns
+    /// * `Self` - A new `BundleSigner` instance
+    pub fn new<S: TransactionSigner + 'static>(signer: S) -> Self {
+        Self(Arc::new(signer))
+    }
+}
+
+impl From<Wallet> for BundleSigner {
+    fn from(wallet: Wallet) -> Self {
+        Self::new(wallet)
+    }
+}
+
+#[async_trait]
+impl Signer for BundleSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        _message: S,
+    ) -> Result<Signature, Self::Error> {
+        Err(SignerError::Unsupported)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        self.0.sign_transaction(tx).await.map_err(|e| SignerError::Signing { inner: e.to_string() })
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        _payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Err(SignerError::Unsupported)
+    }
+
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.0.chain_id()
+    }
+
+    /// The chain id is set by the underlying signer, so it's left unchanged
+    fn with_chain_id<T: Into<u64>>(self, _chain_id: T) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        prelude::rand,
+        signers::LocalWallet,
+        types::{Eip1559TransactionRequest, U256},
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Remote signer that holds the key out of the bundler (e.g., in a KMS)
+    #[derive(Debug)]
+    struct MockRemoteSigner {
+        key: LocalWallet,
+        requests: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl TransactionSigner for MockRemoteSigner {
+        fn address(&self) -> Address {
+            self.key.address()
+        }
+
+        fn chain_id(&self) -> u64 {
+            self.key.chain_id()
+        }
+
+        async fn sign_transaction(&self, tx: &TypedTransaction) -> eyre::Result<Signature> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self.key.sign_transaction(tx).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn bundle_signer_uses_remote_signer() {
+        let key = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(5_u64);
+        let requests = Arc::new(AtomicUsize::new(0));
+        let signer =
+            BundleSigner::new(MockRemoteSigner { key: key.clone(), requests: requests.clone() });
+        assert_eq!(Signer::address(&signer), key.address());
+        assert_eq!(Signer::chain_id(&signer), 5);
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .value(U256::from(1))
+            .chain_id(5_u64)
+            .into();
+        let sig = Signer::sign_transaction(&signer, &tx).await.unwrap();
+        assert_eq!(sig.recover(tx.sighash()).unwrap(), key.address());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(signer.sign_message("message").await.is_err());
+    }
+}
@@ -1,21 +1,30 @@
 //! Simulation (validation) primitives
 
+use crate::EntryPointVersion;
 use ethers::{
     prelude::{EthAbiCodec, EthAbiType},
     types::{Address, H256},
 };
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
 
 /// Time ineterval before user operation expires (in seconds)
 pub const EXPIRATION_TIMESTAMP_DIFF: u64 = 30;
 
+/// Name of the native tracer (e.g., of the Geth or Reth fork with the ERC-4337 tracers) that
+/// collects the same trace as the JavaScript tracer
+pub const NATIVE_TRACER: &str = "bundlerCollectorTracer";
+
 lazy_static! {
     pub static ref CREATE2_OPCODE: String = "CREATE2".into();
     pub static ref RETURN_OPCODE: String = "RETURN".into();
     pub static ref REVERT_OPCODE: String = "REVERT".into();
     pub static ref CREATE_OPCODE: String = "CREATE".into();
+    pub static ref SELFDESTRUCT_OPCODE: String = "SELFDESTRUCT".into();
     pub static ref VALIDATE_PAYMASTER_USER_OP_FUNCTION: String = "validatePaymasterUserOp".into();
     pub static ref FORBIDDEN_OPCODES: HashSet<String> = {
         let mut set = HashSet::new();
@@ -60,3 +69,74 @@ pub struct CodeHash {
 
 /// Storage map
 pub type StorageMap = HashMap<Address, HashMap<String, String>>;
+
+/// Storage slots accessed by a user operation during its validation
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageAccesses {
+    /// Slots read (and not written) by contract
+    pub reads: HashMap<Address, HashSet<String>>,
+    /// Slots written by contract
+    pub writes: HashMap<Address, HashSet<String>>,
+}
+
+/// Tracer used to trace the validation of the user operations (`debug_traceCall`)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ValidationTracer {
+    /// The JavaScript tracer (requires the Ethereum execution client to support JavaScript
+    /// tracers)
+    #[default]
+    Js,
+    /// The native tracer of the Ethereum execution client with the given name, which has to
+    /// collect the same trace as the JavaScript tracer
+    Native(String),
+}
+
+impl FromStr for ValidationTracer {
+    type Err = String;
+
+    /// Parses the tracer from `js`, `native` (the [NATIVE_TRACER](NATIVE_TRACER)) or
+    /// `native:<name>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "js" => Ok(Self::Js),
+            "native" => Ok(Self::Native(NATIVE_TRACER.into())),
+            _ => match s.strip_prefix("native:") {
+                Some(name) if !name.is_empty() => Ok(Self::Native(name.into())),
+                _ => Err(format!("{s} is not a valid validation tracer")),
+            },
+        }
+    }
+}
+
+/// Gets the default opcodes the entities are forbidden to use during the validation
+///
+/// # Arguments
+/// * `version` - The [EntryPointVersion](EntryPointVersion) of the entry point
+///
+/// # Returns
+/// * `HashSet<String>` - The forbidden opcodes
+pub fn forbidden_opcodes(version: EntryPointVersion) -> HashSet<String> {
+    match version {
+        EntryPointVersion::V0_6 | EntryPointVersion::V0_7 => FORBIDDEN_OPCODES.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validation_tracer_from_str() {
+        assert_eq!(ValidationTracer::from_str("js"), Ok(ValidationTracer::Js));
+        assert_eq!(
+            ValidationTracer::from_str("native"),
+            Ok(ValidationTracer::Native(NATIVE_TRACER.into()))
+        );
+        assert_eq!(
+            ValidationTracer::from_str("native:erc7562Tracer"),
+            Ok(ValidationTracer::Native("erc7562Tracer".into()))
+        );
+        assert!(ValidationTracer::from_str("native:").is_err());
+        assert!(ValidationTracer::from_str("call").is_err());
+    }
+}
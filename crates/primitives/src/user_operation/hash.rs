@@ -1,9 +1,10 @@
 //! User operation hash related types and helpers
 
-use ethers::types::H256;
+use super::{EntryPointVersion, UserOperationSigned};
+use ethers::types::{Address, H256};
 use rustc_hex::FromHexError;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 /// User operation hash
 #[derive(
@@ -29,6 +30,13 @@ impl From<[u8; 32]> for UserOperationHash {
     }
 }
 
+impl fmt::Display for UserOperationHash {
+    /// Formats the hash as the full hex string (e.g., for the correlation of the logs)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
 impl FromStr for UserOperationHash {
     type Err = FromHexError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -67,3 +75,119 @@ impl UserOperationHash {
         ret
     }
 }
+
+/// Calculates the hash of the user operation exactly as `getUserOpHash` of the entry point smart
+/// contract does (and as the bundler reports it), i.e., the hash of the user operation without
+/// the signature encoded in the format of the entry point version, the entry point address and the
+/// chain id.
+///
+/// # Arguments
+/// * `uo` - The [UserOperation](UserOperationSigned)
+/// * `entry_point` - The address of the entry point smart contract
+/// * `chain_id` - The chain id
+/// * `version` - The [EntryPointVersion](EntryPointVersion) (e.g.,
+///   [from_address](EntryPointVersion::from_address) of the entry point)
+///
+/// # Returns
+/// * `UserOperationHash` - The hash of the user operation
+pub fn user_operation_hash(
+    uo: &UserOperationSigned,
+    entry_point: &Address,
+    chain_id: u64,
+    version: EntryPointVersion,
+) -> UserOperationHash {
+    uo.hash_with_version(entry_point, chain_id, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::entry_point::{ADDRESS, ADDRESS_V07},
+        pack_paymaster_and_data,
+    };
+    use ethers::{
+        abi::{encode, Token},
+        types::{Bytes, U256},
+        utils::keccak256,
+    };
+
+    /// `getUserOpHash` of the v0.7 entry point as written in `UserOperationLib.sol`
+    /// (eth-infinitism/account-abstraction v0.7.0, `encode` and `hash`), the gas limits and the
+    /// fees are packed into `accountGasLimits` and `gasFees`
+    fn get_user_op_hash_v07(
+        uo: &UserOperationSigned,
+        entry_point: &Address,
+        chain_id: u64,
+    ) -> UserOperationHash {
+        let account_gas_limits = (uo.verification_gas_limit << 128) | uo.call_gas_limit;
+        let gas_fees = (uo.max_priority_fee_per_gas << 128) | uo.max_fee_per_gas;
+        let packed = encode(&[
+            Token::Address(uo.sender),
+            Token::Uint(uo.nonce),
+            Token::FixedBytes(keccak256(&uo.init_code).to_vec()),
+            Token::FixedBytes(keccak256(&uo.call_data).to_vec()),
+            Token::FixedBytes(H256::from_uint(&account_gas_limits).as_bytes().to_vec()),
+            Token::Uint(uo.pre_verification_gas),
+            Token::FixedBytes(H256::from_uint(&gas_fees).as_bytes().to_vec()),
+            Token::FixedBytes(keccak256(&uo.paymaster_and_data).to_vec()),
+        ]);
+        H256::from(keccak256(encode(&[
+            Token::FixedBytes(keccak256(packed).to_vec()),
+            Token::Address(*entry_point),
+            Token::Uint(chain_id.into()),
+        ])))
+        .into()
+    }
+
+    #[test]
+    fn user_operation_hash_matches_entry_point() {
+        let uo = UserOperationSigned {
+            sender: "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap(),
+            nonce: U256::zero(),
+            init_code: "0x9406cc6185a346906296840746125a0e449764545fbfb9cf000000000000000000000000ce0fefa6f7979c4c9b5373e0f5105b7259092c6d0000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_data: "0xb61d27f60000000000000000000000009c5754de1443984659e1b3a8d1931d83475ba29c00000000000000000000000000000000000000000000000000005af3107a400000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_gas_limit: 33_100.into(),
+            verification_gas_limit: 361_460.into(),
+            pre_verification_gas: 44_980.into(),
+            max_fee_per_gas: 1_695_000_030_u64.into(),
+            max_priority_fee_per_gas: 1_695_000_000.into(),
+            paymaster_and_data: Bytes::default(),
+            signature: "0xebfd4657afe1f1c05c1ec65f3f9cc992a3ac083c424454ba61eab93152195e1400d74df01fc9fa53caadcb83a891d478b713016bcc0c64307c1ad3d7ea2e2d921b".parse().unwrap(),
+        };
+        let (ep, ep_v07) = (ADDRESS.parse().unwrap(), ADDRESS_V07.parse().unwrap());
+
+        // user operation on Mumbai (chain 80001) hashed by the v0.6 entry point
+        assert_eq!(
+            user_operation_hash(&uo, &ep, 80_001, EntryPointVersion::V0_6),
+            "0x7c1b8c9df49a9e09ecef0f0fe6841d895850d29820f9a4b494097764085dcd7e".parse().unwrap()
+        );
+        assert_eq!(
+            user_operation_hash(&uo, &ep_v07, 80_001, EntryPointVersion::V0_7),
+            get_user_op_hash_v07(&uo, &ep_v07, 80_001)
+        );
+
+        // the signature is not part of the hash
+        let uo_unsigned = UserOperationSigned { signature: Bytes::default(), ..uo.clone() };
+        assert_eq!(
+            user_operation_hash(&uo_unsigned, &ep_v07, 80_001, EntryPointVersion::V0_7),
+            uo.hash_for_entry_point(&ep_v07, 80_001)
+        );
+
+        // nonce key and paymaster with the gas limits and data
+        let uo = UserOperationSigned {
+            nonce: (U256::from(7) << 64) + 3,
+            paymaster_and_data: pack_paymaster_and_data(
+                Some("0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap()),
+                50_000.into(),
+                10_000.into(),
+                &"0xdeadbeef".parse().unwrap(),
+            ),
+            ..uo
+        };
+        assert_eq!(
+            user_operation_hash(&uo, &ep_v07, 11_155_111, EntryPointVersion::V0_7),
+            get_user_op_hash_v07(&uo, &ep_v07, 11_155_111)
+        );
+    }
+}
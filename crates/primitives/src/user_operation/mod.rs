@@ -1,6 +1,7 @@
 //! Basic transaction type for account abstraction (ERC-4337)
 
 mod hash;
+mod packed;
 mod request;
 
 use crate::{get_address, utils::as_checksum_addr};
@@ -11,7 +12,11 @@ use ethers::{
     types::{Address, Bytes, Log, TransactionReceipt, H256, U256, U64},
     utils::keccak256,
 };
-pub use hash::UserOperationHash;
+pub use hash::{user_operation_hash, UserOperationHash};
+pub use packed::{
+    pack_init_code, pack_paymaster_and_data, pack_uints, unpack_uints, EntryPointVersion,
+    PackedUserOperation, PAYMASTER_DATA_OFFSET,
+};
 pub use request::UserOperationRequest;
 use serde::{Deserialize, Serialize};
 use ssz_rs::List;
@@ -157,6 +162,36 @@ impl UserOperationSigned {
         .into()
     }
 
+    /// Packs the user operation into bytes in the format of the given entry point version
+    pub fn pack_with_version(&self, version: EntryPointVersion) -> Bytes {
+        match version {
+            EntryPointVersion::V0_6 => self.pack(),
+            EntryPointVersion::V0_7 => PackedUserOperation::from(self.clone()).pack(),
+        }
+    }
+
+    /// Calculates the hash of the user operation for the given entry point version
+    pub fn hash_with_version(
+        &self,
+        entry_point: &Address,
+        chain_id: u64,
+        version: EntryPointVersion,
+    ) -> UserOperationHash {
+        match version {
+            EntryPointVersion::V0_6 => self.hash(entry_point, chain_id),
+            EntryPointVersion::V0_7 => {
+                PackedUserOperation::from(self.clone()).hash(entry_point, chain_id)
+            }
+        }
+    }
+
+    /// Calculates the hash of the user operation, selecting the format from the canonical entry
+    /// point address (use [hash_with_version](Self::hash_with_version) for the entry points
+    /// deployed at non-canonical addresses)
+    pub fn hash_for_entry_point(&self, entry_point: &Address, chain_id: u64) -> UserOperationHash {
+        self.hash_with_version(entry_point, chain_id, EntryPointVersion::from_address(entry_point))
+    }
+
     // Builder pattern helpers
 
     /// Sets the sender of the user operation
@@ -440,16 +475,31 @@ pub struct UserOperationReceipt {
     pub tx_receipt: TransactionReceipt,
 }
 
+/// Notification that the bundle transaction including the user operation was mined (pushed to the
+/// subscribers of the RPC endpoint eth_subscribeBundledUserOperations)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundledUserOperation {
+    #[serde(rename = "userOpHash")]
+    pub user_operation_hash: UserOperationHash,
+    #[serde(serialize_with = "as_checksum_addr")]
+    pub entry_point: Address,
+    pub transaction_hash: H256,
+    pub success: bool,
+}
+
 /// Struct that is returned from the RPC endpoint eth_getUserOperationByHash
+///
+/// The transaction and block fields are `null` while the user operation is still pending.
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserOperationByHash {
     pub user_operation: UserOperationSigned,
     #[serde(serialize_with = "as_checksum_addr")]
     pub entry_point: Address,
-    pub transaction_hash: H256,
-    pub block_hash: H256,
-    pub block_number: U64,
+    pub transaction_hash: Option<H256>,
+    pub block_hash: Option<H256>,
+    pub block_number: Option<U64>,
 }
 
 /// Gas estimations for user operation (returned from the RPC endpoint eth_estimateUserOperationGas)
@@ -459,6 +509,18 @@ pub struct UserOperationGasEstimation {
     pub pre_verification_gas: U256,
     pub verification_gas_limit: U256,
     pub call_gas_limit: U256,
+    /// Gas of the paymaster validation, estimated from the gas it uses in the simulation (only
+    /// present if the user operation has a paymaster)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+}
+
+/// Suggested fees of user operation (returned from the RPC endpoint eth_getUserOperationGasPrice)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasPrice {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
 }
 
 #[cfg(test)]
@@ -0,0 +1,347 @@
+//! Packed user operation (EntryPoint v0.7)
+
+use super::{UserOperationHash, UserOperationSigned};
+use crate::{
+    constants::entry_point::{ADDRESS_V07, VERSION, VERSION_V07},
+    get_address,
+};
+use ethers::{
+    abi::AbiEncode,
+    contract::{EthAbiCodec, EthAbiType},
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, ops::Deref, str::FromStr};
+
+/// Version of the entry point smart contract
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntryPointVersion {
+    /// EntryPoint v0.6 (`UserOperation` with separate gas fields)
+    #[default]
+    V0_6,
+    /// EntryPoint v0.7 (`PackedUserOperation` with packed gas fields)
+    V0_7,
+}
+
+impl EntryPointVersion {
+    /// Gets the entry point version from the canonical address of the entry point smart contract
+    /// (v0.7 for the canonical v0.7 address and v0.6 for the rest). The entry points deployed at
+    /// non-canonical addresses carry their declared version instead.
+    pub fn from_address(entry_point: &Address) -> Self {
+        match ADDRESS_V07.parse::<Address>() {
+            Ok(addr) if addr == *entry_point => Self::V0_7,
+            _ => Self::V0_6,
+        }
+    }
+
+    /// Gets the version declared for the entry point smart contract (e.g., a custom or testnet
+    /// deployment), otherwise the version of its canonical address
+    ///
+    /// # Arguments
+    /// * `declared` - The versions declared by the operator
+    /// * `entry_point` - The address of the entry point smart contract
+    ///
+    /// # Returns
+    /// * `EntryPointVersion` - The version of the entry point
+    pub fn from_declared(declared: &HashMap<Address, Self>, entry_point: &Address) -> Self {
+        declared.get(entry_point).copied().unwrap_or_else(|| Self::from_address(entry_point))
+    }
+
+    /// Gets the version string of the entry point smart contract
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V0_6 => VERSION,
+            Self::V0_7 => VERSION_V07,
+        }
+    }
+}
+
+impl FromStr for EntryPointVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim_start_matches('v') {
+            v if v == VERSION || v == "0.6" => Ok(Self::V0_6),
+            v if v == VERSION_V07 || v == "0.7" => Ok(Self::V0_7),
+            _ => Err(format!("unsupported entry point version {s}")),
+        }
+    }
+}
+
+/// Packs two 128-bit values into one 32-byte word (`high` in the upper 16 bytes)
+pub fn pack_uints(high: U256, low: U256) -> H256 {
+    let mut high_bytes = [0u8; 32];
+    let mut low_bytes = [0u8; 32];
+    high.to_big_endian(&mut high_bytes);
+    low.to_big_endian(&mut low_bytes);
+    H256::from_slice(&[&high_bytes[16..], &low_bytes[16..]].concat())
+}
+
+/// Unpacks one 32-byte word into two 128-bit values (`high`, `low`)
+pub fn unpack_uints(packed: &H256) -> (U256, U256) {
+    (U256::from_big_endian(&packed[..16]), U256::from_big_endian(&packed[16..]))
+}
+
+/// Length of the paymaster address and paymaster gas limits at the start of `paymasterAndData`
+pub const PAYMASTER_DATA_OFFSET: usize = 52;
+
+/// User operation in the format of EntryPoint v0.7
+///
+/// `initCode` is `factory` followed by `factoryData` and `paymasterAndData` is `paymaster`
+/// followed by `paymasterVerificationGasLimit` (16 bytes), `paymasterPostOpGasLimit` (16 bytes)
+/// and `paymasterData`.
+#[derive(Default, Clone, Debug, PartialEq, Eq, EthAbiCodec, EthAbiType, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    /// `verificationGasLimit` followed by `callGasLimit`
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    /// `maxPriorityFeePerGas` followed by `maxFeePerGas`
+    pub gas_fees: H256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+/// Packed user operation without signature (helper for calculating the hash)
+#[derive(EthAbiCodec, EthAbiType)]
+struct PackedUserOperationNoSignature {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: H256,
+    pub call_data: H256,
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    pub gas_fees: H256,
+    pub paymaster_and_data: H256,
+}
+
+impl From<PackedUserOperation> for PackedUserOperationNoSignature {
+    fn from(value: PackedUserOperation) -> Self {
+        Self {
+            sender: value.sender,
+            nonce: value.nonce,
+            init_code: keccak256(value.init_code.deref()).into(),
+            call_data: keccak256(value.call_data.deref()).into(),
+            account_gas_limits: value.account_gas_limits,
+            pre_verification_gas: value.pre_verification_gas,
+            gas_fees: value.gas_fees,
+            paymaster_and_data: keccak256(value.paymaster_and_data.deref()).into(),
+        }
+    }
+}
+
+impl From<UserOperationSigned> for PackedUserOperation {
+    fn from(value: UserOperationSigned) -> Self {
+        Self {
+            sender: value.sender,
+            nonce: value.nonce,
+            init_code: value.init_code,
+            call_data: value.call_data,
+            account_gas_limits: pack_uints(value.verification_gas_limit, value.call_gas_limit),
+            pre_verification_gas: value.pre_verification_gas,
+            gas_fees: pack_uints(value.max_priority_fee_per_gas, value.max_fee_per_gas),
+            paymaster_and_data: value.paymaster_and_data,
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<PackedUserOperation> for UserOperationSigned {
+    fn from(value: PackedUserOperation) -> Self {
+        let (verification_gas_limit, call_gas_limit) = unpack_uints(&value.account_gas_limits);
+        let (max_priority_fee_per_gas, max_fee_per_gas) = unpack_uints(&value.gas_fees);
+        Self {
+            sender: value.sender,
+            nonce: value.nonce,
+            init_code: value.init_code,
+            call_data: value.call_data,
+            call_gas_limit,
+            verification_gas_limit,
+            pre_verification_gas: value.pre_verification_gas,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            paymaster_and_data: value.paymaster_and_data,
+            signature: value.signature,
+        }
+    }
+}
+
+impl PackedUserOperation {
+    /// Packs the user operation into bytes
+    pub fn pack(&self) -> Bytes {
+        self.clone().encode().into()
+    }
+
+    /// Packs the user operation without signature to bytes (used for calculating the hash)
+    pub fn pack_without_signature(&self) -> Bytes {
+        PackedUserOperationNoSignature::from(self.clone()).encode().into()
+    }
+
+    /// Calculates the hash of the user operation
+    pub fn hash(&self, entry_point: &Address, chain_id: u64) -> UserOperationHash {
+        H256::from_slice(
+            keccak256(
+                [
+                    keccak256(self.pack_without_signature().deref()).to_vec(),
+                    entry_point.encode(),
+                    U256::from(chain_id).encode(),
+                ]
+                .concat(),
+            )
+            .as_slice(),
+        )
+        .into()
+    }
+
+    /// Gets the factory (optionally if present) of the user operation
+    pub fn factory(&self) -> Option<Address> {
+        get_address(&self.init_code)
+    }
+
+    /// Gets the data passed to the factory
+    pub fn factory_data(&self) -> Bytes {
+        self.init_code.get(20..).map(|data| data.to_vec().into()).unwrap_or_default()
+    }
+
+    /// Gets the paymaster (optionally if present) of the user operation
+    pub fn paymaster(&self) -> Option<Address> {
+        get_address(&self.paymaster_and_data)
+    }
+
+    /// Gets the paymaster verification and post-op gas limits
+    pub fn paymaster_gas_limits(&self) -> (U256, U256) {
+        match self.paymaster_and_data.get(20..PAYMASTER_DATA_OFFSET) {
+            Some(limits) => unpack_uints(&H256::from_slice(limits)),
+            None => (U256::zero(), U256::zero()),
+        }
+    }
+
+    /// Gets the data passed to the paymaster
+    pub fn paymaster_data(&self) -> Bytes {
+        self.paymaster_and_data
+            .get(PAYMASTER_DATA_OFFSET..)
+            .map(|data| data.to_vec().into())
+            .unwrap_or_default()
+    }
+}
+
+/// Builds `initCode` from `factory` and `factoryData`
+pub fn pack_init_code(factory: Option<Address>, factory_data: &Bytes) -> Bytes {
+    match factory {
+        Some(factory) => [factory.as_bytes(), factory_data.as_ref()].concat().into(),
+        None => Bytes::default(),
+    }
+}
+
+/// Builds `paymasterAndData` from `paymaster`, the paymaster gas limits and `paymasterData`
+pub fn pack_paymaster_and_data(
+    paymaster: Option<Address>,
+    paymaster_verification_gas_limit: U256,
+    paymaster_post_op_gas_limit: U256,
+    paymaster_data: &Bytes,
+) -> Bytes {
+    match paymaster {
+        Some(paymaster) => [
+            paymaster.as_bytes(),
+            pack_uints(paymaster_verification_gas_limit, paymaster_post_op_gas_limit).as_bytes(),
+            paymaster_data.as_ref(),
+        ]
+        .concat()
+        .into(),
+        None => Bytes::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_point_version_from_address() {
+        assert_eq!(
+            EntryPointVersion::from_address(&ADDRESS_V07.parse().unwrap()),
+            EntryPointVersion::V0_7
+        );
+        assert_eq!(
+            EntryPointVersion::from_address(
+                &crate::constants::entry_point::ADDRESS.parse().unwrap()
+            ),
+            EntryPointVersion::V0_6
+        );
+    }
+
+    #[test]
+    fn entry_point_version_declared() {
+        let custom = Address::random();
+        assert_eq!(EntryPointVersion::from_address(&custom), EntryPointVersion::V0_6);
+        assert_eq!(
+            EntryPointVersion::from_declared(&HashMap::new(), &custom),
+            EntryPointVersion::V0_6
+        );
+        assert_eq!(
+            EntryPointVersion::from_declared(
+                &HashMap::from([(custom, EntryPointVersion::V0_7)]),
+                &custom
+            ),
+            EntryPointVersion::V0_7
+        );
+
+        assert_eq!("0.7.0".parse(), Ok(EntryPointVersion::V0_7));
+        assert_eq!("v0.6".parse(), Ok(EntryPointVersion::V0_6));
+        assert!("0.8.0".parse::<EntryPointVersion>().is_err());
+    }
+
+    #[test]
+    fn packed_user_operation_gas_fields() {
+        let uo = UserOperationSigned::default()
+            .call_gas_limit(200_000.into())
+            .verification_gas_limit(100_000.into())
+            .max_fee_per_gas(3_000_000_000_u64.into())
+            .max_priority_fee_per_gas(1_000_000_000.into());
+        let packed = PackedUserOperation::from(uo.clone());
+        assert_eq!(
+            packed.account_gas_limits,
+            "0x000000000000000000000000000186a000000000000000000000000000030d40"
+                .parse::<H256>()
+                .unwrap()
+        );
+        assert_eq!(
+            packed.gas_fees,
+            "0x0000000000000000000000003b9aca00000000000000000000000000b2d05e00"
+                .parse::<H256>()
+                .unwrap()
+        );
+        assert_eq!(UserOperationSigned::from(packed), uo);
+    }
+
+    #[test]
+    fn packed_user_operation_factory_and_paymaster() {
+        let factory: Address = "0x9406Cc6185a346906296840746125a0E44976454".parse().unwrap();
+        let paymaster: Address = "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap();
+        let factory_data: Bytes = "0x5fbfb9cf".parse().unwrap();
+        let paymaster_data: Bytes = "0xdeadbeef".parse().unwrap();
+
+        let packed = PackedUserOperation {
+            init_code: pack_init_code(Some(factory), &factory_data),
+            paymaster_and_data: pack_paymaster_and_data(
+                Some(paymaster),
+                50_000.into(),
+                10_000.into(),
+                &paymaster_data,
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(packed.factory(), Some(factory));
+        assert_eq!(packed.factory_data(), factory_data);
+        assert_eq!(packed.paymaster(), Some(paymaster));
+        assert_eq!(packed.paymaster_gas_limits(), (50_000.into(), 10_000.into()));
+        assert_eq!(packed.paymaster_data(), paymaster_data);
+        assert_eq!(pack_init_code(None, &factory_data), Bytes::default());
+    }
+}
@@ -1,11 +1,15 @@
 //! User operation request (optional fields)
 
-use super::UserOperationSigned;
+use super::{pack_init_code, pack_paymaster_and_data, UserOperationSigned};
 use crate::utils::{as_checksum_addr, as_checksum_bytes};
 use ethers::types::{Address, Bytes, U256};
 use serde::{Deserialize, Serialize};
 
 /// User operation with all fields being optional
+///
+/// Both the EntryPoint v0.6 (`initCode`, `paymasterAndData`) and the EntryPoint v0.7 (`factory`,
+/// `factoryData`, `paymaster`, `paymasterVerificationGasLimit`, `paymasterPostOpGasLimit`,
+/// `paymasterData`) formats are accepted. The v0.7 fields take precedence if present.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserOperationRequest {
@@ -31,6 +35,18 @@ pub struct UserOperationRequest {
     pub paymaster_and_data: Bytes,
     #[serde(default)]
     pub signature: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
 }
 
 impl From<UserOperationRequest> for UserOperationSigned {
@@ -38,7 +54,16 @@ impl From<UserOperationRequest> for UserOperationSigned {
         Self {
             sender: user_operation.sender,
             nonce: user_operation.nonce,
-            init_code: user_operation.init_code,
+            init_code: {
+                if user_operation.factory.is_some() {
+                    pack_init_code(
+                        user_operation.factory,
+                        &user_operation.factory_data.unwrap_or_default(),
+                    )
+                } else {
+                    user_operation.init_code
+                }
+            },
             call_data: user_operation.call_data,
             call_gas_limit: {
                 if let Some(call_gas_limit) = user_operation.call_gas_limit {
@@ -75,7 +100,18 @@ impl From<UserOperationRequest> for UserOperationSigned {
                     U256::zero()
                 }
             },
-            paymaster_and_data: user_operation.paymaster_and_data,
+            paymaster_and_data: {
+                if user_operation.paymaster.is_some() {
+                    pack_paymaster_and_data(
+                        user_operation.paymaster,
+                        user_operation.paymaster_verification_gas_limit.unwrap_or_default(),
+                        user_operation.paymaster_post_op_gas_limit.unwrap_or_default(),
+                        &user_operation.paymaster_data.unwrap_or_default(),
+                    )
+                } else {
+                    user_operation.paymaster_and_data
+                }
+            },
             signature: {
                 if let Some(signature) = user_operation.signature {
                     signature
@@ -101,6 +137,12 @@ impl From<UserOperationSigned> for UserOperationRequest {
             max_priority_fee_per_gas: Some(user_operation.max_priority_fee_per_gas),
             paymaster_and_data: user_operation.paymaster_and_data,
             signature: Some(user_operation.signature),
+            factory: None,
+            factory_data: None,
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
         }
     }
 }
@@ -1,9 +1,11 @@
 //! Misc utils
 
+use crate::constants::eip7702::DELEGATION_PREFIX;
 use ethers::{
     types::{Address, Bytes, U256},
     utils::{hex, to_checksum},
 };
+use serde::Deserialize;
 
 /// Converts address to checksum address
 pub fn as_checksum_addr<S>(val: &Address, s: S) -> Result<S::Ok, S::Error>
@@ -44,6 +46,26 @@ where
     serde_hex::SerHex::<serde_hex::StrictPfx>::serialize(val, s)
 }
 
+/// Deserializes u64 from hex string (or number), the counterpart of [as_hex_string]
+pub fn from_hex_string<'de, D>(d: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HexOrNumber {
+        Hex(String),
+        Number(u64),
+    }
+
+    match HexOrNumber::deserialize(d)? {
+        HexOrNumber::Hex(val) => {
+            u64::from_str_radix(val.trim_start_matches("0x"), 16).map_err(serde::de::Error::custom)
+        }
+        HexOrNumber::Number(val) => Ok(val),
+    }
+}
+
 /// If possible, parses address from the first 20 bytes
 pub fn get_address(buf: &[u8]) -> Option<Address> {
     if buf.len() >= 20 {
@@ -52,3 +74,12 @@ pub fn get_address(buf: &[u8]) -> Option<Address> {
         None
     }
 }
+
+/// If the code is an [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702) delegation designator
+/// (`0xef0100 || address`), parses the address the EOA is delegated to
+pub fn get_delegation(code: &[u8]) -> Option<Address> {
+    match code.strip_prefix(&DELEGATION_PREFIX) {
+        Some(addr) if addr.len() == 20 => Some(Address::from_slice(addr)),
+        _ => None,
+    }
+}
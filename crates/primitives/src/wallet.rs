@@ -153,7 +153,7 @@ impl Wallet {
         ep: &Address,
         chain_id: u64,
     ) -> eyre::Result<UserOperation> {
-        let h = uo.hash(ep, chain_id);
+        let h = uo.hash_for_entry_point(ep, chain_id);
         let sig = self.signer.sign_message(h.0.as_bytes()).await?;
         Ok(UserOperation {
             hash: h,
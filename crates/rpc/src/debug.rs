@@ -10,14 +10,18 @@ use jsonrpsee::{
 };
 use silius_grpc::{
     bundler_client::BundlerClient, uo_pool_client::UoPoolClient, AddMempoolRequest,
-    GetAllReputationRequest, GetAllRequest, GetStakeInfoRequest, Mode as GrpcMode, SetModeRequest,
+    ExplainUserOperationRequest, ExportSnapshotRequest, GetAllReputationRequest, GetAllRequest,
+    GetStakeInfoRequest, ImportSnapshotRequest, Mode as GrpcMode, SetModeRequest,
     SetReputationRequest, SetReputationResult,
 };
 use silius_primitives::{
+    bundler::BundleInclusion,
     constants::bundler::BUNDLE_INTERVAL,
+    mempool::{MempoolContent, MempoolSnapshot},
     reputation::{ReputationEntry, StakeInfoResponse},
-    BundlerMode, UserOperation, UserOperationRequest, UserOperationSigned,
+    BundlerMode, UserOperation, UserOperationHash, UserOperationRequest, UserOperationSigned,
 };
+use std::collections::HashSet;
 use tonic::Request;
 
 /// DebugApiServerImpl implements the ERC-4337 `debug` namespace rpc methods trait
@@ -107,7 +111,7 @@ impl DebugApiServer for DebugApiServerImpl {
                     .map(|uo| {
                         let uo: UserOperationSigned = uo.clone().into();
                         UserOperation::from_user_operation_signed(
-                            uo.hash(&ep, res.chain_id),
+                            uo.hash_for_entry_point(&ep, res.chain_id),
                             uo.clone(),
                         )
                         .into()
@@ -123,7 +127,8 @@ impl DebugApiServer for DebugApiServerImpl {
     }
 
     /// Sending an [GetAllRequest](GetAllRequest) to the UoPool gRPC server
-    /// to get all of the [UserOperation](UserOperationRequest) in the mempool.
+    /// to get all of the [UserOperation](UserOperationRequest) in the mempool, in the order they
+    /// would be bundled.
     ///
     /// # Arguments
     /// * `entry_point: Address` - The address of the entry point.
@@ -133,19 +138,52 @@ impl DebugApiServer for DebugApiServerImpl {
     async fn dump_mempool(&self, ep: Address) -> RpcResult<Vec<UserOperationRequest>> {
         let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
-        let req = Request::new(GetAllRequest { ep: Some(ep.into()) });
+        let req = Request::new(GetAllRequest { ep: Some(ep.into()), with_queued: false });
 
         let res = uopool_grpc_client.get_all(req).await.map_err(JsonRpcError::from)?.into_inner();
 
-        let mut uos: Vec<UserOperationRequest> = res
+        let uos: Vec<UserOperationRequest> = res
             .uos
             .iter()
             .map(|uo| UserOperation::from(uo.clone()).user_operation.into())
             .collect();
-        uos.sort_by(|a, b| a.nonce.cmp(&b.nonce));
         Ok(uos)
     }
 
+    /// Sending an [GetAllRequest](GetAllRequest) to the UoPool gRPC server
+    /// to get all of the [UserOperation](UserOperationRequest) in the mempool, split into the
+    /// pending ones and the queued ones, with the failed bundle attempts.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolContent>` - The [MempoolContent](MempoolContent)
+    async fn dump_mempool_content(&self, ep: Address) -> RpcResult<MempoolContent> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetAllRequest { ep: Some(ep.into()), with_queued: true });
+
+        let res = uopool_grpc_client.get_all(req).await.map_err(JsonRpcError::from)?.into_inner();
+
+        let queued = res.queued.into_iter().map(UserOperationHash::from).collect::<HashSet<_>>();
+        let mut content = MempoolContent::default();
+        for uo in res.uos {
+            let uo = UserOperation::from(uo);
+            if queued.contains(&uo.hash) {
+                content.queued.push(uo.user_operation.into());
+            } else {
+                content.pending.push(uo.user_operation.into());
+            }
+        }
+        content.bundle_attempts = res
+            .attempts
+            .into_iter()
+            .filter_map(|uo| uo.hash.map(|hash| (UserOperationHash::from(hash), uo.attempts)))
+            .collect();
+        Ok(content)
+    }
+
     /// Set the reputations for the given array of [ReputationEntry](ReputationEntry)
     /// and send it to the UoPool gRPC service through the
     /// [SetReputationRequest](SetReputationRequest).
@@ -191,6 +229,7 @@ impl DebugApiServer for DebugApiServerImpl {
     ///
     /// # Returns
     /// * `RpcResult<Vec<ReputationEntry>>` - An array of [ReputationEntries](ReputationEntry)
+    ///   sorted by address
     async fn dump_reputation(&self, ep: Address) -> RpcResult<Vec<ReputationEntry>> {
         let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
@@ -205,6 +244,57 @@ impl DebugApiServer for DebugApiServerImpl {
         Ok(res.rep.iter().map(|re| re.clone().into()).collect())
     }
 
+    /// Exports the [UserOperations](UserOperationRequest) and the reputation of the mempool via
+    /// the [ExportSnapshotRequest](ExportSnapshotRequest).
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolSnapshot>` - The [MempoolSnapshot](MempoolSnapshot)
+    async fn dump_mempool_snapshot(&self, ep: Address) -> RpcResult<MempoolSnapshot> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(ExportSnapshotRequest { ep: Some(ep.into()) });
+
+        let res =
+            uopool_grpc_client.export_snapshot(req).await.map_err(JsonRpcError::from)?.into_inner();
+
+        serde_json::from_str(&res.snapshot).map_err(|err| {
+            ErrorObjectOwned::owned(
+                INTERNAL_ERROR_CODE,
+                format!("Error decoding mempool snapshot: {err}"),
+                None::<bool>,
+            )
+        })
+    }
+
+    /// Imports the [MempoolSnapshot](MempoolSnapshot) via the
+    /// [ImportSnapshotRequest](ImportSnapshotRequest).
+    ///
+    /// # Arguments
+    /// * `snapshot: MempoolSnapshot` - The [MempoolSnapshot](MempoolSnapshot) to import.
+    ///
+    /// # Returns
+    /// * `RpcResult<u64>` - The number of imported [UserOperations](UserOperationRequest)
+    async fn import_mempool_snapshot(&self, snapshot: MempoolSnapshot) -> RpcResult<u64> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let snapshot = serde_json::to_string(&snapshot).map_err(|err| {
+            ErrorObjectOwned::owned(
+                INTERNAL_ERROR_CODE,
+                format!("Error encoding mempool snapshot: {err}"),
+                None::<bool>,
+            )
+        })?;
+        let req = Request::new(ImportSnapshotRequest { snapshot });
+
+        let res =
+            uopool_grpc_client.import_snapshot(req).await.map_err(JsonRpcError::from)?.into_inner();
+
+        Ok(res.imported)
+    }
+
     /// Set the bundling mode.
     ///
     /// # Arguments
@@ -243,6 +333,29 @@ impl DebugApiServer for DebugApiServerImpl {
         }
     }
 
+    /// Explains whether a user operation of the mempool would be included in the next bundle, and
+    /// why not otherwise.
+    ///
+    /// # Arguments
+    /// * `user_operation_hash: UserOperationHash` - The hash of the user operation.
+    ///
+    /// # Returns
+    /// * `RpcResult<BundleInclusion>` - Whether the user operation would be included and why not.
+    async fn explain_user_operation(
+        &self,
+        user_operation_hash: UserOperationHash,
+    ) -> RpcResult<BundleInclusion> {
+        let mut bundler_grpc_client = self.bundler_grpc_client.clone();
+
+        let req =
+            Request::new(ExplainUserOperationRequest { hash: Some(user_operation_hash.into()) });
+
+        match bundler_grpc_client.explain_user_operation(req).await {
+            Ok(res) => res.into_inner().try_into().map_err(|s| JsonRpcError::from(s).into()),
+            Err(s) => Err(JsonRpcError::from(s).into()),
+        }
+    }
+
     /// Returns the stake info of the given address.
     ///
     /// # Arguments
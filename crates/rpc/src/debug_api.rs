@@ -3,8 +3,10 @@ use ethers::types::{Address, H256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use serde::{Deserialize, Serialize};
 use silius_primitives::{
+    bundler::BundleInclusion,
+    mempool::{MempoolContent, MempoolSnapshot},
     reputation::{ReputationEntry, StakeInfoResponse},
-    BundlerMode, UserOperationRequest,
+    BundlerMode, UserOperationHash, UserOperationRequest,
 };
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
@@ -67,6 +69,18 @@ pub trait DebugApi {
     #[method(name = "dumpMempool")]
     async fn dump_mempool(&self, entry_point: Address) -> RpcResult<Vec<UserOperationRequest>>;
 
+    /// Get all [UserOperations](UserOperationRequest) of the mempool, split into the pending ones
+    /// and the queued ones (waiting for a nonce gap to be filled), with the number of failed bundle
+    /// attempts of the user operations that failed at bundle time
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolContent>` - The [MempoolContent](MempoolContent)
+    #[method(name = "dumpMempoolContent")]
+    async fn dump_mempool_content(&self, entry_point: Address) -> RpcResult<MempoolContent>;
+
     /// Set the reputations for the given array of [ReputationEntry](ReputationEntry)
     ///
     /// # Arguments
@@ -93,6 +107,28 @@ pub trait DebugApi {
     #[method(name = "dumpReputation")]
     async fn dump_reputation(&self, entry_point: Address) -> RpcResult<Vec<ReputationEntry>>;
 
+    /// Exports the [UserOperations](UserOperationRequest) and the reputation of the mempool into a
+    /// [MempoolSnapshot](MempoolSnapshot), e.g., to migrate them to another bundler.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolSnapshot>` - The [MempoolSnapshot](MempoolSnapshot)
+    #[method(name = "dumpMempoolSnapshot")]
+    async fn dump_mempool_snapshot(&self, entry_point: Address) -> RpcResult<MempoolSnapshot>;
+
+    /// Imports the [MempoolSnapshot](MempoolSnapshot). The reputation is restored and the
+    /// [UserOperations](UserOperationRequest) are validated again, the invalid ones are dropped.
+    ///
+    /// # Arguments
+    /// * `snapshot: MempoolSnapshot` - The [MempoolSnapshot](MempoolSnapshot) to import.
+    ///
+    /// # Returns
+    /// * `RpcResult<u64>` - The number of imported [UserOperations](UserOperationRequest)
+    #[method(name = "importMempoolSnapshot")]
+    async fn import_mempool_snapshot(&self, snapshot: MempoolSnapshot) -> RpcResult<u64>;
+
     /// Set the bundling mode.
     ///
     /// # Arguments
@@ -113,6 +149,20 @@ pub trait DebugApi {
     #[method(name = "sendBundleNow")]
     async fn send_bundle_now(&self) -> RpcResult<H256>;
 
+    /// Explains whether a user operation of the mempool would be included in the next bundle, and
+    /// why not otherwise (e.g., its fee is below the base fee or the bundle is full).
+    ///
+    /// # Arguments
+    /// * `user_operation_hash: UserOperationHash` - The hash of the user operation.
+    ///
+    /// # Returns
+    /// * `RpcResult<BundleInclusion>` - Whether the user operation would be included and why not.
+    #[method(name = "explainUserOperation")]
+    async fn explain_user_operation(
+        &self,
+        user_operation_hash: UserOperationHash,
+    ) -> RpcResult<BundleInclusion>;
+
     /// Returns the stake info of the given address.
     ///
     /// # Arguments
@@ -1,15 +1,17 @@
 use crate::codes::{
-    BANNED_OR_THROTTLED_ENTITY, EXECUTION, OPCODE, SANITY, SIGNATURE, STAKE_TOO_LOW, TIMESTAMP,
-    VALIDATION,
+    BANNED_OR_THROTTLED_ENTITY, EXECUTION, OPCODE, PAYMASTER, SANITY, SIGNATURE, STAKE_TOO_LOW,
+    TIMESTAMP, VALIDATION,
 };
 use jsonrpsee::types::{
     error::{ErrorCode, INTERNAL_ERROR_CODE},
     ErrorObject, ErrorObjectOwned,
 };
+use serde_json::json;
 use silius_mempool::{
     InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, ReputationError, SanityError,
     SimulationError,
 };
+use silius_primitives::constants::validation::entities::SENDER;
 
 /// A wrapper for the [ErrorObjectOwned](ErrorObjectOwned) type.
 pub struct JsonRpcError(pub ErrorObjectOwned);
@@ -44,25 +46,44 @@ impl From<MempoolError> for JsonRpcError {
 
 impl From<ReputationError> for JsonRpcError {
     /// Convert a [ReputationError](ReputationError) to a [JsonRpcError](JsonRpcError).
+    /// The data of the error contains the offending entity (keyed by its role) and the
+    /// stake requirement it does not meet.
     fn from(err: ReputationError) -> Self {
         JsonRpcError(match err {
-            ReputationError::BannedEntity { entity: _, address: _ } => {
-                ErrorObject::owned(BANNED_OR_THROTTLED_ENTITY, err.to_string(), None::<bool>)
-            }
-            ReputationError::ThrottledEntity { entity: _, address: _ } => {
-                ErrorObject::owned(BANNED_OR_THROTTLED_ENTITY, err.to_string(), None::<bool>)
-            }
-            ReputationError::StakeTooLow { entity: _, address: _, stake: _, min_stake: _ } => {
-                ErrorObject::owned(STAKE_TOO_LOW, err.to_string(), None::<bool>)
-            }
+            ReputationError::BannedEntity { ref entity, address } => ErrorObject::owned(
+                BANNED_OR_THROTTLED_ENTITY,
+                err.to_string(),
+                Some(json!({ entity: address })),
+            ),
+            ReputationError::ThrottledEntity { ref entity, address } => ErrorObject::owned(
+                BANNED_OR_THROTTLED_ENTITY,
+                err.to_string(),
+                Some(json!({ entity: address })),
+            ),
+            ReputationError::StakeTooLow {
+                ref entity,
+                address,
+                stake: _,
+                min_stake,
+                min_unstake_delay,
+            } |
             ReputationError::UnstakeDelayTooLow {
-                entity: _,
-                address: _,
+                ref entity,
+                address,
                 unstake_delay: _,
-                min_unstake_delay: _,
-            } => ErrorObject::owned(STAKE_TOO_LOW, err.to_string(), None::<bool>),
-            ReputationError::UnstakedEntity { entity: _, address: _ } => {
-                ErrorObject::owned(STAKE_TOO_LOW, err.to_string(), None::<bool>)
+                min_stake,
+                min_unstake_delay,
+            } => ErrorObject::owned(
+                STAKE_TOO_LOW,
+                err.to_string(),
+                Some(json!({
+                    entity: address,
+                    "minimumStake": min_stake,
+                    "minimumUnstakeDelay": min_unstake_delay,
+                })),
+            ),
+            ReputationError::UnstakedEntity { ref entity, address } => {
+                ErrorObject::owned(STAKE_TOO_LOW, err.to_string(), Some(json!({ entity: address })))
             }
             _ => ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<bool>),
         })
@@ -71,36 +92,130 @@ impl From<ReputationError> for JsonRpcError {
 
 impl From<SanityError> for JsonRpcError {
     /// Convert a [SanityError](SanityError) to a [JsonRpcError](JsonRpcError).
+    /// Invalid fields of the user operation are reported as invalid params, with the offending
+    /// value and the bound it violates in the data of the error.
     fn from(err: SanityError) -> Self {
         JsonRpcError(match err {
             SanityError::VerificationGasLimitTooHigh {
-                verification_gas_limit: _,
-                verification_gas_limit_expected: _,
-            } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
+                verification_gas_limit,
+                verification_gas_limit_expected,
+            } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "verificationGasLimit": verification_gas_limit,
+                    "maximum": verification_gas_limit_expected,
+                })),
+            ),
             SanityError::PreVerificationGasTooLow {
-                pre_verification_gas: _,
-                pre_verification_gas_expected: _,
-            } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
-            SanityError::CallGasLimitTooLow { call_gas_limit: _, call_gas_limit_expected: _ } => {
-                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+                pre_verification_gas,
+                pre_verification_gas_expected,
+            } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "preVerificationGas": pre_verification_gas,
+                    "minimum": pre_verification_gas_expected,
+                })),
+            ),
+            SanityError::PreVerificationGasTooHigh {
+                pre_verification_gas,
+                pre_verification_gas_expected,
+            } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "preVerificationGas": pre_verification_gas,
+                    "maximum": pre_verification_gas_expected,
+                })),
+            ),
+            SanityError::CallGasLimitTooLow { call_gas_limit, call_gas_limit_expected } => {
+                ErrorObject::owned(
+                    SANITY,
+                    err.to_string(),
+                    Some(json!({
+                        "callGasLimit": call_gas_limit,
+                        "minimum": call_gas_limit_expected,
+                    })),
+                )
             }
-            SanityError::MaxFeePerGasTooLow { max_fee_per_gas: _, base_fee_per_gas: _ } => {
-                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            SanityError::MaxFeePerGasTooLow { max_fee_per_gas, max_fee_per_gas_expected } => {
+                ErrorObject::owned(
+                    SANITY,
+                    err.to_string(),
+                    Some(json!({
+                        "maxFeePerGas": max_fee_per_gas,
+                        "minimum": max_fee_per_gas_expected,
+                    })),
+                )
             }
             SanityError::MaxPriorityFeePerGasTooHigh {
-                max_priority_fee_per_gas: _,
-                max_fee_per_gas: _,
-            } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+            } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "maxPriorityFeePerGas": max_priority_fee_per_gas,
+                    "maximum": max_fee_per_gas,
+                })),
+            ),
             SanityError::MaxPriorityFeePerGasTooLow {
-                max_priority_fee_per_gas: _,
-                max_priority_fee_per_gas_expected: _,
-            } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
-            SanityError::Paymaster { inner: _ } => {
-                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+                max_priority_fee_per_gas,
+                max_priority_fee_per_gas_expected,
+            } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "maxPriorityFeePerGas": max_priority_fee_per_gas,
+                    "minimum": max_priority_fee_per_gas_expected,
+                })),
+            ),
+            SanityError::UserOperationTooLarge { size, max_size } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({ "size": size, "maximum": max_size })),
+            ),
+            SanityError::MempoolFull { max_uos, effective_gas_price } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({
+                    "maximumUserOperations": max_uos,
+                    "effectiveGasPrice": effective_gas_price,
+                })),
+            ),
+            SanityError::SenderLimit { sender, count: _, max_uos_per_sender } => {
+                ErrorObject::owned(
+                    STAKE_TOO_LOW,
+                    err.to_string(),
+                    Some(json!({ SENDER: sender, "maximumUserOperations": max_uos_per_sender })),
+                )
             }
+            SanityError::Paymaster { inner: _ } |
+            SanityError::PaymasterDeposit { paymaster: _, deposit: _, required_prefund: _ } |
+            SanityError::PaymasterNotAllowed { address: _ } |
+            SanityError::PaymasterRequired => {
+                ErrorObject::owned(PAYMASTER, err.to_string(), None::<bool>)
+            }
+            SanityError::UnsupportedEntryPointVersion { ref version } => ErrorObject::owned(
+                SANITY,
+                err.to_string(),
+                Some(json!({ "entryPointVersion": version })),
+            ),
             SanityError::Sender { inner: _ } => {
                 ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
             }
+            SanityError::Factory { inner: _ } => {
+                ErrorObject::owned(VALIDATION, err.to_string(), None::<bool>)
+            }
+            SanityError::Signature { inner: _ } => {
+                ErrorObject::owned(SIGNATURE, err.to_string(), None::<bool>)
+            }
+            SanityError::Blocklisted { ref entity, address } => ErrorObject::owned(
+                BANNED_OR_THROTTLED_ENTITY,
+                err.to_string(),
+                Some(json!({ entity: address })),
+            ),
             SanityError::EntityRoles { entity: _, address: _, entity_other: _ } => {
                 ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
             }
@@ -126,7 +241,9 @@ impl From<SimulationError> for JsonRpcError {
             SimulationError::Execution { inner: _ } => {
                 ErrorObject::owned(EXECUTION, err.to_string(), None::<bool>)
             }
-            SimulationError::Opcode { entity: _, opcode: _ } => {
+            SimulationError::Opcode { entity: _, opcode: _ } |
+            SimulationError::ContractCreation { entity: _, opcode: _ } |
+            SimulationError::SelfDestruct { entity: _ } => {
                 ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
             }
             SimulationError::StorageAccess { slot: _ } => {
@@ -171,3 +288,106 @@ impl From<serde_json::Error> for JsonRpcError {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, U256};
+    use serde_json::Value;
+    use silius_primitives::constants::validation::entities::PAYMASTER as PAYMASTER_ENTITY;
+
+    fn data(err: &ErrorObjectOwned) -> Value {
+        serde_json::from_str(err.data().expect("error has data").get()).unwrap()
+    }
+
+    #[test]
+    fn sanity_error_codes_and_data() {
+        let err = ErrorObjectOwned::from(JsonRpcError::from(SanityError::UserOperationTooLarge {
+            size: 2048,
+            max_size: 1024,
+        }));
+        assert_eq!(err.code(), SANITY);
+        assert_eq!(data(&err), json!({ "size": 2048, "maximum": 1024 }));
+
+        let err = ErrorObjectOwned::from(JsonRpcError::from(SanityError::CallGasLimitTooLow {
+            call_gas_limit: 1.into(),
+            call_gas_limit_expected: 2.into(),
+        }));
+        assert_eq!(err.code(), SANITY);
+        assert_eq!(data(&err), json!({ "callGasLimit": U256::from(1), "minimum": U256::from(2) }));
+
+        let sender = Address::random();
+        let err = ErrorObjectOwned::from(JsonRpcError::from(SanityError::SenderLimit {
+            sender,
+            count: 4,
+            max_uos_per_sender: 4,
+        }));
+        assert_eq!(err.code(), STAKE_TOO_LOW);
+        assert_eq!(data(&err), json!({ SENDER: sender, "maximumUserOperations": 4 }));
+
+        let err = ErrorObjectOwned::from(JsonRpcError::from(SanityError::PaymasterDeposit {
+            paymaster: Address::random(),
+            deposit: 1.into(),
+            required_prefund: 2.into(),
+        }));
+        assert_eq!(err.code(), PAYMASTER);
+        assert!(err.data().is_none());
+    }
+
+    #[test]
+    fn reputation_error_codes_and_data() {
+        let address = Address::random();
+
+        let err = ErrorObjectOwned::from(JsonRpcError::from(ReputationError::BannedEntity {
+            entity: PAYMASTER_ENTITY.into(),
+            address,
+        }));
+        assert_eq!(err.code(), BANNED_OR_THROTTLED_ENTITY);
+        assert_eq!(data(&err), json!({ PAYMASTER_ENTITY: address }));
+
+        let expected = json!({
+            PAYMASTER_ENTITY: address,
+            "minimumStake": U256::from(100),
+            "minimumUnstakeDelay": U256::from(86400),
+        });
+
+        let err = ErrorObjectOwned::from(JsonRpcError::from(ReputationError::StakeTooLow {
+            entity: PAYMASTER_ENTITY.into(),
+            address,
+            stake: 1.into(),
+            min_stake: 100.into(),
+            min_unstake_delay: 86400.into(),
+        }));
+        assert_eq!(err.code(), STAKE_TOO_LOW);
+        assert_eq!(data(&err), expected);
+
+        let err = ErrorObjectOwned::from(JsonRpcError::from(ReputationError::UnstakeDelayTooLow {
+            address,
+            entity: PAYMASTER_ENTITY.into(),
+            unstake_delay: 1.into(),
+            min_stake: 100.into(),
+            min_unstake_delay: 86400.into(),
+        }));
+        assert_eq!(err.code(), STAKE_TOO_LOW);
+        assert_eq!(data(&err), expected);
+
+        // reputation errors wrapped by the sanity checks keep their code and data
+        let err = ErrorObjectOwned::from(JsonRpcError::from(SanityError::Reputation(
+            ReputationError::ThrottledEntity { entity: PAYMASTER_ENTITY.into(), address },
+        )));
+        assert_eq!(err.code(), BANNED_OR_THROTTLED_ENTITY);
+        assert_eq!(data(&err), json!({ PAYMASTER_ENTITY: address }));
+    }
+
+    #[test]
+    fn mempool_error_codes() {
+        let err = ErrorObjectOwned::from(JsonRpcError::from(MempoolError {
+            hash: Default::default(),
+            kind: MempoolErrorKind::InvalidUserOperation(
+                InvalidMempoolUserOperationError::Simulation(SimulationError::Signature),
+            ),
+        }));
+        assert_eq!(err.code(), SIGNATURE);
+        assert!(err.data().is_none());
+    }
+}
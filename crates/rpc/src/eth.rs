@@ -4,15 +4,21 @@ use ethers::{
     types::{Address, U64},
     utils::to_checksum,
 };
-use jsonrpsee::{core::RpcResult, types::ErrorObjectOwned};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned},
+    PendingSubscriptionSink, SubscriptionMessage,
+};
 use silius_grpc::{
-    uo_pool_client::UoPoolClient, AddRequest, AddResult, EstimateUserOperationGasRequest,
-    EstimateUserOperationGasResult, UserOperationHashRequest,
+    bundler_client::BundlerClient, uo_pool_client::UoPoolClient, AddRequest, AddResult,
+    EstimateUserOperationGasRequest, EstimateUserOperationGasResult,
+    GetUserOperationTransactionRequest, SubscribeBundledRequest, UserOperationHashRequest, H256,
 };
 use silius_mempool::MempoolError;
 use silius_primitives::{
-    UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
-    UserOperationReceipt, UserOperationRequest, UserOperationSigned,
+    BundledUserOperation, UserOperation, UserOperationByHash, UserOperationGasEstimation,
+    UserOperationGasPrice, UserOperationHash, UserOperationReceipt, UserOperationRequest,
+    UserOperationSigned,
 };
 use std::str::FromStr;
 use tonic::Request;
@@ -22,6 +28,82 @@ use tonic::Request;
 pub struct EthApiServerImpl {
     /// The [UoPool gRPC client](UoPoolClient).
     pub uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
+    /// The [Bundler gRPC client](BundlerClient), used to look up the transactions of the user
+    /// operations submitted by this bundler.
+    pub bundler_grpc_client: Option<BundlerClient<tonic::transport::Channel>>,
+}
+
+impl EthApiServerImpl {
+    /// Looks up the bundle transaction of a user operation submitted by this bundler.
+    ///
+    /// # Arguments
+    /// * `uo_hash: &UserOperationHash` - The hash of the user operation.
+    ///
+    /// # Returns
+    /// * `Option<H256>` - The hash of the bundle transaction, if the user operation was submitted
+    ///   by this bundler.
+    async fn get_user_operation_transaction(&self, uo_hash: &UserOperationHash) -> Option<H256> {
+        let mut bundler_grpc_client = self.bundler_grpc_client.clone()?;
+        let req =
+            Request::new(GetUserOperationTransactionRequest { hash: Some((*uo_hash).into()) });
+        bundler_grpc_client.get_user_operation_transaction(req).await.ok()?.into_inner().tx_hash
+    }
+
+    /// Estimates the gas of a user operation through the uopool gRPC service.
+    ///
+    /// # Arguments
+    /// * `uo: UserOperationRequest` - The user operation to estimate the gas for.
+    /// * `ep: Address` - The address of the entry point.
+    /// * `validate: bool` - Whether the user operation is fully validated before the estimation.
+    ///
+    /// # Returns
+    /// * `RpcResult<UserOperationGasEstimation>` - The estimated gas for the user operation.
+    async fn estimate_gas(
+        &self,
+        uo: UserOperationRequest,
+        ep: Address,
+        validate: bool,
+    ) -> RpcResult<UserOperationGasEstimation> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let res = uopool_grpc_client
+            .get_chain_id(Request::new(()))
+            .await
+            .map_err(JsonRpcError::from)?
+            .into_inner();
+
+        let uo: UserOperationSigned = uo.into();
+
+        let req: Request<EstimateUserOperationGasRequest> =
+            Request::new(EstimateUserOperationGasRequest {
+                uo: Some(
+                    UserOperation::from_user_operation_signed(
+                        uo.hash_for_entry_point(&ep, res.chain_id),
+                        uo.clone(),
+                    )
+                    .into(),
+                ),
+                ep: Some(ep.into()),
+                validate,
+            });
+
+        let res = uopool_grpc_client
+            .estimate_user_operation_gas(req)
+            .await
+            .map_err(JsonRpcError::from)?
+            .into_inner();
+
+        if res.res == EstimateUserOperationGasResult::Estimated as i32 {
+            let gas_est = serde_json::from_str::<UserOperationGasEstimation>(&res.data)
+                .map_err(JsonRpcError::from)?;
+            return Ok(gas_est);
+        }
+
+        Err(JsonRpcError::from(
+            serde_json::from_str::<MempoolError>(&res.data).map_err(JsonRpcError::from)?,
+        )
+        .0)
+    }
 }
 
 #[async_trait]
@@ -83,8 +165,11 @@ impl EthApiServer for EthApiServerImpl {
 
         let req = Request::new(AddRequest {
             uo: Some(
-                UserOperation::from_user_operation_signed(uo.hash(&ep, res.chain_id), uo.clone())
-                    .into(),
+                UserOperation::from_user_operation_signed(
+                    uo.hash_for_entry_point(&ep, res.chain_id),
+                    uo.clone(),
+                )
+                .into(),
             ),
             ep: Some(ep.into()),
         });
@@ -120,44 +205,51 @@ impl EthApiServer for EthApiServerImpl {
         uo: UserOperationRequest,
         ep: Address,
     ) -> RpcResult<UserOperationGasEstimation> {
-        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
-
-        let res = uopool_grpc_client
-            .get_chain_id(Request::new(()))
-            .await
-            .map_err(JsonRpcError::from)?
-            .into_inner();
+        self.estimate_gas(uo, ep, false).await
+    }
 
-        let uo: UserOperationSigned = uo.into();
+    /// Validate a user operation the same way as [send_user_operation](Self::send_user_operation)
+    /// (without adding it into the mempool) and estimate its gas if it's valid, in one round
+    /// trip.
+    ///
+    /// # Arguments
+    /// * `user_operation: [UserOperation](UserOperationRequest)` - User operation to validate and
+    ///   estimate the gas for.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<UserOperationGasEstimation>` - The estimated gas for the
+    ///   [UserOperation](UserOperation), or the reason why it's rejected as the error.
+    async fn validate_and_estimate_user_operation_gas(
+        &self,
+        uo: UserOperationRequest,
+        ep: Address,
+    ) -> RpcResult<UserOperationGasEstimation> {
+        self.estimate_gas(uo, ep, true).await
+    }
 
-        let req: Request<EstimateUserOperationGasRequest> =
-            Request::new(EstimateUserOperationGasRequest {
-                uo: Some(
-                    UserOperation::from_user_operation_signed(
-                        uo.hash(&ep, res.chain_id),
-                        uo.clone(),
-                    )
-                    .into(),
-                ),
-                ep: Some(ep.into()),
-            });
+    /// Suggest the fees of a [UserOperation](UserOperation), derived from the current base fee,
+    /// the priority fees paid in the recent blocks and the min fees accepted by the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<UserOperationGasPrice>` - The suggested `maxFeePerGas` and
+    ///   `maxPriorityFeePerGas`.
+    async fn get_user_operation_gas_price(&self) -> RpcResult<UserOperationGasPrice> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
         let res = uopool_grpc_client
-            .estimate_user_operation_gas(req)
+            .get_gas_price(Request::new(()))
             .await
             .map_err(JsonRpcError::from)?
             .into_inner();
 
-        if res.res == EstimateUserOperationGasResult::Estimated as i32 {
-            let gas_est = serde_json::from_str::<UserOperationGasEstimation>(&res.data)
-                .map_err(JsonRpcError::from)?;
-            return Ok(gas_est);
-        }
-
-        Err(JsonRpcError::from(
-            serde_json::from_str::<MempoolError>(&res.data).map_err(JsonRpcError::from)?,
-        )
-        .0)
+        Ok(UserOperationGasPrice {
+            max_fee_per_gas: res.max_fee_per_gas.map(Into::into).unwrap_or_default(),
+            max_priority_fee_per_gas: res
+                .max_priority_fee_per_gas
+                .map(Into::into)
+                .unwrap_or_default(),
+        })
     }
 
     /// Retrieve the receipt of a [UserOperation](UserOperation).
@@ -174,7 +266,9 @@ impl EthApiServer for EthApiServerImpl {
     ) -> RpcResult<Option<UserOperationReceipt>> {
         match UserOperationHash::from_str(&uo_hash) {
             Ok(uo_hash) => {
-                let req = Request::new(UserOperationHashRequest { hash: Some(uo_hash.into()) });
+                let tx_hash = self.get_user_operation_transaction(&uo_hash).await;
+                let req =
+                    Request::new(UserOperationHashRequest { hash: Some(uo_hash.into()), tx_hash });
 
                 match self.uopool_grpc_client.clone().get_user_operation_receipt(req).await {
                     Ok(res) => {
@@ -230,7 +324,9 @@ impl EthApiServer for EthApiServerImpl {
     ) -> RpcResult<Option<UserOperationByHash>> {
         match UserOperationHash::from_str(&uo_hash) {
             Ok(uo_hash) => {
-                let req = Request::new(UserOperationHashRequest { hash: Some(uo_hash.into()) });
+                let tx_hash = self.get_user_operation_transaction(&uo_hash).await;
+                let req =
+                    Request::new(UserOperationHashRequest { hash: Some(uo_hash.into()), tx_hash });
 
                 match self.uopool_grpc_client.clone().get_user_operation_by_hash(req).await {
                     Ok(res) => {
@@ -238,14 +334,13 @@ impl EthApiServer for EthApiServerImpl {
 
                         let uo: Option<UserOperationByHash> = res.user_operation.and_then(|uo| {
                             let entry_point = res.entry_point?.into();
-                            let block_hash = res.block_hash?.into();
-                            let transaction_hash = res.transaction_hash?.into();
+                            let block_hash = res.block_hash.map(Into::into);
                             Some(UserOperationByHash {
                                 user_operation: uo.into(),
                                 entry_point,
-                                block_number: res.block_number.into(),
+                                block_number: block_hash.map(|_| res.block_number.into()),
                                 block_hash,
-                                transaction_hash,
+                                transaction_hash: res.transaction_hash.map(Into::into),
                             })
                         });
                         Ok(uo)
@@ -267,4 +362,53 @@ impl EthApiServer for EthApiServerImpl {
             )),
         }
     }
+
+    /// Subscribe to the [UserOperations](UserOperation) submitted by this bundler once the bundle
+    /// containing them is mined.
+    ///
+    /// # Arguments
+    /// * `pending: PendingSubscriptionSink` - The pending subscription.
+    /// * `user_operation_hashes: Option<Vec<UserOperationHash>>` - The hashes of the user
+    ///   operations to be notified about (all if not provided).
+    ///
+    /// # Returns
+    /// * `SubscriptionResult` - Ok once the subscription is closed.
+    async fn subscribe_bundled_user_operations(
+        &self,
+        pending: PendingSubscriptionSink,
+        user_operation_hashes: Option<Vec<UserOperationHash>>,
+    ) -> SubscriptionResult {
+        let Some(mut bundler_grpc_client) = self.bundler_grpc_client.clone() else {
+            pending
+                .reject(ErrorObjectOwned::owned(
+                    INTERNAL_ERROR_CODE,
+                    "Bundling service is not available".to_string(),
+                    None::<bool>,
+                ))
+                .await;
+            return Ok(());
+        };
+
+        let req = Request::new(SubscribeBundledRequest {
+            uos: user_operation_hashes.unwrap_or_default().into_iter().map(Into::into).collect(),
+        });
+        let mut stream = match bundler_grpc_client.subscribe_bundled(req).await {
+            Ok(res) => res.into_inner(),
+            Err(s) => {
+                pending.reject(JsonRpcError::from(s)).await;
+                return Ok(());
+            }
+        };
+
+        let sink = pending.accept().await?;
+        while let Some(event) = stream.message().await? {
+            let msg = SubscriptionMessage::from_json(&BundledUserOperation::from(event))?;
+            if sink.send(msg).await.is_err() {
+                // the subscriber disconnected
+                break;
+            }
+        }
+
+        Ok(())
+    }
 }
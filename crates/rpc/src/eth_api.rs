@@ -1,9 +1,12 @@
 pub use crate::eth::EthApiServerImpl;
 use ethers::types::{Address, U64};
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 use silius_primitives::{
-    UserOperationByHash, UserOperationGasEstimation, UserOperationHash, UserOperationReceipt,
-    UserOperationRequest,
+    BundledUserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationGasPrice,
+    UserOperationHash, UserOperationReceipt, UserOperationRequest,
 };
 
 /// The ERC-4337 `eth` namespace RPC methods trait
@@ -58,6 +61,34 @@ pub trait EthApi {
         entry_point: Address,
     ) -> RpcResult<UserOperationGasEstimation>;
 
+    /// Validate a user operation the same way as [send_user_operation](Self::send_user_operation)
+    /// (without adding it into the mempool) and estimate its gas if it's valid, in one round
+    /// trip. The user operation should be signed and carry its gas limits.
+    ///
+    /// # Arguments
+    /// * `user_operation: [UserOperation](UserOperationRequest)` - User operation to validate and
+    ///   estimate the gas for.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<UserOperationGasEstimation>` - The estimated gas for the
+    ///   [UserOperation](UserOperation), or the reason why it's rejected as the error.
+    #[method(name = "validateAndEstimateUserOperationGas")]
+    async fn validate_and_estimate_user_operation_gas(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+    ) -> RpcResult<UserOperationGasEstimation>;
+
+    /// Suggest the fees of a [UserOperation](UserOperation), derived from the current base fee,
+    /// the priority fees paid in the recent blocks and the min fees accepted by the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<UserOperationGasPrice>` - The suggested `maxFeePerGas` and
+    ///   `maxPriorityFeePerGas`.
+    #[method(name = "getUserOperationGasPrice")]
+    async fn get_user_operation_gas_price(&self) -> RpcResult<UserOperationGasPrice>;
+
     /// Retrieve the receipt of a [UserOperation](UserOperation).
     /// The receipt contains the results of the operation, such as the amount of gas used.
     ///
@@ -87,4 +118,24 @@ pub trait EthApi {
         &self,
         user_operation_hash: String,
     ) -> RpcResult<Option<UserOperationByHash>>;
+
+    /// Subscribe to the [UserOperations](UserOperation) submitted by this bundler once the bundle
+    /// containing them is mined. Only available over WebSocket.
+    ///
+    /// # Arguments
+    /// * `user_operation_hashes: Option<Vec<UserOperationHash>>` - The hashes of the user
+    ///   operations to be notified about (all if not provided).
+    ///
+    /// # Returns
+    /// * `SubscriptionResult` - Emits a [BundledUserOperation](BundledUserOperation) for every user
+    ///   operation with the hash of the bundle transaction and whether it succeeded.
+    #[subscription(
+        name = "subscribeBundledUserOperations" => "bundledUserOperation",
+        unsubscribe = "unsubscribeBundledUserOperations",
+        item = BundledUserOperation
+    )]
+    async fn subscribe_bundled_user_operations(
+        &self,
+        user_operation_hashes: Option<Vec<UserOperationHash>>,
+    ) -> SubscriptionResult;
 }
@@ -8,6 +8,7 @@ mod error;
 mod eth;
 pub mod eth_api;
 pub mod middleware;
+pub mod rate_limit;
 mod rpc;
 mod web3;
 pub mod web3_api;
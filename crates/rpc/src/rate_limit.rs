@@ -0,0 +1,269 @@
+use ethers::utils::{hex, keccak256};
+use hyper::{header::HeaderMap, Body, Request, Response, StatusCode};
+use jsonrpsee::types::ErrorObjectOwned;
+use serde_json::{json, Value};
+use silius_metrics::rpc::record_rate_limited;
+use std::{
+    collections::HashMap,
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// Header of the API key identifying the client
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Header with the IP address of the client, set by the reverse proxy in front of the server
+pub const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// JSON-RPC method whose calls are rate limited
+const RATE_LIMITED_METHOD: &str = "eth_sendUserOperation";
+
+/// JSON-RPC error code of a rate limited request ("limit exceeded" of EIP-1474)
+const LIMIT_EXCEEDED: i32 = -32005;
+
+/// Limits the number of user operations each client submits in a fixed window. The clients are
+/// identified by the API key (if set) or by the IP address forwarded by the reverse proxy, the
+/// other requests share one limit. The clones share the same counters.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    /// Start of the current window and the number of user operations submitted in it by client
+    clients: Arc<Mutex<HashMap<String, (Instant, u64)>>>,
+    /// Max number of user operations each client submits in the window
+    max_uos: u64,
+    /// Duration of the window
+    window: Duration,
+}
+
+impl RateLimiter {
+    /// Creates a new [RateLimiter](RateLimiter)
+    ///
+    /// # Arguments
+    /// * `max_uos` - Max number of user operations each client submits in the window
+    /// * `window` - Duration of the window
+    ///
+    /// # Returns
+    /// * `RateLimiter` - The [RateLimiter](RateLimiter)
+    pub fn new(max_uos: u64, window: Duration) -> Self {
+        Self { clients: Default::default(), max_uos, window }
+    }
+
+    /// Identifies the client of the request by the API key (only a hash of the key is kept) or the
+    /// IP address forwarded by the reverse proxy
+    ///
+    /// # Arguments
+    /// * `headers` - The headers of the HTTP request
+    ///
+    /// # Returns
+    /// * `String` - The identifier of the client
+    pub fn client(headers: &HeaderMap) -> String {
+        if let Some(key) = headers.get(API_KEY_HEADER).and_then(|key| key.to_str().ok()) {
+            return format!("key:{}", hex::encode(&keccak256(key)[..8]));
+        }
+
+        // the proxy appends the address of the client it received the request from
+        headers
+            .get(FORWARDED_FOR_HEADER)
+            .and_then(|addrs| addrs.to_str().ok())
+            .and_then(|addrs| addrs.split(',').next_back())
+            .map(|addr| format!("ip:{}", addr.trim()))
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    /// Counts the user operations submitted by the client
+    ///
+    /// # Arguments
+    /// * `client` - The identifier of the client
+    /// * `count` - The number of submitted user operations
+    ///
+    /// # Returns
+    /// * `bool` - False if the client exceeded the limit (the user operations are not counted)
+    pub fn check(&self, client: &str, count: u64) -> bool {
+        let now = Instant::now();
+        let mut clients = self.clients.lock().expect("rate limiter lock poisoned");
+
+        if !clients.contains_key(client) {
+            // the clients whose window expired are dropped, so that the map doesn't grow
+            clients.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let (start, used) = clients.entry(client.to_string()).or_insert((now, 0));
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *used = 0;
+        }
+
+        if used.saturating_add(count) > self.max_uos {
+            return false;
+        }
+
+        *used += count;
+        true
+    }
+
+    /// The error returned to the clients that exceeded the limit
+    fn error(&self) -> ErrorObjectOwned {
+        ErrorObjectOwned::owned(
+            LIMIT_EXCEEDED,
+            format!(
+                "Rate limit of {} user operations per {}s exceeded",
+                self.max_uos,
+                self.window.as_secs()
+            ),
+            None::<bool>,
+        )
+    }
+}
+
+/// Counts the calls of the rate limited method in the body of the JSON-RPC request (a single call
+/// or a batch)
+///
+/// # Arguments
+/// * `body` - The body of the HTTP request
+///
+/// # Returns
+/// * `u64` - The number of rate limited calls
+fn rate_limited_calls(body: &[u8]) -> u64 {
+    let is_limited = |call: &Value| call.get("method") == Some(&json!(RATE_LIMITED_METHOD));
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(calls)) => calls.iter().filter(|call| is_limited(call)).count() as u64,
+        Ok(call) => is_limited(&call) as u64,
+        Err(_) => 0,
+    }
+}
+
+/// The rate limit layer for the HTTP [json rpc server](crate::JsonRpcServer)
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    /// The [RateLimiter](RateLimiter) shared by the connections
+    pub rate_limiter: RateLimiter,
+}
+
+impl RateLimitLayer {
+    /// Create a new rate limit layer
+    ///
+    /// # Arguments
+    /// * `rate_limiter: RateLimiter` - The [RateLimiter](RateLimiter)
+    ///
+    /// # Returns
+    /// * `Self` - A RateLimitLayer instance
+    pub fn new(rate_limiter: RateLimiter) -> Self {
+        Self { rate_limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitRequest<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitRequest { inner, rate_limiter: self.rate_limiter.clone() }
+    }
+}
+
+/// The RPC request checked against the rate limit of its client
+#[derive(Debug, Clone)]
+pub struct RateLimitRequest<S> {
+    /// The inner service
+    inner: S,
+    /// The [RateLimiter](RateLimiter)
+    rate_limiter: RateLimiter,
+}
+
+impl<S> Service<Request<Body>> for RateLimitRequest<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Response: 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let rate_limiter = self.rate_limiter.clone();
+        let clone = self.inner.clone();
+        // take the service that was ready
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let res_fut = async move {
+            let (req_h, req_b) = req.into_parts();
+            let req_bb = hyper::body::to_bytes(req_b).await?;
+
+            let calls = rate_limited_calls(&req_bb);
+            if calls > 0 && !rate_limiter.check(&RateLimiter::client(&req_h.headers), calls) {
+                record_rate_limited();
+                let body = json!({ "jsonrpc": "2.0", "id": null, "error": rate_limiter.error() });
+                return Ok(Response::builder()
+                    .status(StatusCode::TOO_MANY_REQUESTS)
+                    .header(hyper::header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.to_string()))?);
+            }
+
+            inner.call(Request::from_parts(req_h, Body::from(req_bb))).await.map_err(Into::into)
+        };
+
+        Box::pin(res_fut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_check() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+
+        assert!(limiter.check("a", 2));
+        assert!(limiter.clone().check("a", 1));
+        assert!(!limiter.check("a", 1));
+
+        // the limit is per client
+        assert!(limiter.check("b", 3));
+
+        // the counters are reset once the window expires
+        let limiter = RateLimiter::new(1, Duration::ZERO);
+        assert!(limiter.check("a", 1));
+        assert!(limiter.check("a", 1));
+    }
+
+    #[test]
+    fn rate_limiter_client() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(RateLimiter::client(&headers), "unknown");
+
+        headers.insert(FORWARDED_FOR_HEADER, "10.0.0.1, 192.168.0.1".parse().unwrap());
+        assert_eq!(RateLimiter::client(&headers), "ip:192.168.0.1");
+
+        headers.insert(API_KEY_HEADER, "secret".parse().unwrap());
+        assert!(RateLimiter::client(&headers).starts_with("key:"));
+        assert!(!RateLimiter::client(&headers).contains("secret"));
+    }
+
+    #[test]
+    fn rate_limited_calls_in_batch() {
+        let call = |method: &str| json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+
+        assert_eq!(rate_limited_calls(call(RATE_LIMITED_METHOD).to_string().as_bytes()), 1);
+        assert_eq!(rate_limited_calls(call("eth_chainId").to_string().as_bytes()), 0);
+        assert_eq!(
+            rate_limited_calls(
+                json!([call(RATE_LIMITED_METHOD), call("eth_chainId"), call(RATE_LIMITED_METHOD)])
+                    .to_string()
+                    .as_bytes()
+            ),
+            2
+        );
+        assert_eq!(rate_limited_calls(b"not json"), 0);
+    }
+}
@@ -1,4 +1,7 @@
-use super::middleware::ProxyJsonRpcLayer;
+use super::{
+    middleware::ProxyJsonRpcLayer,
+    rate_limit::{RateLimitLayer, RateLimiter},
+};
 use eyre::Error;
 use hyper::{http::HeaderValue, Method};
 use jsonrpsee::{
@@ -22,6 +25,9 @@ pub struct JsonRpcServer {
     http_methods: Methods,
     /// The [cors layer](CorsLayer) for HTTP server to filter requests.
     http_cors_layer: Option<CorsLayer>,
+    /// The [rate limit layer](RateLimitLayer) for HTTP server to limit the submitted user
+    /// operations.
+    http_rate_limit_layer: Option<RateLimitLayer>,
     /// Whether to start a WS server.
     ws: bool,
     /// WS address to listen on.
@@ -75,6 +81,7 @@ impl JsonRpcServer {
             http_port,
             http_methods: Methods::new(),
             http_cors_layer: None,
+            http_rate_limit_layer: None,
             ws,
             ws_addr,
             ws_port,
@@ -135,6 +142,19 @@ impl JsonRpcServer {
         self
     }
 
+    /// Add a rate limit layer to the HTTP server.
+    ///
+    /// # Arguments
+    /// * `rate_limiter: RateLimiter` - The [RateLimiter](RateLimiter) of the submitted user
+    ///   operations.
+    ///
+    /// # Returns
+    /// * `Self` - The JsonRpcServer instance.
+    pub fn with_rate_limit(mut self, rate_limiter: RateLimiter) -> Self {
+        self.http_rate_limit_layer = Some(RateLimitLayer::new(rate_limiter));
+        self
+    }
+
     pub fn with_metrics(mut self) -> Self {
         self.metric_layer = Some(MetricsLayer::new());
         self
@@ -174,6 +194,7 @@ impl JsonRpcServer {
         let http_handle = if self.http {
             let service = ServiceBuilder::new()
                 .option_layer(self.http_cors_layer.clone())
+                .option_layer(self.http_rate_limit_layer.clone())
                 .option_layer(self.proxy_layer.clone());
             let rpc_service = RpcServiceBuilder::new().option_layer(self.metric_layer.clone());
 
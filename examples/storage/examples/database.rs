@@ -9,8 +9,13 @@ use silius_mempool::{
 use silius_primitives::{
     constants::{
         entry_point::ADDRESS,
-        validation::reputation::{
-            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, MIN_UNSTAKE_DELAY, THROTTLING_SLACK,
+        mempool::GAS_INCREASE_PERC,
+        validation::{
+            reputation::{
+                BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, MIN_UNSTAKE_DELAY,
+                SAME_SENDER_MEMPOOL_COUNT, THROTTLING_SLACK,
+            },
+            sanity::{MAX_UO_SIZE, MIN_BASE_FEE_PERC, STAKED_GRACE_PERC},
         },
     },
     provider::create_http_provider,
@@ -63,7 +68,21 @@ async fn main() -> eyre::Result<()> {
             U256::from(5000000),
             mempool,
             reputation,
-            new_canonical(entry_point, chain, U256::from(5000000), U256::from(1)),
+            new_canonical(
+                entry_point,
+                chain,
+                U256::from(5000000),
+                U256::from(1),
+                MAX_UO_SIZE,
+                GAS_INCREASE_PERC.into(),
+                Default::default(),
+                SAME_SENDER_MEMPOOL_COUNT,
+                None,
+                U256::zero(),
+                MIN_BASE_FEE_PERC,
+                STAKED_GRACE_PERC,
+                Default::default(),
+            ),
             None,
         );
 
@@ -12,7 +12,16 @@ use ethers::{
 };
 use silius_contracts::EntryPoint;
 use silius_mempool::{validate::validator::new_canonical, UoPool};
-use silius_primitives::{UserOperationSigned, Wallet as UoWallet};
+use silius_primitives::{
+    constants::{
+        mempool::GAS_INCREASE_PERC,
+        validation::{
+            reputation::SAME_SENDER_MEMPOOL_COUNT,
+            sanity::{MAX_UO_SIZE, MIN_BASE_FEE_PERC, STAKED_GRACE_PERC},
+        },
+    },
+    UserOperationSigned, Wallet as UoWallet,
+};
 use std::sync::Arc;
 
 async fn setup_basic() -> eyre::Result<(
@@ -40,7 +49,21 @@ async fn estimate_with_zero() -> eyre::Result<()> {
     let entry = EntryPoint::new(client.clone(), entry_point.address);
     let entry_for_uopool = EntryPoint::new(client.clone(), entry_point.address);
     let min_priority_fee_per_gas = 0.into();
-    let validator = new_canonical(entry, chain, max_verification_gas, min_priority_fee_per_gas);
+    let validator = new_canonical(
+        entry,
+        chain,
+        max_verification_gas,
+        min_priority_fee_per_gas,
+        MAX_UO_SIZE,
+        GAS_INCREASE_PERC.into(),
+        Default::default(),
+        SAME_SENDER_MEMPOOL_COUNT,
+        None,
+        U256::zero(),
+        MIN_BASE_FEE_PERC,
+        STAKED_GRACE_PERC,
+        Default::default(),
+    );
     let mut uopool = UoPool::new(
         entry_for_uopool,
         validator,
@@ -49,6 +72,7 @@ async fn estimate_with_zero() -> eyre::Result<()> {
         max_verification_gas,
         chain,
         None,
+        None,
     );
 
     let wallet = MnemonicBuilder::<English>::default().phrase(SEED_PHRASE).build()?;
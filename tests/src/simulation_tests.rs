@@ -31,7 +31,14 @@ use silius_mempool::{
     UserOperationsBySender, WriteMap,
 };
 use silius_primitives::{
-    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    constants::{
+        mempool::GAS_INCREASE_PERC,
+        validation::{
+            entities::{FACTORY, PAYMASTER, SENDER},
+            reputation::SAME_SENDER_MEMPOOL_COUNT,
+            sanity::{MAX_UO_SIZE, MIN_BASE_FEE_PERC, STAKED_GRACE_PERC},
+        },
+    },
     reputation::ReputationEntry,
     simulation::CodeHash,
     UserOperation, UserOperationHash, UserOperationSigned,
@@ -151,8 +158,21 @@ async fn setup_database() -> eyre::Result<DatabaseContext> {
     let entry_point = EntryPoint::new(client.clone(), ep.address);
     let c = Chain::from(chain_id);
 
-    let validator =
-        new_canonical(entry_point, c.clone(), U256::from(3000000_u64), U256::from(1u64));
+    let validator = new_canonical(
+        entry_point,
+        c.clone(),
+        U256::from(3000000_u64),
+        U256::from(1u64),
+        MAX_UO_SIZE,
+        GAS_INCREASE_PERC.into(),
+        Default::default(),
+        SAME_SENDER_MEMPOOL_COUNT,
+        None,
+        U256::zero(),
+        MIN_BASE_FEE_PERC,
+        STAKED_GRACE_PERC,
+        Default::default(),
+    );
 
     Ok(DatabaseContext {
         client: client.clone(),
@@ -176,8 +196,21 @@ async fn setup_memory() -> eyre::Result<MemoryContext> {
     let entry_point = EntryPoint::new(client.clone(), ep.address);
     let c = Chain::from(chain_id);
 
-    let validator =
-        new_canonical(entry_point, c.clone(), U256::from(3000000_u64), U256::from(1u64));
+    let validator = new_canonical(
+        entry_point,
+        c.clone(),
+        U256::from(3000000_u64),
+        U256::from(1u64),
+        MAX_UO_SIZE,
+        GAS_INCREASE_PERC.into(),
+        Default::default(),
+        SAME_SENDER_MEMPOOL_COUNT,
+        None,
+        U256::zero(),
+        MIN_BASE_FEE_PERC,
+        STAKED_GRACE_PERC,
+        Default::default(),
+    );
     Ok(MemoryContext {
         client: client.clone(),
         _geth,
@@ -550,7 +583,7 @@ macro_rules!fail_if_create_too_many {
             .await;
             assert!(matches!(
                 res,
-                Err(InvalidMempoolUserOperationError::Simulation(SimulationError::Opcode { entity, opcode })) if entity==SENDER && opcode == "CREATE2"
+                Err(InvalidMempoolUserOperationError::Simulation(SimulationError::ContractCreation { entity, opcode })) if entity==SENDER && opcode == "CREATE2"
             ));
 
             Ok(())